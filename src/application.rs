@@ -1,40 +1,309 @@
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, Local, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 
 use serde_with::serde_as;
 use std::collections::btree_map;
 use std::fmt::{Display, Formatter};
+use std::path::Path;
 use std::{collections::BTreeMap, path::PathBuf};
 
 use anyhow::{bail, ensure, Context, Result};
 
 use dialoguer::FuzzySelect;
+use itertools::Itertools;
+use owo_colors::Style;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::audit::{self, AuditEntry};
+use crate::clock::SharedClock;
+use crate::color::{gradient, paint, ColorChoice};
+use crate::crypt;
+use crate::error::Error;
+use crate::i18n::t;
+use crate::storage::{self, StateFormat};
+use crate::time::{SpanStyle, TimeWindow};
+
+mod aliases;
+mod chart;
+mod demo;
+mod dependencies;
+mod doctor;
+mod export;
+mod heatmap;
+mod merge;
+mod practice_set;
+mod report;
+mod running;
+
+pub use aliases::is_glob;
+pub use chart::ChartBucket;
+use dependencies::Dependency;
+pub use export::Export;
+pub use practice_set::PracticeSet;
+pub use report::ReportFormat;
+use running::RunningSession;
 
 /// User exposed (via [``SubCommand::config``](crate::cli::SubCommand::Config)) configuration.
 #[serde_as]
-#[derive(Serialize, Deserialize, Clone, Copy)]
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct UserConfig {
     #[serde_as(as = "serde_with::DurationSeconds<i64>")]
     #[serde(rename = "grace_period_in_seconds")]
     /// Grace period adds extra time in progress display. This aids against practices creeping earlier.
     pub grace_period: Duration,
+    /// Period used by `prac add` when none is given.
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    #[serde(rename = "default_period_in_seconds")]
+    pub default_period: Option<Duration>,
+    /// Editor for `prac notes` if `$VISUAL` and `$EDITOR` are unset. May include arguments.
+    pub editor: Option<String>,
+    /// Only accept practice names as they are stored, rather than ignoring case and Unicode
+    /// normalization (see [`name_key`]).
+    pub exact_names: bool,
+    /// Default order of `prac list`.
+    pub list_sort: ListSort,
+    /// Always show cumulative time in `prac list`, as if `--cumulative` were passed.
+    pub list_cumulative: bool,
+    /// Always show periods in `prac list`, as if `--period` were passed.
+    pub list_period: bool,
+    /// Always show the danger bar in `prac list`, as if `--danger` were passed.
+    pub list_danger: bool,
+    /// Always show how the last periods went in `prac list`, as if `--sparkline` were passed.
+    pub list_sparkline: bool,
+    /// Always show budget bars in `prac list`, as if `--budget` were passed.
+    pub list_budget: bool,
+    /// Always show when practices were last logged and are due in `prac list`, as if
+    /// `--verbose` were passed.
+    pub list_verbose: bool,
+    /// Longest name `prac list` shows before cutting it short with an ellipsis, in characters.
+    /// Zero for no limit but the terminal's width.
+    pub list_name_width: usize,
+    /// When to use color, unless overridden by `--color`.
+    pub color: ColorChoice,
+    /// Day weekly goals reset on, at local midnight.
+    pub week_start: Weekday,
+    /// Time `prac touch` logs, none by default.
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "touch_time_in_seconds")]
+    pub touch_time: Duration,
+    /// Longest plausible session. `prac stop` asks what to log for a longer one, e.g. one left
+    /// running overnight, and otherwise logs this much. Zero for no limit.
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "max_session_in_seconds")]
+    pub max_session: Duration,
+    /// How durations are written out, e.g. `1w 2d`, `1w2d`, or `1 week 2 days`.
+    pub span_style: SpanStyle,
+    /// Language for messages, e.g. `de` or `pt-BR`. If unset, `$LANG` decides.
+    pub locale: Option<String>,
+    /// Maximum width of bars in `prac list`. If unset (or zero), bars fill the terminal.
+    pub bar_width: Option<usize>,
+    /// Character for the elapsed part of bars.
+    pub bar_fill: Option<char>,
+    /// Character for the remaining part of bars.
+    pub bar_empty: Option<char>,
+    /// Character for the elapsed part of bars within the grace period.
+    pub bar_grace: Option<char>,
+    /// Draw bars with ASCII characters only, for terminals or fonts that mangle the defaults.
+    pub ascii: bool,
+    /// Git remote `prac sync` pushes to and pulls from.
+    pub sync_remote: Option<String>,
+    /// How long removed practices stay in the trash before being purged.
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "trash_retention_in_seconds")]
+    pub trash_retention: Duration,
+    /// Append every change to the audit log beside the statefile (see [`crate::audit`]).
+    pub audit_log: bool,
+    /// How the statefile is written, unless its extension says (see [`crate::storage`]).
+    pub state_format: StateFormat,
+    /// Write the statefile without pretty printing, which is smaller and faster for long histories.
+    pub state_compact: bool,
+    /// Compress the statefile with zstd. Compressed statefiles are read whatever this says.
+    pub state_compress: bool,
+    /// Backups of the statefile to keep from before changes, besides the last of each recent day
+    /// and week. Zero for no backups.
+    pub backup_count: usize,
+    /// Number of periods a practice may go without ever being logged before `prac review`
+    /// suggests archiving it.
+    pub prune_after_periods: u32,
+    /// Whether the periods of practices in maintenance adapt to how they go.
+    pub adaptive_periods: AdaptivePeriods,
+    /// Factor adaptive periods lengthen or shorten a period by, above 1.
+    pub adaptive_factor: f64,
+    /// Periods on time in a row after which adaptive periods lengthen a period.
+    pub adaptive_streak: usize,
+    /// URLs a [`LogEvent`] is posted to whenever a practice is logged.
+    pub webhooks: Vec<String>,
 }
 
 impl Default for UserConfig {
     fn default() -> Self {
         Self {
             grace_period: Duration::zero(),
+            default_period: None,
+            editor: None,
+            exact_names: false,
+            list_sort: ListSort::default(),
+            list_cumulative: false,
+            list_period: false,
+            list_danger: false,
+            list_sparkline: false,
+            list_budget: false,
+            list_verbose: false,
+            list_name_width: 30,
+            color: ColorChoice::default(),
+            week_start: Weekday::Mon,
+            touch_time: Duration::zero(),
+            max_session: Duration::hours(4),
+            span_style: SpanStyle::default(),
+            locale: None,
+            bar_width: None,
+            bar_fill: None,
+            bar_empty: None,
+            bar_grace: None,
+            ascii: false,
+            sync_remote: None,
+            trash_retention: Duration::days(30),
+            audit_log: true,
+            state_format: StateFormat::default(),
+            state_compact: false,
+            state_compress: false,
+            backup_count: 10,
+            prune_after_periods: 3,
+            adaptive_periods: AdaptivePeriods::default(),
+            adaptive_factor: 1.5,
+            adaptive_streak: 3,
+            webhooks: Vec::new(),
+        }
+    }
+}
+
+impl UserConfig {
+    /// Print each field for humans.
+    pub fn print(&self) {
+        for key in crate::config::KEYS {
+            println!("{} = {}", key.name, key.get(self));
         }
     }
 }
+
+/// Order of practices in `prac list`.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ListSort {
+    /// Alphabetically.
+    #[default]
+    Name,
+    /// Furthest through their period first.
+    Progress,
+    /// Shortest period first.
+    Period,
+    /// Most cumulative time first.
+    Cumulative,
+    /// Furthest through their period first, but after the practices they depend on (see
+    /// `prac depend`) until those are logged for the day.
+    Smart,
+}
+
+/// What `prac list --group-by` puts practices in sections by.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListGroup {
+    /// Where the practice can be done (see `prac context`), those without one last.
+    Context,
+    /// Active, maintenance, or retired (see `prac set-state`).
+    State,
+}
+
+/// How logging a practice moves the start of its period, i.e. restarts its bar.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogMode {
+    /// Every log restarts the period.
+    #[default]
+    Reset,
+    /// Logs add up toward the target time per period (see `prac budget`), and the period restarts
+    /// once it is reached.
+    Accumulate,
+    /// The first log after a period ends starts the next one; more logs within it don't move it.
+    Checkpoint,
+}
+
+/// How many times its period a practice in [`Lifecycle::Maintenance`] may go between sessions.
+pub const MAINTENANCE_FACTOR: i32 = 4;
+
+/// Shortest period, in seconds, as progress through periods is measured in whole seconds.
+const MIN_PERIOD_SECONDS: i64 = 1;
+
+/// Refuse periods shorter than [`MIN_PERIOD_SECONDS`], which bars can't be drawn for.
+fn check_period(period: Option<Duration>) -> Result<()> {
+    ensure!(
+        period.is_none_or(|period| period.num_seconds() >= MIN_PERIOD_SECONDS),
+        Error::Parse(t!(
+            "period-too-short",
+            period = crate::time::Span::from(period.unwrap_or_default()).format()
+        ))
+    );
+    Ok(())
+}
+
+/// Whether the periods of practices in maintenance adapt to how they go, as in spaced repetition:
+/// lengthened by [`UserConfig::adaptive_factor`] after [`UserConfig::adaptive_streak`] periods on
+/// time in a row, and shortened by it after a missed period.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AdaptivePeriods {
+    #[default]
+    Off,
+    /// Suggest the new period in `prac review`.
+    Suggest,
+    /// Change the period without asking, as soon as it is due to change.
+    Auto,
+}
+
+/// A change adaptive periods made to the period of a practice.
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PeriodAdjustment {
+    at: DateTime<Utc>,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "from_in_seconds")]
+    from: Duration,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "to_in_seconds")]
+    to: Duration,
+    /// Made without asking, as `adaptive_periods` was `auto`.
+    automatic: bool,
+}
+
+/// How much a practice is kept up, see `prac set-state`.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Lifecycle {
+    /// Practiced every period.
+    #[default]
+    Active,
+    /// Only kept warm, every [`MAINTENANCE_FACTOR`] periods.
+    Maintenance,
+    /// No longer practiced, the same as archived.
+    Retired,
+}
+
 /// This is the application config, which includes user-editable [``UserConfig``]
 /// , as well as other items like version that the user probably shouldn't touch.
 #[derive(Serialize, Deserialize)]
+#[allow(clippy::struct_field_names)]
 struct Config {
     /// Version of prac that created this state file.
     version: String,
     /// User editable configuration.
     user_config: UserConfig,
+    /// Last time `prac list` pointed out never-logged practices, so it doesn't nag on every run.
+    #[serde(default)]
+    last_prune_nudge: Option<DateTime<Utc>>,
+    /// Whether this is the made-up state of `prac --demo`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    demo: bool,
 }
 
 impl Default for Config {
@@ -42,36 +311,143 @@ impl Default for Config {
         Self {
             version: env!("CARGO_PKG_VERSION").to_owned(),
             user_config: UserConfig::default(),
+            last_prune_nudge: None,
+            demo: false,
         }
     }
 }
 
+/// A single logged session of a practice.
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LogEntry {
+    /// When the session was logged
+    at: DateTime<Utc>,
+    /// Time spent practicing
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    time: Duration,
+    /// ID of the time tracker entry this was imported from, to not import it twice.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    /// A line about how the session went, or why the period was skipped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    /// Whether this is a period skipped on purpose with `prac skip`, rather than a session.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    skipped: bool,
+}
+
+/// A version of a practice's notes.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct NoteRevision {
+    /// When this version was written, unknown for notes from before revisions were kept.
+    at: Option<DateTime<Utc>>,
+    notes: String,
+}
+
+/// An item of a practice's checklist.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ChecklistItem {
+    item: String,
+    /// When the item was last checked off. It counts as done until the period restarts.
+    checked: Option<DateTime<Utc>>,
+}
+
 /// A practice is an activity that you wish to repeat every so often. Not so much a task (completion oriented), not a habit (in absolute time), or scheduling-item.
+#[allow(clippy::struct_excessive_bools)]
 #[serde_as]
 #[derive(Serialize, Deserialize)]
 pub struct Practice {
-    /// Time practice created by user
+    /// Time practice created by user. Statefiles from before it was kept get it from the first
+    /// log, see [`State::migrate`].
+    #[serde(default)]
     created: DateTime<Utc>,
     /// Last time practice was logged
     logged: DateTime<Utc>,
-    /// How often you wish to repeat practice (starting from last log)
-    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
-    period: Duration,
+    /// How often you wish to repeat practice (starting from last log), or none for log-only
+    /// practices, which only accumulate time.
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    period: Option<Duration>,
     /// Unique id of practice, will be used for retrieval
     name: String,
     /// Plain-text notes where user can set goals, track progress, etc.
     notes: String,
+    /// When the current notes were written, unknown for notes from before revisions were kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notes_edited: Option<DateTime<Utc>>,
+    /// Earlier versions of the notes, replaced since, oldest first. The current notes aren't in it.
+    #[serde(default)]
+    note_history: Vec<NoteRevision>,
     /// Cumulative time spent on this practice
     #[serde_as(as = "serde_with::DurationSeconds<i64>")]
     cumulative: Duration,
+    /// Short statement of why the user wants to keep up this practice, shown when it slumps.
+    #[serde(default)]
+    why: Option<String>,
+    /// Archived practices are kept (with their history) but hidden from `prac list`.
+    #[serde(default)]
+    archived: bool,
+    /// Practices in maintenance are only kept warm, so their period is stretched by
+    /// [`MAINTENANCE_FACTOR`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    maintenance: bool,
+    /// Hidden practices are tracked as usual but left out of `prac list` unless asked for.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    hidden: bool,
+    /// Short names the practice can also be referred to by, see [`State::resolve_name`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    /// Free-form fields the user keeps with the practice, e.g. a teacher or a book chapter.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: BTreeMap<String, String>,
+    /// Every logged session, oldest first. Practices from before history was kept may have
+    /// cumulative time not accounted for here.
+    #[serde(default)]
+    logs: Vec<LogEntry>,
+    /// How each period ended, oldest first, kept up as logs come in and periods lapse (see
+    /// [`Practice::update_outcomes`]) rather than worked out from the whole history each time.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    outcomes: Vec<PeriodOutcome>,
+    /// Whether [`Practice::outcomes`] no longer match the logs, e.g. after a log was edited, and
+    /// have to be worked out afresh.
+    #[serde(skip)]
+    outcomes_stale: bool,
+    /// Changes made to the period by adaptive periods, oldest first (see [`AdaptivePeriods`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    period_adjustments: Vec<PeriodAdjustment>,
+    /// Time the user aims to spend per week, tracked alongside (not instead of) the period.
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    #[serde(default)]
+    weekly_goal: Option<Duration>,
+    /// Time the user aims to spend in each period, for practices better measured by time spent
+    /// than by a single session. Periods are counted from when the practice was created.
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    #[serde(default)]
+    target_time_per_period: Option<Duration>,
+    /// How long a session of the practice typically takes, for `prac suggest --have`.
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    session_length: Option<Duration>,
+    #[serde(default)]
+    log_mode: LogMode,
+    /// Things to get done each period, e.g. write summary, push, post.
+    #[serde(default)]
+    checklist: Vec<ChecklistItem>,
+    /// Time of day the practice goes best, preferred by `prac suggest`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    best_time: Option<TimeWindow>,
+    /// Where the practice can be done, e.g. "home" or "gym". Practices without one can be done
+    /// anywhere.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    context: Option<String>,
     // TODO maybe a Completion struct? then a body enum {practice, Task} that contains Vec<Comepletion> for practice and raw
     // Completion for task. Trying not to prematurely optimize.
 }
 
 impl Practice {
-    /// Create a new practice with the given name, notes, and period, starting with no logged time.
-    fn new(name: String, notes: String, period: Duration) -> Self {
-        let created = Utc::now();
+    /// Create a new practice with the given name, notes, and period, starting with no logged time
+    /// as of `created`.
+    fn new(name: String, notes: String, period: Option<Duration>, created: DateTime<Utc>) -> Self {
         let logged = created;
 
         Self {
@@ -80,310 +456,4363 @@ impl Practice {
             period,
             name,
             notes,
+            notes_edited: None,
+            note_history: Vec::new(),
             cumulative: Duration::seconds(0),
+            why: None,
+            archived: false,
+            maintenance: false,
+            hidden: false,
+            aliases: Vec::new(),
+            metadata: BTreeMap::new(),
+            logs: Vec::new(),
+            outcomes: Vec::new(),
+            outcomes_stale: false,
+            period_adjustments: Vec::new(),
+            weekly_goal: None,
+            target_time_per_period: None,
+            session_length: None,
+            log_mode: LogMode::default(),
+            checklist: Vec::new(),
+            best_time: None,
+            context: None,
+        }
+    }
+
+    /// Whether the practice can be done in `context`, which it can anywhere without a context of
+    /// its own. Contexts are compared ignoring case.
+    fn fits_context(&self, context: Option<&str>) -> bool {
+        match (context, &self.context) {
+            (Some(wanted), Some(own)) => wanted.trim().eq_ignore_ascii_case(own),
+            _ => true,
+        }
+    }
+
+    /// How much the practice is kept up.
+    const fn lifecycle(&self) -> Lifecycle {
+        if self.archived {
+            Lifecycle::Retired
+        } else if self.maintenance {
+            Lifecycle::Maintenance
+        } else {
+            Lifecycle::Active
+        }
+    }
+
+    /// The section of `prac list --group-by` the practice goes in: where the section goes, and
+    /// its heading.
+    fn list_group(&self, group: ListGroup) -> (usize, String) {
+        match group {
+            ListGroup::Context => self.context.as_ref().map_or_else(
+                || (1, t!("list-no-context")),
+                |context| (0, context.clone()),
+            ),
+            ListGroup::State => {
+                let lifecycle = self.lifecycle();
+                let name = clap::ValueEnum::to_possible_value(&lifecycle)
+                    .expect("lifecycles aren't skipped")
+                    .get_name()
+                    .to_owned();
+                (lifecycle as usize, name)
+            }
         }
     }
 
-    /// Number of seconds elapsed since last practice
-    fn elapsed(&self) -> Duration {
-        let now = Utc::now();
+    /// The period the practice is due after, stretched for practices in maintenance.
+    fn due_period(&self) -> Option<Duration> {
+        let factor = if self.maintenance {
+            MAINTENANCE_FACTOR
+        } else {
+            1
+        };
+        self.period.map(|period| period * factor)
+    }
+
+    /// Number of seconds elapsed since last practice, as of `now`
+    fn elapsed(&self, now: DateTime<Utc>) -> Duration {
         now - self.logged
     }
-}
 
-impl Display for Practice {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if self.name.contains(' ') {
-            write!(f, "\"{}\"", self.name)
+    /// When the practice was last logged and when it is due, e.g. "last logged 3d ago, due in 4d
+    /// (grace until +1d)", as of `now`, for `prac list --verbose`.
+    fn describe_timing(&self, grace_period: Duration, now: DateTime<Utc>) -> String {
+        let ago = |at: DateTime<Utc>| crate::time::Span::from(now - at).format_abbreviated();
+        let last = match self.logs.last() {
+            Some(entry) => t!("timing-last-logged", ago = ago(entry.at)),
+            None if self.never_logged() => t!("timing-never-logged", ago = ago(self.created)),
+            None => t!("timing-last-logged", ago = ago(self.logged)),
+        };
+        let last = if self.never_logged() {
+            last
         } else {
-            write!(f, "{}", self.name)
+            t!("timing-with-age", age = ago(self.created), last = last)
+        };
+        let Some(period) = self.due_period() else {
+            return last;
+        };
+        // Short periods go by too fast for whole minutes or hours to tell how far along they are.
+        let span = |duration: Duration| {
+            let span = crate::time::Span::from(duration);
+            if period < Duration::hours(1) {
+                span.format_seconds()
+            } else {
+                span.format_abbreviated()
+            }
+        };
+        let elapsed = self.elapsed(now);
+        let due = if elapsed < period {
+            if grace_period > Duration::zero() {
+                t!(
+                    "timing-due-in-grace",
+                    due = span(period - elapsed),
+                    grace = span(grace_period)
+                )
+            } else {
+                t!("timing-due-in", due = span(period - elapsed))
+            }
+        } else if elapsed < period + grace_period {
+            t!(
+                "timing-in-grace",
+                ago = span(elapsed - period),
+                grace = span(period + grace_period - elapsed)
+            )
+        } else {
+            t!("timing-overdue", overdue = span(elapsed - period))
+        };
+        t!("timing", last = last, due = due)
+    }
+
+    /// Log a session, moving the start of the period as the [`LogMode`] says. Sessions from
+    /// before the period started are history, and leave it be.
+    fn log(&mut self, entry: LogEntry) {
+        let restart = match (self.log_mode, self.due_period()) {
+            _ if entry.at < self.logged => false,
+            (LogMode::Reset, _) | (_, None) => true,
+            (LogMode::Accumulate, Some(_)) => self
+                .target_time_per_period
+                .is_none_or(|target| self.logged_this_period(entry.at) + entry.time >= target),
+            (LogMode::Checkpoint, Some(period)) => entry.at >= self.logged + period,
+        };
+        if restart {
+            self.logged = entry.at;
         }
+        self.cumulative += entry.time;
+        self.insert_log(entry);
     }
-}
 
-/// Application state containing all data for prac, also w/ serialization/deserialization.
-#[derive(Serialize, Deserialize, Default)]
-pub struct State {
-    config: Config,
-    practices: BTreeMap<String, Practice>,
-}
+    /// Log `entry` and restart the bar, whatever the log mode.
+    fn restart(&mut self, entry: LogEntry) {
+        self.logged = self.logged.max(entry.at);
+        self.cumulative += entry.time;
+        self.insert_log(entry);
+    }
 
-impl State {
-    pub fn new() -> Self {
-        Self::default()
+    /// Take log entry `index` out, along with its time. Should the bar have restarted from it, it
+    /// restarts from the session before instead.
+    fn remove_log(&mut self, index: usize) -> Option<LogEntry> {
+        if index >= self.logs.len() {
+            return None;
+        }
+        let entry = self.logs.remove(index);
+        self.cumulative -= entry.time;
+        self.outcomes_stale = true;
+        if self.logged == entry.at {
+            self.logged = self.logs.last().map_or(self.created, |last| last.at);
+        }
+        Some(entry)
     }
 
-    pub fn update_version(&mut self) {
-        self.config.version = env!("CARGO_PKG_VERSION").to_owned();
+    /// Insert a log entry, keeping entries in chronological order. Entries before the last one,
+    /// or before the last outcome, leave the outcomes to be worked out afresh.
+    fn insert_log(&mut self, entry: LogEntry) {
+        let index = self
+            .logs
+            .partition_point(|existing| existing.at <= entry.at);
+        if index < self.logs.len()
+            || self
+                .outcomes
+                .last()
+                .is_some_and(|outcome| entry.at <= outcome.ended)
+        {
+            self.outcomes_stale = true;
+        }
+        self.logs.insert(index, entry);
     }
 
-    pub fn list(&self, cumulative: bool, period: bool, danger: bool) -> Result<()> {
-        if self.practices.is_empty() {
-            println!("You don't have any practices yet. Add some with `prac add`.");
-            return Ok(());
+    /// Fold `other` into this practice: time and history are combined, notes concatenated, and the
+    /// earlier creation (and later log) kept. This practice's name, period, and reason win, but
+    /// `other`'s fill in where this has none.
+    fn absorb(&mut self, other: Self) {
+        self.created = self.created.min(other.created);
+        self.logged = self.logged.max(other.logged);
+        self.cumulative += other.cumulative;
+        for entry in other.logs {
+            self.insert_log(entry);
+        }
+        self.note_history.extend(other.note_history);
+        let notes = match (self.notes.trim().is_empty(), other.notes.trim().is_empty()) {
+            (_, true) => self.notes.clone(),
+            (true, false) => other.notes.clone(),
+            (false, false) => format!("{}\n\n{}", self.notes.trim_end(), other.notes),
+        };
+        // Both sides' notes are replaced by the combination, unless they are it.
+        let ours = NoteRevision {
+            at: self.notes_edited,
+            notes: std::mem::replace(&mut self.notes, notes),
+        };
+        self.notes_edited = ours.at.max(other.notes_edited);
+        self.supersede_notes(ours);
+        self.supersede_notes(NoteRevision {
+            at: other.notes_edited,
+            notes: other.notes,
+        });
+        self.note_history.sort_by_key(|revision| revision.at);
+        if self.why.is_none() {
+            self.why = other.why;
+        }
+        if self.weekly_goal.is_none() {
+            self.weekly_goal = other.weekly_goal;
+        }
+        if self.target_time_per_period.is_none() {
+            self.target_time_per_period = other.target_time_per_period;
+        }
+        if self.session_length.is_none() {
+            self.session_length = other.session_length;
+        }
+        for item in other.checklist {
+            if !self.checklist.iter().any(|ours| ours.item == item.item) {
+                self.checklist.push(item);
+            }
+        }
+        for alias in other.aliases {
+            if !self.aliases.contains(&alias) {
+                self.aliases.push(alias);
+            }
+        }
+        for (key, value) in other.metadata {
+            self.metadata.entry(key).or_insert(value);
+        }
+        self.archived &= other.archived;
+    }
+
+    /// Replace the notes, keeping the previous version in [`Practice::note_history`].
+    fn set_notes(&mut self, notes: String, at: DateTime<Utc>) {
+        if notes == self.notes {
+            return;
         }
+        let previous = NoteRevision {
+            at: self.notes_edited.replace(at),
+            notes: std::mem::replace(&mut self.notes, notes),
+        };
+        self.supersede_notes(previous);
+    }
 
-        let start_messages = self
-            .practices
-            .keys()
-            .map(|name| format!("  {name} "))
-            .collect::<Vec<_>>();
+    /// Keep `revision`, replaced by the current notes, in [`Practice::note_history`], unless it is
+    /// the same as them, empty, or there already.
+    fn supersede_notes(&mut self, revision: NoteRevision) {
+        if revision.notes != self.notes
+            && !revision.notes.trim().is_empty()
+            && !self.note_history.contains(&revision)
+        {
+            self.note_history.push(revision);
+        }
+    }
 
-        let end_messages = &self
-            .practices
-            .values()
-            .map(|practice| {
-                let period_time = super::time::FlatTime::from(practice.period);
-                let cumulative_time = super::time::FlatTime::from(practice.cumulative);
+    /// Cumulative time logged before history was kept, and so missing from [`Practice::logs`].
+    fn untracked(&self) -> Duration {
+        let tracked = self
+            .logs
+            .iter()
+            .fold(Duration::zero(), |sum, entry| sum + entry.time);
+        (self.cumulative - tracked).max(Duration::zero())
+    }
 
-                match (cumulative, period) {
-                    (true, true) => format!(
-                        " {} c / {} p  ",
-                        cumulative_time.format_abbreviated(),
-                        period_time.format_abbreviated(),
-                    ),
-                    (true, false) => {
-                        format!(" {}  ", cumulative_time.format())
-                    }
-                    (false, true) => format!(" {}  ", period_time.format()),
-                    (false, false) => "  ".to_string(),
-                }
+    /// Whether the practice has never been logged.
+    const fn never_logged(&self) -> bool {
+        self.cumulative.is_zero() && self.logs.is_empty()
+    }
+
+    /// A practice is slumping once more than twice its period has passed since it was last logged.
+    fn is_slumping(&self, now: DateTime<Utc>) -> bool {
+        self.due_period()
+            .is_some_and(|period| self.elapsed(now) > period * 2)
+    }
+
+    /// How the period that ended at `ended` after starting at `started` went: on time if it was
+    /// logged within `period`, in grace if within the grace period after, else missed.
+    fn outcome(
+        started: DateTime<Utc>,
+        ended: DateTime<Utc>,
+        skipped: bool,
+        period: Duration,
+        grace_period: Duration,
+    ) -> Outcome {
+        let gap = ended - started;
+        if skipped {
+            Outcome::Skipped
+        } else if gap <= period {
+            Outcome::OnTime
+        } else if gap <= period + grace_period {
+            Outcome::Grace
+        } else {
+            Outcome::Missed
+        }
+    }
+
+    /// Work out the outcomes from the whole history, judged by the current period. A period that
+    /// went unlogged past its grace period was missed when it lapsed, not when it was logged.
+    fn replay_outcomes(&self, grace_period: Duration, now: DateTime<Utc>) -> Vec<PeriodOutcome> {
+        let Some(period) = self.due_period() else {
+            return Vec::new();
+        };
+        let mut previous = self.created;
+        let mut outcomes = self
+            .logs
+            .iter()
+            .map(|entry| {
+                let started = std::mem::replace(&mut previous, entry.at);
+                let outcome = Self::outcome(started, entry.at, entry.skipped, period, grace_period);
+                let ended = if outcome == Outcome::Missed {
+                    started + period + grace_period
+                } else {
+                    entry.at
+                };
+                PeriodOutcome { ended, outcome }
             })
             .collect::<Vec<_>>();
+        let lapsed = previous + period + grace_period;
+        if lapsed < now {
+            outcomes.push(PeriodOutcome {
+                ended: lapsed,
+                outcome: Outcome::Missed,
+            });
+        }
+        outcomes
+    }
 
-        let max_start_len = start_messages.iter().map(String::len).max().unwrap();
-        let max_end_len = end_messages.iter().map(String::len).max().unwrap();
+    /// Bring the outcomes up to date: one for each log since the last outcome, unless its period
+    /// already lapsed, and one for the current period if it has lapsed by `now`. After history was
+    /// changed, they are worked out afresh instead.
+    fn update_outcomes(&mut self, grace_period: Duration, now: DateTime<Utc>) {
+        if self.outcomes_stale {
+            self.outcomes = self.replay_outcomes(grace_period, now);
+            self.outcomes_stale = false;
+            return;
+        }
+        let Some(period) = self.due_period() else {
+            return;
+        };
+        let covered = self.outcomes.last().map(|outcome| outcome.ended);
+        let first_new = covered.map_or(0, |covered| {
+            self.logs.partition_point(|entry| entry.at <= covered)
+        });
+        for index in first_new..self.logs.len() {
+            let started = index
+                .checked_sub(1)
+                .map_or(self.created, |previous| self.logs[previous].at);
+            // The period already lapsed, and was counted as missed then.
+            if covered.is_some_and(|covered| covered > started) {
+                continue;
+            }
+            let entry = &self.logs[index];
+            let outcome = Self::outcome(started, entry.at, entry.skipped, period, grace_period);
+            let ended = if outcome == Outcome::Missed {
+                started + period + grace_period
+            } else {
+                entry.at
+            };
+            self.outcomes.push(PeriodOutcome { ended, outcome });
+        }
+        let started = self.logs.last().map_or(self.created, |entry| entry.at);
+        let lapsed = started + period + grace_period;
+        if lapsed < now
+            && self
+                .outcomes
+                .last()
+                .is_none_or(|outcome| outcome.ended <= started)
+        {
+            self.outcomes.push(PeriodOutcome {
+                ended: lapsed,
+                outcome: Outcome::Missed,
+            });
+        }
+    }
 
-        let padded_start_messages = start_messages
+    /// How each of the last `count` periods went, oldest first. Empty for log-only practices.
+    fn recent_outcomes(&self, count: usize) -> Vec<Outcome> {
+        if self.due_period().is_none() {
+            return Vec::new();
+        }
+        let recent = &self.outcomes[self.outcomes.len().saturating_sub(count)..];
+        recent.iter().map(|outcome| outcome.outcome).collect()
+    }
+
+    /// Number of periods in a row, up to the last, that were on time or within the grace period.
+    /// Skipped periods neither count nor break the streak.
+    fn streak(&self) -> usize {
+        self.recent_outcomes(usize::MAX)
             .iter()
-            .map(|s| format!("{s:>max_start_len$}"))
-            .collect::<Vec<_>>();
-        let padded_end_messages = end_messages
+            .rev()
+            .take_while(|outcome| !matches!(outcome, Outcome::Missed))
+            .filter(|outcome| !matches!(outcome, Outcome::Skipped))
+            .count()
+    }
+
+    /// How the last `count` periods went, absent without periods that ended yet.
+    fn adherence(&self, count: usize) -> Option<Adherence> {
+        Adherence::from_outcomes(self.recent_outcomes(count))
+    }
+
+    /// The period adaptive periods would change this practice's to, if it is in maintenance:
+    /// longer by `factor` after `streak` periods on time in a row, or shorter by it after a missed
+    /// one. Only periods that ended since the last change count.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    fn adapted_period(&self, factor: f64, streak: usize) -> Option<Duration> {
+        let period = self.period.filter(|_| self.maintenance && factor > 1.0)?;
+        let since = self
+            .period_adjustments
+            .last()
+            .map(|adjustment| adjustment.at);
+        let recent = self
+            .outcomes
             .iter()
-            .map(|s| format!("{s:<max_end_len$}"))
+            .filter(|outcome| since.is_none_or(|since| outcome.ended > since))
+            .map(|outcome| outcome.outcome)
+            .filter(|outcome| *outcome != Outcome::Skipped)
             .collect::<Vec<_>>();
+        let scaled = |by: f64| Duration::seconds((period.num_seconds() as f64 * by).round() as i64);
+        let streak = streak.max(1);
+        let adapted = match recent.last()? {
+            Outcome::Missed => scaled(factor.recip()),
+            _ if recent.len() >= streak
+                && recent[recent.len() - streak..]
+                    .iter()
+                    .all(|outcome| *outcome == Outcome::OnTime) =>
+            {
+                scaled(factor)
+            }
+            _ => return None,
+        };
+        let adapted = round_period(adapted);
+        (adapted != period).then_some(adapted)
+    }
 
-        let term_width = termion::terminal_size()
-            .context("failed to obtain termsize")?
-            .0;
-        let padding_width = max_start_len + max_end_len;
-        let bar_width = (term_width as usize)
-            .checked_sub(padding_width)
-            .with_context(|| {
-                format!("term width {term_width} too small, must be at least {padding_width}")
-            })?;
+    /// Change the period to `to` at `at`, as adaptive periods do, keeping track of the change.
+    fn adapt_period(&mut self, to: Duration, automatic: bool, at: DateTime<Utc>) {
+        if let Some(from) = self.period {
+            self.period_adjustments.push(PeriodAdjustment {
+                at,
+                from,
+                to,
+                automatic,
+            });
+        }
+        self.period = Some(to);
+    }
 
-        println!();
-        for (practice, start, end) in itertools::izip!(
-            self.practices.values(),
-            padded_start_messages,
-            padded_end_messages
-        ) {
-            let grace_adjusted_period = practice.period + self.config.user_config.grace_period;
-            #[allow(clippy::cast_precision_loss)]
-            let fraction = practice.elapsed().num_seconds() as f64
-                / grace_adjusted_period.num_seconds() as f64;
+    /// Average time between the last `count` logs, if there are enough to tell.
+    fn average_interval(&self, count: usize) -> Option<Duration> {
+        let recent = &self.logs[self.logs.len().saturating_sub(count)..];
+        let intervals = i32::try_from(recent.len().checked_sub(1)?).ok()?;
+        if intervals < DRIFT_MIN_INTERVALS {
+            return None;
+        }
+        Some((recent.last()?.at - recent.first()?.at) / intervals)
+    }
 
-            let whole_bar = format!("{}{}{}", start, crate::utils::bar(bar_width, fraction), end);
+    /// Start of the current budget period, counting whole periods from creation.
+    fn period_start(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let period = self.period?.num_seconds();
+        let elapsed = (now - self.created).num_seconds().max(0);
+        Some(self.created + Duration::seconds(elapsed - elapsed % period.max(1)))
+    }
 
-            println!("{whole_bar}");
+    /// Time logged toward the target time per period by `now`. Accumulating practices count from
+    /// the start of their period, leaving out the log that started it, which finished the last one.
+    fn logged_this_period(&self, now: DateTime<Utc>) -> Duration {
+        match self.log_mode {
+            LogMode::Accumulate => self
+                .logs
+                .iter()
+                .filter(|entry| entry.at > self.logged && entry.at <= now)
+                .fold(Duration::zero(), |sum, entry| sum + entry.time),
+            LogMode::Reset | LogMode::Checkpoint => self
+                .period_start(now)
+                .map_or_else(Duration::zero, |start| self.logged_since(start)),
         }
-        println!();
+    }
 
-        if danger {
-            let sum_progress: i64 = self
-                .practices
-                .values()
-                .map(|p| p.elapsed().num_seconds())
-                .sum();
-            let sum_period: i64 = self
-                .practices
-                .values()
-                .map(|p| (p.period + self.config.user_config.grace_period).num_seconds())
-                .sum();
+    /// The checklist, with whether each item was checked off since the period restarted.
+    fn checklist(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.checklist.iter().map(|item| {
+            let done = item.checked.is_some_and(|checked| checked >= self.logged);
+            (item.item.as_str(), done)
+        })
+    }
 
-            #[allow(clippy::cast_precision_loss)]
-            let sum_fraction = sum_progress as f64 / sum_period as f64;
+    /// Median time between logs, in whole days, if there are at least two.
+    fn typical_interval(&self) -> Option<Duration> {
+        let mut intervals = self
+            .logs
+            .windows(2)
+            .map(|pair| pair[1].at - pair[0].at)
+            .collect::<Vec<_>>();
+        intervals.sort();
+        let median = *intervals.get(intervals.len() / 2)?;
+        let days = (median.num_hours() + 12) / 24;
+        Some(Duration::days(days.max(1)))
+    }
 
-            // TODO make red
-            let sum_bar = crate::utils::bar(bar_width, sum_fraction);
-            let start = format!("  {} ", "danger");
-            let end = String::new();
+    /// Time logged since `since`.
+    fn logged_since(&self, since: DateTime<Utc>) -> Duration {
+        self.logs
+            .iter()
+            .filter(|entry| entry.at >= since)
+            .fold(Duration::zero(), |sum, entry| sum + entry.time)
+    }
+}
 
-            println!("{start:>max_start_len$}{sum_bar}{end:<max_end_len$}");
+impl Display for Practice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.name.contains(' ') {
+            write!(f, "\"{}\"", self.name)
+        } else {
+            write!(f, "{}", self.name)
         }
-
-        Ok(())
     }
+}
 
-    /// Find the name of a practice either validating an name input, or if not provided, prompting the user to select one.
-    pub fn find_name(&self) -> Result<&str> {
-        let options = &self.practices.keys().collect::<Vec<_>>();
+/// Display options for [`State::list`].
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Default)]
+pub struct ListOptions {
+    /// Show cumulative time alongside bars.
+    pub cumulative: bool,
+    /// Show period alongside bars.
+    pub period: bool,
+    /// Show the "danger bar" below the list.
+    pub danger: bool,
+    /// Colorize output.
+    pub color: bool,
+    /// Overrides [`UserConfig::bar_width`].
+    pub bar_width: Option<usize>,
+    /// Overrides [`UserConfig::bar_fill`].
+    pub bar_fill: Option<char>,
+    /// Overrides [`UserConfig::bar_empty`].
+    pub bar_empty: Option<char>,
+    /// Forces [`UserConfig::ascii`].
+    pub ascii: bool,
+    /// Also show archived practices (dimmed).
+    pub archived: bool,
+    /// Overrides [`UserConfig::list_sort`].
+    pub sort: Option<ListSort>,
+    /// Show how the last periods went after each bar.
+    pub sparkline: bool,
+    /// Show time logged this period against [`Practice::target_time_per_period`].
+    pub budget: bool,
+    /// Show when each practice was last logged and when it is due.
+    pub verbose: bool,
+    /// Only show practices that can be done in this [`Practice::context`].
+    pub context: Option<String>,
+    /// Show practices in sections, each in the order of [`Self::sort`].
+    pub group_by: Option<ListGroup>,
+    /// Only show practices at least this far through their (grace adjusted) period.
+    pub due_only: Option<f64>,
+    /// Only show practices logged within this long.
+    pub recent: Option<Duration>,
+    /// Also show hidden practices (see [`Practice::hidden`]).
+    pub hidden: bool,
+    /// Show names in full, rather than cut to [`UserConfig::list_name_width`].
+    pub full_names: bool,
+    /// Show aliases after names (see [`Practice::aliases`]).
+    pub aliases: bool,
+}
 
-        let selection_index = FuzzySelect::new()
-            .with_prompt("Select practice")
-            .items(options)
-            .interact_opt()
-            .context("Selection error.")?;
+/// Intervals between logs needed before `prac review` judges how a period fits.
+const DRIFT_MIN_INTERVALS: i32 = 3;
+/// Logs `prac review` averages intervals over, so old habits don't count.
+const DRIFT_LOGS: usize = 10;
+/// How far the average interval may stray from the period before `prac review` mentions it.
+const DRIFT_TOLERANCE: f64 = 0.25;
 
-        match selection_index {
-            Some(i) => Ok(options[i].as_str()),
-            None => bail!("No item selected"),
+/// A practice logged at a noticeably different pace than its period, found by
+/// [`State::drifting`].
+pub struct Drift<'a> {
+    pub name: &'a str,
+    pub period: Duration,
+    /// Average time between recent logs.
+    pub average: Duration,
+    /// A round period close to the average.
+    pub suggested: Duration,
+}
+
+/// A practice in maintenance whose period adaptive periods would change, found by
+/// [`State::adaptations`].
+pub struct Adaptation<'a> {
+    pub name: &'a str,
+    pub period: Duration,
+    pub adapted: Duration,
+}
+
+/// Number of past periods shown by `prac list --sparkline`.
+const SPARKLINE_PERIODS: usize = 10;
+/// Narrowest bar `prac list` cuts names short to make room for.
+const MIN_BAR_WIDTH: usize = 10;
+/// Number of past periods `prac stats` tallies adherence over, unless told otherwise.
+pub const ADHERENCE_PERIODS: usize = 30;
+
+/// How a period went.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// Logged within the period.
+    OnTime,
+    /// Logged within the grace period.
+    Grace,
+    Missed,
+    /// Skipped on purpose with `prac skip`.
+    Skipped,
+}
+
+/// How a period of a practice ended, and when: when it was logged, or, if it was missed, when it
+/// lapsed.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct PeriodOutcome {
+    ended: DateTime<Utc>,
+    outcome: Outcome,
+}
+
+impl Outcome {
+    /// Sparkline glyph, taller for better outcomes.
+    const fn glyph(self, ascii: bool) -> char {
+        match (self, ascii) {
+            (Self::OnTime, false) => '█',
+            (Self::Grace, false) => '▄',
+            (Self::Missed, false) => '▁',
+            (Self::Skipped, false) => '·',
+            (Self::OnTime, true) => '#',
+            (Self::Grace, true) => '+',
+            (Self::Missed, true) => '_',
+            (Self::Skipped, true) => '.',
         }
     }
 
-    pub fn get_notes(&self, name: &str) -> Result<&str> {
-        let notes = self
-            .practices
-            .get(name)
-            .with_context(|| format!("\"{name}\" not found."))?
-            .notes
-            .as_str();
-        Ok(notes)
+    const fn style(self) -> Style {
+        match self {
+            Self::OnTime => Style::new().green(),
+            Self::Grace => Style::new().yellow(),
+            Self::Missed => Style::new().red(),
+            Self::Skipped => Style::new().dimmed(),
+        }
     }
+}
 
-    pub fn get_user_config(&self) -> &UserConfig {
-        &self.config.user_config
-    }
+/// Fields searched by [`State::find`], in the order results are printed.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Name,
+    Why,
+    Notes,
+    /// Lines of earlier note revisions no longer in the current notes.
+    NoteHistory,
+}
 
-    /// Get the path to the default location state file.
-    /// Search order: [`dirs::data_dir`]/prac/prac.json, [`dirs::home_dir`]/.prac.json
-    /// This may be overridden elsewhere, in either the `PRAC_PATH` env var, or with the --file arg.
-    pub fn get_path() -> Result<PathBuf> {
-        if let Some(data_home) = dirs::data_dir() {
-            let default_dir = data_home.join("prac");
-            std::fs::create_dir_all(&default_dir)
-                .with_context(|| format!("could not create {}", default_dir.display()))?;
-            let path = default_dir.join("prac.json");
-            Ok(path)
-        } else {
-            let path = dirs::home_dir()
-                .context("could not find home directory")?
-                .join(".prac.json");
-            Ok(path)
+impl SearchField {
+    const ALL: [Self; 4] = [Self::Name, Self::Why, Self::Notes, Self::NoteHistory];
+}
+
+impl Display for SearchField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name => write!(f, "{}", t!("field-names")),
+            Self::Why => write!(f, "{}", t!("field-why")),
+            Self::Notes => write!(f, "{}", t!("field-notes")),
+            Self::NoteHistory => write!(f, "{}", t!("field-note-history")),
         }
     }
 }
 
-/// See [``Cli::SubCommand``](crate::cli::SubCommand) for documentation.
-#[warn(clippy::print_stdout, clippy::print_stderr)]
-#[allow(clippy::missing_docs_in_private_items)]
-// TODO move option hanlding, completely abstract interface from CLI
+/// A single hit of [`State::find`].
+#[derive(Serialize)]
+pub struct FindMatch<'a> {
+    field: SearchField,
+    practice: &'a str,
+    /// The matching line, trimmed.
+    text: &'a str,
+}
+
+/// Results of [`State::find`].
+#[derive(Serialize)]
+pub struct FindReport<'a> {
+    matches: Vec<FindMatch<'a>>,
+}
+
+/// A practice as shown by `prac list`, for machine-readable output.
 #[serde_as]
-#[derive(Serialize, Deserialize)]
-pub enum StateTransition {
-    Add {
-        name: String,
-        #[serde_as(as = "serde_with::DurationSeconds<i64>")]
-        period: Duration,
-    },
-    Log {
-        name: String,
-        #[serde_as(as = "serde_with::DurationSeconds<i64>")]
-        /// Maximum amount of time intended to spend. Both useful as a time-boxing strategy and as
-        /// a backstop to neglected termination.
-        time: Duration,
-    },
-    Notes {
-        name: String,
-        notes: String,
-    },
-    Remove {
-        name: String,
-    },
+#[derive(Serialize)]
+pub struct PracticeSummary<'a> {
+    name: &'a str,
+    archived: bool,
+    why: Option<&'a str>,
+    lifecycle: Lifecycle,
+    /// When the practice was added.
+    created: DateTime<Utc>,
+    /// Last time practice was logged
+    logged: DateTime<Utc>,
+    /// Absent for log-only practices.
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    #[serde(rename = "period_in_seconds")]
+    period: Option<Duration>,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "elapsed_in_seconds")]
+    elapsed: Duration,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "cumulative_in_seconds")]
+    cumulative: Duration,
+    /// Fraction of the grace adjusted period elapsed, i.e. how full the bar is (may exceed 1).
+    /// Absent for log-only practices.
+    progress: Option<f64>,
+    /// Section the practice is listed in, with `--group-by`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    aliases: &'a [String],
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: &'a BTreeMap<String, String>,
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    #[serde(rename = "weekly_goal_in_seconds")]
+    weekly_goal: Option<Duration>,
+    /// Time logged this week, absent without a weekly goal.
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    #[serde(rename = "this_week_in_seconds")]
+    this_week: Option<Duration>,
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    #[serde(rename = "target_time_per_period_in_seconds")]
+    target_time_per_period: Option<Duration>,
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    #[serde(rename = "session_length_in_seconds")]
+    session_length: Option<Duration>,
+    log_mode: LogMode,
+    /// Time of day the practice goes best, e.g. "05:00-12:00".
+    best_time: Option<TimeWindow>,
+    context: Option<&'a str>,
+    checklist: Vec<ChecklistStatus<'a>>,
+    /// Time logged in the current period, absent without a target time per period.
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    #[serde(rename = "this_period_in_seconds")]
+    this_period: Option<Duration>,
+    /// How the last periods went, oldest first. Empty for log-only practices.
+    recent: Vec<Outcome>,
+}
+
+/// Placeholders of `prac list --template`, besides `{meta.<field>}` for metadata fields.
+pub const LIST_PLACEHOLDERS: [&str; 15] = [
+    "name",
+    "period",
+    "period_seconds",
+    "elapsed",
+    "elapsed_seconds",
+    "elapsed_pct",
+    "last_logged",
+    "created",
+    "cumulative",
+    "cumulative_seconds",
+    "context",
+    "state",
+    "group",
+    "why",
+    "aliases",
+];
+
+impl PracticeSummary<'_> {
+    /// The value of `placeholder` for `prac list --template`. Placeholders the practice has no
+    /// value for, e.g. `{period}` of a log-only practice, are empty.
+    fn placeholder(&self, placeholder: &str) -> Option<String> {
+        if let Some(field) = placeholder.strip_prefix("meta.") {
+            return Some(self.metadata.get(field).cloned().unwrap_or_default());
+        }
+        let span = |duration: Duration| crate::time::Span::from(duration).format();
+        let at = |at: DateTime<Utc>| {
+            at.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        };
+        Some(match placeholder {
+            "name" => self.name.to_owned(),
+            "period" => self.period.map(span).unwrap_or_default(),
+            "period_seconds" => self
+                .period
+                .map(|period| period.num_seconds().to_string())
+                .unwrap_or_default(),
+            "elapsed" => span(self.elapsed),
+            "elapsed_seconds" => self.elapsed.num_seconds().to_string(),
+            "elapsed_pct" => self
+                .progress
+                .map(|progress| format!("{:.0}", progress * 100.0))
+                .unwrap_or_default(),
+            "last_logged" => at(self.logged),
+            "created" => at(self.created),
+            "cumulative" => span(self.cumulative),
+            "cumulative_seconds" => self.cumulative.num_seconds().to_string(),
+            "context" => self.context.unwrap_or_default().to_owned(),
+            "state" => clap::ValueEnum::to_possible_value(&self.lifecycle)
+                .expect("lifecycles aren't skipped")
+                .get_name()
+                .to_owned(),
+            "group" => self.group.clone().unwrap_or_default(),
+            "why" => self.why.unwrap_or_default().to_owned(),
+            "aliases" => self.aliases.join(","),
+            _ => return None,
+        })
+    }
+}
+
+/// A checklist item as shown by `prac list`.
+#[derive(Serialize)]
+pub struct ChecklistStatus<'a> {
+    item: &'a str,
+    /// Whether the item was checked off this period.
+    done: bool,
+}
+
+/// Everything `prac list` shows, for machine-readable output.
+#[derive(Serialize)]
+pub struct ListReport<'a> {
+    practices: Vec<PracticeSummary<'a>>,
+    /// Fraction shown by the danger bar, absent without unarchived periodic practices.
+    danger: Option<f64>,
+}
+
+impl ListReport<'_> {
+    /// Print a line per practice, rendered with `template` (see [`LIST_PLACEHOLDERS`]).
+    pub fn print_template(&self, template: &str) -> Result<()> {
+        let template = crate::template::unescape(template);
+        for practice in &self.practices {
+            let line = crate::template::render(&template, &LIST_PLACEHOLDERS, |placeholder| {
+                practice.placeholder(placeholder)
+            })?;
+            println!("{line}");
+        }
+        Ok(())
+    }
+}
+
+/// How often periods ended on time, within the grace period, or missed, as fractions of those
+/// not skipped.
+#[derive(Serialize, Clone, Copy)]
+pub struct Adherence {
+    /// Periods counted, not including skipped ones.
+    periods: usize,
+    skipped: usize,
+    on_time: f64,
+    grace: f64,
+    missed: f64,
+}
+
+impl Adherence {
+    /// Tally `outcomes`, absent if all of them were skipped.
+    #[allow(clippy::cast_precision_loss)]
+    fn from_outcomes(outcomes: impl IntoIterator<Item = Outcome>) -> Option<Self> {
+        let (mut on_time, mut grace, mut missed, mut skipped) = (0, 0, 0, 0);
+        for outcome in outcomes {
+            match outcome {
+                Outcome::OnTime => on_time += 1,
+                Outcome::Grace => grace += 1,
+                Outcome::Missed => missed += 1,
+                Outcome::Skipped => skipped += 1,
+            }
+        }
+        let periods = on_time + grace + missed;
+        let fraction = |count: usize| count as f64 / periods as f64;
+        (periods > 0).then(|| Self {
+            periods,
+            skipped,
+            on_time: fraction(on_time),
+            grace: fraction(grace),
+            missed: fraction(missed),
+        })
+    }
+
+    /// E.g. "completed 78% of the last 30 periods on time, 12% in grace, 10% missed".
+    fn describe(&self) -> String {
+        let percent = |fraction: f64| format!("{:.0}", fraction * 100.0);
+        t!(
+            "adherence",
+            periods = self.periods,
+            on_time = percent(self.on_time),
+            grace = percent(self.grace),
+            missed = percent(self.missed),
+        )
+    }
+}
+
+/// How a practice's last periods went, for `prac stats`.
+#[derive(Serialize)]
+pub struct PracticeAdherence<'a> {
+    name: &'a str,
+    #[serde(flatten)]
+    adherence: Adherence,
+}
+
+/// A change adaptive periods made to a practice's period, for `prac stats`.
+#[derive(Serialize)]
+pub struct PracticeAdjustment<'a> {
+    name: &'a str,
+    #[serde(flatten)]
+    adjustment: &'a PeriodAdjustment,
+}
+
+/// Totals over every practice, for `prac stats` and `prac serve`.
+#[serde_as]
+#[derive(Serialize)]
+pub struct Stats<'a> {
+    /// Practices not archived.
+    practices: usize,
+    archived: usize,
+    /// Unarchived practices past their period and grace period.
+    overdue: usize,
+    /// Fraction shown by the danger bar, absent without unarchived periodic practices.
+    danger: Option<f64>,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "total_time_in_seconds")]
+    total_time: Duration,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "this_week_in_seconds")]
+    this_week: Duration,
+    /// Sessions logged since the start of the week.
+    sessions_this_week: usize,
+    /// When the oldest practice was added, absent without practices.
+    practicing_since: Option<DateTime<Utc>>,
+    /// How the last periods of every unarchived practice went, absent if none has ended yet.
+    adherence: Option<Adherence>,
+    /// How the last periods of each unarchived practice went, for those with any.
+    by_practice: Vec<PracticeAdherence<'a>>,
+    /// Changes adaptive periods made to the periods of unarchived practices, oldest first.
+    period_adjustments: Vec<PracticeAdjustment<'a>>,
+}
+
+impl Stats<'_> {
+    /// Print the totals for humans, then a line per practice.
+    pub fn print(&self) {
+        if let Some(since) = self.practicing_since {
+            println!(
+                "{}",
+                t!(
+                    "stats-time",
+                    since = since
+                        .with_timezone(&Local)
+                        .format("%a %Y-%m-%d")
+                        .to_string(),
+                    total = crate::time::Span::from(self.total_time).format_abbreviated(),
+                    week = crate::time::Span::from(self.this_week).format_abbreviated(),
+                    sessions = self.sessions_this_week,
+                )
+            );
+        }
+        println!(
+            "{}",
+            t!(
+                "stats-practices",
+                practices = self.practices,
+                archived = self.archived,
+                overdue = self.overdue,
+            )
+        );
+        if let Some(adherence) = &self.adherence {
+            println!(
+                "{}",
+                t!("stats-adherence-overall", adherence = adherence.describe())
+            );
+        }
+        if self.by_practice.is_empty() {
+            return;
+        }
+        println!();
+        let width = self
+            .by_practice
+            .iter()
+            .map(|practice| practice.name.chars().count())
+            .max()
+            .unwrap_or_default();
+        for practice in &self.by_practice {
+            println!(
+                "  {:width$}  {}",
+                practice.name,
+                practice.adherence.describe()
+            );
+        }
+        if self.period_adjustments.is_empty() {
+            return;
+        }
+        println!();
+        println!("{}", t!("stats-adjustments"));
+        for PracticeAdjustment { name, adjustment } in &self.period_adjustments {
+            let span = |duration: Duration| crate::time::Span::from(duration).format();
+            println!(
+                "  {}",
+                t!(
+                    "stats-adjustment",
+                    at = adjustment
+                        .at
+                        .with_timezone(&Local)
+                        .format("%a %Y-%m-%d")
+                        .to_string(),
+                    name = *name,
+                    from = span(adjustment.from),
+                    to = span(adjustment.to),
+                    automatic = adjustment.automatic.to_string(),
+                )
+            );
+        }
+    }
+}
+
+/// A log entry as shown by `prac history`, numbered from 1.
+#[serde_as]
+#[derive(Serialize)]
+pub struct HistoryEntry {
+    number: usize,
+    at: DateTime<Utc>,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "time_in_seconds")]
+    time: Duration,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    /// Whether the period was skipped on purpose, rather than practiced.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    skipped: bool,
+}
+
+impl HistoryEntry {
+    /// Print the entry on a line after `start`, its number and when it was.
+    fn print(&self, start: &str) {
+        let time = if self.skipped {
+            t!("history-skipped")
+        } else {
+            crate::time::Span::from(self.time).format()
+        };
+        match &self.note {
+            Some(note) => println!("{start}  {time}  {note}"),
+            None => println!("{start}  {time}"),
+        }
+    }
+}
+
+/// The log history of a practice.
+#[serde_as]
+#[derive(Serialize)]
+pub struct HistoryReport<'a> {
+    name: &'a str,
+    entries: Vec<HistoryEntry>,
+    /// Cumulative time logged before history was kept.
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "untracked_in_seconds")]
+    untracked: Duration,
+    /// Whether only sessions in a time range are shown.
+    #[serde(skip)]
+    filtered: bool,
+}
+
+impl HistoryReport<'_> {
+    /// Print the history for humans.
+    pub fn print(&self) {
+        self.print_if_empty();
+        let width = self.number_width();
+        for entry in &self.entries {
+            let at = entry.at.with_timezone(&Local).format("%a %Y-%m-%d %H:%M");
+            entry.print(&format!("  {:>width$}  {at}", entry.number));
+        }
+        self.print_untracked();
+    }
+
+    /// Print the history for humans, grouped by day with each day's total.
+    pub fn print_timeline(&self) {
+        self.print_if_empty();
+        let width = self.number_width();
+        let days = self
+            .entries
+            .iter()
+            .group_by(|entry| entry.at.with_timezone(&Local).date_naive());
+        for (day, entries) in &days {
+            let entries = entries.collect::<Vec<_>>();
+            let total = entries
+                .iter()
+                .fold(Duration::zero(), |total, entry| total + entry.time);
+            println!(
+                "{}  {}",
+                day.format("%a %Y-%m-%d"),
+                crate::time::Span::from(total).format()
+            );
+            for entry in entries {
+                let at = entry.at.with_timezone(&Local).format("%H:%M");
+                entry.print(&format!("  {:>width$}  {at}", entry.number));
+            }
+        }
+        self.print_untracked();
+    }
+
+    fn print_if_empty(&self) {
+        if !self.entries.is_empty() {
+            return;
+        }
+        if self.filtered {
+            println!("{}", t!("history-empty-range", name = self.name));
+        } else {
+            println!("{}", t!("history-empty", name = self.name));
+        }
+    }
+
+    /// Width of the widest session number, to align them.
+    fn number_width(&self) -> usize {
+        self.entries
+            .last()
+            .map_or(1, |entry| entry.number.to_string().len())
+    }
+
+    fn print_untracked(&self) {
+        if !self.untracked.is_zero() {
+            println!(
+                "  {}",
+                t!(
+                    "history-untracked",
+                    time = crate::time::Span::from(self.untracked).format()
+                )
+            );
+        }
+    }
+}
+
+/// A removed practice, kept for [`UserConfig::trash_retention`] in case it's wanted back.
+#[derive(Serialize, Deserialize)]
+pub struct TrashedPractice {
+    removed: DateTime<Utc>,
+    practice: Practice,
+}
+
+/// A trashed practice as shown by `prac trash list`.
+#[derive(Serialize)]
+pub struct TrashEntry<'a> {
+    name: &'a str,
+    removed: DateTime<Utc>,
+    /// When the practice will be purged for good.
+    purged: DateTime<Utc>,
+}
+
+/// Contents of the trash, most recently removed first.
+#[derive(Serialize)]
+pub struct TrashReport<'a> {
+    entries: Vec<TrashEntry<'a>>,
+}
+
+impl TrashReport<'_> {
+    /// Print the trash for humans.
+    pub fn print(&self) {
+        if self.entries.is_empty() {
+            println!("{}", t!("trash-empty"));
+        }
+        for entry in &self.entries {
+            println!(
+                "  {}",
+                t!(
+                    "trash-entry",
+                    name = entry.name,
+                    removed = entry
+                        .removed
+                        .with_timezone(&Local)
+                        .format("%a %Y-%m-%d %H:%M")
+                        .to_string(),
+                    purged = entry
+                        .purged
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d")
+                        .to_string(),
+                )
+            );
+        }
+    }
+}
+
+/// A practice created by [`State::import`].
+#[serde_as]
+#[derive(Serialize)]
+pub struct ImportedPractice {
+    name: String,
+    completions: usize,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "period_in_seconds")]
+    period: Duration,
+    /// Whether the period was guessed from the history, the tracker having no schedule.
+    guessed_period: bool,
+}
+
+/// What [`State::import`] did.
+#[derive(Serialize)]
+pub struct ImportReport {
+    imported: Vec<ImportedPractice>,
+    /// Habits not imported, as a practice of the same name exists.
+    skipped: Vec<String>,
+}
+
+impl ImportReport {
+    /// Print the import for humans.
+    pub fn print(&self) {
+        for practice in &self.imported {
+            let period = crate::time::Span::from(practice.period).format();
+            let message = if practice.guessed_period {
+                t!(
+                    "imported-practice-guessed",
+                    name = &practice.name,
+                    count = practice.completions,
+                    period = period
+                )
+            } else {
+                t!(
+                    "imported-practice",
+                    name = &practice.name,
+                    count = practice.completions,
+                    period = period
+                )
+            };
+            println!("  {message}");
+        }
+        if !self.skipped.is_empty() {
+            println!(
+                "  {}",
+                t!("skipped-existing", names = self.skipped.join(", "))
+            );
+        }
+        if self.imported.is_empty() && self.skipped.is_empty() {
+            println!("{}", t!("nothing-to-import"));
+        }
+    }
+}
+
+/// Time logged to a practice by [`State::import_time`].
+#[serde_as]
+#[derive(Serialize)]
+pub struct ImportedTime {
+    practice: String,
+    entries: usize,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "time_in_seconds")]
+    time: Duration,
+}
+
+/// What [`State::import_time`] did.
+#[derive(Serialize)]
+pub struct TimeImportReport {
+    logged: Vec<ImportedTime>,
+    /// Entries already imported before.
+    duplicates: usize,
+    /// Projects matching no practice, with how many entries they had.
+    unmatched: BTreeMap<String, usize>,
+}
+
+impl TimeImportReport {
+    /// Print the import for humans.
+    pub fn print(&self) {
+        for logged in &self.logged {
+            println!(
+                "  {}",
+                t!(
+                    "imported-time",
+                    time = crate::time::Span::from(logged.time).format(),
+                    name = &logged.practice,
+                    count = logged.entries
+                )
+            );
+        }
+        if self.duplicates > 0 {
+            println!(
+                "  {}",
+                t!("imported-time-duplicates", count = self.duplicates)
+            );
+        }
+        if !self.unmatched.is_empty() {
+            let projects = self
+                .unmatched
+                .iter()
+                .map(|(project, count)| {
+                    let project = if project.is_empty() {
+                        t!("no-project")
+                    } else {
+                        project.clone()
+                    };
+                    format!("{project} ({count})")
+                })
+                .join(", ");
+            println!("  {}", t!("imported-time-unmatched", projects = projects));
+        }
+        if self.logged.is_empty() && self.duplicates == 0 && self.unmatched.is_empty() {
+            println!("{}", t!("nothing-to-import"));
+        }
+    }
+}
+
+/// A practice as reported by [`TransitionReport`].
+#[serde_as]
+#[derive(Serialize)]
+pub struct PracticeResult {
+    name: String,
+    archived: bool,
+    logged: DateTime<Utc>,
+    /// Absent for log-only practices.
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    #[serde(rename = "period_in_seconds")]
+    period: Option<Duration>,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "cumulative_in_seconds")]
+    cumulative: Duration,
+    /// Periods in a row the practice has been kept up.
+    streak: usize,
+}
+
+/// What a transition changed, for the machine-readable output of commands that change practices.
+#[derive(Serialize)]
+pub struct TransitionReport {
+    /// Kind of transition, see [`StateTransition::name`].
+    transition: &'static str,
+    /// The practice beforehand, absent if it was added or restored.
+    before: Option<PracticeResult>,
+    /// The practice afterwards, absent if it was removed.
+    after: Option<PracticeResult>,
+    /// Name of the practice afterwards, to look it up once the transition is applied.
+    #[serde(skip)]
+    name_after: Option<String>,
+}
+
+impl TransitionReport {
+    /// Start a report on `transition`, before it is applied to `state`.
+    pub fn new(state: &State, transition: &StateTransition) -> Self {
+        let (before, after) = transition.practice_names();
+        Self {
+            transition: transition.name(),
+            before: before.and_then(|name| state.practice_result(name)),
+            after: None,
+            name_after: after.map(|name| name.trim().to_owned()),
+        }
+    }
+
+    /// Finish the report once the transition has been applied to `state`.
+    pub fn finish(mut self, state: &State) -> Self {
+        self.after = self
+            .name_after
+            .as_deref()
+            .and_then(|name| state.practice_result(name));
+        self
+    }
+}
+
+/// Payload posted to the configured webhooks when a practice is logged.
+#[serde_as]
+#[derive(Serialize)]
+pub struct LogEvent {
+    /// Always "log", so receivers can tell events apart should there ever be others.
+    event: &'static str,
+    practice: String,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "duration_in_seconds")]
+    duration: Duration,
+    timestamp: DateTime<Utc>,
+    /// Periods in a row the practice has been kept up, counting this log.
+    streak: usize,
+    /// A summary for humans, which is what Discord shows of the payload.
+    content: String,
+}
+
+/// Application state containing all data for prac, also w/ serialization/deserialization.
+#[derive(Serialize, Deserialize, Default)]
+pub struct State {
+    config: Config,
+    practices: BTreeMap<String, Practice>,
+    /// Removed practices, oldest first.
+    #[serde(default)]
+    trash: Vec<TrashedPractice>,
+    /// Practices best done before others on the same day, see `prac depend`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    dependencies: Vec<Dependency>,
+    /// Sessions started with `prac start` and not yet stopped.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    running: Vec<RunningSession>,
+    /// Key to encrypt the statefile with, if it is encrypted. Never saved, of course.
+    #[serde(skip)]
+    key: Option<crypt::Key>,
+    /// Transitions applied since the state was last saved, for the audit log.
+    #[serde(skip)]
+    unaudited: Vec<AuditEntry>,
+    /// The statefile as last read or written, unencrypted, to tell whether saving changes it.
+    #[serde(skip)]
+    on_disk: Option<Vec<u8>>,
+    /// Whether the statefile was backed up before this run first changed it.
+    #[serde(skip)]
+    backed_up: bool,
+    /// What "now" is, see [`crate::clock`].
+    #[serde(skip)]
+    clock: SharedClock,
+    /// Why the statefile mustn't be changed, if it mustn't.
+    #[serde(skip)]
+    read_only: Option<ReadOnly>,
+}
+
+/// Why a statefile is only read, and never saved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadOnly {
+    /// `--read-only` was given.
+    Asked,
+    /// The statefile, or the directory it is in, isn't writable.
+    NotWritable,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Print the bar of practice `name` on a line of its own, with its streak, as feedback right
+    /// after logging it. Log-only practices show their cumulative time instead.
+    pub fn print_bar(&self, name: &str, color: bool) -> Result<()> {
+        let practice = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?;
+        let user_config = &self.config.user_config;
+        let Some(fraction) = self.progress(practice) else {
+            let total = crate::time::Span::from(practice.cumulative).format();
+            println!("{}", t!("log-feedback-total", name = name, total = total));
+            return Ok(());
+        };
+        let start = format!("  {name} ");
+        let streak = practice.streak();
+        let end = if streak > 0 {
+            format!("  {}", t!("log-feedback-streak", count = streak))
+        } else {
+            String::new()
+        };
+        let term_width = crossterm::terminal::size().map_or(80, |(width, _)| usize::from(width));
+        let mut bar_width = term_width.saturating_sub(start.len() + end.len());
+        if let Some(max_width) = user_config.bar_width.filter(|&width| width > 0) {
+            bar_width = bar_width.min(max_width);
+        }
+        let glyphs = crate::utils::BarGlyphs::new(
+            user_config.bar_fill,
+            user_config.bar_empty,
+            user_config.ascii,
+        )
+        .with_grace(user_config.bar_grace, user_config.ascii);
+        println!(
+            "{}{}{end}",
+            paint(&start, Style::new().bold(), color),
+            self.paint_progress_bar(
+                practice,
+                bar_width,
+                glyphs,
+                (gradient(fraction), Style::new().yellow()),
+                color
+            ),
+        );
+        Ok(())
+    }
+
+    /// Whether this is the made-up state of `prac --demo`, which mustn't reach hooks or webhooks.
+    pub const fn is_demo(&self) -> bool {
+        self.config.demo
+    }
+
+    pub fn update_version(&mut self) {
+        self.config.version = env!("CARGO_PKG_VERSION").to_owned();
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub fn list(&self, options: &ListOptions) -> Result<()> {
+        let user_config = &self.config.user_config;
+        let now = self.now();
+        let cumulative = options.cumulative || user_config.list_cumulative;
+        let period = options.period || user_config.list_period;
+        let danger = options.danger || user_config.list_danger;
+        let sparkline = options.sparkline || user_config.list_sparkline;
+        let budget = options.budget || user_config.list_budget;
+        let verbose = options.verbose || user_config.list_verbose;
+        let color = options.color;
+        let ascii = options.ascii || user_config.ascii || !crate::utils::unicode_terminal();
+        let glyphs = crate::utils::BarGlyphs::new(
+            options.bar_fill.or(user_config.bar_fill),
+            options.bar_empty.or(user_config.bar_empty),
+            ascii,
+        )
+        .with_grace(user_config.bar_grace, ascii);
+
+        if self.practices.is_empty() {
+            println!("{}", t!("list-no-practices"));
+            return Ok(());
+        }
+
+        let practices = self.sorted_practices(options);
+        if practices.is_empty() {
+            if self.practices.values().all(|practice| practice.archived) {
+                println!("{}", t!("list-all-archived"));
+            } else {
+                println!("{}", t!("list-none-shown"));
+            }
+            return Ok(());
+        }
+        // Log-only practices have no bar to show, just their totals.
+        let (practices, log_only): (Vec<_>, Vec<_>) = practices
+            .into_iter()
+            .partition(|(_, practice)| practice.period.is_some());
+
+        let end_messages = &practices
+            .iter()
+            .map(|(_, practice)| {
+                let period_time = crate::time::Span::from(practice.period.unwrap_or_default());
+                let cumulative_time = crate::time::Span::from(practice.cumulative);
+
+                match (cumulative, period) {
+                    (true, true) => format!(
+                        " {}  ",
+                        t!(
+                            "list-cumulative-and-period",
+                            cumulative = cumulative_time.format_abbreviated(),
+                            period = period_time.format_abbreviated(),
+                        )
+                    ),
+                    (true, false) => {
+                        format!(" {}  ", cumulative_time.format())
+                    }
+                    (false, true) => format!(" {}  ", period_time.format()),
+                    (false, false) => "  ".to_string(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Practices with a weekly goal (or, with `--budget`, a target time per period) get more
+        // bars, labelled and padded like the first. These fill up, rather than drain, as time is
+        // logged.
+        let targets = practices
+            .iter()
+            .map(|(_, practice)| {
+                let weekly = self.weekly_progress(practice).map(|progress| {
+                    (
+                        format!("  {} ", t!("list-week")),
+                        progress,
+                        Style::new().cyan(),
+                    )
+                });
+                let budget = budget_progress(practice, now)
+                    .filter(|_| budget)
+                    .map(|progress| {
+                        (
+                            format!("  {} ", t!("list-budget")),
+                            progress,
+                            Style::new().magenta(),
+                        )
+                    });
+                weekly
+                    .into_iter()
+                    .chain(budget)
+                    .map(|(label, (done, target), style)| {
+                        let end = format!(
+                            " {} / {}  ",
+                            crate::time::Span::from(done).format_abbreviated(),
+                            crate::time::Span::from(target).format_abbreviated(),
+                        );
+                        (label, done, target, end, style)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let max_end_len = end_messages
+            .iter()
+            .map(|end| end.chars().count())
+            .chain(
+                targets
+                    .iter()
+                    .flatten()
+                    .map(|(_, _, _, end, _)| end.chars().count()),
+            )
+            .max()
+            .unwrap_or_default();
+
+        let term_width = usize::from(
+            crossterm::terminal::size()
+                .context(t!("termsize-failed"))?
+                .0,
+        );
+        // Sparklines go between the bar and the end message, padded to the same width.
+        let sparkline_width = if sparkline { SPARKLINE_PERIODS + 1 } else { 0 };
+        // Long names are cut short, so that they leave room for a bar and line up.
+        let name_width = if options.full_names {
+            usize::MAX
+        } else {
+            let room = term_width.saturating_sub(sparkline_width + max_end_len + MIN_BAR_WIDTH + 3);
+            match user_config.list_name_width {
+                0 => room,
+                width => width.min(room),
+            }
+        };
+        let start_messages = practices
+            .iter()
+            .map(|(name, practice)| {
+                let name = crate::utils::ellipsize(name, name_width, ascii);
+                if options.aliases && !practice.aliases.is_empty() {
+                    format!("  {name} ({}) ", practice.aliases.join(", "))
+                } else {
+                    format!("  {name} ")
+                }
+            })
+            .collect::<Vec<_>>();
+        let max_start_len = start_messages
+            .iter()
+            .map(|start| start.chars().count())
+            .chain(
+                targets
+                    .iter()
+                    .flatten()
+                    .map(|(label, ..)| label.chars().count()),
+            )
+            .max()
+            .unwrap_or_default();
+
+        let padded_start_messages = start_messages
+            .iter()
+            .map(|s| format!("{s:>max_start_len$}"))
+            .collect::<Vec<_>>();
+        let padded_end_messages = end_messages
+            .iter()
+            .map(|s| format!("{s:<max_end_len$}"))
+            .collect::<Vec<_>>();
+
+        let padding_width = max_start_len + sparkline_width + max_end_len;
+        let mut bar_width = term_width.checked_sub(padding_width).with_context(|| {
+            t!(
+                "term-too-narrow",
+                width = term_width,
+                needed = padding_width
+            )
+        })?;
+        let max_width = options.bar_width.or(user_config.bar_width);
+        if let Some(max_width) = max_width.filter(|&width| width > 0) {
+            bar_width = bar_width.min(max_width);
+        }
+
+        // Sections of `--group-by`, with how many practices each has and their total time.
+        let groups = options.group_by.map(|group| {
+            practices
+                .iter()
+                .group_by(|(_, practice)| practice.list_group(group).1)
+                .into_iter()
+                .map(|(heading, practices)| {
+                    let (count, cumulative) = practices.fold(
+                        (0, Duration::zero()),
+                        |(count, cumulative), (_, practice)| {
+                            (count + 1, cumulative + practice.cumulative)
+                        },
+                    );
+                    (heading, count, cumulative)
+                })
+                .collect::<Vec<_>>()
+        });
+        let mut groups = groups.into_iter().flatten();
+        let mut group = None;
+
+        let has_bars = !practices.is_empty();
+        if has_bars {
+            println!();
+        }
+        for ((_, practice), start, end, targets) in itertools::izip!(
+            practices,
+            padded_start_messages,
+            padded_end_messages,
+            targets
+        ) {
+            if let Some(group_by) = options.group_by {
+                let heading = practice.list_group(group_by).1;
+                if group.as_ref() != Some(&heading) {
+                    if group.is_some() {
+                        println!();
+                    }
+                    if let Some((_, count, cumulative)) = groups.next() {
+                        let subtotal = t!(
+                            "list-group-subtotal",
+                            count = count,
+                            cumulative = crate::time::Span::from(cumulative).format_abbreviated()
+                        );
+                        println!(
+                            "  {} {}",
+                            paint(&heading, Style::new().bold().underline(), color),
+                            paint(&subtotal, Style::new().dimmed(), color)
+                        );
+                    }
+                    group = Some(heading);
+                }
+            }
+            let fraction = self.progress(practice).unwrap_or_default();
+
+            let (name_style, bar_style, grace_style) = if practice.archived {
+                (
+                    Style::new().dimmed(),
+                    Style::new().dimmed(),
+                    Style::new().dimmed(),
+                )
+            } else if practice.maintenance {
+                (
+                    Style::new().italic(),
+                    gradient(fraction),
+                    Style::new().yellow(),
+                )
+            } else {
+                (
+                    Style::new().bold(),
+                    gradient(fraction),
+                    Style::new().yellow(),
+                )
+            };
+            let recent = if sparkline {
+                let outcomes = practice.recent_outcomes(SPARKLINE_PERIODS);
+                let line = outcomes
+                    .iter()
+                    .map(|outcome| {
+                        let glyph = outcome.glyph(ascii).to_string();
+                        paint(&glyph, outcome.style(), color)
+                    })
+                    .collect::<String>();
+                format!(" {line}{}", " ".repeat(SPARKLINE_PERIODS - outcomes.len()))
+            } else {
+                String::new()
+            };
+            let whole_bar = format!(
+                "{}{}{recent}{}",
+                paint(&start, name_style, color),
+                self.paint_progress_bar(
+                    practice,
+                    bar_width,
+                    glyphs,
+                    (bar_style, grace_style),
+                    color
+                ),
+                end
+            );
+
+            println!("{whole_bar}");
+
+            for (label, done, target, end, style) in targets {
+                #[allow(clippy::cast_precision_loss)]
+                let fraction = done.num_seconds() as f64 / target.num_seconds().max(1) as f64;
+                let style = if practice.archived {
+                    Style::new().dimmed()
+                } else {
+                    style
+                };
+                println!(
+                    "{}{}{}{}",
+                    paint(
+                        &format!("{label:>max_start_len$}"),
+                        Style::new().dimmed(),
+                        color
+                    ),
+                    paint(
+                        &crate::utils::bar(bar_width, fraction, glyphs),
+                        style,
+                        color
+                    ),
+                    " ".repeat(sparkline_width),
+                    end.trim_end()
+                );
+            }
+
+            if verbose {
+                let timing = practice.describe_timing(user_config.grace_period, now);
+                let timing = format!("{:>max_start_len$}{timing}", "");
+                println!("{}", paint(&timing, Style::new().dimmed(), color));
+            }
+
+            if !practice.checklist.is_empty() {
+                let (done, todo) = if ascii {
+                    ("[x]", "[ ]")
+                } else {
+                    ("☑", "☐")
+                };
+                let items = practice
+                    .checklist()
+                    .map(|(item, checked)| {
+                        if checked {
+                            paint(&format!("{done} {item}"), Style::new().dimmed(), color)
+                        } else {
+                            format!("{todo} {item}")
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                println!("{:>max_start_len$}{}", "", items.join("  "));
+            }
+
+            // Remind the user in their own words why they started, but only once it's needed.
+            if let Some(why) = practice.why.as_ref().filter(|_| practice.is_slumping(now)) {
+                let why = format!("{:>max_start_len$}{why}", format!("{} ", t!("list-why")));
+                println!("{}", paint(&why, Style::new().dimmed(), color));
+            }
+        }
+        if has_bars {
+            println!();
+        }
+
+        if danger && has_bars {
+            let sum_fraction = self.danger();
+            let sum_bar = crate::utils::bar(bar_width, sum_fraction, glyphs);
+            let start = format!("{:>max_start_len$}", format!("  {} ", t!("list-danger")));
+            let end = String::new();
+
+            println!(
+                "{}{}{end:<max_end_len$}",
+                paint(&start, Style::new().bold().red(), color),
+                paint(&sum_bar, Style::new().red(), color)
+            );
+        }
+
+        if !log_only.is_empty() {
+            if cumulative {
+                let heading = format!("{} ", t!("list-log-only"));
+                let width = log_only
+                    .iter()
+                    .map(|(name, _)| name.chars().count() + 3)
+                    .chain([max_start_len, heading.len() + 2])
+                    .max()
+                    .unwrap_or_default();
+                if has_bars {
+                    println!();
+                }
+                println!(
+                    "{}",
+                    paint(&format!("{heading:>width$}"), Style::new().dimmed(), color)
+                );
+                for (name, practice) in log_only {
+                    let style = if practice.archived {
+                        Style::new().dimmed()
+                    } else {
+                        Style::new().bold()
+                    };
+                    println!(
+                        "{}{}",
+                        paint(&format!("{:>width$}", format!("  {name} ")), style, color),
+                        crate::time::Span::from(practice.cumulative).format()
+                    );
+                }
+                println!();
+            } else {
+                let hint = format!("  {}", t!("list-log-only-hidden", count = log_only.len()));
+                println!("{}", paint(&hint, Style::new().dimmed(), color));
+            }
+        }
+
+        let hidden = self
+            .practices
+            .values()
+            .filter(|practice| practice.hidden && !options.hidden)
+            .filter(|practice| options.archived || !practice.archived)
+            .count();
+        if hidden > 0 {
+            let hint = format!("  {}", t!("list-hidden", count = hidden));
+            println!("{}", paint(&hint, Style::new().dimmed(), color));
+        }
+
+        Ok(())
+    }
+
+    /// Time logged this week and the weekly goal, for practices with one.
+    fn weekly_progress(&self, practice: &Practice) -> Option<(Duration, Duration)> {
+        let goal = practice.weekly_goal?;
+        let start = crate::time::week_start(
+            &self.now().with_timezone(&Local),
+            self.config.user_config.week_start,
+        );
+        Some((practice.logged_since(start.with_timezone(&Utc)), goal))
+    }
+
+    /// Practices shown by `prac list` with the given options, in order.
+    fn sorted_practices(&self, options: &ListOptions) -> Vec<(&String, &Practice)> {
+        let mut practices = self
+            .practices
+            .iter()
+            .filter(|(_, practice)| options.archived || !practice.archived)
+            .filter(|(_, practice)| practice.fits_context(options.context.as_deref()))
+            .filter(|(_, practice)| options.hidden || !practice.hidden)
+            .filter(|(_, practice)| {
+                options.due_only.is_none_or(|fraction| {
+                    self.progress(practice)
+                        .is_some_and(|progress| progress >= fraction)
+                })
+            })
+            .filter(|(_, practice)| {
+                options
+                    .recent
+                    .is_none_or(|recent| practice.elapsed(self.now()) <= recent)
+            })
+            .collect::<Vec<_>>();
+        // Practices are stored by name, so that order needs no sorting.
+        match options.sort.unwrap_or(self.config.user_config.list_sort) {
+            ListSort::Name => (),
+            ListSort::Progress | ListSort::Smart => practices.sort_by(|(_, a), (_, b)| {
+                let progress = |practice| self.progress(practice).unwrap_or(f64::NEG_INFINITY);
+                progress(b).total_cmp(&progress(a))
+            }),
+            // Log-only practices, having no period, go last.
+            ListSort::Period => {
+                practices.sort_by_key(|(_, practice)| (practice.period.is_none(), practice.period));
+            }
+            ListSort::Cumulative => {
+                practices.sort_by_key(|(_, practice)| std::cmp::Reverse(practice.cumulative));
+            }
+        }
+        if options.sort.unwrap_or(self.config.user_config.list_sort) == ListSort::Smart {
+            practices = self.order_by_dependencies(practices);
+        }
+        // Practices only kept warm go below those that need deciding on.
+        practices.sort_by_key(|(_, practice)| practice.maintenance);
+        // Sorting is stable, so each section keeps the order above.
+        if let Some(group) = options.group_by {
+            practices.sort_by_cached_key(|(_, practice)| practice.list_group(group));
+        }
+        practices
+    }
+
+    /// The progress bar of `practice`, in the first of `styles` but for the part within the grace
+    /// period, which is drawn with its own glyph in the second.
+    fn paint_progress_bar(
+        &self,
+        practice: &Practice,
+        bar_width: usize,
+        glyphs: crate::utils::BarGlyphs,
+        styles: (Style, Style),
+        color: bool,
+    ) -> String {
+        let fraction = self.progress(practice).unwrap_or_default();
+        let grace_period = self.config.user_config.grace_period;
+        #[allow(clippy::cast_precision_loss)]
+        let grace_from = match practice.due_period() {
+            Some(period) if grace_period > Duration::zero() => {
+                period.num_seconds() as f64 / (period + grace_period).num_seconds() as f64
+            }
+            _ => 1.0,
+        };
+        let (elapsed, grace, remaining) =
+            crate::utils::grace_bar(bar_width, fraction, grace_from, glyphs);
+        format!(
+            "{}{}{}",
+            paint(&elapsed, styles.0, color),
+            paint(&grace, styles.1, color),
+            paint(&remaining, styles.0, color)
+        )
+    }
+
+    /// Fraction of the (grace adjusted) period elapsed since `practice` was last logged, or none
+    /// for log-only practices.
+    fn progress(&self, practice: &Practice) -> Option<f64> {
+        let grace_adjusted_period = practice.due_period()? + self.config.user_config.grace_period;
+        #[allow(clippy::cast_precision_loss)]
+        let fraction = practice.elapsed(self.now()).num_seconds() as f64
+            / grace_adjusted_period.num_seconds() as f64;
+        Some(fraction)
+    }
+
+    /// Sum progression through all (unarchived, periodic) periods, shown as the "danger bar".
+    fn danger(&self) -> f64 {
+        let active = || {
+            self.practices
+                .values()
+                .filter(|p| !p.archived)
+                .filter_map(|p| Some((p, p.due_period()?)))
+        };
+        let now = self.now();
+        let sum_progress: i64 = active().map(|(p, _)| p.elapsed(now).num_seconds()).sum();
+        let sum_period: i64 = active()
+            .map(|(_, period)| (period + self.config.user_config.grace_period).num_seconds())
+            .sum();
+
+        #[allow(clippy::cast_precision_loss)]
+        let sum_fraction = sum_progress as f64 / sum_period as f64;
+        sum_fraction
+    }
+
+    /// Practices added at least [`UserConfig::prune_after_periods`] periods ago that have never
+    /// been logged, with a short human readable explanation.
+    pub fn never_logged(&self) -> Vec<(&str, String)> {
+        let periods =
+            i32::try_from(self.config.user_config.prune_after_periods).unwrap_or(i32::MAX);
+        let now = self.now();
+        self.practices
+            .iter()
+            .filter(|(_, practice)| !practice.archived && practice.never_logged())
+            .filter_map(|(name, practice)| Some((name, practice, practice.due_period()?)))
+            .filter(|(_, practice, period)| now - practice.created >= *period * periods)
+            .map(|(name, practice, period)| {
+                let age = crate::time::Span::from(now - practice.created);
+                let period = crate::time::Span::from(period);
+                let explanation = t!(
+                    "never-logged-explanation",
+                    age = age.format_abbreviated(),
+                    period = period.format()
+                );
+                (name.as_str(), explanation)
+            })
+            .collect()
+    }
+
+    /// A period to suggest for a new practice called `name`: the median period of practices sharing
+    /// a word of its name, e.g. "piano scales" for "piano pieces", else the `default_period` config,
+    /// else the median period of every practice. Archived and log-only practices don't count.
+    pub fn suggested_period(&self, name: &str) -> Option<Duration> {
+        let words = |name: &str| {
+            name.to_lowercase()
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| word.chars().count() >= SIMILAR_WORD_LENGTH)
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>()
+        };
+        let median = |mut periods: Vec<Duration>| {
+            periods.sort();
+            periods.get(periods.len().saturating_sub(1) / 2).copied()
+        };
+        let periodic = || {
+            self.practices
+                .values()
+                .filter(|practice| !practice.archived)
+                .filter_map(|practice| Some((practice, practice.period?)))
+        };
+        let new_words = words(name);
+        let similar = periodic()
+            .filter(|(practice, _)| {
+                words(&practice.name)
+                    .iter()
+                    .any(|word| new_words.contains(word))
+            })
+            .map(|(_, period)| period)
+            .collect();
+        median(similar)
+            .or(self.config.user_config.default_period)
+            .or_else(|| median(periodic().map(|(_, period)| period).collect()))
+    }
+
+    /// Unarchived practices whose recent logs are on average further apart, or closer together,
+    /// than their period by more than [`DRIFT_TOLERANCE`]. Adjusting the period to how often you
+    /// actually practice keeps the feedback honest.
+    pub fn drifting(&self) -> Vec<Drift<'_>> {
+        self.practices
+            .iter()
+            .filter(|(_, practice)| !practice.archived)
+            .filter_map(|(name, practice)| {
+                let period = practice.due_period()?;
+                let average = practice.average_interval(DRIFT_LOGS)?;
+                #[allow(clippy::cast_precision_loss)]
+                let ratio = average.num_seconds() as f64 / period.num_seconds().max(1) as f64;
+                if (ratio - 1.0).abs() <= DRIFT_TOLERANCE {
+                    return None;
+                }
+                Some(Drift {
+                    name,
+                    period,
+                    average,
+                    suggested: round_period(average),
+                })
+            })
+            .collect()
+    }
+
+    /// Unarchived practices in maintenance whose period adaptive periods would change, unless
+    /// they are off.
+    pub fn adaptations(&self) -> Vec<Adaptation<'_>> {
+        let config = &self.config.user_config;
+        if config.adaptive_periods == AdaptivePeriods::Off {
+            return Vec::new();
+        }
+        self.practices
+            .iter()
+            .filter(|(_, practice)| !practice.archived)
+            .filter_map(|(name, practice)| {
+                Some(Adaptation {
+                    name,
+                    period: practice.period?,
+                    adapted: practice
+                        .adapted_period(config.adaptive_factor, config.adaptive_streak)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Change the periods adaptive periods call for, if they are set to do so without asking.
+    fn adapt_periods(&mut self) {
+        if self.config.user_config.adaptive_periods != AdaptivePeriods::Auto {
+            return;
+        }
+        let adaptations = self
+            .adaptations()
+            .into_iter()
+            .map(|adaptation| (adaptation.name.to_owned(), adaptation.adapted))
+            .collect::<Vec<_>>();
+        let now = self.now();
+        for (name, adapted) in adaptations {
+            if let Some(practice) = self.practices.get_mut(&name) {
+                practice.adapt_period(adapted, true, now);
+            }
+        }
+    }
+
+    /// Returns a reminder about never-logged practices, at most once a day, to be shown by
+    /// `prac list`. Only call it when the reminder will be shown, as it is then not shown again
+    /// for a day, which saves the statefile.
+    pub fn prune_nudge(&mut self) -> Option<String> {
+        let now = self.now();
+        if self
+            .config
+            .last_prune_nudge
+            .is_some_and(|last| now - last < Duration::days(1))
+        {
+            return None;
+        }
+        let count = self.never_logged().len();
+        if count == 0 {
+            return None;
+        }
+        self.config.last_prune_nudge = Some(now);
+        Some(t!("prune-nudge", count = count))
+    }
+
+    /// Explain exactly what [`StateTransition::Reset`] would change.
+    pub fn describe_reset(&self) -> String {
+        let now = self.now();
+        let mut lines = vec![t!("reset-description", count = self.practices.len())];
+        lines.extend(self.practices.iter().map(|(name, practice)| {
+            let ago = crate::time::Span::from(now - practice.logged);
+            format!(
+                "  {}",
+                t!(
+                    "reset-practice",
+                    name = name,
+                    ago = ago.format_abbreviated()
+                )
+            )
+        }));
+        let cumulative = self
+            .practices
+            .values()
+            .fold(Duration::zero(), |sum, practice| sum + practice.cumulative);
+        lines.push(t!(
+            "reset-kept",
+            total = crate::time::Span::from(cumulative).format()
+        ));
+        lines.join("\n")
+    }
+
+    /// Machine-readable equivalent of [`State::list`].
+    pub fn list_report(&self, options: &ListOptions) -> ListReport<'_> {
+        let now = self.now();
+        let practices = self
+            .sorted_practices(options)
+            .into_iter()
+            .map(|(name, practice)| PracticeSummary {
+                name,
+                archived: practice.archived,
+                why: practice.why.as_deref(),
+                lifecycle: practice.lifecycle(),
+                created: practice.created,
+                logged: practice.logged,
+                period: practice.period,
+                elapsed: practice.elapsed(now),
+                cumulative: practice.cumulative,
+                progress: self.progress(practice),
+                group: options.group_by.map(|group| practice.list_group(group).1),
+                aliases: &practice.aliases,
+                metadata: &practice.metadata,
+                weekly_goal: practice.weekly_goal,
+                this_week: self.weekly_progress(practice).map(|(done, _)| done),
+                target_time_per_period: practice.target_time_per_period,
+                session_length: practice.session_length,
+                log_mode: practice.log_mode,
+                best_time: practice.best_time,
+                context: practice.context.as_deref(),
+                checklist: practice
+                    .checklist()
+                    .map(|(item, done)| ChecklistStatus { item, done })
+                    .collect(),
+                this_period: budget_progress(practice, now).map(|(done, _)| done),
+                recent: practice.recent_outcomes(SPARKLINE_PERIODS),
+            })
+            .collect();
+        ListReport {
+            practices,
+            danger: self
+                .practices
+                .values()
+                .any(|practice| !practice.archived && practice.period.is_some())
+                .then(|| self.danger()),
+        }
+    }
+
+    /// Totals over every practice, with adherence over the last `periods` periods of each.
+    /// Archived practices count toward time.
+    pub fn stats(&self, periods: usize) -> Stats<'_> {
+        let active = || {
+            self.practices
+                .values()
+                .filter(|practice| !practice.archived)
+        };
+        let week_start = crate::time::week_start(
+            &self.now().with_timezone(&Local),
+            self.config.user_config.week_start,
+        )
+        .with_timezone(&Utc);
+        let this_week = || {
+            self.practices
+                .values()
+                .flat_map(|practice| &practice.logs)
+                .filter(|entry| entry.at >= week_start)
+        };
+        Stats {
+            practices: active().count(),
+            archived: self.practices.len() - active().count(),
+            overdue: active()
+                .filter(|practice| {
+                    self.progress(practice)
+                        .is_some_and(|progress| progress > 1.0)
+                })
+                .count(),
+            danger: active()
+                .any(|practice| practice.period.is_some())
+                .then(|| self.danger()),
+            total_time: self
+                .practices
+                .values()
+                .fold(Duration::zero(), |sum, practice| sum + practice.cumulative),
+            this_week: this_week().fold(Duration::zero(), |sum, entry| sum + entry.time),
+            sessions_this_week: this_week().count(),
+            practicing_since: self
+                .practices
+                .values()
+                .map(|practice| practice.created)
+                .min(),
+            adherence: Adherence::from_outcomes(
+                active().flat_map(|practice| practice.recent_outcomes(periods)),
+            ),
+            by_practice: active()
+                .filter_map(|practice| {
+                    Some(PracticeAdherence {
+                        name: &practice.name,
+                        adherence: practice.adherence(periods)?,
+                    })
+                })
+                .collect(),
+            period_adjustments: active()
+                .flat_map(|practice| {
+                    practice
+                        .period_adjustments
+                        .iter()
+                        .map(|adjustment| PracticeAdjustment {
+                            name: &practice.name,
+                            adjustment,
+                        })
+                })
+                .sorted_by_key(|entry| entry.adjustment.at)
+                .collect(),
+        }
+    }
+
+    /// Search practice names, reasons, and notes for `query` (case insensitive), grouped by the
+    /// field they were found in.
+    pub fn find(&self, query: &str) -> FindReport<'_> {
+        let needle = query.to_lowercase();
+
+        let mut matches = Vec::new();
+        for field in SearchField::ALL {
+            for practice in self.practices.values() {
+                let lines: Vec<&str> = match field {
+                    SearchField::Name => vec![practice.name.as_str()],
+                    SearchField::Why => practice.why.as_deref().into_iter().collect(),
+                    SearchField::Notes => practice.notes.lines().collect(),
+                    SearchField::NoteHistory => {
+                        let current = practice.notes.lines().collect::<Vec<_>>();
+                        practice
+                            .note_history
+                            .iter()
+                            .flat_map(|revision| revision.notes.lines())
+                            .filter(|line| !current.contains(line))
+                            .unique()
+                            .collect()
+                    }
+                };
+                matches.extend(
+                    lines
+                        .into_iter()
+                        .filter(|line| line.to_lowercase().contains(&needle))
+                        .map(|line| FindMatch {
+                            field,
+                            practice: &practice.name,
+                            text: line.trim(),
+                        }),
+                );
+            }
+        }
+        FindReport { matches }
+    }
+
+    /// Print the results of [`State::find`] for humans.
+    pub fn print_find(&self, report: &FindReport, query: &str, color: bool) {
+        if report.matches.is_empty() {
+            println!("{}", t!("find-nothing", query = query));
+            return;
+        }
+
+        for (field, hits) in &report.matches.iter().group_by(|hit| hit.field) {
+            println!("{}", paint(&field.to_string(), Style::new().bold(), color));
+            for hit in hits {
+                let practice = &self.practices[hit.practice];
+                match field {
+                    SearchField::Name => println!("  {practice}"),
+                    _ => println!("  {practice}: {}", hit.text),
+                }
+            }
+        }
+    }
+
+    /// Whether a practice named `name` exists.
+    pub fn contains(&self, name: &str) -> bool {
+        self.practices.contains_key(name)
+    }
+
+    /// The metadata fields of practice `name`.
+    pub fn metadata_of(&self, name: &str) -> Result<&BTreeMap<String, String>> {
+        let practice = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?;
+        Ok(&practice.metadata)
+    }
+
+    /// Find the name of a practice either validating an name input, or if not provided, prompting the user to select one.
+    pub fn find_name(&self) -> Result<&str> {
+        self.select_name(&t!("select-practice"))
+    }
+
+    /// Fuzzy select a practice name, with a custom prompt.
+    pub fn select_name(&self, prompt: &str) -> Result<&str> {
+        let options = &self.practices.keys().collect::<Vec<_>>();
+
+        let selection_index = FuzzySelect::new()
+            .with_prompt(prompt)
+            .items(options)
+            .interact_opt()
+            .context(t!("selection-failed"))?;
+
+        match selection_index {
+            Some(i) => Ok(options[i].as_str()),
+            None => bail!(Error::Aborted(t!("nothing-selected"))),
+        }
+    }
+
+    pub fn get_notes(&self, name: &str) -> Result<&str> {
+        let notes = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?
+            .notes
+            .as_str();
+        Ok(notes)
+    }
+
+    pub fn get_why(&self, name: &str) -> Result<Option<&str>> {
+        let why = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?
+            .why
+            .as_deref();
+        Ok(why)
+    }
+
+    pub fn get_context(&self, name: &str) -> Result<Option<&str>> {
+        let practice = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?;
+        Ok(practice.context.as_deref())
+    }
+
+    pub fn get_session_length(&self, name: &str) -> Result<Option<Duration>> {
+        let session_length = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?
+            .session_length;
+        Ok(session_length)
+    }
+
+    pub fn get_best_time(&self, name: &str) -> Result<Option<TimeWindow>> {
+        let best_time = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?
+            .best_time;
+        Ok(best_time)
+    }
+
+    pub fn get_weekly_goal(&self, name: &str) -> Result<Option<Duration>> {
+        let goal = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?
+            .weekly_goal;
+        Ok(goal)
+    }
+
+    /// Add habits from another tracker as practices, logging `time` per completion. Habits without
+    /// a schedule get a period guessed from how often they were done, or the `default_period`
+    /// config (else a day) if that can't be told. Existing practices are left alone.
+    pub fn import(
+        &mut self,
+        habits: Vec<crate::import::ImportedHabit>,
+        time: Duration,
+    ) -> Result<ImportReport> {
+        let mut report = ImportReport {
+            imported: Vec::new(),
+            skipped: Vec::new(),
+        };
+        for habit in habits {
+            let name = normalize_name(&habit.name)?;
+            if self.practices.contains_key(&name) || self.ensure_distinct_name(&name, None).is_err()
+            {
+                report.skipped.push(name);
+                continue;
+            }
+            let mut practice = Practice::new(
+                name.clone(),
+                habit.description.unwrap_or_default(),
+                habit.period,
+                self.now(),
+            );
+            let completions = habit.completions.len();
+            for at in habit.completions {
+                practice.insert_log(LogEntry {
+                    at,
+                    time,
+                    source: None,
+                    note: None,
+                    skipped: false,
+                });
+            }
+            practice.cumulative = time * i32::try_from(completions)?;
+            if let (Some(first), Some(last)) = (practice.logs.first(), practice.logs.last()) {
+                practice.created = practice.created.min(first.at);
+                practice.logged = last.at;
+            }
+            practice.archived = habit.archived;
+
+            let guessed = practice
+                .period
+                .is_none()
+                .then(|| practice.typical_interval());
+            let guessed_period = matches!(guessed, Some(Some(_)));
+            let period = practice.period.unwrap_or_else(|| {
+                guessed
+                    .flatten()
+                    .or(self.config.user_config.default_period)
+                    .unwrap_or_else(|| Duration::days(1))
+            });
+            practice.period = Some(period);
+            report.imported.push(ImportedPractice {
+                name: name.clone(),
+                completions,
+                period,
+                guessed_period,
+            });
+            self.practices.insert(name, practice);
+        }
+        Ok(report)
+    }
+
+    /// Log time tracker entries to practices: those of a project in `map` to the practice it maps
+    /// to, and the others to the practice named like their project, if any. Entries already
+    /// imported are skipped, so the same export can be imported again as it grows.
+    pub fn import_time(
+        &mut self,
+        entries: Vec<crate::import::TimeEntry>,
+        map: &BTreeMap<String, String>,
+    ) -> Result<TimeImportReport> {
+        for practice in map.values() {
+            ensure!(
+                self.practices.contains_key(practice),
+                "\"{practice}\" not found."
+            );
+        }
+        let mut report = TimeImportReport {
+            logged: Vec::new(),
+            duplicates: 0,
+            unmatched: BTreeMap::new(),
+        };
+        let mut logged = BTreeMap::<String, (usize, Duration)>::new();
+        for entry in entries {
+            let project = entry.project.trim();
+            let name = map.get(project).map_or(project, String::as_str);
+            let Some(practice) = self.practices.get_mut(name) else {
+                *report.unmatched.entry(project.to_owned()).or_default() += 1;
+                continue;
+            };
+            let source = Some(entry.id);
+            if practice.logs.iter().any(|log| log.source == source) {
+                report.duplicates += 1;
+                continue;
+            }
+            let log = LogEntry {
+                at: entry.start,
+                time: entry.time,
+                source,
+                note: None,
+                skipped: false,
+            };
+            practice.log(log);
+            let total = logged
+                .entry(practice.name.clone())
+                .or_insert((0, Duration::zero()));
+            total.0 += 1;
+            total.1 += entry.time;
+        }
+        report.logged = logged
+            .into_iter()
+            .map(|(practice, (entries, time))| ImportedTime {
+                practice,
+                entries,
+                time,
+            })
+            .collect();
+        Ok(report)
+    }
+
+    /// Practice `name` as reported by [`TransitionReport`], if there is one.
+    fn practice_result(&self, name: &str) -> Option<PracticeResult> {
+        let practice = self.practices.get(name)?;
+        Some(PracticeResult {
+            name: practice.name.clone(),
+            archived: practice.archived,
+            logged: practice.logged,
+            period: practice.period,
+            cumulative: practice.cumulative,
+            streak: practice.streak(),
+        })
+    }
+
+    /// The event of the session of `name` just logged, for webhooks: the one logged `at`, or
+    /// without a time, just now and so the last.
+    pub fn log_event(&self, name: &str, at: Option<DateTime<Utc>>) -> Result<LogEvent> {
+        let practice = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?;
+        let entry = at
+            .map_or_else(
+                || practice.logs.last(),
+                |at| practice.logs.iter().rev().find(|entry| entry.at == at),
+            )
+            .context(t!("never-logged"))?;
+        let streak = practice.streak();
+        let time = crate::time::Span::from(entry.time).format();
+        let content = if streak > 1 {
+            t!(
+                "log-event-streak",
+                name = &practice.name,
+                time = time,
+                streak = streak
+            )
+        } else {
+            t!("log-event", name = &practice.name, time = time)
+        };
+        Ok(LogEvent {
+            event: "log",
+            practice: practice.name.clone(),
+            duration: entry.time,
+            timestamp: entry.at,
+            streak,
+            content,
+        })
+    }
+
+    /// Items of a practice's checklist, and whether each is done this period.
+    pub fn get_checklist(&self, name: &str) -> Result<Vec<(&str, bool)>> {
+        let practice = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?;
+        Ok(practice.checklist().collect())
+    }
+
+    pub fn get_target_time_per_period(&self, name: &str) -> Result<Option<Duration>> {
+        let target = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?
+            .target_time_per_period;
+        Ok(target)
+    }
+
+    /// Print every revision of a practice's notes, oldest first.
+    pub fn print_note_history(&self, name: &str, color: bool) -> Result<()> {
+        let practice = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?;
+        if practice.note_history.is_empty() {
+            if practice.notes.is_empty() {
+                println!("{}", t!("no-notes", name = name));
+            } else {
+                println!("{}", t!("no-note-history", name = name));
+            }
+            return Ok(());
+        }
+        let current = NoteRevision {
+            at: practice.notes_edited,
+            notes: practice.notes.clone(),
+        };
+        let revisions = practice
+            .note_history
+            .iter()
+            .chain(std::iter::once(&current));
+        for (index, revision) in revisions.enumerate() {
+            let at = revision.at.map_or_else(
+                || t!("note-history-untracked"),
+                |at| {
+                    at.with_timezone(&Local)
+                        .format("%a %Y-%m-%d %H:%M")
+                        .to_string()
+                },
+            );
+            if index > 0 {
+                println!();
+            }
+            println!(
+                "{}",
+                paint(&format!("{}. {at}", index + 1), Style::new().bold(), color)
+            );
+            println!("{}", revision.notes.trim_end());
+        }
+        Ok(())
+    }
+
+    /// Contents of the trash, most recently removed first.
+    pub fn trash_report(&self) -> TrashReport<'_> {
+        let retention = self.config.user_config.trash_retention;
+        let entries = self
+            .trash
+            .iter()
+            .rev()
+            .map(|trashed| TrashEntry {
+                name: &trashed.practice.name,
+                removed: trashed.removed,
+                purged: trashed.removed + retention,
+            })
+            .collect();
+        TrashReport { entries }
+    }
+
+    /// Drop trashed practices removed longer than [`UserConfig::trash_retention`] ago.
+    pub fn purge_trash(&mut self) {
+        let cutoff = self.now() - self.config.user_config.trash_retention;
+        self.trash.retain(|trashed| trashed.removed > cutoff);
+    }
+
+    /// Names of trashed practices, most recently removed first, without duplicates.
+    pub fn trashed_names(&self) -> Vec<&str> {
+        self.trash
+            .iter()
+            .rev()
+            .map(|trashed| trashed.practice.name.as_str())
+            .unique()
+            .collect()
+    }
+
+    /// The log history of a practice.
+    /// Index into the log history of `name` of the session `entry`: its number as shown by
+    /// `prac history`, or when it was, to the minute.
+    pub fn find_log(&self, name: &str, entry: &str) -> Result<usize> {
+        let practice = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?;
+        if let Ok(number) = entry.trim().parse::<usize>() {
+            let index = number
+                .checked_sub(1)
+                .context(t!("sessions-numbered-from-one"))?;
+            ensure!(
+                index < practice.logs.len(),
+                Error::NotFound(t!("no-entry", name = name, number = number))
+            );
+            return Ok(index);
+        }
+        let at = crate::time::parse_time_point(entry)?;
+        let matches = practice
+            .logs
+            .iter()
+            .positions(|log| log.at >= at && log.at < at + Duration::minutes(1))
+            .collect::<Vec<_>>();
+        match matches[..] {
+            [index] => Ok(index),
+            [] => bail!(Error::NotFound(t!("no-entry-at", name = name, at = entry))),
+            _ => bail!(t!("several-entries-at", name = name, at = entry)),
+        }
+    }
+
+    /// The sessions of practice `name` logged from `since` and before `until`, if given.
+    pub fn history(
+        &self,
+        name: &str,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<HistoryReport<'_>> {
+        let (name, practice) = self
+            .practices
+            .get_key_value(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?;
+        let entries = practice
+            .logs
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| HistoryEntry {
+                number: index + 1,
+                at: entry.at,
+                time: entry.time,
+                note: entry.note.clone(),
+                skipped: entry.skipped,
+            })
+            .filter(|entry| since.is_none_or(|since| entry.at >= since))
+            .filter(|entry| until.is_none_or(|until| entry.at < until))
+            .collect();
+        Ok(HistoryReport {
+            name,
+            entries,
+            // Time from before history was kept can't be placed after `since`.
+            untracked: if since.is_some() {
+                Duration::zero()
+            } else {
+                practice.untracked()
+            },
+            filtered: since.is_some() || until.is_some(),
+        })
+    }
+
+    pub fn get_user_config(&self) -> &UserConfig {
+        &self.config.user_config
+    }
+
+    /// Read the state from `path`, or start afresh if there is no statefile yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self {
+                clock: SharedClock::from_env()?,
+                ..Self::new()
+            });
+        }
+        let bytes = std::fs::read(path).context(t!("read-state-failed"))?;
+        let (json, key) = crypt::open(bytes, None)?;
+        let json = storage::decompress(json)?;
+        let mut state: Self = storage::read(&json).with_context(|| {
+            Error::StateCorrupt(t!("parse-state-failed", path = path.display().to_string()))
+        })?;
+        state.key = key;
+        state.on_disk = Some(json);
+        state.clock = SharedClock::from_env()?;
+        state.migrate();
+        state.update_outcomes();
+        Ok(state)
+    }
+
+    /// Parse the contents of a statefile, decrypting them if need be (see [`crypt::open`] for how
+    /// `key` is used). The state is saved encrypted again if it was read encrypted.
+    pub fn from_bytes(bytes: Vec<u8>, key: Option<&crypt::Key>) -> Result<Self> {
+        let (json, key) = crypt::open(bytes, key)?;
+        let mut state: Self = storage::read(&json)?;
+        state.key = key;
+        state.clock = SharedClock::from_env()?;
+        state.migrate();
+        state.update_outcomes();
+        Ok(state)
+    }
+
+    /// Fill in what statefiles written by older versions of prac lack. Practices without a
+    /// creation date get the time of their first log (or, without history, of their last), and
+    /// none is created after its first log, which imports and `prac log --at` could cause.
+    fn migrate(&mut self) {
+        let practices = self
+            .practices
+            .values_mut()
+            .chain(self.trash.iter_mut().map(|trashed| &mut trashed.practice));
+        for practice in practices {
+            let first = practice
+                .logs
+                .first()
+                .map_or(practice.logged, |entry| entry.at);
+            practice.created = if practice.created == DateTime::<Utc>::UNIX_EPOCH {
+                first
+            } else {
+                practice.created.min(first)
+            };
+            // Statefiles from before outcomes were kept have them worked out from history.
+            if practice.outcomes.is_empty() && !practice.logs.is_empty() {
+                practice.outcomes_stale = true;
+            }
+            // Note history used to end with the current notes.
+            if practice.notes_edited.is_none()
+                && practice
+                    .note_history
+                    .last()
+                    .is_some_and(|revision| revision.notes == practice.notes)
+            {
+                practice.notes_edited = practice.note_history.pop().and_then(|last| last.at);
+            }
+        }
+    }
+
+    /// The current time, as the state's clock tells it.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// The state's clock, for timing what runs on after a command, like sessions and blocks.
+    pub const fn clock(&self) -> &SharedClock {
+        &self.clock
+    }
+
+    /// Run the state on `clock` from now on, e.g. to preview the list at another time, returning
+    /// the clock it ran on before.
+    pub const fn set_clock(&mut self, clock: SharedClock) -> SharedClock {
+        std::mem::replace(&mut self.clock, clock)
+    }
+
+    /// Never save the state, for `reason`.
+    pub const fn set_read_only(&mut self, reason: ReadOnly) {
+        self.read_only = Some(reason);
+    }
+
+    /// Fail if the state is read-only, before anything about it is changed.
+    pub fn ensure_writable(&self) -> Result<()> {
+        match self.read_only {
+            None => Ok(()),
+            Some(ReadOnly::Asked) => bail!(Error::ReadOnly(t!("read-only-asked"))),
+            Some(ReadOnly::NotWritable) => bail!(Error::ReadOnly(t!("read-only-not-writable"))),
+        }
+    }
+
+    /// Bring every practice's outcomes up to date (see [`Practice::update_outcomes`]).
+    fn update_outcomes(&mut self) {
+        let grace_period = self.config.user_config.grace_period;
+        let now = self.now();
+        for practice in self.practices.values_mut() {
+            practice.update_outcomes(grace_period, now);
+        }
+    }
+
+    /// Replace the state with `other`, e.g. a backup, to be saved in its place.
+    pub fn replace(&mut self, other: Self) {
+        let on_disk = self.on_disk.take();
+        let backed_up = self.backed_up;
+        let clock = self.clock.clone();
+        let read_only = self.read_only;
+        *self = other;
+        self.on_disk = on_disk;
+        self.backed_up = backed_up;
+        self.clock = clock;
+        self.read_only = read_only;
+    }
+
+    /// The key the state is encrypted with when saved, if any.
+    pub const fn encryption(&self) -> Option<&crypt::Key> {
+        self.key.as_ref()
+    }
+
+    /// Encrypt the state with `key` when saving, or save it in plain JSON if `None`.
+    pub fn set_encryption(&mut self, key: Option<crypt::Key>) {
+        self.key = key;
+    }
+
+    /// The state serialized as it is written to `path`, before compression and encryption, with
+    /// its version and outcomes brought up to date.
+    fn encode(&mut self, path: &Path) -> Result<Vec<u8>> {
+        self.update_version();
+        self.update_outcomes();
+        let format = StateFormat::for_path(path, self.config.user_config.state_format);
+        format.write(self, self.config.user_config.state_compact)
+    }
+
+    /// Compress and encrypt `encoded` state as configured.
+    fn seal(&self, mut encoded: Vec<u8>) -> Result<Vec<u8>> {
+        if self.config.user_config.state_compress {
+            encoded = storage::compress(&encoded)?;
+        }
+        if let Some(key) = &self.key {
+            encoded = key.encrypt(&encoded)?;
+        }
+        Ok(encoded)
+    }
+
+    /// The contents of a statefile at `path` holding the state, for copies of it like the one
+    /// `prac sync` commits. Unlike [`State::save`], nothing is backed up, audited or summarized,
+    /// and the state's own statefile still counts as unsaved.
+    pub fn file_contents(&mut self, path: &Path) -> Result<Vec<u8>> {
+        let encoded = self.encode(path)?;
+        self.seal(encoded)
+    }
+
+    /// Write the state to `path` atomically, creating parent directories as needed. Read-only
+    /// states are left unwritten: changes to them can only be incidental, like outcomes brought up
+    /// to date, as everything else checks [`State::ensure_writable`] first.
+    pub fn save(&mut self, path: &Path) -> Result<()> {
+        if self.read_only.is_some() {
+            return Ok(());
+        }
+        if !path.parent().is_some_and(Path::exists) {
+            // create all subdirs
+            let parent = path.parent().context(t!("state-path-no-parent"))?;
+            if !parent.exists() {
+                std::fs::create_dir_all(parent).context(t!("create-parents-failed"))?;
+            }
+        }
+
+        let bytes = self.encode(path)?;
+        let changed = self
+            .on_disk
+            .as_ref()
+            .is_some_and(|on_disk| *on_disk != bytes);
+        let keep = self.config.user_config.backup_count;
+        if changed && !self.backed_up && !self.config.demo && keep > 0 {
+            crate::backup::backup(path, self.now())?;
+            crate::backup::rotate(path, keep, self.now())?;
+            self.backed_up = true;
+        }
+        // Encrypting a plain statefile mustn't leave readable copies of it behind.
+        if let Some(key) = &self.key {
+            if std::fs::read(path).is_ok_and(|existing| !crypt::is_encrypted(&existing)) {
+                crate::backup::seal(path, key)?;
+            }
+        }
+        self.on_disk = Some(bytes.clone());
+        let bytes = self.seal(bytes)?;
+        // A symlinked statefile is replaced where it points, keeping the link. One that hasn't
+        // changed is left alone, as most commands change nothing.
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+        let unchanged = std::fs::read(&path).is_ok_and(|existing| existing == bytes);
+        if !unchanged {
+            storage::replace(&path, &bytes).context(t!("write-state-failed"))?;
+        }
+
+        // The state is saved by now, so a failure here mustn't look like it wasn't.
+        let unaudited = std::mem::take(&mut self.unaudited);
+        let audited = if self.key.is_some() {
+            // An audit log would keep an encrypted state in the clear, and one written before it
+            // was encrypted is removed.
+            audit::remove(&path)
+        } else if self.config.user_config.audit_log && !unaudited.is_empty() {
+            audit::append(&path, &unaudited)
+        } else {
+            Ok(())
+        };
+        if let Err(error) = audited {
+            eprintln!("{error:#}");
+        }
+        if unchanged && crate::prompt::summary_path(&path).exists() == self.key.is_none() {
+            return Ok(());
+        }
+        // A summary of an encrypted state would leak it, so it is only kept for plain ones.
+        let summary = match (&self.key, &self.on_disk) {
+            (None, Some(bytes)) => crate::prompt::write_summary(&path, bytes),
+            _ => match std::fs::remove_file(crate::prompt::summary_path(&path)) {
+                Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error.into()),
+                _ => Ok(()),
+            },
+        };
+        if let Err(error) = summary {
+            eprintln!("{error:#}");
+        }
+        Ok(())
+    }
+
+    /// Get the path to the default location state file.
+    /// Search order: [`dirs::data_dir`]/prac/prac.json (`%APPDATA%\prac\prac.json` on Windows),
+    /// [`dirs::home_dir`]/.prac.json
+    /// This may be overridden elsewhere, in either the `PRAC_PATH` env var, or with the --file arg.
+    pub fn get_path() -> Result<PathBuf> {
+        if let Some(data_home) = dirs::data_dir() {
+            let default_dir = data_home.join("prac");
+            std::fs::create_dir_all(&default_dir)
+                .with_context(|| t!("create-failed", path = default_dir.display().to_string()))?;
+            let path = default_dir.join("prac.json");
+            Ok(path)
+        } else {
+            let path = dirs::home_dir()
+                .context(t!("no-home-dir"))?
+                .join(".prac.json");
+            Ok(path)
+        }
+    }
+}
+
+/// See [``Cli::SubCommand``](crate::cli::SubCommand) for documentation.
+#[warn(clippy::print_stdout, clippy::print_stderr)]
+#[allow(clippy::missing_docs_in_private_items)]
+// TODO move option hanlding, completely abstract interface from CLI
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+pub enum StateTransition {
+    Add {
+        name: String,
+        #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+        period: Option<Duration>,
+        #[serde(default)]
+        why: Option<String>,
+        #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+        #[serde(default)]
+        weekly_goal: Option<Duration>,
+    },
+    Log {
+        name: String,
+        #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+        /// Maximum amount of time intended to spend. Both useful as a time-boxing strategy and as
+        /// a backstop to neglected termination.
+        time: Duration,
+        /// When the session was, now if `None`.
+        #[serde(default)]
+        at: Option<DateTime<Utc>>,
+        #[serde(default)]
+        note: Option<String>,
+    },
+    /// Start a stopwatch session of a practice.
+    Start {
+        name: String,
+        /// Start even while other sessions are running.
+        #[serde(default)]
+        parallel: bool,
+    },
+    /// Stop a running session, logging `time`: usually the time since it started.
+    Stop {
+        name: String,
+        #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+        time: Duration,
+    },
+    /// Stop a running session without logging it.
+    Cancel {
+        name: String,
+    },
+    /// Restart a practice's bar without logging real time, only [`UserConfig::touch_time`].
+    Touch {
+        name: String,
+    },
+    /// Skip the current period on purpose, restarting the bar without breaking the streak.
+    Skip {
+        name: String,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    Notes {
+        name: String,
+        notes: String,
+        #[serde(default)]
+        append: bool,
+    },
+    Why {
+        name: String,
+        why: Option<String>,
+    },
+    Goal {
+        name: String,
+        #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+        weekly_goal: Option<Duration>,
+    },
+    Budget {
+        name: String,
+        #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+        target_time_per_period: Option<Duration>,
+    },
+    SessionLength {
+        name: String,
+        #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+        session_length: Option<Duration>,
+    },
+    BestTime {
+        name: String,
+        best_time: Option<TimeWindow>,
+    },
+    Context {
+        name: String,
+        context: Option<String>,
+    },
+    EditMode {
+        name: String,
+        mode: LogMode,
+    },
+    Checklist {
+        name: String,
+        item: String,
+        edit: ChecklistEdit,
+    },
+    Archive {
+        name: String,
+        archived: bool,
+    },
+    Hide {
+        name: String,
+        hidden: bool,
+    },
+    /// Let `alias` stand for `name`, or, with `remove`, no longer.
+    Alias {
+        name: String,
+        alias: String,
+        remove: bool,
+    },
+    /// Set the metadata field `key` of `name` to `value`, or, without one, remove it.
+    Meta {
+        name: String,
+        key: String,
+        value: Option<String>,
+    },
+    /// Several transitions applied together, e.g. archiving every practice matching a pattern.
+    /// Should any fail, the command fails and none are saved.
+    Bulk {
+        transitions: Vec<Self>,
+    },
+    SetState {
+        name: String,
+        lifecycle: Lifecycle,
+    },
+    /// Declare (or, with `remove`, retract) that `on` is best done before `name`.
+    Depend {
+        name: String,
+        on: String,
+        #[serde(default)]
+        remove: bool,
+    },
+    /// Correct a logged session: its time, when it was, or its note, where given.
+    EditLog {
+        name: String,
+        /// Index into the practice's log history.
+        index: usize,
+        #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+        #[serde(default)]
+        time: Option<Duration>,
+        #[serde(default)]
+        at: Option<DateTime<Utc>>,
+        #[serde(default)]
+        note: Option<String>,
+    },
+    /// Delete a logged session, taking its time off the cumulative time.
+    DeleteLog {
+        name: String,
+        /// Index into the practice's log history.
+        index: usize,
+    },
+    /// Move (part of) a logged session to another practice.
+    ReassignLog {
+        name: String,
+        /// Index into the practice's log history.
+        index: usize,
+        to: String,
+        /// How much of the session to move, all of it if `None`.
+        #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+        #[serde(default)]
+        time: Option<Duration>,
+    },
+    Remove {
+        name: String,
+    },
+    Restore {
+        name: String,
+        new_name: Option<String>,
+    },
     Rename {
         current_name: String,
         new_name: String,
     },
+    Merge {
+        from: String,
+        into: String,
+    },
     Reset,
     EditPeriod {
+        name: String,
+        #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+        new_period: Option<Duration>,
+    },
+    /// Change the period of a practice in maintenance as adaptive periods suggested.
+    AdaptPeriod {
         name: String,
         #[serde_as(as = "serde_with::DurationSeconds<i64>")]
-        new_period: Duration,
+        period: Duration,
     },
     Config {
         new_config: UserConfig,
     },
 }
 
-#[warn(clippy::print_stdout, clippy::print_stderr)] // This shouldn't use cli at all (warning doesn't catch stdin, but if we're printing, something is wrong)
+impl StateTransition {
+    /// What the transition would change, for `--dry-run`.
+    #[allow(clippy::too_many_lines)]
+    pub fn describe(&self) -> String {
+        let span = |duration: &Duration| crate::time::Span::from(*duration).format();
+        match self {
+            Self::Add { name, period, .. } => period.as_ref().map_or_else(
+                || t!("would-add-log-only", name = name),
+                |period| t!("would-add", name = name, period = span(period)),
+            ),
+            Self::Log { name, time, .. } => t!("would-log", name = name, time = span(time)),
+            Self::Touch { name } => t!("would-touch", name = name),
+            Self::Start { name, .. } => t!("would-start", name = name),
+            Self::Stop { name, time } => t!("would-stop", name = name, time = span(time)),
+            Self::Cancel { name } => t!("would-cancel", name = name),
+            Self::Skip { name, .. } => t!("would-skip", name = name),
+            Self::Notes {
+                name, append: true, ..
+            } => t!("would-append-notes", name = name),
+            Self::Notes { name, .. } => t!("would-set-notes", name = name),
+            Self::Why { name, why: Some(_) } => t!("would-set-why", name = name),
+            Self::Why { name, why: None } => t!("would-clear-why", name = name),
+            Self::Goal { name, weekly_goal } => weekly_goal.as_ref().map_or_else(
+                || t!("would-clear-goal", name = name),
+                |goal| t!("would-set-goal", name = name, goal = span(goal)),
+            ),
+            Self::Budget {
+                name,
+                target_time_per_period,
+            } => target_time_per_period.as_ref().map_or_else(
+                || t!("would-clear-budget", name = name),
+                |target| t!("would-set-budget", name = name, target = span(target)),
+            ),
+            Self::SessionLength {
+                name,
+                session_length,
+            } => session_length.as_ref().map_or_else(
+                || t!("would-clear-session-length", name = name),
+                |length| {
+                    t!(
+                        "would-set-session-length",
+                        name = name,
+                        length = span(length)
+                    )
+                },
+            ),
+            Self::Context { name, context } => context.as_ref().map_or_else(
+                || t!("would-clear-context", name = name),
+                |context| t!("would-set-context", name = name, context = context),
+            ),
+            Self::BestTime { name, best_time } => best_time.as_ref().map_or_else(
+                || t!("would-clear-best-time", name = name),
+                |window| {
+                    t!(
+                        "would-set-best-time",
+                        name = name,
+                        window = window.to_string()
+                    )
+                },
+            ),
+            Self::EditMode { name, mode } => {
+                let mode = clap::ValueEnum::to_possible_value(mode)
+                    .map(|value| value.get_name().to_owned())
+                    .unwrap_or_default();
+                t!("would-edit-mode", name = name, mode = mode)
+            }
+            Self::Checklist { name, item, edit } => match edit {
+                ChecklistEdit::Add => t!("would-add-item", name = name, item = item),
+                ChecklistEdit::Remove => t!("would-remove-item", name = name, item = item),
+                ChecklistEdit::Check => t!("would-check-item", name = name, item = item),
+                ChecklistEdit::Uncheck => t!("would-uncheck-item", name = name, item = item),
+            },
+            Self::Archive {
+                name,
+                archived: true,
+            } => t!("would-archive", name = name),
+            Self::Archive { name, .. } => t!("would-unarchive", name = name),
+            Self::Hide { name, hidden: true } => t!("would-hide", name = name),
+            Self::Hide { name, .. } => t!("would-unhide", name = name),
+            Self::Alias {
+                name,
+                alias,
+                remove: false,
+            } => t!("would-alias", name = name, alias = alias),
+            Self::Alias { name, alias, .. } => t!("would-unalias", name = name, alias = alias),
+            Self::SetState { name, lifecycle } => {
+                let lifecycle = clap::ValueEnum::to_possible_value(lifecycle)
+                    .map(|value| value.get_name().to_owned())
+                    .unwrap_or_default();
+                t!("would-set-state", name = name, state = lifecycle)
+            }
+            Self::Depend {
+                name,
+                on,
+                remove: false,
+            } => t!("would-depend", name = name, on = on),
+            Self::Depend { name, on, .. } => t!("would-undepend", name = name, on = on),
+            Self::EditLog { name, index, .. } => {
+                t!("would-edit-log", name = name, number = index + 1)
+            }
+            Self::DeleteLog { name, index } => {
+                t!("would-delete-log", name = name, number = index + 1)
+            }
+            Self::ReassignLog {
+                name,
+                index,
+                to,
+                time,
+            } => time.as_ref().map_or_else(
+                || t!("would-reassign", name = name, number = index + 1, to = to),
+                |time| {
+                    t!(
+                        "would-reassign-part",
+                        name = name,
+                        number = index + 1,
+                        to = to,
+                        time = span(time)
+                    )
+                },
+            ),
+            Self::Remove { name } => t!("would-remove", name = name),
+            Self::Restore { name, new_name } => new_name.as_ref().map_or_else(
+                || t!("would-restore", name = name),
+                |new_name| t!("would-restore-as", name = name, new_name = new_name),
+            ),
+            Self::Rename {
+                current_name,
+                new_name,
+            } => t!("would-rename", name = current_name, new_name = new_name),
+            Self::Merge { from, into } => t!("would-merge", from = from, into = into),
+            Self::Meta {
+                name,
+                key,
+                value: Some(value),
+            } => t!("would-set-meta", name = name, key = key, value = value),
+            Self::Meta { name, key, .. } => t!("would-unset-meta", name = name, key = key),
+            Self::Reset => t!("would-reset"),
+            Self::Bulk { transitions } => transitions
+                .iter()
+                .map(Self::describe)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Self::EditPeriod { name, new_period } => new_period.as_ref().map_or_else(
+                || t!("would-edit-period-log-only", name = name),
+                |period| t!("would-edit-period", name = name, period = span(period)),
+            ),
+            Self::AdaptPeriod { name, period } => {
+                t!("would-adapt-period", name = name, period = span(period))
+            }
+            Self::Config { .. } => t!("would-set-config"),
+        }
+    }
+
+    /// Names of the practice the transition is about, before and after it is applied. Either is
+    /// `None` where there is no such practice, e.g. before one is added.
+    pub fn practice_names(&self) -> (Option<&str>, Option<&str>) {
+        match self {
+            Self::Add { name, .. } => (None, Some(name)),
+            Self::Log { name, .. }
+            | Self::Touch { name }
+            | Self::Start { name, .. }
+            | Self::Stop { name, .. }
+            | Self::Cancel { name }
+            | Self::Skip { name, .. }
+            | Self::Notes { name, .. }
+            | Self::Why { name, .. }
+            | Self::Goal { name, .. }
+            | Self::Budget { name, .. }
+            | Self::SessionLength { name, .. }
+            | Self::BestTime { name, .. }
+            | Self::Context { name, .. }
+            | Self::EditMode { name, .. }
+            | Self::Checklist { name, .. }
+            | Self::Archive { name, .. }
+            | Self::Hide { name, .. }
+            | Self::Alias { name, .. }
+            | Self::Meta { name, .. }
+            | Self::SetState { name, .. }
+            | Self::Depend { name, .. }
+            | Self::EditLog { name, .. }
+            | Self::DeleteLog { name, .. }
+            | Self::ReassignLog { name, .. }
+            | Self::EditPeriod { name, .. }
+            | Self::AdaptPeriod { name, .. } => (Some(name), Some(name)),
+            Self::Remove { name } => (Some(name), None),
+            Self::Restore { name, new_name } => (None, Some(new_name.as_ref().unwrap_or(name))),
+            Self::Rename {
+                current_name,
+                new_name,
+            } => (Some(current_name), Some(new_name)),
+            Self::Merge { into, .. } => (Some(into), Some(into)),
+            Self::Reset | Self::Config { .. } | Self::Bulk { .. } => (None, None),
+        }
+    }
+
+    /// Name of the kind of transition, as in hook names, e.g. `edit-period`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Add { .. } => "add",
+            Self::Log { .. } => "log",
+            Self::Touch { .. } => "touch",
+            Self::Start { .. } => "start",
+            Self::Stop { .. } => "stop",
+            Self::Cancel { .. } => "cancel",
+            Self::Skip { .. } => "skip",
+            Self::Notes { .. } => "notes",
+            Self::Why { .. } => "why",
+            Self::Goal { .. } => "goal",
+            Self::Budget { .. } => "budget",
+            Self::SessionLength { .. } => "session-length",
+            Self::BestTime { .. } => "best-time",
+            Self::Context { .. } => "context",
+            Self::EditMode { .. } => "edit-mode",
+            Self::Checklist { .. } => "checklist",
+            Self::Archive { .. } => "archive",
+            Self::Hide { .. } => "hide",
+            Self::Alias { .. } => "alias",
+            Self::SetState { .. } => "set-state",
+            Self::Depend { .. } => "depend",
+            Self::EditLog { .. } => "edit-log",
+            Self::DeleteLog { .. } => "delete-log",
+            Self::ReassignLog { .. } => "reassign-log",
+            Self::Remove { .. } => "remove",
+            Self::Restore { .. } => "restore",
+            Self::Rename { .. } => "rename",
+            Self::Merge { .. } => "merge",
+            Self::Reset => "reset",
+            Self::Meta { .. } => "meta",
+            Self::Bulk { .. } => "bulk",
+            Self::EditPeriod { .. } => "edit-period",
+            Self::AdaptPeriod { .. } => "adapt-period",
+            Self::Config { .. } => "config",
+        }
+    }
+}
+
+/// Shortest word two practice names can share to be taken as similar, leaving out e.g. "of".
+const SIMILAR_WORD_LENGTH: usize = 3;
+
+/// `period` rounded to whole days if it is a day or more, to whole hours or minutes if it is an
+/// hour or minute or more, otherwise to whole seconds, and at least one of them.
+fn round_period(period: Duration) -> Duration {
+    let unit = [Duration::days(1), Duration::hours(1), Duration::minutes(1)]
+        .into_iter()
+        .find(|unit| period >= *unit)
+        .unwrap_or_else(|| Duration::seconds(1));
+    let units = (period.num_seconds() + unit.num_seconds() / 2) / unit.num_seconds();
+    unit * i32::try_from(units.max(1)).unwrap_or(i32::MAX)
+}
+
+/// Time logged in the period current at `now` and the target time per period, for practices with
+/// one.
+fn budget_progress(practice: &Practice, now: DateTime<Utc>) -> Option<(Duration, Duration)> {
+    let target = practice
+        .target_time_per_period
+        .filter(|_| practice.period.is_some())?;
+    Some((practice.logged_this_period(now), target))
+}
+
+/// Change to a checklist item, see [`StateTransition::Checklist`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChecklistEdit {
+    Add,
+    Remove,
+    Check,
+    Uncheck,
+}
+
+/// Trim surrounding whitespace from a practice name and bring it to Unicode normal form C,
+/// rejecting names left empty. Names differing only in surrounding whitespace, or in how the same
+/// characters are encoded, would otherwise show up as identical duplicates.
+fn normalize_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    ensure!(!name.is_empty(), t!("practice-name-empty"));
+    Ok(name.nfc().collect())
+}
+
+/// What practice names are compared by, unless the `exact_names` config is set: `Steno`, `steno`
+/// and ` steno `, or "é" written as one character or as "e" and an accent, are the same name.
+fn name_key(name: &str) -> String {
+    name.trim().nfc().collect::<String>().to_lowercase()
+}
+
+/// Apply `transition` to `state`, recording it for the audit log once the state is saved.
 pub fn handle_transition(state: &mut State, transition: StateTransition) -> Result<()> {
+    let recorded = serde_json::to_value(&transition).context(t!("serialize-transition-failed"))?;
+    apply_transition(state, transition)?;
+    state.update_outcomes();
+    state.adapt_periods();
+    let at = state.now();
+    state.unaudited.push(AuditEntry::new(recorded, at));
+    Ok(())
+}
+
+#[allow(clippy::too_many_lines)]
+#[warn(clippy::print_stdout, clippy::print_stderr)] // This shouldn't use cli at all (warning doesn't catch stdin, but if we're printing, something is wrong)
+fn apply_transition(state: &mut State, transition: StateTransition) -> Result<()> {
+    let now = state.now();
     match transition {
-        StateTransition::Add { name, period } => {
-            let practice = Practice::new(name.clone(), String::new(), period);
+        StateTransition::Add {
+            name,
+            period,
+            why,
+            weekly_goal,
+        } => {
+            let name = normalize_name(&name)?;
+            state.ensure_distinct_name(&name, None)?;
+            check_period(period)?;
+            let mut practice = Practice::new(name.clone(), String::new(), period, now);
+            practice.why = why;
+            practice.weekly_goal = weekly_goal;
             match state.practices.entry(practice.name.clone()) {
                 btree_map::Entry::Occupied(_) => {
-                    bail!("Practice with name \"{name}\" already exists.")
+                    bail!(Error::Conflict(t!("practice-exists", name = &name)))
                 }
                 btree_map::Entry::Vacant(entry) => entry.insert(practice),
             };
             Ok(())
         }
-        StateTransition::Log { name, time } => {
+        StateTransition::Log {
+            name,
+            time,
+            at,
+            note,
+        } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            let at = at.unwrap_or(now);
+            ensure!(at <= now, t!("log-in-future"));
+            practice.log(LogEntry {
+                at,
+                time,
+                source: None,
+                note,
+                skipped: false,
+            });
+            Ok(())
+        }
+        StateTransition::Start { name, parallel } => state.start_session(name, parallel),
+        StateTransition::Stop { name, time } => {
+            state.end_session(&name)?;
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            practice.log(LogEntry {
+                at: now,
+                time,
+                source: None,
+                note: None,
+                skipped: false,
+            });
+            Ok(())
+        }
+        StateTransition::Cancel { name } => state.end_session(&name).map(|_| ()),
+        StateTransition::Touch { name } => {
+            let time = state.config.user_config.touch_time;
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            // Unlike a log, a touch restarts the bar whatever the practice's log mode.
+            practice.restart(LogEntry {
+                at: now,
+                time,
+                source: None,
+                note: None,
+                skipped: false,
+            });
+            Ok(())
+        }
+        StateTransition::Skip { name, reason } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            ensure!(practice.period.is_some(), t!("skip-log-only"));
+            practice.restart(LogEntry {
+                at: now,
+                time: Duration::zero(),
+                source: None,
+                note: reason,
+                skipped: true,
+            });
+            Ok(())
+        }
+        StateTransition::Notes {
+            name,
+            notes,
+            append,
+        } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            let notes = if append {
+                let stamp = now.with_timezone(&Local).format("%a %Y-%m-%d %H:%M");
+                let entry = format!("--- {stamp} ---\n{}", notes.trim_end());
+                if practice.notes.trim().is_empty() {
+                    entry
+                } else {
+                    format!("{}\n\n{entry}", practice.notes.trim_end())
+                }
+            } else {
+                notes
+            };
+            practice.set_notes(notes, now);
+            Ok(())
+        }
+        StateTransition::Why { name, why } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            practice.why = why;
+            Ok(())
+        }
+        StateTransition::Goal { name, weekly_goal } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            practice.weekly_goal = weekly_goal;
+            Ok(())
+        }
+        StateTransition::SessionLength {
+            name,
+            session_length,
+        } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            practice.session_length = session_length;
+            Ok(())
+        }
+        StateTransition::BestTime { name, best_time } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            practice.best_time = best_time;
+            Ok(())
+        }
+        StateTransition::Context { name, context } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            practice.context = context
+                .map(|context| context.trim().to_owned())
+                .filter(|context| !context.is_empty());
+            Ok(())
+        }
+        StateTransition::Budget {
+            name,
+            target_time_per_period,
+        } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            ensure!(
+                practice.period.is_some() || target_time_per_period.is_none(),
+                t!("budget-log-only", name = &name)
+            );
+            practice.target_time_per_period = target_time_per_period;
+            Ok(())
+        }
+        StateTransition::Checklist { name, item, edit } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            let item = item.trim();
+            let index = practice
+                .checklist
+                .iter()
+                .position(|entry| entry.item == item);
+            match (edit, index) {
+                (ChecklistEdit::Add, None) => {
+                    ensure!(!item.is_empty(), t!("checklist-item-empty"));
+                    practice.checklist.push(ChecklistItem {
+                        item: item.to_owned(),
+                        checked: None,
+                    });
+                }
+                (ChecklistEdit::Add, Some(_)) => {
+                    bail!(Error::Conflict(t!(
+                        "checklist-item-exists",
+                        name = &name,
+                        item = item
+                    )))
+                }
+                (_, None) => bail!(Error::NotFound(t!(
+                    "checklist-item-not-found",
+                    name = &name,
+                    item = item
+                ))),
+                (ChecklistEdit::Remove, Some(index)) => {
+                    practice.checklist.remove(index);
+                }
+                (ChecklistEdit::Check, Some(index)) => {
+                    practice.checklist[index].checked = Some(now);
+                }
+                (ChecklistEdit::Uncheck, Some(index)) => {
+                    practice.checklist[index].checked = None;
+                }
+            }
+            Ok(())
+        }
+        StateTransition::EditMode { name, mode } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            ensure!(
+                mode != LogMode::Accumulate || practice.target_time_per_period.is_some(),
+                t!("accumulate-needs-budget", name = &name)
+            );
+            practice.log_mode = mode;
+            Ok(())
+        }
+        StateTransition::Archive { name, archived } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            practice.archived = archived;
+            Ok(())
+        }
+        StateTransition::Hide { name, hidden } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            practice.hidden = hidden;
+            Ok(())
+        }
+        StateTransition::Alias {
+            name,
+            alias,
+            remove: false,
+        } => state.add_alias(&name, &alias),
+        StateTransition::Alias { name, alias, .. } => state.remove_alias(&name, &alias),
+        StateTransition::SetState { name, lifecycle } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            practice.archived = lifecycle == Lifecycle::Retired;
+            practice.maintenance = lifecycle == Lifecycle::Maintenance;
+            Ok(())
+        }
+        StateTransition::Depend {
+            name,
+            on,
+            remove: false,
+        } => state.add_dependency(name, on),
+        StateTransition::Depend { name, on, .. } => state.remove_dependency(&name, &on),
+        StateTransition::EditLog {
+            name,
+            index,
+            time,
+            at,
+            note,
+        } => {
             let practice = state
                 .practices
                 .get_mut(&name)
-                .context("Practice not found.")?;
-            practice.logged = Utc::now();
-            practice.cumulative = practice.cumulative + time;
+                .with_context(|| Error::NotFound(t!("practice-name-not-found", name = &name)))?;
+            let restarted_from = practice.logged;
+            let mut entry = practice.remove_log(index).with_context(|| {
+                Error::NotFound(t!("no-entry", name = &name, number = index + 1))
+            })?;
+            if let Some(time) = time {
+                ensure!(!entry.skipped, t!("edit-skipped-time"));
+                ensure!(time > Duration::zero(), t!("edit-log-no-time"));
+                entry.time = time;
+            }
+            if let Some(at) = at {
+                ensure!(at <= now, t!("log-in-future"));
+                entry.at = at;
+            }
+            if let Some(note) = note {
+                entry.note = Some(note.trim().to_owned()).filter(|note| !note.is_empty());
+            }
+            // The bar restarts from the session as corrected, if it restarted from it before.
+            if restarted_from != practice.logged {
+                practice.logged = practice.logged.max(entry.at);
+            }
+            practice.cumulative += entry.time;
+            practice.insert_log(entry);
             Ok(())
         }
-        StateTransition::Notes { name, notes } => {
+        StateTransition::DeleteLog { name, index } => {
             let practice = state
                 .practices
                 .get_mut(&name)
-                .context("Practice not found.")?;
-            practice.notes = notes;
+                .with_context(|| Error::NotFound(t!("practice-name-not-found", name = &name)))?;
+            practice.remove_log(index).with_context(|| {
+                Error::NotFound(t!("no-entry", name = &name, number = index + 1))
+            })?;
+            Ok(())
+        }
+        StateTransition::ReassignLog {
+            name,
+            index,
+            to,
+            time,
+        } => {
+            ensure!(name != to, t!("reassign-same-practice"));
+            ensure!(
+                state.practices.contains_key(&to),
+                Error::NotFound(t!("practice-name-not-found", name = &to))
+            );
+            let from = state
+                .practices
+                .get_mut(&name)
+                .with_context(|| Error::NotFound(t!("practice-name-not-found", name = &name)))?;
+            let entry = from.logs.get_mut(index).with_context(|| {
+                Error::NotFound(t!("no-entry", name = &name, number = index + 1))
+            })?;
+            ensure!(!entry.skipped, t!("reassign-skipped"));
+            let at = entry.at;
+            let note = entry.note.clone();
+            let time = time.unwrap_or(entry.time);
+            ensure!(
+                time > Duration::zero() && time <= entry.time,
+                t!("reassign-time-out-of-range")
+            );
+
+            if time == entry.time {
+                from.remove_log(index);
+            } else {
+                entry.time -= time;
+                from.cumulative -= time;
+            }
+
+            let to = state
+                .practices
+                .get_mut(&to)
+                .expect("we already checked for key membership");
+            to.insert_log(LogEntry {
+                at,
+                time,
+                source: None,
+                note,
+                skipped: false,
+            });
+            to.cumulative += time;
+            to.logged = to.logged.max(at);
             Ok(())
         }
         StateTransition::Remove { name } => {
             let practice = state.practices.entry(name.clone());
-            match practice {
+            let practice = match practice {
                 btree_map::Entry::Vacant(_) => {
-                    bail!("Practice with name \"{name}\" not found. (Case sensitive)")
+                    bail!(Error::NotFound(t!("practice-name-not-found", name = &name)))
                 }
                 btree_map::Entry::Occupied(entry) => entry.remove(),
             };
+            state.drop_dependencies(&name);
+            state.drop_session(&name);
+            state.trash.push(TrashedPractice {
+                removed: now,
+                practice,
+            });
+            Ok(())
+        }
+        StateTransition::Restore { name, new_name } => {
+            // The most recently removed practice of that name wins.
+            let index = state
+                .trash
+                .iter()
+                .rposition(|trashed| trashed.practice.name == name)
+                .with_context(|| Error::NotFound(t!("not-in-trash", name = &name)))?;
+            let new_name = normalize_name(new_name.as_deref().unwrap_or(&name))?;
+            ensure!(
+                !state.practices.contains_key(&new_name),
+                Error::Conflict(t!("restore-name-taken", name = &new_name))
+            );
+            state.ensure_distinct_name(&new_name, None)?;
+            let mut practice = state.trash.remove(index).practice;
+            practice.name.clone_from(&new_name);
+            state.practices.insert(new_name, practice);
             Ok(())
         }
         StateTransition::Rename {
             current_name,
             new_name,
         } => {
+            let new_name = normalize_name(&new_name)?;
             ensure!(
-                state.practices.contains_key(&new_name),
-                "Practice with name \"{new_name}\" already exists."
+                state.practices.contains_key(&current_name),
+                Error::NotFound(t!("practice-name-not-found", name = &current_name))
             );
             ensure!(
                 !state.practices.contains_key(&new_name),
-                "Practice with name \"{new_name}\" already exists."
+                Error::Conflict(t!("practice-exists", name = &new_name))
             );
+            state.ensure_distinct_name(&new_name, Some(&current_name))?;
 
-            let practice = state
+            let mut practice = state
                 .practices
                 .remove(&current_name)
                 .expect("we already checked for key membership");
+            practice.name.clone_from(&new_name);
+            state.rename_in_dependencies(&current_name, &new_name);
+            state.rename_in_sessions(&current_name, &new_name);
             state.practices.insert(new_name, practice);
             Ok(())
         }
+        StateTransition::Merge { from, into } => {
+            ensure!(from != into, t!("merge-into-itself"));
+            ensure!(
+                state.practices.contains_key(&into),
+                Error::NotFound(t!("practice-name-not-found", name = &into))
+            );
+            let from = state
+                .practices
+                .remove(&from)
+                .with_context(|| Error::NotFound(t!("practice-name-not-found", name = &from)))?;
+            state.rename_in_dependencies(&from.name, &into);
+            state.rename_in_sessions(&from.name, &into);
+            state
+                .practices
+                .get_mut(&into)
+                .expect("we already checked for key membership")
+                .absorb(from);
+            Ok(())
+        }
+        StateTransition::Meta { name, key, value } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .with_context(|| Error::NotFound(t!("practice-name-not-found", name = &name)))?;
+            let key = key.trim();
+            ensure!(!key.is_empty(), t!("meta-key-empty"));
+            if let Some(value) = value {
+                practice.metadata.insert(key.to_owned(), value);
+            } else {
+                practice.metadata.remove(key).with_context(|| {
+                    Error::NotFound(t!("meta-key-not-found", name = &name, key = key))
+                })?;
+            }
+            Ok(())
+        }
+        StateTransition::Bulk { transitions } => {
+            for transition in transitions {
+                apply_transition(state, transition)?;
+            }
+            Ok(())
+        }
         StateTransition::Reset => {
-            let now = Utc::now();
             for practice in state.practices.values_mut() {
                 practice.logged = now;
             }
             Ok(())
         }
         StateTransition::EditPeriod { name, new_period } => {
+            check_period(new_period)?;
             let practice = state
                 .practices
                 .get_mut(&name)
-                .context("Practice not found.")?;
+                .context(Error::NotFound(t!("practice-unknown")))?;
             practice.period = new_period;
             Ok(())
         }
+        StateTransition::AdaptPeriod { name, period } => {
+            let practice = state
+                .practices
+                .get_mut(&name)
+                .context(Error::NotFound(t!("practice-unknown")))?;
+            practice.adapt_period(period, false, now);
+            Ok(())
+        }
         StateTransition::Config { new_config } => {
             state.config.user_config = new_config;
             Ok(())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{handle_transition, LogMode, Outcome, State, StateTransition};
+    use crate::clock::{FixedClock, SharedClock};
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn set_now(state: &mut State, now: DateTime<Utc>) {
+        state.set_clock(SharedClock::new(FixedClock(now)));
+    }
+
+    fn add(state: &mut State, name: &str) -> anyhow::Result<()> {
+        let add = StateTransition::Add {
+            name: name.to_owned(),
+            period: Some(Duration::days(1)),
+            why: None,
+            weekly_goal: None,
+        };
+        handle_transition(state, add)
+    }
+
+    fn rename(state: &mut State, current_name: &str, new_name: &str) -> anyhow::Result<()> {
+        let rename = StateTransition::Rename {
+            current_name: current_name.to_owned(),
+            new_name: new_name.to_owned(),
+        };
+        handle_transition(state, rename)
+    }
+
+    fn log(state: &mut State, name: &str, minutes: i64) {
+        let log = StateTransition::Log {
+            name: name.to_owned(),
+            time: Duration::minutes(minutes),
+            at: None,
+            note: None,
+        };
+        handle_transition(state, log).unwrap();
+    }
+
+    /// A state with practices "steno" and "chess", created at noon on March 1st.
+    fn state() -> State {
+        let mut state = State::new();
+        set_now(&mut state, at(1, 12));
+        add(&mut state, "steno").unwrap();
+        add(&mut state, "chess").unwrap();
+        state
+    }
+
+    #[test]
+    fn test_rename() {
+        let mut state = state();
+        let transitions = [
+            StateTransition::Alias {
+                name: "steno".to_owned(),
+                alias: "st".to_owned(),
+                remove: false,
+            },
+            StateTransition::Depend {
+                name: "steno".to_owned(),
+                on: "chess".to_owned(),
+                remove: false,
+            },
+            StateTransition::Start {
+                name: "steno".to_owned(),
+                parallel: false,
+            },
+        ];
+        for transition in transitions {
+            handle_transition(&mut state, transition).unwrap();
+        }
+
+        rename(&mut state, "steno", "stenography").unwrap();
+        assert!(!state.practices.contains_key("steno"));
+        assert_eq!(state.practices["stenography"].name, "stenography");
+        assert_eq!(state.aliases_of("stenography").unwrap(), ["st"]);
+        assert_eq!(state.resolve_name("st".to_owned()), "stenography");
+        assert_eq!(state.dependencies_of("stenography").unwrap(), ["chess"]);
+        assert_eq!(state.running_since("stenography"), Some(at(1, 12)));
+        assert_eq!(state.running_since("steno"), None);
+
+        // Renaming it back is just as free, to any name not taken.
+        rename(&mut state, "stenography", "steno").unwrap();
+        assert_eq!(state.dependencies_of("steno").unwrap(), ["chess"]);
+        let error = rename(&mut state, "piano", "keys").unwrap_err();
+        assert_eq!(error.to_string(), "Practice with name \"piano\" not found.");
+    }
+
+    #[test]
+    fn test_name_validation() {
+        let mut state = state();
+        add(&mut state, "  piano ").unwrap();
+        assert!(state.practices.contains_key("piano"));
+        for name in ["", "   "] {
+            let error = add(&mut state, name).unwrap_err();
+            assert_eq!(error.to_string(), "Practice name can't be empty.");
+            let error = rename(&mut state, "piano", name).unwrap_err();
+            assert_eq!(error.to_string(), "Practice name can't be empty.");
+        }
+        let error = add(&mut state, " piano").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Practice with name \"piano\" already exists."
+        );
+        let error = rename(&mut state, "piano", "chess ").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Practice with name \"chess\" already exists."
+        );
+        assert!(add(&mut state, "Piano").is_err());
+        assert!(rename(&mut state, "steno", "Chess").is_err());
+
+        // A practice may change the case of its own name.
+        rename(&mut state, "piano", "Piano").unwrap();
+        assert!(state.practices.contains_key("Piano"));
+        assert_eq!(state.practices.len(), 3);
+    }
+
+    #[test]
+    fn test_log_modes() {
+        let mut state = state();
+        let edit_mode = |name: &str, mode| StateTransition::EditMode {
+            name: name.to_owned(),
+            mode,
+        };
+        // There is nothing to accumulate towards without a budget.
+        assert!(handle_transition(&mut state, edit_mode("steno", LogMode::Accumulate)).is_err());
+        let budget = StateTransition::Budget {
+            name: "steno".to_owned(),
+            target_time_per_period: Some(Duration::hours(1)),
+        };
+        handle_transition(&mut state, budget).unwrap();
+        handle_transition(&mut state, edit_mode("steno", LogMode::Accumulate)).unwrap();
+        handle_transition(&mut state, edit_mode("chess", LogMode::Checkpoint)).unwrap();
+
+        // Accumulating restarts the bar once the logs add up to the budget.
+        set_now(&mut state, at(1, 14));
+        log(&mut state, "steno", 20);
+        assert_eq!(state.practices["steno"].logged, at(1, 12));
+        set_now(&mut state, at(1, 16));
+        log(&mut state, "steno", 40);
+        assert_eq!(state.practices["steno"].logged, at(1, 16));
+        assert_eq!(state.practices["steno"].cumulative, Duration::hours(1));
+
+        // A checkpoint restarts it only with the first log after the period.
+        set_now(&mut state, at(1, 20));
+        log(&mut state, "chess", 30);
+        assert_eq!(state.practices["chess"].logged, at(1, 12));
+        set_now(&mut state, at(2, 13));
+        log(&mut state, "chess", 30);
+        assert_eq!(state.practices["chess"].logged, at(2, 13));
+
+        // Resetting restarts it with every log, but not with one from before it last restarted.
+        handle_transition(&mut state, edit_mode("chess", LogMode::Reset)).unwrap();
+        set_now(&mut state, at(2, 15));
+        log(&mut state, "chess", 30);
+        assert_eq!(state.practices["chess"].logged, at(2, 15));
+        let earlier = StateTransition::Log {
+            name: "chess".to_owned(),
+            time: Duration::minutes(30),
+            at: Some(at(2, 14)),
+            note: None,
+        };
+        handle_transition(&mut state, earlier).unwrap();
+        assert_eq!(state.practices["chess"].logged, at(2, 15));
+        assert_eq!(state.practices["chess"].cumulative, Duration::hours(2));
+    }
+
+    #[test]
+    fn test_touch() {
+        let mut state = state();
+        let checkpoint = StateTransition::EditMode {
+            name: "chess".to_owned(),
+            mode: LogMode::Checkpoint,
+        };
+        handle_transition(&mut state, checkpoint).unwrap();
+        let touch = |name: &str| StateTransition::Touch {
+            name: name.to_owned(),
+        };
+
+        // A touch restarts the bar even where a log wouldn't, adding no time by default.
+        set_now(&mut state, at(1, 18));
+        handle_transition(&mut state, touch("chess")).unwrap();
+        let chess = &state.practices["chess"];
+        assert_eq!(chess.logged, at(1, 18));
+        assert_eq!(chess.cumulative, Duration::zero());
+        assert_eq!(chess.logs.len(), 1);
+
+        state.config.user_config.touch_time = Duration::minutes(5);
+        set_now(&mut state, at(1, 20));
+        handle_transition(&mut state, touch("chess")).unwrap();
+        let chess = &state.practices["chess"];
+        assert_eq!(chess.logged, at(1, 20));
+        assert_eq!(chess.cumulative, Duration::minutes(5));
+        assert!(handle_transition(&mut state, touch("piano")).is_err());
+    }
+
+    #[test]
+    fn test_skip() {
+        let mut state = state();
+        set_now(&mut state, at(2, 10));
+        let skip = StateTransition::Skip {
+            name: "steno".to_owned(),
+            reason: Some("Sick".to_owned()),
+        };
+        handle_transition(&mut state, skip).unwrap();
+        let steno = &state.practices["steno"];
+        assert_eq!(steno.logged, at(2, 10));
+        assert_eq!(steno.cumulative, Duration::zero());
+        let entry = &steno.logs[0];
+        assert!(entry.skipped);
+        assert_eq!(entry.note.as_deref(), Some("Sick"));
+        assert!(matches!(steno.recent_outcomes(1)[..], [Outcome::Skipped]));
+
+        let log_only = StateTransition::Add {
+            name: "reading".to_owned(),
+            period: None,
+            why: None,
+            weekly_goal: None,
+        };
+        handle_transition(&mut state, log_only).unwrap();
+        let skip = StateTransition::Skip {
+            name: "reading".to_owned(),
+            reason: None,
+        };
+        let error = handle_transition(&mut state, skip).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Log-only practices have no period to skip."
+        );
+    }
+
+    #[test]
+    fn test_cancel_and_stop() {
+        let mut state = state();
+        let start = || StateTransition::Start {
+            name: "steno".to_owned(),
+            parallel: false,
+        };
+        let cancel = || StateTransition::Cancel {
+            name: "steno".to_owned(),
+        };
+        handle_transition(&mut state, start()).unwrap();
+        set_now(&mut state, at(2, 9));
+        handle_transition(&mut state, cancel()).unwrap();
+        assert_eq!(state.running_since("steno"), None);
+        assert!(state.practices["steno"].logs.is_empty());
+        let error = handle_transition(&mut state, cancel()).unwrap_err();
+        assert_eq!(error.to_string(), "\"steno\" has no running session.");
+
+        // Stopping logs the time it is given, such as one trimmed to the longest session.
+        handle_transition(&mut state, start()).unwrap();
+        set_now(&mut state, at(2, 21));
+        let stop = StateTransition::Stop {
+            name: "steno".to_owned(),
+            time: Duration::hours(4),
+        };
+        handle_transition(&mut state, stop).unwrap();
+        let steno = &state.practices["steno"];
+        assert_eq!(state.running_since("steno"), None);
+        assert_eq!(steno.logged, at(2, 21));
+        assert_eq!(steno.cumulative, Duration::hours(4));
+    }
+
+    #[test]
+    fn test_parallel_sessions() {
+        let mut state = state();
+        let start = |name: &str, parallel| StateTransition::Start {
+            name: name.to_owned(),
+            parallel,
+        };
+        handle_transition(&mut state, start("steno", false)).unwrap();
+        let error = handle_transition(&mut state, start("steno", true)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "A session of \"steno\" is already running."
+        );
+        let error = handle_transition(&mut state, start("chess", false)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "A session of \"steno\" is running. Stop it first, or start \"chess\" with --parallel."
+        );
+        assert_eq!(state.running_since("chess"), None);
+
+        set_now(&mut state, at(1, 13));
+        handle_transition(&mut state, start("chess", true)).unwrap();
+        assert_eq!(state.running_since("steno"), Some(at(1, 12)));
+        assert_eq!(state.running_since("chess"), Some(at(1, 13)));
+        assert!(handle_transition(&mut state, start("piano", true)).is_err());
+    }
+
+    #[test]
+    fn test_bulk() {
+        let mut state = state();
+        let archive = |name: &str| StateTransition::Archive {
+            name: name.to_owned(),
+            archived: true,
+        };
+        let hide = |name: &str| StateTransition::Hide {
+            name: name.to_owned(),
+            hidden: true,
+        };
+        let audited = state.unaudited.len();
+        let bulk = StateTransition::Bulk {
+            transitions: vec![archive("steno"), archive("chess"), hide("chess")],
+        };
+        handle_transition(&mut state, bulk).unwrap();
+        assert!(state.practices.values().all(|practice| practice.archived));
+        assert!(!state.practices["steno"].hidden);
+        assert!(state.practices["chess"].hidden);
+        assert_eq!(state.unaudited.len(), audited + 1);
+
+        // One practice that isn't there fails the lot, for the command not to save any of it.
+        let bulk = StateTransition::Bulk {
+            transitions: vec![hide("steno"), hide("piano")],
+        };
+        let error = handle_transition(&mut state, bulk).unwrap_err();
+        assert_eq!(error.to_string(), "Practice not found.");
+        assert_eq!(state.unaudited.len(), audited + 1);
+    }
+
+    #[test]
+    fn test_outcomes() {
+        let mut state = state();
+        state.config.user_config.grace_period = Duration::hours(6);
+        let outcomes = |state: &State| {
+            let steno = &state.practices["steno"];
+            steno
+                .outcomes
+                .iter()
+                .map(|outcome| (outcome.ended, outcome.outcome))
+                .collect::<Vec<_>>()
+        };
+
+        set_now(&mut state, at(2, 10));
+        log(&mut state, "steno", 30);
+        set_now(&mut state, at(3, 14));
+        log(&mut state, "steno", 30);
+        // A period is missed when it lapses, whatever is done then.
+        set_now(&mut state, at(5, 0));
+        log(&mut state, "chess", 30);
+        let missed = (at(4, 20), Outcome::Missed);
+        assert_eq!(
+            outcomes(&state),
+            [
+                (at(2, 10), Outcome::OnTime),
+                (at(3, 14), Outcome::Grace),
+                missed
+            ]
+        );
+        assert_eq!(state.practices["steno"].streak(), 0);
+
+        // Logging after it lapsed starts the next period, without counting the missed one twice.
+        set_now(&mut state, at(5, 10));
+        log(&mut state, "steno", 30);
+        set_now(&mut state, at(6, 8));
+        let skip = StateTransition::Skip {
+            name: "steno".to_owned(),
+            reason: None,
+        };
+        handle_transition(&mut state, skip).unwrap();
+        set_now(&mut state, at(7, 9));
+        log(&mut state, "steno", 30);
+        assert_eq!(
+            outcomes(&state)[2..],
+            [
+                missed,
+                (at(6, 8), Outcome::Skipped),
+                (at(7, 9), Outcome::Grace)
+            ]
+        );
+        assert_eq!(state.practices["steno"].streak(), 1);
+
+        // Kept up as it goes, they match working them out from the whole history.
+        let steno = &state.practices["steno"];
+        let replayed = steno.replay_outcomes(Duration::hours(6), at(7, 9));
+        let replayed = replayed
+            .iter()
+            .map(|outcome| (outcome.ended, outcome.outcome));
+        assert!(replayed.eq(outcomes(&state)));
+    }
+}
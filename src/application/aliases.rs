@@ -0,0 +1,294 @@
+//! Finding the practice a name refers to: names are matched ignoring case and Unicode
+//! normalization (unless the `exact_names` config is set), and practices can have short aliases,
+//! e.g. `dsp` for "distributed systems programming", accepted wherever a practice name is.
+
+use anyhow::{bail, ensure, Context, Result};
+
+use super::{name_key, normalize_name, State};
+use crate::error::Error;
+use crate::i18n::t;
+use crate::utils;
+
+impl State {
+    /// The practice `name` refers to: the practice of that name if there is one, else the practice
+    /// with that alias, else the only practice with that name or alias but for case and
+    /// normalization, else `name` as it is, for the command to complain about.
+    pub fn resolve_name(&self, name: String) -> String {
+        if self.practices.contains_key(&name) {
+            return name;
+        }
+        if let Some(practice) = self
+            .practices
+            .values()
+            .find(|practice| practice.aliases.contains(&name))
+        {
+            return practice.name.clone();
+        }
+        if self.get_user_config().exact_names {
+            return name;
+        }
+        let key = name_key(&name);
+        let mut matches = self.practices.values().filter(|practice| {
+            name_key(&practice.name) == key
+                || practice.aliases.iter().any(|alias| name_key(alias) == key)
+        });
+        match (matches.next(), matches.next()) {
+            (Some(practice), None) => practice.name.clone(),
+            _ => name,
+        }
+    }
+
+    /// Refuse `name` for a new or renamed practice if it is an alias of another practice, or its
+    /// name but for case and normalization, so that lookups stay unambiguous. Practices of exactly
+    /// that name are left for the caller to complain about. `renamed` is the practice being
+    /// renamed, which may change its name's case or take one of its own aliases.
+    pub(super) fn ensure_distinct_name(&self, name: &str, renamed: Option<&str>) -> Result<()> {
+        let exact = self.get_user_config().exact_names;
+        let key = name_key(name);
+        let same = |other: &str| {
+            if exact {
+                other == name
+            } else {
+                name_key(other) == key
+            }
+        };
+        let others = || {
+            self.practices
+                .values()
+                .filter(|practice| Some(practice.name.as_str()) != renamed)
+        };
+        if let Some(other) =
+            others().find(|practice| practice.aliases.iter().any(|alias| same(alias)))
+        {
+            bail!(Error::Conflict(t!(
+                "alias-taken",
+                alias = name,
+                name = other.name.as_str()
+            )));
+        }
+        if exact {
+            return Ok(());
+        }
+        if let Some(other) =
+            others().find(|practice| practice.name != name && name_key(&practice.name) == key)
+        {
+            bail!(Error::Conflict(t!(
+                "practice-exists-like",
+                name = name,
+                other = other.name.as_str()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Names of the practices closest to `name`, which matches none, closest first: those it
+    /// starts, then those a typo or so away from it or an alias.
+    pub fn closest_names(&self, name: &str) -> Vec<&str> {
+        /// Most suggestions to make.
+        const SUGGESTIONS: usize = 3;
+        let key = name_key(name);
+        // A typo per three letters, so that short names aren't "corrected" to anything.
+        let length = key.chars().count();
+        let allowed = (length / 3).max(1);
+        let mut closest = self
+            .practices
+            .values()
+            .filter_map(|practice| {
+                let distance = std::iter::once(&practice.name)
+                    .chain(&practice.aliases)
+                    .map(|other| {
+                        let other = name_key(other);
+                        if length > 1 && other.starts_with(&key) {
+                            0
+                        } else {
+                            utils::edit_distance(&key, &other)
+                        }
+                    })
+                    .min()?;
+                (distance <= allowed && distance < length)
+                    .then_some((distance, practice.name.as_str()))
+            })
+            .collect::<Vec<_>>();
+        closest.sort_unstable();
+        closest
+            .into_iter()
+            .take(SUGGESTIONS)
+            .map(|(_, name)| name)
+            .collect()
+    }
+
+    /// Names of the practices `patterns` pick out, in order and without repeats, and, with a
+    /// `context`, of the practices in it (see [`Practice::context`](super::Practice)). Patterns
+    /// with `*`, `?` or `[` are globs, e.g. `piano*`, matched ignoring case unless the
+    /// `exact_names` config is set; others are names as [`State::resolve_name`] takes them.
+    /// Patterns that pick out nothing are refused.
+    pub fn select_practices(
+        &self,
+        patterns: &[String],
+        context: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let exact = self.get_user_config().exact_names;
+        let mut names = Vec::new();
+        for pattern in patterns {
+            let matches = if is_glob(pattern) {
+                let (pattern, options) = if exact {
+                    (pattern.trim().to_owned(), glob::MatchOptions::new())
+                } else {
+                    let options = glob::MatchOptions {
+                        case_sensitive: false,
+                        ..glob::MatchOptions::new()
+                    };
+                    (name_key(pattern), options)
+                };
+                let pattern = glob::Pattern::new(&pattern).map_err(|error| {
+                    Error::Parse(t!("bad-pattern", pattern = &pattern, error = error.msg))
+                })?;
+                self.practices
+                    .keys()
+                    .filter(|name| {
+                        let name = if exact {
+                            (*name).clone()
+                        } else {
+                            name_key(name)
+                        };
+                        pattern.matches_with(&name, options)
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>()
+            } else {
+                let name = self.resolve_name(pattern.clone());
+                if self.practices.contains_key(&name) {
+                    vec![name]
+                } else {
+                    Vec::new()
+                }
+            };
+            ensure!(
+                !matches.is_empty(),
+                Error::NotFound(t!("no-practice-matches", pattern = pattern.as_str()))
+            );
+            names.extend(matches);
+        }
+        if let Some(context) = context {
+            let matches = self
+                .practices
+                .values()
+                .filter(|practice| practice.context.as_deref() == Some(context))
+                .map(|practice| practice.name.clone())
+                .collect::<Vec<_>>();
+            ensure!(
+                !matches.is_empty(),
+                Error::NotFound(t!("no-practice-in-context", context = context))
+            );
+            names.extend(matches);
+        }
+        let mut seen = Vec::new();
+        names.retain(|name| {
+            let new = !seen.contains(name);
+            seen.push(name.clone());
+            new
+        });
+        Ok(names)
+    }
+
+    /// Aliases of practice `name`.
+    pub fn aliases_of(&self, name: &str) -> Result<&[String]> {
+        let practice = self
+            .practices
+            .get(name)
+            .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?;
+        Ok(&practice.aliases)
+    }
+
+    /// Let `alias` stand for practice `name`. An alias can't be the name or alias of another
+    /// practice.
+    pub(super) fn add_alias(&mut self, name: &str, alias: &str) -> Result<()> {
+        let alias = normalize_name(alias)?;
+        ensure!(
+            self.practices.contains_key(name),
+            Error::NotFound(t!("practice-name-not-found", name = name))
+        );
+        let exact = self.get_user_config().exact_names;
+        let same = |other: &str| {
+            if exact {
+                other == alias
+            } else {
+                name_key(other) == name_key(&alias)
+            }
+        };
+        if let Some(other) = self.practices.keys().find(|other| same(other)) {
+            bail!(Error::Conflict(t!(
+                "alias-is-practice",
+                alias = other.as_str()
+            )));
+        }
+        if let Some(other) = self.practices.values().find(|practice| {
+            practice.name != name && practice.aliases.iter().any(|other| same(other))
+        }) {
+            bail!(Error::Conflict(t!(
+                "alias-taken",
+                alias = &alias,
+                name = other.name.as_str()
+            )));
+        }
+        let practice = self
+            .practices
+            .get_mut(name)
+            .expect("we already checked for key membership");
+        if !practice.aliases.contains(&alias) {
+            practice.aliases.push(alias);
+        }
+        Ok(())
+    }
+
+    pub(super) fn remove_alias(&mut self, name: &str, alias: &str) -> Result<()> {
+        let practice = self
+            .practices
+            .get_mut(name)
+            .with_context(|| Error::NotFound(t!("practice-name-not-found", name = name)))?;
+        let index = practice
+            .aliases
+            .iter()
+            .position(|existing| existing == alias.trim())
+            .with_context(|| Error::NotFound(t!("alias-not-found", alias = alias, name = name)))?;
+        practice.aliases.remove(index);
+        Ok(())
+    }
+}
+
+/// Whether `pattern` is a glob rather than a name.
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::State;
+    use crate::application::{handle_transition, StateTransition};
+    use chrono::Duration;
+
+    fn add(state: &mut State, name: &str) -> anyhow::Result<()> {
+        let add = StateTransition::Add {
+            name: name.to_owned(),
+            period: Some(Duration::days(1)),
+            why: None,
+            weekly_goal: None,
+        };
+        handle_transition(state, add)
+    }
+
+    #[test]
+    fn test_name_taken_by_alias() {
+        let mut state = State::new();
+        add(&mut state, "steno").unwrap();
+        let alias = StateTransition::Alias {
+            name: "steno".to_owned(),
+            alias: "st".to_owned(),
+            remove: false,
+        };
+        handle_transition(&mut state, alias).unwrap();
+
+        let error = add(&mut state, "St").unwrap_err();
+        assert_eq!(error.to_string(), "\"St\" already stands for \"steno\".");
+    }
+}
@@ -0,0 +1,345 @@
+//! Bar chart of the time logged per week or month, for `prac chart`, in the terminal or saved as
+//! an SVG or PNG image.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Duration, Local, Months, NaiveDate};
+use serde::Serialize;
+use serde_with::serde_as;
+
+use super::State;
+use crate::error::Error;
+use crate::i18n::t;
+use crate::time::Span;
+use crate::utils::{self, BarGlyphs};
+
+/// Widest the bars get, even in a wide terminal.
+const MAX_BAR_WIDTH: usize = 40;
+/// Change in the average time per bucket, as a fraction, below which the trend is steady.
+const STEADY: f64 = 0.1;
+/// Size of saved charts, in pixels.
+const IMAGE_SIZE: (u32, u32) = (900, 450);
+/// Color of the bars and line of saved charts, the green of `prac heatmap`.
+const IMAGE_COLOR: plotters::style::RGBColor = plotters::style::RGBColor(38, 166, 65);
+
+/// Span of time logs are summed over.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartBucket {
+    Week,
+    Month,
+}
+
+/// Time logged in a week or month.
+#[serde_as]
+#[derive(Serialize)]
+pub struct ChartBar {
+    /// First day of the week or month.
+    start: NaiveDate,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "time_in_seconds")]
+    time: Duration,
+}
+
+/// Time logged per week or month, for one practice or all of them.
+#[derive(Serialize)]
+pub struct Chart<'a> {
+    /// Absent when summed over every practice.
+    practice: Option<&'a str>,
+    bucket: ChartBucket,
+    /// Whether each bar is the time logged up to the end of its week or month, rather than in it.
+    cumulative: bool,
+    /// Oldest first, the last being the current week or month.
+    bars: Vec<ChartBar>,
+}
+
+/// Average time of `bars`.
+fn average(bars: &[ChartBar]) -> Duration {
+    let sum = bars
+        .iter()
+        .fold(Duration::zero(), |sum, bar| sum + bar.time);
+    sum / i32::try_from(bars.len().max(1)).unwrap_or(i32::MAX)
+}
+
+impl Chart<'_> {
+    /// The week or month of `bar`, e.g. "2024-03-04" for the week starting then, or "Mar 2024".
+    fn label(&self, bar: &ChartBar) -> String {
+        match self.bucket {
+            ChartBucket::Week => bar.start.format("%Y-%m-%d").to_string(),
+            ChartBucket::Month => bar.start.format("%b %Y").to_string(),
+        }
+    }
+
+    /// Print a bar per week or month, oldest first, then whether the time logged is trending up
+    /// or down.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn print(&self, glyphs: BarGlyphs) {
+        let label = |bar: &ChartBar| self.label(bar);
+        let times = self
+            .bars
+            .iter()
+            .map(|bar| Span::from(bar.time).format_abbreviated())
+            .collect::<Vec<_>>();
+        let label_width = self
+            .bars
+            .first()
+            .map_or(0, |bar| label(bar).chars().count());
+        let time_width = times
+            .iter()
+            .map(|time| time.chars().count())
+            .max()
+            .unwrap_or(0);
+        let term_width = crossterm::terminal::size().map_or(80, |(width, _)| usize::from(width));
+        let bar_width = term_width
+            .saturating_sub(label_width + time_width + 6)
+            .clamp(1, MAX_BAR_WIDTH);
+        let max = self
+            .bars
+            .iter()
+            .map(|bar| bar.time.num_seconds())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        for (bar, time) in self.bars.iter().zip(&times) {
+            let fraction = bar.time.num_seconds() as f64 / max as f64;
+            println!(
+                "  {}  {}  {time:>time_width$}",
+                label(bar),
+                utils::bar(bar_width, fraction, glyphs)
+            );
+        }
+
+        // The current week or month isn't over, so it would drag the trend down.
+        let Some((_, complete)) = self.bars.split_last() else {
+            return;
+        };
+        if self.cumulative || complete.len() < 2 {
+            return;
+        }
+        let (earlier, recent) = complete.split_at(complete.len() / 2);
+        let (earlier, recent) = (average(earlier), average(recent));
+        let bucket = match self.bucket {
+            ChartBucket::Week => t!("chart-week"),
+            ChartBucket::Month => t!("chart-month"),
+        };
+        let change = (recent - earlier).num_seconds() as f64 / earlier.num_seconds().max(1) as f64;
+        let trend = if change.abs() < STEADY {
+            "steady"
+        } else if change > 0.0 {
+            "up"
+        } else {
+            "down"
+        };
+        println!();
+        println!(
+            "{}",
+            t!(
+                "chart-trend",
+                trend = trend,
+                earlier = Span::from(earlier).format_abbreviated(),
+                recent = Span::from(recent).format_abbreviated(),
+                bucket = bucket,
+            )
+        );
+    }
+}
+
+impl Chart<'_> {
+    /// Save the chart to `path`, as an SVG or PNG image by its extension: bars of the hours
+    /// logged per week or month, or a line of the hours logged in all if cumulative.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+        let svg = self.render_svg()?;
+        let written = match extension.as_deref() {
+            Some("svg") => std::fs::write(path, svg),
+            Some("png") => {
+                let png = rasterize(&svg)?;
+                std::fs::write(path, png)
+            }
+            _ => bail!(Error::Parse(t!(
+                "chart-format-unknown",
+                path = path.display().to_string()
+            ))),
+        };
+        written.with_context(|| t!("chart-write-failed", path = path.display().to_string()))
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    pub(super) fn render_svg(&self) -> Result<String> {
+        use plotters::prelude::*;
+
+        let hours = |bar: &ChartBar| bar.time.num_seconds() as f64 / 3600.0;
+        let top = self.bars.iter().map(hours).fold(1.0, f64::max) * 1.1;
+        let title = self
+            .practice
+            .map_or_else(|| t!("chart-all"), ToOwned::to_owned);
+        let label = |value: &SegmentValue<usize>| match value {
+            SegmentValue::Exact(index) | SegmentValue::CenterOf(index) => self
+                .bars
+                .get(*index)
+                .map(|bar| self.label(bar))
+                .unwrap_or_default(),
+            SegmentValue::Last => String::new(),
+        };
+
+        // Segmented ranges have a segment per value, including the last.
+        let segments = (0..self.bars.len().saturating_sub(1)).into_segmented();
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, IMAGE_SIZE).into_drawing_area();
+            root.fill(&WHITE)?;
+            let mut chart = ChartBuilder::on(&root)
+                .caption(title, ("sans-serif", 20))
+                .margin(15)
+                .x_label_area_size(30)
+                .y_label_area_size(50)
+                .build_cartesian_2d(segments, 0.0..top)?;
+            chart
+                .configure_mesh()
+                .disable_x_mesh()
+                .x_labels(self.bars.len().min(12))
+                .x_label_formatter(&label)
+                .y_desc(t!("chart-hours"))
+                .draw()?;
+            if self.cumulative {
+                chart.draw_series(LineSeries::new(
+                    self.bars
+                        .iter()
+                        .enumerate()
+                        .map(|(index, bar)| (SegmentValue::CenterOf(index), hours(bar))),
+                    IMAGE_COLOR.stroke_width(2),
+                ))?;
+            } else {
+                chart.draw_series(self.bars.iter().enumerate().map(|(index, bar)| {
+                    let mut rectangle = Rectangle::new(
+                        [
+                            (SegmentValue::Exact(index), 0.0),
+                            (SegmentValue::Exact(index + 1), hours(bar)),
+                        ],
+                        IMAGE_COLOR.filled(),
+                    );
+                    rectangle.set_margin(0, 0, 4, 4);
+                    rectangle
+                }))?;
+            }
+            root.present()?;
+        }
+        Ok(svg)
+    }
+}
+
+/// Render `svg` to a PNG image, with the system's fonts for its text.
+fn rasterize(svg: &str) -> Result<Vec<u8>> {
+    use resvg::{tiny_skia, usvg};
+
+    let mut options = usvg::Options::default();
+    let fonts = options.fontdb_mut();
+    fonts.load_system_fonts();
+    // Sans serif means Arial unless told otherwise, which few systems but Windows and macOS have.
+    let sans_serif = usvg::fontdb::Query {
+        families: &[usvg::fontdb::Family::SansSerif],
+        ..Default::default()
+    };
+    if fonts.query(&sans_serif).is_none() {
+        let fallback = fonts
+            .faces()
+            .flat_map(|face| &face.families)
+            .map(|(family, _)| family)
+            .find(|family| family.contains("Sans"))
+            .cloned();
+        if let Some(family) = fallback {
+            fonts.set_sans_serif_family(family);
+        }
+    }
+    let tree = usvg::Tree::from_str(svg, &options)?;
+    let size = tree.size().to_int_size();
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size.width(), size.height()).context(t!("chart-render-failed"))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().context(t!("chart-render-failed"))
+}
+
+impl State {
+    /// Time logged per `bucket` over the last `count` of them (counting the current one), to
+    /// `name` or, without one, to every practice. If `cumulative`, each bar is the time logged up
+    /// to the end of its bucket instead, counting logs before the first.
+    pub fn chart(
+        &self,
+        name: Option<&str>,
+        bucket: ChartBucket,
+        count: u32,
+        cumulative: bool,
+    ) -> Result<Chart<'_>> {
+        let (name, practices) = match name {
+            Some(name) => {
+                let (name, practice) = self
+                    .practices
+                    .get_key_value(name)
+                    .with_context(|| Error::NotFound(t!("practice-name-not-found", name = name)))?;
+                (Some(name.as_str()), vec![practice])
+            }
+            None => (None, self.practices.values().collect()),
+        };
+
+        let now = self.now().with_timezone(&Local);
+        let back = count.saturating_sub(1);
+        let starts = match bucket {
+            ChartBucket::Week => {
+                let this_week =
+                    crate::time::week_start(&now, self.config.user_config.week_start).date_naive();
+                (0..=back)
+                    .rev()
+                    .map(|weeks| this_week - Duration::weeks(i64::from(weeks)))
+                    .collect::<Vec<_>>()
+            }
+            ChartBucket::Month => {
+                let this_month = now
+                    .date_naive()
+                    .with_day(1)
+                    .expect("months have a first day");
+                (0..=back)
+                    .rev()
+                    .map(|months| this_month - Months::new(months))
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        let mut bars = starts
+            .iter()
+            .map(|&start| ChartBar {
+                start,
+                time: Duration::zero(),
+            })
+            .collect::<Vec<_>>();
+        for entry in practices.iter().flat_map(|practice| &practice.logs) {
+            let date = entry.at.with_timezone(&Local).date_naive();
+            // The bucket a log falls in is the last to start on or before it.
+            match starts
+                .partition_point(|start| *start <= date)
+                .checked_sub(1)
+            {
+                Some(index) => bars[index].time += entry.time,
+                None if cumulative => bars[0].time += entry.time,
+                None => {}
+            }
+        }
+        if cumulative {
+            let mut total = Duration::zero();
+            for bar in &mut bars {
+                total += bar.time;
+                bar.time = total;
+            }
+        }
+        Ok(Chart {
+            practice: name,
+            bucket,
+            cumulative,
+            bars,
+        })
+    }
+}
@@ -0,0 +1,128 @@
+//! `prac --demo`: made-up practices to try prac, or show it off, without a real statefile.
+
+use chrono::{DateTime, Duration, Utc};
+
+use super::{ChecklistItem, LogEntry, Practice, State};
+
+/// A practice added `age` before `now`, with `sessions` of how long ago and how many minutes
+/// logged, oldest first.
+fn practice(
+    now: DateTime<Utc>,
+    name: &str,
+    period: Option<Duration>,
+    age: Duration,
+    sessions: impl IntoIterator<Item = (Duration, i64)>,
+) -> Practice {
+    let mut practice = Practice::new(name.to_owned(), String::new(), period, now - age);
+    practice.logged = practice.created;
+    for (ago, minutes) in sessions {
+        practice.log(LogEntry {
+            at: now - ago,
+            time: Duration::minutes(minutes),
+            source: None,
+            note: None,
+            skipped: false,
+        });
+    }
+    practice
+}
+
+/// Sessions every `every`, `count` of them ending `last` ago, of varying length around
+/// `minutes`. Every `skip`th is missed, so the history isn't too perfect.
+fn sessions(
+    every: Duration,
+    count: i32,
+    last: Duration,
+    minutes: i64,
+    skip: i32,
+) -> impl Iterator<Item = (Duration, i64)> {
+    (0..count)
+        .rev()
+        .filter(move |index| skip == 0 || index % skip != skip - 1)
+        .map(move |index| {
+            (
+                last + every * index,
+                minutes + i64::from(index % 3) * 10 - 10,
+            )
+        })
+}
+
+impl State {
+    /// A state of made-up practices at every stage of their periods: some just logged, some
+    /// getting on, some overdue, and one never logged.
+    pub fn demo() -> Self {
+        let mut state = Self::new();
+        state.config.demo = true;
+        let now = state.now();
+        let day = Duration::days(1);
+
+        let mut guitar = practice(
+            now,
+            "guitar",
+            Some(day),
+            Duration::weeks(6),
+            sessions(day, 40, Duration::hours(5), 40, 7),
+        );
+        guitar.why = Some("To play at my sister's wedding".to_owned());
+        guitar.weekly_goal = Some(Duration::hours(5));
+        "# Repertoire\n\n- Blackbird\n- Romanza\n".clone_into(&mut guitar.notes);
+
+        let meditation = practice(
+            now,
+            "meditation",
+            Some(day),
+            Duration::weeks(10),
+            sessions(day, 60, Duration::hours(19), 20, 0),
+        );
+
+        let mut running = practice(
+            now,
+            "running",
+            Some(Duration::days(3)),
+            Duration::weeks(8),
+            sessions(Duration::days(3), 12, Duration::days(4), 35, 4),
+        );
+        running.why = Some("To keep up with the kids".to_owned());
+
+        let mut spanish = practice(
+            now,
+            "spanish",
+            Some(Duration::days(2)),
+            Duration::weeks(5),
+            sessions(Duration::days(2), 14, Duration::hours(30), 30, 5),
+        );
+        spanish.checklist = ["vocab", "podcast"]
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| ChecklistItem {
+                item: item.to_owned(),
+                checked: (index == 0).then(|| now - Duration::hours(29)),
+            })
+            .collect();
+
+        let call_home = practice(
+            now,
+            "call home",
+            Some(Duration::weeks(1)),
+            Duration::weeks(12),
+            sessions(Duration::weeks(1), 10, Duration::days(2), 25, 0),
+        );
+
+        let reading = practice(
+            now,
+            "reading",
+            None,
+            Duration::weeks(20),
+            sessions(Duration::days(3), 30, Duration::days(1), 45, 0),
+        );
+
+        let journaling = practice(now, "journaling", Some(day), Duration::hours(3), []);
+
+        for practice in [
+            guitar, meditation, running, spanish, call_home, reading, journaling,
+        ] {
+            state.practices.insert(practice.name.clone(), practice);
+        }
+        state
+    }
+}
@@ -0,0 +1,211 @@
+//! Soft ordering between practices, e.g. warmups before steno, for `prac suggest` and
+//! `prac list --sort smart`.
+
+use anyhow::{ensure, Context, Result};
+use chrono::{Duration, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{ListOptions, ListSort, Practice, State};
+use crate::error::Error;
+use crate::i18n::t;
+
+/// Practice `before` is best done before practice `after` on the same day.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Dependency {
+    before: String,
+    after: String,
+}
+
+/// The practice `prac suggest` picks.
+#[derive(Serialize)]
+pub struct Suggestion<'a> {
+    name: &'a str,
+    /// Fraction of the grace adjusted period elapsed, as in `prac list --output json`. Absent for
+    /// log-only practices.
+    progress: Option<f64>,
+}
+
+impl Dependency {
+    pub(super) fn before(&self) -> &str {
+        &self.before
+    }
+
+    pub(super) fn after(&self) -> &str {
+        &self.after
+    }
+}
+
+impl Suggestion<'_> {
+    pub fn print(&self) {
+        println!("{}", t!("suggestion", name = self.name));
+    }
+}
+
+impl State {
+    /// Practices `name` is best done after.
+    pub fn dependencies_of(&self, name: &str) -> Result<Vec<&str>> {
+        ensure!(
+            self.practices.contains_key(name),
+            Error::NotFound(t!("practice-not-found", name = name))
+        );
+        Ok(self
+            .dependencies
+            .iter()
+            .filter(|dependency| dependency.after == name)
+            .map(|dependency| dependency.before.as_str())
+            .collect())
+    }
+
+    /// Whether `to` can be reached from `from` by following dependencies, i.e. `from` has to come
+    /// before `to`.
+    fn comes_before(&self, from: &str, to: &str) -> bool {
+        let mut stack = vec![from];
+        let mut seen = Vec::new();
+        while let Some(name) = stack.pop() {
+            if name == to {
+                return true;
+            }
+            if seen.contains(&name) {
+                continue;
+            }
+            seen.push(name);
+            stack.extend(
+                self.dependencies
+                    .iter()
+                    .filter(|dependency| dependency.before == name)
+                    .map(|dependency| dependency.after.as_str()),
+            );
+        }
+        false
+    }
+
+    /// Declare that `before` is best done before `after`, refusing dependencies that would go
+    /// round in a circle.
+    pub(super) fn add_dependency(&mut self, after: String, before: String) -> Result<()> {
+        for name in [&after, &before] {
+            ensure!(
+                self.practices.contains_key(name),
+                Error::NotFound(t!("practice-name-not-found", name = name))
+            );
+        }
+        ensure!(after != before, t!("depend-on-itself"));
+        ensure!(
+            !self.comes_before(&after, &before),
+            Error::Conflict(t!("dependency-cycle", name = &after, on = &before))
+        );
+        let dependency = Dependency { before, after };
+        if !self.dependencies.contains(&dependency) {
+            self.dependencies.push(dependency);
+        }
+        Ok(())
+    }
+
+    pub(super) fn remove_dependency(&mut self, after: &str, before: &str) -> Result<()> {
+        let index = self
+            .dependencies
+            .iter()
+            .position(|dependency| dependency.after == after && dependency.before == before)
+            .with_context(|| {
+                Error::NotFound(t!("dependency-not-found", name = after, on = before))
+            })?;
+        self.dependencies.remove(index);
+        Ok(())
+    }
+
+    /// Point dependencies on practice `from` at `to` instead, after a rename or merge.
+    pub(super) fn rename_in_dependencies(&mut self, from: &str, to: &str) {
+        for dependency in &mut self.dependencies {
+            for name in [&mut dependency.before, &mut dependency.after] {
+                if name == from {
+                    to.clone_into(name);
+                }
+            }
+        }
+        // A merge may leave a practice depending on itself, or the same dependency twice.
+        self.dependencies
+            .retain(|dependency| dependency.before != dependency.after);
+        let mut seen = Vec::new();
+        self.dependencies.retain(|dependency| {
+            let new = !seen.contains(dependency);
+            seen.push(dependency.clone());
+            new
+        });
+    }
+
+    /// Forget the dependencies of and on a removed practice.
+    pub(super) fn drop_dependencies(&mut self, name: &str) {
+        self.dependencies
+            .retain(|dependency| dependency.before != name && dependency.after != name);
+    }
+
+    /// Reorder `practices` so that those not yet logged today come before the practices that
+    /// depend on them. Otherwise the order is kept.
+    pub(super) fn order_by_dependencies<'a>(
+        &self,
+        mut practices: Vec<(&'a String, &'a Practice)>,
+    ) -> Vec<(&'a String, &'a Practice)> {
+        let today = crate::time::day_start(&self.now().with_timezone(&Local)).with_timezone(&Utc);
+        let pending = self
+            .dependencies
+            .iter()
+            .filter(|dependency| {
+                self.practices
+                    .get(&dependency.before)
+                    .is_some_and(|before| before.logs.last().is_none_or(|entry| entry.at < today))
+            })
+            .collect::<Vec<_>>();
+        let mut ordered = Vec::with_capacity(practices.len());
+        while !practices.is_empty() {
+            // The first practice not waiting on another still to come. Dependencies can't go in
+            // a circle, but should a merge have made one, the first practice breaks it.
+            let index = practices
+                .iter()
+                .position(|(name, _)| {
+                    !pending.iter().any(|dependency| {
+                        dependency.after == **name
+                            && practices
+                                .iter()
+                                .any(|(other, _)| **other == dependency.before)
+                    })
+                })
+                .unwrap_or(0);
+            ordered.push(practices.remove(index));
+        }
+        ordered
+    }
+
+    /// The practice to do next: the unarchived one furthest through its period, unless it's best
+    /// done after another not yet logged today. Practices whose best time of day it is come
+    /// first, then those without one. With a `context`, only practices that can be done there are
+    /// suggested, and with time `available`, only those whose typical session fits in it (or that
+    /// have no typical session length).
+    pub fn suggest(
+        &self,
+        context: Option<String>,
+        available: Option<Duration>,
+    ) -> Option<Suggestion<'_>> {
+        let options = ListOptions {
+            sort: Some(ListSort::Smart),
+            context,
+            ..ListOptions::default()
+        };
+        let now = self.now().with_timezone(&Local).time();
+        let (name, practice) = self
+            .sorted_practices(&options)
+            .into_iter()
+            .filter(|(_, practice)| {
+                available
+                    .zip(practice.session_length)
+                    .is_none_or(|(available, length)| length <= available)
+            })
+            .min_by_key(|(_, practice)| {
+                practice
+                    .best_time
+                    .map_or(1, |window| if window.contains(now) { 0 } else { 2 })
+            })?;
+        Some(Suggestion {
+            name,
+            progress: self.progress(practice),
+        })
+    }
+}
@@ -0,0 +1,434 @@
+//! Consistency checks of the state, for `prac doctor`.
+
+use std::time::Instant;
+
+use chrono::Duration;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{name_key, State};
+use crate::i18n::t;
+use crate::storage::{self, StateFormat};
+
+/// Size of the serialized state, in bytes, above which `prac doctor` suggests writing it compact
+/// or compressed. Below it, saving takes a few milliseconds however it is written.
+const LARGE_STATE: usize = 1 << 20;
+
+/// A problem found in the state.
+#[derive(Serialize)]
+pub struct Finding {
+    /// Practice concerned, if any.
+    practice: Option<String>,
+    problem: String,
+    /// Whether `--fix` knows a safe repair.
+    fixable: bool,
+    /// Whether the repair was applied.
+    fixed: bool,
+}
+
+/// Everything `prac doctor` found.
+#[derive(Serialize)]
+pub struct DoctorReport {
+    findings: Vec<Finding>,
+}
+
+impl DoctorReport {
+    /// Print the findings for humans.
+    pub fn print(&self) {
+        if self.findings.is_empty() {
+            println!("{}", t!("doctor-no-problems"));
+            return;
+        }
+        for finding in &self.findings {
+            let status = match (finding.fixed, finding.fixable) {
+                (true, _) => t!("doctor-fixed"),
+                (false, true) => t!("doctor-fixable"),
+                (false, false) => t!("doctor-manual"),
+            };
+            match &finding.practice {
+                Some(practice) => println!("  [{status}] {practice}: {}", finding.problem),
+                None => println!("  [{status}] {}", finding.problem),
+            }
+        }
+        if self.findings.iter().any(|f| f.fixable && !f.fixed) {
+            println!("{}", t!("doctor-run-fix"));
+        }
+    }
+}
+
+/// Collects findings, applying repairs as they are found if fixing.
+struct Checkup {
+    fix: bool,
+    findings: Vec<Finding>,
+}
+
+impl Checkup {
+    /// Record a problem with no safe automatic repair.
+    fn manual(&mut self, practice: Option<&str>, problem: String) {
+        self.findings.push(Finding {
+            practice: practice.map(ToOwned::to_owned),
+            problem,
+            fixable: false,
+            fixed: false,
+        });
+    }
+
+    /// Record a problem, running `repair` if fixing.
+    fn fixable(&mut self, practice: Option<&str>, problem: String, repair: impl FnOnce()) {
+        if self.fix {
+            repair();
+        }
+        self.findings.push(Finding {
+            practice: practice.map(ToOwned::to_owned),
+            problem,
+            fixable: true,
+            fixed: self.fix,
+        });
+    }
+}
+
+/// A size in bytes, for humans.
+#[allow(clippy::cast_precision_loss)]
+fn format_size(bytes: usize) -> String {
+    if bytes < 1 << 10 {
+        format!("{bytes} B")
+    } else if bytes < 1 << 20 {
+        format!("{:.1} KiB", bytes as f64 / f64::from(1 << 10))
+    } else {
+        format!("{:.1} MiB", bytes as f64 / f64::from(1 << 20))
+    }
+}
+
+/// Paths of fields in `raw` that are missing from `known`, i.e. that prac doesn't know about and
+/// drops when it saves.
+fn unknown_fields(raw: &Value, known: &Value, path: &str, unknown: &mut Vec<String>) {
+    match (raw, known) {
+        (Value::Object(raw), Value::Object(known)) => {
+            for (key, value) in raw {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match known.get(key) {
+                    Some(known) => unknown_fields(value, known, &path, unknown),
+                    None => unknown.push(path),
+                }
+            }
+        }
+        (Value::Array(raw), Value::Array(known)) => {
+            for (index, (raw, known)) in raw.iter().zip(known).enumerate() {
+                unknown_fields(raw, known, &format!("{path}[{index}]"), unknown);
+            }
+        }
+        _ => (),
+    }
+}
+
+impl State {
+    /// Check the state for problems that manual edits (or bugs) can leave behind, repairing the
+    /// safe ones if `fix`. `raw` is the statefile as it is on disk, to find fields prac ignores,
+    /// and `format` the one it is written in, to time writing it.
+    #[allow(clippy::too_many_lines)]
+    pub fn doctor(&mut self, raw: Option<&Value>, format: StateFormat, fix: bool) -> DoctorReport {
+        let mut checkup = Checkup {
+            fix,
+            findings: Vec::new(),
+        };
+        let now = self.now();
+
+        if let Some(raw) = raw {
+            let known = serde_json::to_value(&*self).unwrap_or(Value::Null);
+            let mut unknown = Vec::new();
+            unknown_fields(raw, &known, "", &mut unknown);
+            for path in unknown {
+                // Any command that saves drops them, this one included, so there is nothing left
+                // for `--fix` to do.
+                checkup.manual(None, t!("doctor-unknown-field", path = path));
+            }
+        }
+
+        let version = env!("CARGO_PKG_VERSION");
+        if !self.config.version.is_empty() && self.config.version != version {
+            checkup.manual(
+                None,
+                t!(
+                    "doctor-other-version",
+                    written = self.config.version.as_str(),
+                    version = version
+                ),
+            );
+        }
+
+        let configured = self.config.user_config.state_format;
+        if format != configured {
+            checkup.manual(
+                None,
+                t!(
+                    "doctor-state-format",
+                    configured = configured.name(),
+                    format = format.name()
+                ),
+            );
+        }
+        self.check_size(&mut checkup, format);
+
+        if self.config.user_config.grace_period < Duration::zero() {
+            checkup.fixable(None, t!("doctor-grace-negative"), || {
+                self.config.user_config.grace_period = Duration::zero();
+            });
+        }
+
+        let names = self.practices.keys().cloned().collect::<Vec<_>>();
+        for (index, key) in names.iter().enumerate() {
+            let practice = self.practices.get_mut(key).expect("key was just listed");
+            let name = Some(key.as_str());
+
+            if practice.name != *key {
+                checkup.fixable(
+                    name,
+                    t!(
+                        "doctor-misnamed",
+                        key = key.as_str(),
+                        name = practice.name.as_str()
+                    ),
+                    || practice.name.clone_from(key),
+                );
+            }
+            // Names that only differ in surrounding whitespace, case, or normalization look like
+            // duplicates.
+            let looks_like = |other: &String| name_key(other) == name_key(key);
+            if let Some(other) = names[index + 1..].iter().find(|other| looks_like(other)) {
+                checkup.manual(name, t!("doctor-duplicate", other = other.as_str()));
+            }
+
+            if let Some(period) = practice.period.filter(|period| *period <= Duration::zero()) {
+                checkup.manual(
+                    name,
+                    t!("doctor-period-not-positive", seconds = period.num_seconds()),
+                );
+            }
+            // Before checking the cumulative time, which dropping these sessions adds back to.
+            let negative = practice
+                .logs
+                .iter()
+                .filter(|entry| entry.time < Duration::zero())
+                .count();
+            if negative > 0 {
+                checkup.fixable(
+                    name,
+                    t!("doctor-negative-sessions", count = negative),
+                    || {
+                        practice.logs.retain(|entry| {
+                            if entry.time < Duration::zero() {
+                                practice.cumulative -= entry.time;
+                            }
+                            entry.time >= Duration::zero()
+                        });
+                        practice.outcomes_stale = true;
+                    },
+                );
+            }
+            if practice.cumulative < Duration::zero() {
+                checkup.fixable(name, t!("doctor-cumulative-negative"), || {
+                    practice.cumulative = Duration::zero();
+                });
+            }
+            if practice
+                .weekly_goal
+                .is_some_and(|goal| goal <= Duration::zero())
+            {
+                checkup.fixable(name, t!("doctor-goal-not-positive"), || {
+                    practice.weekly_goal = None;
+                });
+            }
+            if practice
+                .target_time_per_period
+                .is_some_and(|target| target <= Duration::zero())
+            {
+                checkup.fixable(name, t!("doctor-budget-not-positive"), || {
+                    practice.target_time_per_period = None;
+                });
+            }
+            if practice.created > now {
+                checkup.fixable(name, t!("doctor-created-in-future"), || {
+                    practice.created = now;
+                });
+            }
+            if practice.logged > now {
+                checkup.fixable(name, t!("doctor-logged-in-future"), || {
+                    practice.logged = now;
+                });
+            }
+
+            let future = practice.logs.iter().filter(|entry| entry.at > now).count();
+            if future > 0 {
+                checkup.fixable(
+                    name,
+                    t!("doctor-sessions-in-future", count = future),
+                    || {
+                        for entry in practice.logs.iter_mut().filter(|entry| entry.at > now) {
+                            entry.at = now;
+                        }
+                    },
+                );
+            }
+            if !practice.logs.is_sorted_by_key(|entry| entry.at) {
+                checkup.fixable(name, t!("doctor-out-of-order"), || {
+                    practice.logs.sort_by_key(|entry| entry.at);
+                });
+            }
+            let tracked = practice
+                .logs
+                .iter()
+                .fold(Duration::zero(), |sum, entry| sum + entry.time);
+            if tracked > practice.cumulative {
+                checkup.fixable(name, t!("doctor-cumulative-short"), || {
+                    practice.cumulative = tracked;
+                });
+            }
+        }
+
+        DoctorReport {
+            findings: checkup.findings,
+        }
+    }
+
+    /// Time writing the state pretty printed, compact and compressed, and suggest the configs
+    /// that make a large statefile smaller and faster to save.
+    fn check_size(&self, checkup: &mut Checkup, format: StateFormat) {
+        let timed = |compact: bool, compress: bool| {
+            let start = Instant::now();
+            let mut bytes = format.write(self, compact).ok()?;
+            if compress {
+                bytes = storage::compress(&bytes).ok()?;
+            }
+            Some((bytes.len(), start.elapsed().as_millis()))
+        };
+        let config = &self.config.user_config;
+        let (Some(pretty), Some(compact)) = (timed(false, false), timed(true, false)) else {
+            return;
+        };
+        if pretty.0 > LARGE_STATE && !config.state_compact {
+            checkup.manual(
+                None,
+                t!(
+                    "doctor-large-pretty",
+                    size = format_size(pretty.0),
+                    millis = pretty.1.to_string(),
+                    compact_size = format_size(compact.0),
+                    compact_millis = compact.1.to_string()
+                ),
+            );
+        }
+        let Some(compressed) = timed(config.state_compact, true) else {
+            return;
+        };
+        let (size, millis) = if config.state_compact {
+            compact
+        } else {
+            pretty
+        };
+        if size > LARGE_STATE && !config.state_compress {
+            checkup.manual(
+                None,
+                t!(
+                    "doctor-large-compress",
+                    size = format_size(size),
+                    millis = millis.to_string(),
+                    compressed_size = format_size(compressed.0),
+                    compressed_millis = compressed.1.to_string()
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DoctorReport, State};
+    use crate::storage::StateFormat;
+    use chrono::Duration;
+    use serde_json::Value;
+    use std::path::Path;
+
+    /// Run the checkup over the fixture statefile, returning the state as left afterwards.
+    fn doctor(fix: bool) -> (State, DoctorReport) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/doctor/state.json");
+        let bytes = std::fs::read(path).unwrap();
+        let raw: Value = serde_json::from_slice(&bytes).unwrap();
+        let mut state = State::from_bytes(bytes, None).unwrap();
+        let report = state.doctor(Some(&raw), StateFormat::Json, fix);
+        (state, report)
+    }
+
+    fn problems<'a>(
+        report: &'a DoctorReport,
+        practice: Option<&str>,
+    ) -> Vec<(&'a str, bool, bool)> {
+        report
+            .findings
+            .iter()
+            .filter(|finding| finding.practice.as_deref() == practice)
+            .map(|finding| (finding.problem.as_str(), finding.fixable, finding.fixed))
+            .collect()
+    }
+
+    #[test]
+    fn test_findings() {
+        let (_, report) = doctor(false);
+        let general = problems(&report, None);
+        assert!(general.contains(&(
+            "unknown field \"config.user_config.mood\" is ignored by this version of prac and \
+             dropped whenever it saves",
+            false,
+            false
+        )));
+        assert!(general.contains(&("grace period is negative", true, false)));
+        assert_eq!(
+            problems(&report, Some("scales")),
+            [("1 session with negative time", true, false)]
+        );
+        assert_eq!(
+            problems(&report, Some("steno")),
+            [
+                ("stored under \"steno\" but named \"Steno\"", true, false),
+                ("sessions are out of order", true, false),
+                (
+                    "cumulative time is less than the sessions logged",
+                    true,
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fix() {
+        let (state, report) = doctor(true);
+        assert!(report
+            .findings
+            .iter()
+            .all(|finding| finding.fixed == finding.fixable));
+        assert_eq!(state.config.user_config.grace_period, Duration::zero());
+
+        // Dropping the negative session adds its time back rather than leaving it subtracted.
+        let scales = &state.practices["scales"];
+        assert_eq!(scales.logs.len(), 2);
+        assert_eq!(scales.cumulative, Duration::minutes(40));
+        assert_eq!(
+            problems(&report, Some("scales")),
+            [("1 session with negative time", true, true)]
+        );
+
+        let steno = &state.practices["steno"];
+        assert_eq!(steno.name, "steno");
+        assert!(steno.logs.is_sorted_by_key(|entry| entry.at));
+        assert_eq!(steno.cumulative, Duration::minutes(20));
+
+        // A second checkup finds nothing left to fix.
+        let mut state = state;
+        let report = state.doctor(None, StateFormat::Json, false);
+        assert!(report.findings.iter().all(|finding| !finding.fixable));
+    }
+}
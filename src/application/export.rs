@@ -0,0 +1,157 @@
+//! `prac export json`: a stable, versioned export of the practices for other tools.
+//!
+//! The statefile's layout is prac's own business and changes as prac does; the export's only
+//! changes with [`VERSION`]. Its JSON Schema is generated from the types below, so their doc
+//! comments are its documentation.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use super::{LogMode, State};
+
+/// Version of the export format. Adding fields keeps it; renaming, removing, or changing the
+/// meaning of any bumps it.
+pub const VERSION: u32 = 1;
+
+/// Every practice prac tracks, with its full history.
+#[derive(Serialize, JsonSchema)]
+#[schemars(title = "prac export", rename = "PracExport")]
+pub struct Export {
+    /// Version of the export format, currently 1.
+    version: u32,
+    /// When the export was made.
+    exported_at: DateTime<Utc>,
+    practices: Vec<ExportedPractice>,
+}
+
+/// A practice: something to come back to every period.
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "Practice")]
+struct ExportedPractice {
+    /// Unique name of the practice.
+    name: String,
+    /// How often the practice is meant to be done, or null if it only accumulates time.
+    period_in_seconds: Option<i64>,
+    /// Why the user keeps up the practice.
+    why: Option<String>,
+    /// Free-form notes, in Markdown.
+    notes: String,
+    created_at: DateTime<Utc>,
+    /// When the current period started, usually the last session. The practice is due a period
+    /// after this.
+    period_started_at: DateTime<Utc>,
+    /// Total time spent, which can exceed the sum of the sessions for practices from before
+    /// sessions were recorded.
+    total_time_in_seconds: i64,
+    /// Time to spend per week, if the user set a goal.
+    weekly_goal_in_seconds: Option<i64>,
+    /// Time to spend per period, if the user set a budget.
+    target_time_per_period_in_seconds: Option<i64>,
+    /// How logging a session restarts the period.
+    log_mode: ExportedLogMode,
+    /// Archived practices are kept but no longer tracked.
+    archived: bool,
+    /// Things to get done each period.
+    checklist: Vec<ExportedChecklistItem>,
+    /// Free-form fields the user keeps with the practice, e.g. a teacher or a book chapter.
+    metadata: BTreeMap<String, String>,
+    /// Every recorded session, oldest first.
+    sessions: Vec<ExportedSession>,
+}
+
+/// How logging a session restarts a practice's period.
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[schemars(rename = "LogMode")]
+enum ExportedLogMode {
+    /// Every session restarts the period.
+    Reset,
+    /// Sessions add up, and the period restarts once they reach the per period target.
+    Accumulate,
+    /// Sessions only restart the period once it has passed.
+    Checkpoint,
+}
+
+impl From<LogMode> for ExportedLogMode {
+    fn from(mode: LogMode) -> Self {
+        match mode {
+            LogMode::Reset => Self::Reset,
+            LogMode::Accumulate => Self::Accumulate,
+            LogMode::Checkpoint => Self::Checkpoint,
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "ChecklistItem")]
+struct ExportedChecklistItem {
+    item: String,
+    /// When the item was last checked off, if ever. It is done if this is after the period
+    /// started.
+    checked_at: Option<DateTime<Utc>>,
+}
+
+/// A logged session.
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "Session")]
+struct ExportedSession {
+    at: DateTime<Utc>,
+    time_in_seconds: i64,
+}
+
+impl Export {
+    /// The export's JSON Schema.
+    pub fn schema() -> serde_json::Value {
+        schemars::schema_for!(Self).to_value()
+    }
+}
+
+impl State {
+    /// Every practice, archived or not, in the export format.
+    pub fn export(&self) -> Export {
+        let practices = self
+            .practices
+            .values()
+            .map(|practice| ExportedPractice {
+                name: practice.name.clone(),
+                period_in_seconds: practice.period.map(|period| period.num_seconds()),
+                why: practice.why.clone(),
+                notes: practice.notes.clone(),
+                created_at: practice.created,
+                period_started_at: practice.logged,
+                total_time_in_seconds: practice.cumulative.num_seconds(),
+                weekly_goal_in_seconds: practice.weekly_goal.map(|goal| goal.num_seconds()),
+                target_time_per_period_in_seconds: practice
+                    .target_time_per_period
+                    .map(|target| target.num_seconds()),
+                log_mode: practice.log_mode.into(),
+                archived: practice.archived,
+                checklist: practice
+                    .checklist
+                    .iter()
+                    .map(|item| ExportedChecklistItem {
+                        item: item.item.clone(),
+                        checked_at: item.checked,
+                    })
+                    .collect(),
+                metadata: practice.metadata.clone(),
+                sessions: practice
+                    .logs
+                    .iter()
+                    .map(|entry| ExportedSession {
+                        at: entry.at,
+                        time_in_seconds: entry.time.num_seconds(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Export {
+            version: VERSION,
+            exported_at: self.now(),
+            practices,
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! GitHub-style activity calendar of logged time, for `prac heatmap`.
+
+use std::collections::BTreeMap;
+
+use anyhow::{ensure, Context, Result};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use owo_colors::Style;
+use serde::Serialize;
+use serde_with::serde_as;
+
+use super::State;
+use crate::color::paint;
+use crate::error::Error;
+use crate::i18n::t;
+
+/// Glyphs for days with nothing logged, then for each quarter of the busiest day's time.
+const LEVELS: [char; 5] = ['·', '░', '▒', '▓', '█'];
+const ASCII_LEVELS: [char; 5] = ['.', '-', '+', '*', '#'];
+
+/// Time logged on a day.
+#[serde_as]
+#[derive(Serialize)]
+pub struct HeatmapDay {
+    date: NaiveDate,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "time_in_seconds")]
+    time: Duration,
+}
+
+/// Time logged per day over the last weeks, for one practice or all of them.
+#[derive(Serialize)]
+pub struct Heatmap<'a> {
+    /// Absent when summed over every practice.
+    practice: Option<&'a str>,
+    /// Every day from the start of the first week through today.
+    days: Vec<HeatmapDay>,
+}
+
+/// Intensity of a day, 0 for nothing logged, and otherwise 1 to 4 by quarter of `max`.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn level(time: Duration, max: Duration) -> usize {
+    if time <= Duration::zero() || max <= Duration::zero() {
+        return 0;
+    }
+    let fraction = time.num_seconds() as f64 / max.num_seconds() as f64;
+    ((fraction * 4.0).ceil() as usize).clamp(1, 4)
+}
+
+/// Shade of green for an intensity, darkest for the busiest days as on GitHub.
+const fn shade(level: usize) -> Style {
+    match level {
+        0 => Style::new().bright_black(),
+        1 => Style::new().truecolor(14, 68, 41),
+        2 => Style::new().truecolor(0, 109, 50),
+        3 => Style::new().truecolor(38, 166, 65),
+        _ => Style::new().truecolor(57, 211, 83),
+    }
+}
+
+impl Heatmap<'_> {
+    /// Print the calendar for humans: a column per week and a row per weekday, with month names
+    /// above the weeks they start in.
+    pub fn print(&self, color: bool, ascii: bool) {
+        let levels = if ascii { ASCII_LEVELS } else { LEVELS };
+        let max = self
+            .days
+            .iter()
+            .map(|day| day.time)
+            .max()
+            .unwrap_or_else(Duration::zero);
+        let weeks = self.days.chunks(7).collect::<Vec<_>>();
+
+        let mut months = String::from("    ");
+        let mut last_month = None;
+        for (index, week) in weeks.iter().enumerate() {
+            let month = week[0].date.month();
+            if last_month == Some(month) {
+                continue;
+            }
+            last_month = Some(month);
+            // Skipped if the previous month's name is still in the way.
+            let column = 4 + 2 * index;
+            if months.trim_end().len() < column {
+                months.push_str(&" ".repeat(column - months.len()));
+                months.push_str(&week[0].date.format("%b").to_string());
+            }
+        }
+        println!("{months}");
+
+        let mut weekday = self.days[0].date.weekday();
+        for row in 0..7 {
+            let mut line = format!("{:<4}", weekday.to_string());
+            for week in &weeks {
+                match week.get(row) {
+                    Some(day) => {
+                        let level = level(day.time, max);
+                        let glyph = levels[level].to_string();
+                        line.push_str(&paint(&glyph, shade(level), color));
+                        line.push(' ');
+                    }
+                    None => break,
+                }
+            }
+            println!("{}", line.trim_end());
+            weekday = weekday.succ();
+        }
+
+        let key = levels
+            .iter()
+            .enumerate()
+            .map(|(level, glyph)| paint(&glyph.to_string(), shade(level), color))
+            .collect::<String>();
+        let total = self
+            .days
+            .iter()
+            .fold(Duration::zero(), |sum, day| sum + day.time);
+        println!(
+            "    less {key} more   {} in {} weeks, busiest day {}",
+            crate::time::Span::from(total).format(),
+            weeks.len(),
+            crate::time::Span::from(max).format()
+        );
+    }
+}
+
+impl State {
+    /// Time logged per day over the last `weeks` weeks (counting the current one), to `name` or,
+    /// without one, to every practice.
+    pub fn heatmap(&self, name: Option<&str>, weeks: usize) -> Result<Heatmap<'_>> {
+        ensure!(weeks > 0, t!("heatmap-no-weeks"));
+        let (name, practices) = match name {
+            Some(name) => {
+                let (name, practice) = self
+                    .practices
+                    .get_key_value(name)
+                    .with_context(|| Error::NotFound(t!("practice-not-found", name = name)))?;
+                (Some(name.as_str()), vec![practice])
+            }
+            None => (None, self.practices.values().collect()),
+        };
+
+        let now = self.now().with_timezone(&Local);
+        let today = now.date_naive();
+        let weeks = i64::try_from(weeks)?;
+        let start = crate::time::week_start(&now, self.config.user_config.week_start).date_naive()
+            - Duration::weeks(weeks - 1);
+
+        let mut logged = BTreeMap::<NaiveDate, Duration>::new();
+        for practice in practices {
+            for entry in &practice.logs {
+                let date = entry.at.with_timezone(&Local).date_naive();
+                if date >= start {
+                    *logged.entry(date).or_insert_with(Duration::zero) += entry.time;
+                }
+            }
+        }
+
+        let days = start
+            .iter_days()
+            .take_while(|date| *date <= today)
+            .map(|date| HeatmapDay {
+                date,
+                time: logged.get(&date).copied().unwrap_or_else(Duration::zero),
+            })
+            .collect();
+        Ok(Heatmap {
+            practice: name,
+            days,
+        })
+    }
+}
@@ -0,0 +1,585 @@
+//! Structured merging of states that diverged from a common ancestor, for `prac sync`, and of
+//! unrelated statefiles, for `prac merge-state`.
+
+use anyhow::Result;
+use serde::Serialize;
+
+use chrono::Duration;
+
+use super::{Config, NoteRevision, Practice, State};
+
+/// Whether two values serialize identically, for types without [`PartialEq`].
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Three-way merge of a single value: whichever side changed it wins, ours if both did.
+fn pick<T: Serialize>(base: Option<&T>, ours: T, theirs: T) -> T {
+    if base.is_some_and(|base| same(base, &ours)) {
+        theirs
+    } else {
+        ours
+    }
+}
+
+/// Three-way merge of a collection, with entries identified by `key`: entries added on either
+/// side are kept, and entries removed on either side are dropped. Ours come first.
+fn merge_by<T, K: PartialEq>(
+    base: &[T],
+    ours: Vec<T>,
+    theirs: Vec<T>,
+    key: impl Fn(&T) -> K,
+) -> Vec<T> {
+    let base = base.iter().map(&key).collect::<Vec<_>>();
+    let our_keys = ours.iter().map(&key).collect::<Vec<_>>();
+    let their_keys = theirs.iter().map(&key).collect::<Vec<_>>();
+
+    let mut merged = ours
+        .into_iter()
+        .zip(&our_keys)
+        .filter(|(_, key)| !base.contains(key) || their_keys.contains(key))
+        .map(|(entry, _)| entry)
+        .collect::<Vec<_>>();
+    merged.extend(
+        theirs
+            .into_iter()
+            .zip(&their_keys)
+            .filter(|(_, key)| !our_keys.contains(key) && !base.contains(key))
+            .map(|(entry, _)| entry),
+    );
+    merged
+}
+
+/// A field set differently in two statefiles being merged, for the user to choose between.
+pub struct Conflict<'a> {
+    pub practice: &'a str,
+    pub field: &'static str,
+    pub ours: String,
+    pub theirs: String,
+}
+
+impl Practice {
+    /// Merge `other`, the same practice from another statefile, into this one. Sessions are
+    /// combined (a session logged at the same moment in both is kept once) and the larger
+    /// cumulative time is kept. Differing notes and periods are left to `resolve`.
+    fn merge_file(
+        &mut self,
+        other: Self,
+        resolve: &mut impl FnMut(&Conflict) -> Result<bool>,
+    ) -> Result<()> {
+        self.created = self.created.min(other.created);
+        self.logged = self.logged.max(other.logged);
+        for entry in other.logs {
+            if !self.logs.iter().any(|ours| ours.at == entry.at) {
+                self.insert_log(entry);
+            }
+        }
+        let tracked = self
+            .logs
+            .iter()
+            .fold(Duration::zero(), |sum, entry| sum + entry.time);
+        self.cumulative = self.cumulative.max(other.cumulative).max(tracked);
+
+        if self.period != other.period {
+            let show = |period: Option<Duration>| {
+                period.map_or_else(
+                    || "log-only".to_owned(),
+                    |period| crate::time::Span::from(period).format(),
+                )
+            };
+            let conflict = Conflict {
+                practice: &self.name,
+                field: "period",
+                ours: show(self.period),
+                theirs: show(other.period),
+            };
+            if resolve(&conflict)? {
+                self.period = other.period;
+            }
+        }
+        self.note_history = merge_by(
+            &[],
+            std::mem::take(&mut self.note_history),
+            other.note_history,
+            |revision| (revision.at, revision.notes.clone()),
+        );
+        if self.notes != other.notes {
+            let conflict = Conflict {
+                practice: &self.name,
+                field: "notes",
+                ours: self.notes.clone(),
+                theirs: other.notes.clone(),
+            };
+            // The notes not kept are kept in the history.
+            let replaced = if resolve(&conflict)? {
+                NoteRevision {
+                    at: std::mem::replace(&mut self.notes_edited, other.notes_edited),
+                    notes: std::mem::replace(&mut self.notes, other.notes),
+                }
+            } else {
+                NoteRevision {
+                    at: other.notes_edited,
+                    notes: other.notes,
+                }
+            };
+            self.supersede_notes(replaced);
+        }
+        self.note_history.sort_by_key(|revision| revision.at);
+
+        if self.why.is_none() {
+            self.why = other.why;
+        }
+        if self.weekly_goal.is_none() {
+            self.weekly_goal = other.weekly_goal;
+        }
+        if self.target_time_per_period.is_none() {
+            self.target_time_per_period = other.target_time_per_period;
+        }
+        if self.session_length.is_none() {
+            self.session_length = other.session_length;
+        }
+        if self.checklist.is_empty() {
+            self.checklist = other.checklist;
+        }
+        self.archived &= other.archived;
+        Ok(())
+    }
+
+    /// Three-way merge of a practice changed on both sides.
+    #[allow(clippy::too_many_lines)]
+    fn merge(base: Option<&Self>, ours: Self, theirs: Self) -> Self {
+        // Notes changed on both sides can't be combined line by line; the latest edit wins.
+        let theirs_newer = theirs.notes_edited > ours.notes_edited;
+        let untracked = pick(
+            base.map(Self::untracked).as_ref(),
+            ours.untracked(),
+            theirs.untracked(),
+        );
+        let mut logs = merge_by(
+            base.map_or(&[][..], |base| &base.logs),
+            ours.logs,
+            theirs.logs,
+            |entry| (entry.at, entry.time),
+        );
+        logs.sort_by_key(|entry| entry.at);
+        let cumulative = logs.iter().fold(untracked, |sum, entry| sum + entry.time);
+
+        let our_notes = NoteRevision {
+            at: ours.notes_edited,
+            notes: ours.notes,
+        };
+        let their_notes = NoteRevision {
+            at: theirs.notes_edited,
+            notes: theirs.notes,
+        };
+        // The notes kept, and those they replace, which go in the history.
+        let (kept, replaced) = match base {
+            Some(base) if base.notes == our_notes.notes => (their_notes, our_notes),
+            Some(base) if base.notes == their_notes.notes => (our_notes, their_notes),
+            _ if theirs_newer => (their_notes, our_notes),
+            _ => (our_notes, their_notes),
+        };
+        let note_history = merge_by(
+            base.map_or(&[][..], |base| &base.note_history),
+            ours.note_history,
+            theirs.note_history,
+            |revision| (revision.at, revision.notes.clone()),
+        );
+        let mut period_adjustments = merge_by(
+            base.map_or(&[][..], |base| &base.period_adjustments),
+            ours.period_adjustments,
+            theirs.period_adjustments,
+            |adjustment| adjustment.at,
+        );
+        period_adjustments.sort_by_key(|adjustment| adjustment.at);
+
+        let mut merged = Self {
+            created: ours.created.min(theirs.created),
+            logged: ours.logged.max(theirs.logged),
+            period: pick(base.map(|base| &base.period), ours.period, theirs.period),
+            name: ours.name,
+            notes: kept.notes,
+            notes_edited: kept.at,
+            note_history,
+            cumulative,
+            why: pick(base.map(|base| &base.why), ours.why, theirs.why),
+            archived: pick(
+                base.map(|base| &base.archived),
+                ours.archived,
+                theirs.archived,
+            ),
+            maintenance: pick(
+                base.map(|base| &base.maintenance),
+                ours.maintenance,
+                theirs.maintenance,
+            ),
+            hidden: pick(base.map(|base| &base.hidden), ours.hidden, theirs.hidden),
+            aliases: pick(base.map(|base| &base.aliases), ours.aliases, theirs.aliases),
+            metadata: pick(
+                base.map(|base| &base.metadata),
+                ours.metadata,
+                theirs.metadata,
+            ),
+            logs,
+            // Worked out afresh from the merged history.
+            outcomes: Vec::new(),
+            outcomes_stale: true,
+            period_adjustments,
+            weekly_goal: pick(
+                base.map(|base| &base.weekly_goal),
+                ours.weekly_goal,
+                theirs.weekly_goal,
+            ),
+            target_time_per_period: pick(
+                base.map(|base| &base.target_time_per_period),
+                ours.target_time_per_period,
+                theirs.target_time_per_period,
+            ),
+            log_mode: pick(
+                base.map(|base| &base.log_mode),
+                ours.log_mode,
+                theirs.log_mode,
+            ),
+            checklist: pick(
+                base.map(|base| &base.checklist),
+                ours.checklist,
+                theirs.checklist,
+            ),
+            best_time: pick(
+                base.map(|base| &base.best_time),
+                ours.best_time,
+                theirs.best_time,
+            ),
+            session_length: pick(
+                base.map(|base| &base.session_length),
+                ours.session_length,
+                theirs.session_length,
+            ),
+            context: pick(base.map(|base| &base.context), ours.context, theirs.context),
+        };
+        merged.supersede_notes(replaced);
+        merged.note_history.sort_by_key(|revision| revision.at);
+        merged
+    }
+}
+
+impl State {
+    /// Three-way merge of two states descended from `base` (or, without a common ancestor, a
+    /// union of both). Practices, sessions, and trash entries added on either side are kept and
+    /// those removed on either side are dropped, unless the other side changed them meanwhile.
+    /// Other changes win over unchanged values, and ours win over theirs.
+    pub fn merge(base: Option<&Self>, mut ours: Self, mut theirs: Self) -> Self {
+        let mut names = ours.practices.keys().cloned().collect::<Vec<_>>();
+        names.extend(theirs.practices.keys().cloned());
+        names.sort();
+        names.dedup();
+
+        let mut practices = std::collections::BTreeMap::new();
+        for name in names {
+            let base = base.and_then(|base| base.practices.get(&name));
+            let merged = match (ours.practices.remove(&name), theirs.practices.remove(&name)) {
+                (Some(ours), Some(theirs)) => Practice::merge(base, ours, theirs),
+                // Removed on the other side, so kept only if changed on this one since.
+                (Some(one), None) | (None, Some(one)) => match base {
+                    Some(base) if same(base, &one) => continue,
+                    _ => one,
+                },
+                (None, None) => continue,
+            };
+            practices.insert(name, merged);
+        }
+
+        let mut trash = merge_by(
+            base.map_or(&[][..], |base| &base.trash),
+            ours.trash,
+            theirs.trash,
+            |trashed| (trashed.removed, trashed.practice.name.clone()),
+        );
+        trash.sort_by_key(|trashed| trashed.removed);
+
+        let mut dependencies = merge_by(
+            base.map_or(&[][..], |base| &base.dependencies),
+            ours.dependencies,
+            theirs.dependencies,
+            Clone::clone,
+        );
+        dependencies.retain(|dependency| {
+            practices.contains_key(dependency.before())
+                && practices.contains_key(dependency.after())
+        });
+
+        let mut running = merge_by(
+            base.map_or(&[][..], |base| &base.running),
+            ours.running,
+            theirs.running,
+            Clone::clone,
+        );
+        running.retain(|session| practices.contains_key(session.name()));
+
+        let config = Config {
+            version: ours.config.version,
+            user_config: pick(
+                base.map(|base| &base.config.user_config),
+                ours.config.user_config,
+                theirs.config.user_config,
+            ),
+            last_prune_nudge: ours
+                .config
+                .last_prune_nudge
+                .max(theirs.config.last_prune_nudge),
+            demo: ours.config.demo,
+        };
+
+        Self {
+            config,
+            practices,
+            trash,
+            dependencies,
+            running,
+            key: ours.key,
+            unaudited: ours.unaudited,
+            on_disk: ours.on_disk,
+            backed_up: ours.backed_up,
+            clock: ours.clock,
+            read_only: ours.read_only,
+        }
+    }
+
+    /// Merge `other`, a statefile that may share no history with this one, into this state.
+    /// Practices are matched by name, and those only in `other` are added; see
+    /// [`Practice::merge_file`] for practices in both. Our config is kept. Returns the names of
+    /// the practices added. `resolve` is asked about each [`Conflict`], and returns whether to take
+    /// their value over ours.
+    pub fn merge_file(
+        &mut self,
+        other: Self,
+        mut resolve: impl FnMut(&Conflict) -> Result<bool>,
+    ) -> Result<Vec<String>> {
+        let mut added = Vec::new();
+        for (name, practice) in other.practices {
+            if let Some(ours) = self.practices.get_mut(&name) {
+                ours.merge_file(practice, &mut resolve)?;
+            } else {
+                added.push(name.clone());
+                self.practices.insert(name, practice);
+            }
+        }
+        self.trash = merge_by(
+            &[],
+            std::mem::take(&mut self.trash),
+            other.trash,
+            |trashed| (trashed.removed, trashed.practice.name.clone()),
+        );
+        self.trash.sort_by_key(|trashed| trashed.removed);
+        Ok(added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_by, Practice, State};
+    use crate::application::LogEntry;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    fn at(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap()
+    }
+
+    fn entry(day: u32, minutes: i64) -> LogEntry {
+        LogEntry {
+            at: at(day),
+            time: Duration::minutes(minutes),
+            source: None,
+            note: None,
+            skipped: false,
+        }
+    }
+
+    fn practice(name: &str, logs: Vec<LogEntry>) -> Practice {
+        let mut practice = Practice::new(
+            name.to_owned(),
+            String::new(),
+            Some(Duration::days(1)),
+            at(1),
+        );
+        for entry in logs {
+            practice.cumulative += entry.time;
+            practice.insert_log(entry);
+        }
+        practice
+    }
+
+    fn state(practices: Vec<Practice>) -> State {
+        let mut state = State::default();
+        for practice in practices {
+            state.practices.insert(practice.name.clone(), practice);
+        }
+        state
+    }
+
+    #[test]
+    fn test_merge_by() {
+        let merged = merge_by(&[1, 2, 3], vec![1, 2, 4], vec![2, 3, 5], |n| *n);
+        // 1 and 3 were removed on one side each, 4 and 5 added.
+        assert_eq!(merged, vec![2, 4, 5]);
+
+        let merged = merge_by(&[], vec![1, 2], vec![2, 3], |n| *n);
+        assert_eq!(merged, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_merge_sessions() {
+        let base = state(vec![practice("steno", vec![entry(2, 10)])]);
+        let ours = state(vec![practice("steno", vec![entry(2, 10), entry(3, 20)])]);
+        let theirs = state(vec![practice("steno", vec![entry(2, 10), entry(4, 30)])]);
+
+        let merged = State::merge(Some(&base), ours, theirs);
+        let steno = &merged.practices["steno"];
+        let days = steno.logs.iter().map(|entry| entry.at).collect::<Vec<_>>();
+        assert_eq!(days, vec![at(2), at(3), at(4)]);
+        assert_eq!(steno.cumulative, Duration::minutes(60));
+    }
+
+    #[test]
+    fn test_merge_changes() {
+        let base = state(vec![practice("steno", vec![]), practice("chess", vec![])]);
+        let mut ours = state(vec![practice("steno", vec![]), practice("chess", vec![])]);
+        ours.practices.get_mut("steno").unwrap().why = Some("speed".to_owned());
+        // Removed on their side, unchanged on ours.
+        let mut theirs = state(vec![practice("steno", vec![]), practice("go", vec![])]);
+        theirs.practices.get_mut("steno").unwrap().period = Some(Duration::days(2));
+
+        let merged = State::merge(Some(&base), ours, theirs);
+        let names = merged.practices.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(names, vec!["go", "steno"]);
+        let steno = &merged.practices["steno"];
+        assert_eq!(steno.why.as_deref(), Some("speed"));
+        assert_eq!(steno.period, Some(Duration::days(2)));
+    }
+
+    #[test]
+    fn test_merge_removed_but_changed() {
+        let base = state(vec![practice("steno", vec![])]);
+        let ours = state(vec![practice("steno", vec![entry(2, 10)])]);
+        let theirs = state(vec![]);
+
+        let merged = State::merge(Some(&base), ours, theirs);
+        assert_eq!(merged.practices["steno"].logs.len(), 1);
+    }
+
+    /// A state with `steno`, whose notes were set to each of `versions` on its day in turn.
+    fn noted(versions: &[(&str, u32)]) -> State {
+        let mut steno = practice("steno", vec![]);
+        for (notes, day) in versions {
+            steno.set_notes((*notes).to_owned(), at(*day));
+        }
+        state(vec![steno])
+    }
+
+    /// The superseded notes of `steno` in `state`, oldest first.
+    fn note_history(state: &State) -> Vec<&str> {
+        state.practices["steno"]
+            .note_history
+            .iter()
+            .map(|revision| revision.notes.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_notes() {
+        let ours = noted(&[("a", 1), ("b", 2)]);
+        assert_eq!(ours.practices["steno"].notes, "b");
+        assert_eq!(note_history(&ours), vec!["a"]);
+
+        // Changed on our side only.
+        let merged = State::merge(Some(&noted(&[("a", 1)])), ours, noted(&[("a", 1)]));
+        assert_eq!(merged.practices["steno"].notes, "b");
+        assert_eq!(note_history(&merged), vec!["a"]);
+
+        // Changed on both sides: their newer notes win, and ours are kept as history.
+        let merged = State::merge(
+            Some(&noted(&[("a", 1)])),
+            noted(&[("a", 1), ("b", 2)]),
+            noted(&[("a", 1), ("c", 3)]),
+        );
+        assert_eq!(merged.practices["steno"].notes, "c");
+        assert_eq!(note_history(&merged), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_merge_file_sessions() {
+        let mut ours = state(vec![practice("steno", vec![entry(2, 10), entry(3, 20)])]);
+        let theirs = state(vec![
+            // Logged at the same moment as ours, so the same session.
+            practice("steno", vec![entry(2, 15), entry(4, 30)]),
+            practice("go", vec![entry(2, 5)]),
+        ]);
+
+        let added = ours
+            .merge_file(theirs, |conflict| panic!("asked about {}", conflict.field))
+            .unwrap();
+        assert_eq!(added, vec!["go"]);
+        let steno = &ours.practices["steno"];
+        let sessions = steno
+            .logs
+            .iter()
+            .map(|entry| (entry.at, entry.time.num_minutes()))
+            .collect::<Vec<_>>();
+        assert_eq!(sessions, vec![(at(2), 10), (at(3), 20), (at(4), 30)]);
+        // More than either side had, counting the sessions only they logged.
+        assert_eq!(steno.cumulative, Duration::minutes(60));
+    }
+
+    #[test]
+    fn test_merge_file_cumulative() {
+        let mut ours = state(vec![practice("steno", vec![entry(2, 10)])]);
+        let mut theirs = state(vec![practice("steno", vec![entry(2, 10)])]);
+        // Time they tracked whose sessions were pruned.
+        theirs.practices.get_mut("steno").unwrap().cumulative = Duration::minutes(90);
+
+        ours.merge_file(theirs, |_| Ok(false)).unwrap();
+        assert_eq!(ours.practices["steno"].cumulative, Duration::minutes(90));
+    }
+
+    #[test]
+    fn test_merge_file_conflicts() {
+        let mut ours = noted(&[("a", 1)]);
+        let mut theirs = noted(&[("b", 2)]);
+        theirs.practices.get_mut("steno").unwrap().period = Some(Duration::days(2));
+
+        // Take their period but keep our notes.
+        let mut asked = Vec::new();
+        ours.merge_file(theirs, |conflict| {
+            asked.push((conflict.practice.to_owned(), conflict.field));
+            Ok(conflict.field == "period")
+        })
+        .unwrap();
+        assert_eq!(
+            asked,
+            vec![
+                ("steno".to_owned(), "period"),
+                ("steno".to_owned(), "notes")
+            ]
+        );
+        let steno = &ours.practices["steno"];
+        assert_eq!(steno.period, Some(Duration::days(2)));
+        assert_eq!(steno.notes, "a");
+        assert_eq!(steno.notes_edited, Some(at(1)));
+        // Their notes aren't lost.
+        assert_eq!(note_history(&ours), vec!["b"]);
+
+        // Taking their notes keeps ours in the history instead.
+        let mut ours = noted(&[("a", 1)]);
+        ours.merge_file(noted(&[("b", 2)]), |_| Ok(true)).unwrap();
+        assert_eq!(ours.practices["steno"].notes, "b");
+        assert_eq!(note_history(&ours), vec!["a"]);
+    }
+
+    #[test]
+    fn test_merge_file_aborted() {
+        let mut ours = noted(&[("a", 1)]);
+        let result = ours.merge_file(noted(&[("b", 2)]), |_| anyhow::bail!("aborted"));
+        assert!(result.is_err());
+        assert_eq!(ours.practices["steno"].notes, "a");
+    }
+}
@@ -0,0 +1,137 @@
+//! Sets of practice definitions in TOML, for `prac add --from-file` and `prac template`.
+
+use anyhow::{Context, Result};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use super::{ChecklistEdit, LogMode, State, StateTransition};
+use crate::i18n::t;
+use crate::time::{parse_time_span, Span};
+
+/// A practice as written in a practice set: how it is set up, without any logged time. Durations
+/// are time spans as typed on the command line, e.g. `"1d 12h"`.
+#[derive(Serialize, Deserialize)]
+struct PracticeSpec {
+    name: String,
+    /// Absent for log-only practices.
+    period: Option<String>,
+    why: Option<String>,
+    weekly_goal: Option<String>,
+    target_time_per_period: Option<String>,
+    #[serde(default)]
+    log_mode: LogMode,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    checklist: Vec<String>,
+}
+
+/// Practices to add in one go, e.g.
+///
+/// ```toml
+/// [[practice]]
+/// name = "steno"
+/// period = "1d"
+/// why = "Write as fast as I think"
+/// checklist = ["drills", "dictation"]
+/// ```
+#[derive(Serialize, Deserialize)]
+pub struct PracticeSet {
+    #[serde(rename = "practice", default)]
+    practices: Vec<PracticeSpec>,
+}
+
+/// Parse an optional time span, naming the practice and field if it doesn't parse.
+fn span(value: Option<&str>, name: &str, field: &str) -> Result<Option<Duration>> {
+    value
+        .map(|value| {
+            parse_time_span(value)
+                .with_context(|| t!("practice-set-invalid-span", field = field, name = name))
+        })
+        .transpose()
+}
+
+impl PracticeSet {
+    pub fn parse(toml: &str) -> Result<Self> {
+        toml::from_str(toml).context(t!("practice-set-parse-failed"))
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).context(t!("practice-set-serialize-failed"))
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.practices.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.practices.iter().map(|spec| spec.name.trim())
+    }
+
+    /// Transitions adding every practice of the set, grouped by practice, except those named in
+    /// `skip`.
+    pub fn transitions(&self, skip: impl Fn(&str) -> bool) -> Result<Vec<Vec<StateTransition>>> {
+        let mut all = Vec::new();
+        for spec in self.practices.iter().filter(|spec| !skip(spec.name.trim())) {
+            let name = spec.name.trim().to_owned();
+            let mut transitions = vec![StateTransition::Add {
+                name: name.clone(),
+                period: span(spec.period.as_deref(), &name, "period")?,
+                why: spec.why.clone(),
+                weekly_goal: span(spec.weekly_goal.as_deref(), &name, "weekly_goal")?,
+            }];
+            let target = span(
+                spec.target_time_per_period.as_deref(),
+                &name,
+                "target_time_per_period",
+            )?;
+            if target.is_some() {
+                transitions.push(StateTransition::Budget {
+                    name: name.clone(),
+                    target_time_per_period: target,
+                });
+            }
+            if spec.log_mode != LogMode::default() {
+                transitions.push(StateTransition::EditMode {
+                    name: name.clone(),
+                    mode: spec.log_mode,
+                });
+            }
+            transitions.extend(
+                spec.checklist
+                    .iter()
+                    .map(|item| StateTransition::Checklist {
+                        name: name.clone(),
+                        item: item.clone(),
+                        edit: ChecklistEdit::Add,
+                    }),
+            );
+            all.push(transitions);
+        }
+        Ok(all)
+    }
+}
+
+impl State {
+    /// How the unarchived practices are set up, to recreate them elsewhere.
+    pub fn practice_set(&self) -> PracticeSet {
+        let span = |duration: Option<Duration>| duration.map(|d| Span::from(d).format());
+        let practices = self
+            .practices
+            .values()
+            .filter(|practice| !practice.archived)
+            .map(|practice| PracticeSpec {
+                name: practice.name.clone(),
+                period: span(practice.period),
+                why: practice.why.clone(),
+                weekly_goal: span(practice.weekly_goal),
+                target_time_per_period: span(practice.target_time_per_period),
+                log_mode: practice.log_mode,
+                checklist: practice
+                    .checklist
+                    .iter()
+                    .map(|item| item.item.clone())
+                    .collect(),
+            })
+            .collect();
+        PracticeSet { practices }
+    }
+}
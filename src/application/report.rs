@@ -0,0 +1,336 @@
+//! `prac report`: an overview of every practice as a Markdown or HTML document, e.g. to commit
+//! into a weekly review repository.
+//!
+//! The report is built as a list of [`Block`]s, then rendered in either format, so that both have
+//! the same content. Only HTML gets a chart, as an inline SVG.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, Utc};
+
+use super::chart::ChartBucket;
+use super::{Adherence, Practice, State, ADHERENCE_PERIODS};
+use crate::i18n::t;
+use crate::time::Span;
+
+/// Weeks charted in HTML reports.
+const CHART_WEEKS: u32 = 12;
+/// How many times its period a gap between logs must last to be notable.
+const GAP_PERIODS: i32 = 2;
+
+/// Document format of `prac report`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    /// The format to save a report to `path` in, by its extension, or Markdown.
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension)
+                if extension.eq_ignore_ascii_case("html")
+                    || extension.eq_ignore_ascii_case("htm") =>
+            {
+                Self::Html
+            }
+            _ => Self::Markdown,
+        }
+    }
+}
+
+/// A part of the report, rendered alike in either format.
+enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    Table(Vec<String>, Vec<Vec<String>>),
+    List(Vec<String>),
+    /// An SVG image, left out of Markdown.
+    Chart(String),
+}
+
+/// Escape `text` for HTML.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Escape `text` for a Markdown table cell.
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn render_markdown(blocks: &[Block]) -> String {
+    let mut document = String::new();
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                let _ = writeln!(document, "{} {text}\n", "#".repeat(*level));
+            }
+            Block::Paragraph(text) => {
+                let _ = writeln!(document, "{text}\n");
+            }
+            Block::Table(headers, rows) => {
+                let row = |cells: &[String]| {
+                    let cells = cells
+                        .iter()
+                        .map(|cell| escape_cell(cell))
+                        .collect::<Vec<_>>();
+                    format!("| {} |", cells.join(" | "))
+                };
+                let _ = writeln!(document, "{}", row(headers));
+                let _ = writeln!(document, "|{}", " --- |".repeat(headers.len()));
+                for cells in rows {
+                    let _ = writeln!(document, "{}", row(cells));
+                }
+                document.push('\n');
+            }
+            Block::List(items) => {
+                for item in items {
+                    let _ = writeln!(document, "- {item}");
+                }
+                document.push('\n');
+            }
+            Block::Chart(_) => {}
+        }
+    }
+    document.truncate(document.trim_end().len());
+    document.push('\n');
+    document
+}
+
+fn render_html(title: &str, blocks: &[Block]) -> String {
+    let mut document = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n\
+         body {{ font-family: sans-serif; max-width: 60em; margin: 2em auto; padding: 0 1em; }}\n\
+         table {{ border-collapse: collapse; margin-bottom: 1em; }}\n\
+         th, td {{ border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }}\n\
+         svg {{ max-width: 100%; height: auto; }}\n\
+         </style>\n</head>\n<body>\n",
+        escape_html(title)
+    );
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                let _ = writeln!(document, "<h{level}>{}</h{level}>", escape_html(text));
+            }
+            Block::Paragraph(text) => {
+                let _ = writeln!(document, "<p>{}</p>", escape_html(text));
+            }
+            Block::Table(headers, rows) => {
+                document.push_str("<table>\n<tr>");
+                for header in headers {
+                    let _ = write!(document, "<th>{}</th>", escape_html(header));
+                }
+                document.push_str("</tr>\n");
+                for cells in rows {
+                    document.push_str("<tr>");
+                    for cell in cells {
+                        let _ = write!(document, "<td>{}</td>", escape_html(cell));
+                    }
+                    document.push_str("</tr>\n");
+                }
+                document.push_str("</table>\n");
+            }
+            Block::List(items) => {
+                document.push_str("<ul>\n");
+                for item in items {
+                    let _ = writeln!(document, "<li>{}</li>", escape_html(item));
+                }
+                document.push_str("</ul>\n");
+            }
+            Block::Chart(svg) => {
+                let _ = writeln!(document, "{svg}");
+            }
+        }
+    }
+    document.push_str("</body>\n</html>\n");
+    document
+}
+
+/// The longest stretch `practice` went without being logged, up to now, if it lasted over
+/// [`GAP_PERIODS`] times its period: when it started, how long it lasted, and the period.
+fn longest_gap(
+    practice: &Practice,
+    now: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, Duration, Duration)> {
+    let period = practice.due_period()?;
+    let times = practice
+        .logs
+        .iter()
+        .map(|entry| entry.at)
+        .chain(std::iter::once(now))
+        .collect::<Vec<_>>();
+    times
+        .windows(2)
+        .map(|pair| (pair[0], pair[1] - pair[0]))
+        .max_by_key(|(_, length)| *length)
+        .filter(|(_, length)| *length > period * GAP_PERIODS)
+        .map(|(from, length)| (from, length, period))
+}
+
+/// E.g. "Mon 2024-03-04", in local time.
+fn date(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local).format("%a %Y-%m-%d").to_string()
+}
+
+/// A practice's row in its context's table, see the headers in [`State::report`].
+fn table_row(practice: &Practice, week_start: DateTime<Utc>) -> Vec<String> {
+    let adherence = practice.adherence(ADHERENCE_PERIODS);
+    let adherence_cell = |fraction: fn(&Adherence) -> f64| {
+        adherence
+            .as_ref()
+            .map_or_else(String::new, |adherence| percent(fraction(adherence)))
+    };
+    let this_week = practice
+        .logs
+        .iter()
+        .filter(|entry| entry.at >= week_start)
+        .fold(Duration::zero(), |sum, entry| sum + entry.time);
+    vec![
+        practice.name.clone(),
+        practice.due_period().map_or_else(
+            || t!("report-log-only"),
+            |period| Span::from(period).format(),
+        ),
+        practice
+            .logs
+            .last()
+            .map_or_else(|| t!("report-never"), |entry| date(entry.at)),
+        Span::from(practice.cumulative).format_abbreviated(),
+        Span::from(this_week).format_abbreviated(),
+        practice.streak().to_string(),
+        adherence_cell(|adherence| adherence.on_time),
+        adherence_cell(|adherence| adherence.grace),
+        adherence_cell(|adherence| adherence.missed),
+    ]
+}
+
+/// E.g. "78%".
+fn percent(fraction: f64) -> String {
+    format!("{:.0}%", fraction * 100.0)
+}
+
+impl State {
+    /// An overview of every unarchived practice as a document in `format`: totals, a table per
+    /// context, and the notable gaps between sessions, with a chart of the last weeks in HTML.
+    pub fn report(&self, format: ReportFormat) -> Result<String> {
+        let now = self.now();
+        let title = t!("report-title", date = date(now));
+        let mut blocks = vec![Block::Heading(1, title.clone())];
+
+        let stats = self.stats(ADHERENCE_PERIODS);
+        let mut summary = vec![t!(
+            "stats-practices",
+            practices = stats.practices,
+            archived = stats.archived,
+            overdue = stats.overdue,
+        )];
+        if let Some(since) = stats.practicing_since {
+            summary.push(t!(
+                "stats-time",
+                since = date(since),
+                total = Span::from(stats.total_time).format_abbreviated(),
+                week = Span::from(stats.this_week).format_abbreviated(),
+                sessions = stats.sessions_this_week,
+            ));
+        }
+        if let Some(adherence) = &stats.adherence {
+            summary.push(t!(
+                "stats-adherence-overall",
+                adherence = adherence.describe()
+            ));
+        }
+        blocks.push(Block::Paragraph(summary.join(" ")));
+
+        if format == ReportFormat::Html {
+            let chart = self.chart(None, ChartBucket::Week, CHART_WEEKS, false)?;
+            blocks.push(Block::Heading(2, t!("report-chart", weeks = CHART_WEEKS)));
+            blocks.push(Block::Chart(chart.render_svg()?));
+        }
+
+        let week_start = crate::time::week_start(
+            &now.with_timezone(&Local),
+            self.config.user_config.week_start,
+        )
+        .with_timezone(&Utc);
+        let headers = vec![
+            t!("report-practice"),
+            t!("report-period"),
+            t!("report-last-logged"),
+            t!("report-total"),
+            t!("report-this-week"),
+            t!("report-streak"),
+            t!("report-on-time"),
+            t!("report-grace"),
+            t!("report-missed"),
+        ];
+        let mut contexts = BTreeMap::<Option<&str>, Vec<Vec<String>>>::new();
+        for practice in self
+            .practices
+            .values()
+            .filter(|practice| !practice.archived)
+        {
+            contexts
+                .entry(practice.context.as_deref())
+                .or_default()
+                .push(table_row(practice, week_start));
+        }
+        // Practices without a context come after those with one.
+        let without = contexts.remove(&None);
+        let sections = contexts
+            .into_iter()
+            .map(|(context, rows)| (context.unwrap_or_default().to_owned(), rows))
+            .chain(without.map(|rows| (t!("report-no-context"), rows)));
+        for (heading, rows) in sections {
+            blocks.push(Block::Heading(2, heading));
+            blocks.push(Block::Table(headers.clone(), rows));
+        }
+
+        blocks.extend(self.report_gaps(now));
+
+        Ok(match format {
+            ReportFormat::Markdown => render_markdown(&blocks),
+            ReportFormat::Html => render_html(&title, &blocks),
+        })
+    }
+
+    /// The notable gaps section of the report: practices that went over [`GAP_PERIODS`] times
+    /// their period without being logged, the longest gaps for their period first.
+    fn report_gaps(&self, now: DateTime<Utc>) -> Vec<Block> {
+        let mut gaps = self
+            .practices
+            .values()
+            .filter(|practice| !practice.archived)
+            .filter_map(|practice| Some((practice, longest_gap(practice, now)?)))
+            .collect::<Vec<_>>();
+        gaps.sort_by_key(|(_, (_, length, period))| {
+            std::cmp::Reverse(length.num_seconds() / period.num_seconds().max(1))
+        });
+        if gaps.is_empty() {
+            return Vec::new();
+        }
+        vec![
+            Block::Heading(2, t!("report-gaps")),
+            Block::List(
+                gaps.into_iter()
+                    .map(|(practice, (from, length, period))| {
+                        t!(
+                            "report-gap",
+                            name = practice.name.as_str(),
+                            length = Span::from(length).format_abbreviated(),
+                            from = date(from),
+                            period = Span::from(period).format(),
+                        )
+                    })
+                    .collect(),
+            ),
+        ]
+    }
+}
@@ -0,0 +1,180 @@
+//! Stopwatch sessions: `prac start` a practice, then `prac stop` it to log the time in between,
+//! or `prac cancel` it to log nothing.
+
+use anyhow::{bail, ensure, Context, Result};
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use super::State;
+use crate::error::Error;
+use crate::i18n::t;
+
+/// A session of practice `name` started at `started` and not yet stopped.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RunningSession {
+    name: String,
+    started: DateTime<Utc>,
+}
+
+/// A running session as `prac sessions` shows it.
+#[serde_as]
+#[derive(Serialize)]
+pub struct ActiveSession<'a> {
+    name: &'a str,
+    started: DateTime<Utc>,
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "elapsed_in_seconds")]
+    elapsed: Duration,
+}
+
+/// The running sessions, earliest started first.
+#[derive(Serialize)]
+pub struct SessionsReport<'a> {
+    sessions: Vec<ActiveSession<'a>>,
+}
+
+impl RunningSession {
+    pub(super) fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl SessionsReport<'_> {
+    /// Print the running sessions for humans.
+    pub fn print(&self) {
+        if self.sessions.is_empty() {
+            println!("{}", t!("nothing-running"));
+        }
+        for session in &self.sessions {
+            println!(
+                "  {}",
+                t!(
+                    "session-entry",
+                    name = session.name,
+                    started = session
+                        .started
+                        .with_timezone(&Local)
+                        .format("%a %H:%M")
+                        .to_string(),
+                    elapsed = crate::time::Span::from(session.elapsed).format_seconds(),
+                )
+            );
+        }
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Names of the practices running, to choose from.
+    pub fn names(&self) -> Vec<&str> {
+        self.sessions.iter().map(|session| session.name).collect()
+    }
+}
+
+impl State {
+    /// When the running session of `name` started, if one is running.
+    pub fn running_since(&self, name: &str) -> Option<DateTime<Utc>> {
+        self.running
+            .iter()
+            .find(|session| session.name == name)
+            .map(|session| session.started)
+    }
+
+    pub fn sessions_report(&self) -> SessionsReport<'_> {
+        let now = self.now();
+        let mut sessions = self
+            .running
+            .iter()
+            .map(|session| ActiveSession {
+                name: &session.name,
+                started: session.started,
+                elapsed: now - session.started,
+            })
+            .collect::<Vec<_>>();
+        sessions.sort_by_key(|session| session.started);
+        SessionsReport { sessions }
+    }
+
+    /// The practice of the running session to stop or cancel: `name` if given, else the only
+    /// practice running.
+    pub fn running_name(&self, name: Option<String>) -> Result<String> {
+        if let Some(name) = name {
+            ensure!(
+                self.running_since(&name).is_some(),
+                Error::NotFound(t!("not-running", name = &name))
+            );
+            return Ok(name);
+        }
+        match &self.running[..] {
+            [] => bail!(Error::NotFound(t!("nothing-running"))),
+            [session] => Ok(session.name.clone()),
+            sessions => bail!(t!(
+                "several-running",
+                names = sessions
+                    .iter()
+                    .map(|session| format!("\"{}\"", session.name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// Start a session of `name`. Unless `parallel`, no other session may be running, so that
+    /// time isn't counted twice by accident.
+    pub(super) fn start_session(&mut self, name: String, parallel: bool) -> Result<()> {
+        ensure!(
+            self.practices.contains_key(&name),
+            Error::NotFound(t!("practice-name-not-found", name = &name))
+        );
+        ensure!(
+            self.running_since(&name).is_none(),
+            Error::Conflict(t!("already-running", name = &name))
+        );
+        if let Some(other) = self.running.first().filter(|_| !parallel) {
+            bail!(Error::Conflict(t!(
+                "other-running",
+                name = &name,
+                other = other.name.as_str()
+            )));
+        }
+        self.running.push(RunningSession {
+            name,
+            started: self.now(),
+        });
+        Ok(())
+    }
+
+    /// Stop the running session of `name`, returning when it started.
+    pub(super) fn end_session(&mut self, name: &str) -> Result<DateTime<Utc>> {
+        let index = self
+            .running
+            .iter()
+            .position(|session| session.name == name)
+            .with_context(|| Error::NotFound(t!("not-running", name = name)))?;
+        Ok(self.running.remove(index).started)
+    }
+
+    /// Point a running session of practice `from` at `to` instead, after a rename or merge. Should
+    /// both be running, the earlier session is kept.
+    pub(super) fn rename_in_sessions(&mut self, from: &str, to: &str) {
+        for session in &mut self.running {
+            if session.name == from {
+                to.clone_into(&mut session.name);
+            }
+        }
+        self.running.sort_by_key(|session| session.started);
+        let mut seen = Vec::new();
+        self.running.retain(|session| {
+            let new = !seen.contains(&session.name);
+            seen.push(session.name.clone());
+            new
+        });
+    }
+
+    /// Forget the running session of a removed practice.
+    pub(super) fn drop_session(&mut self, name: &str) {
+        self.running.retain(|session| session.name != name);
+    }
+}
@@ -0,0 +1,91 @@
+//! An append-only record of every transition saved to a statefile, e.g. `prac.audit.jsonl` beside
+//! `prac.json`, for finding out how a state came about or recovering from a bad one.
+//!
+//! Each line is a JSON object with the time (`at`) and the `transition`, as hooks get it. Lines are
+//! only appended once the statefile is saved, so dry runs and failed batches leave no trace.
+//! Encrypted statefiles have no audit log, as it would be in the clear: encrypting a statefile
+//! removes the one it had.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::i18n::t;
+
+/// A transition applied to the state, waiting for the state to be saved.
+#[derive(Serialize)]
+pub struct AuditEntry {
+    at: DateTime<Utc>,
+    transition: serde_json::Value,
+}
+
+impl AuditEntry {
+    pub const fn new(transition: serde_json::Value, at: DateTime<Utc>) -> Self {
+        Self { at, transition }
+    }
+}
+
+/// The audit log of the statefile at `state_path`: beside it, with `.audit.jsonl` for its
+/// extension.
+pub fn path(state_path: &Path) -> PathBuf {
+    state_path.with_extension("audit.jsonl")
+}
+
+/// Remove the audit log of the statefile at `state_path`, if it has one.
+pub fn remove(state_path: &Path) -> Result<()> {
+    let path = path(state_path);
+    match std::fs::remove_file(&path) {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+            Err(error).with_context(|| t!("audit-remove-failed", path = path.display().to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Append `entries` to the audit log of the statefile at `state_path`, creating it if need be.
+pub fn append(state_path: &Path, entries: &[AuditEntry]) -> Result<()> {
+    let path = path(state_path);
+    let mut lines = Vec::new();
+    for entry in entries {
+        serde_json::to_writer(&mut lines, entry).context(t!("serialize-transition-failed"))?;
+        lines.push(b'\n');
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| t!("audit-write-failed", path = path.display().to_string()))?;
+    file.write_all(&lines)
+        .with_context(|| t!("audit-write-failed", path = path.display().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::path;
+    use crate::application::{handle_transition, State, StateTransition};
+    use crate::crypt::Key;
+    use chrono::Duration;
+
+    #[test]
+    fn test_removed_on_encrypting() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_path = dir.path().join("prac.json");
+        let mut state = State::new();
+        let add = StateTransition::Add {
+            name: "Scales".to_owned(),
+            period: Some(Duration::days(1)),
+            why: None,
+            weekly_goal: None,
+        };
+        handle_transition(&mut state, add).unwrap();
+        state.save(&state_path).unwrap();
+        assert!(path(&state_path).exists());
+
+        state.set_encryption(Some(Key::weak("hunter2")));
+        state.save(&state_path).unwrap();
+        assert!(!path(&state_path).exists());
+    }
+}
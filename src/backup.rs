@@ -0,0 +1,330 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, Utc};
+use serde::Serialize;
+
+use crate::crypt::{self, Key};
+use crate::error::Error;
+use crate::i18n::t;
+use crate::storage;
+
+/// Format of the timestamp in backup names, which is also their id.
+const TIMESTAMP: &str = "%Y%m%dT%H%M%S%.3fZ";
+/// Days back to keep the last backup of each day for, besides the latest backups.
+const DAILY: i64 = 7;
+/// Weeks back to keep the last backup of each week for, besides the latest backups.
+const WEEKLY: i64 = 4;
+
+/// A backup of the statefile.
+#[derive(Serialize)]
+pub struct Backup {
+    /// Identifies the backup to `prac backup restore`: the time it was taken, as in its name.
+    id: String,
+    taken: DateTime<Utc>,
+    path: PathBuf,
+}
+
+/// The backups of a statefile, latest first.
+#[derive(Serialize)]
+pub struct BackupReport {
+    backups: Vec<Backup>,
+}
+
+impl BackupReport {
+    /// Print the backups for humans, numbered from the latest.
+    pub fn print(&self) {
+        if self.backups.is_empty() {
+            println!("{}", t!("no-backups"));
+        }
+        let width = self.backups.len().to_string().len();
+        for (index, backup) in self.backups.iter().enumerate() {
+            let taken = backup
+                .taken
+                .with_timezone(&Local)
+                .format("%a %Y-%m-%d %H:%M:%S");
+            println!("  {:>width$}  {taken}  {}", index + 1, backup.id);
+        }
+    }
+}
+
+/// Directory backups of the statefile at `state_path` are kept in.
+fn backup_dir(state_path: &Path) -> Result<PathBuf> {
+    Ok(state_path
+        .parent()
+        .context(t!("state-path-no-parent"))?
+        .join("backups"))
+}
+
+/// The name of a statefile without, and its extension with, the dot, e.g. `prac` and `.json`.
+fn name_parts(state_path: &Path) -> Result<(String, String)> {
+    let stem = state_path
+        .file_stem()
+        .context(t!("state-path-no-file-name"))?
+        .to_string_lossy()
+        .into_owned();
+    let extension = state_path
+        .extension()
+        .map_or_else(String::new, |extension| {
+            format!(".{}", extension.to_string_lossy())
+        });
+    Ok((stem, extension))
+}
+
+/// Copy the statefile into the backup directory, named for the time `now`. Returns `None` if there
+/// is no statefile yet, and so nothing to back up.
+pub fn backup(state_path: &Path, now: DateTime<Utc>) -> Result<Option<PathBuf>> {
+    if !state_path.exists() {
+        return Ok(None);
+    }
+    let dir = backup_dir(state_path)?;
+    std::fs::create_dir_all(&dir)
+        .with_context(|| t!("create-failed", path = dir.display().to_string()))?;
+
+    let (stem, extension) = name_parts(state_path)?;
+    let timestamp = now.format(TIMESTAMP);
+    let backup_path = dir.join(format!("{stem}-{timestamp}{extension}"));
+
+    std::fs::copy(state_path, &backup_path)
+        .with_context(|| t!("backup-failed", path = backup_path.display().to_string()))?;
+    Ok(Some(backup_path))
+}
+
+/// The backups of the statefile at `state_path`, latest first. Other files in the backup
+/// directory are left out.
+pub fn list(state_path: &Path) -> Result<BackupReport> {
+    let dir = backup_dir(state_path)?;
+    let (stem, extension) = name_parts(state_path)?;
+    let mut backups = Vec::new();
+    if dir.is_dir() {
+        for entry in std::fs::read_dir(&dir)
+            .with_context(|| t!("read-failed", path = dir.display().to_string()))?
+        {
+            let path = entry?.path();
+            let Some(id) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(&format!("{stem}-")))
+                .and_then(|name| name.strip_suffix(&extension))
+            else {
+                continue;
+            };
+            let Ok(taken) = NaiveDateTime::parse_from_str(id, TIMESTAMP) else {
+                continue;
+            };
+            backups.push(Backup {
+                id: id.to_owned(),
+                taken: taken.and_utc(),
+                path,
+            });
+        }
+    }
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.taken));
+    Ok(BackupReport { backups })
+}
+
+/// The path of backup `id` of the statefile at `state_path`: its number in `prac backup list`, or
+/// its id.
+pub fn find(state_path: &Path, id: &str) -> Result<PathBuf> {
+    let report = list(state_path)?;
+    let backup = match id.trim().parse::<usize>() {
+        Ok(number) => number
+            .checked_sub(1)
+            .and_then(|index| report.backups.into_iter().nth(index)),
+        Err(_) => report
+            .backups
+            .into_iter()
+            .find(|backup| backup.id == id.trim()),
+    };
+    let backup = backup.with_context(|| Error::NotFound(t!("backup-not-found", id = id)))?;
+    Ok(backup.path)
+}
+
+/// Delete old backups of the statefile at `state_path`, keeping the latest `keep`, and the last
+/// of each of the last days and weeks before `now`.
+pub fn rotate(state_path: &Path, keep: usize, now: DateTime<Utc>) -> Result<()> {
+    let now = now.with_timezone(&Local);
+    let mut kept_days = Vec::new();
+    let mut kept_weeks = Vec::new();
+    for (index, backup) in list(state_path)?.backups.into_iter().enumerate() {
+        let taken = backup.taken.with_timezone(&Local);
+        let day = taken.date_naive();
+        let week = taken.iso_week();
+        let recent_day = now.signed_duration_since(taken) < Duration::days(DAILY);
+        let recent_week = now.signed_duration_since(taken) < Duration::weeks(WEEKLY);
+        // Backups come latest first, so the first of each day or week is its last.
+        let last_of_day = recent_day && !kept_days.contains(&day);
+        if last_of_day {
+            kept_days.push(day);
+        }
+        let last_of_week = recent_week && !kept_weeks.contains(&week);
+        if last_of_week {
+            kept_weeks.push(week);
+        }
+        if index >= keep && !last_of_day && !last_of_week {
+            std::fs::remove_file(&backup.path)
+                .with_context(|| t!("remove-failed", path = backup.path.display().to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Encrypt the backups of the statefile at `state_path` that are in the clear with `key`, for when
+/// the statefile itself comes to be encrypted.
+pub fn seal(state_path: &Path, key: &Key) -> Result<()> {
+    for backup in list(state_path)?.backups {
+        let path = backup.path.display().to_string();
+        let bytes = std::fs::read(&backup.path).with_context(|| t!("read-failed", path = &path))?;
+        if !crypt::is_encrypted(&bytes) {
+            storage::replace(&backup.path, &key.encrypt(&bytes)?)
+                .with_context(|| t!("write-failed", path = &path))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backup, find, list, rotate};
+    use crate::application::{handle_transition, State, StateTransition};
+    use crate::crypt::{self, Key};
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use std::path::Path;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, hour, minute, 0).unwrap()
+    }
+
+    fn ids(state_path: &Path) -> Vec<String> {
+        list(state_path)
+            .unwrap()
+            .backups
+            .into_iter()
+            .map(|backup| backup.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_backup_and_find() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_path = dir.path().join("prac.json");
+        assert_eq!(backup(&state_path, at(1, 12, 0)).unwrap(), None);
+
+        std::fs::write(&state_path, "first").unwrap();
+        let first = backup(&state_path, at(1, 12, 0)).unwrap().unwrap();
+        std::fs::write(&state_path, "second").unwrap();
+        let second = backup(&state_path, at(2, 12, 0)).unwrap().unwrap();
+        // Neither other files nor backups of other statefiles are listed.
+        let backups = dir.path().join("backups");
+        std::fs::write(backups.join("notes.txt"), "").unwrap();
+        std::fs::write(backups.join("other-20240303T120000.000Z.json"), "").unwrap();
+
+        assert_eq!(
+            ids(&state_path),
+            ["20240302T120000.000Z", "20240301T120000.000Z"]
+        );
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "first");
+        // By number in the list, latest first, or by id.
+        assert_eq!(find(&state_path, "1").unwrap(), second);
+        assert_eq!(find(&state_path, "20240301T120000.000Z").unwrap(), first);
+        assert!(find(&state_path, "0").is_err());
+        assert!(find(&state_path, "3").is_err());
+    }
+
+    #[test]
+    fn test_rotate() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_path = dir.path().join("prac.json");
+        std::fs::write(&state_path, "").unwrap();
+        // Minutes apart, so on the same day wherever the test runs.
+        let taken = [
+            at(20, 11, 0),
+            at(18, 12, 1),
+            at(18, 12, 0),
+            at(5, 12, 1),
+            at(5, 12, 0),
+            Utc.with_ymd_and_hms(2024, 2, 1, 12, 0, 0).unwrap(),
+        ];
+        for time in taken {
+            backup(&state_path, time).unwrap();
+        }
+
+        rotate(&state_path, 1, at(20, 12, 0)).unwrap();
+        assert_eq!(
+            ids(&state_path),
+            [
+                // The latest.
+                "20240320T110000.000Z",
+                // The last of a recent day.
+                "20240318T120100.000Z",
+                // The last of a recent week.
+                "20240305T120100.000Z",
+            ]
+        );
+
+        // However old, the latest are kept.
+        rotate(&state_path, 3, at(20, 12, 0) + Duration::weeks(52)).unwrap();
+        assert_eq!(ids(&state_path).len(), 3);
+        rotate(&state_path, 2, at(20, 12, 0) + Duration::weeks(52)).unwrap();
+        assert_eq!(
+            ids(&state_path),
+            ["20240320T110000.000Z", "20240318T120100.000Z"]
+        );
+    }
+
+    #[test]
+    fn test_save_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_path = dir.path().join("prac.json");
+        State::new().save(&state_path).unwrap();
+        let before = std::fs::read(&state_path).unwrap();
+
+        // Backed up once per run, before the first change is saved.
+        let mut state = State::load(&state_path).unwrap();
+        for name in ["Scales", "Arpeggios"] {
+            let add = StateTransition::Add {
+                name: name.to_owned(),
+                period: Some(Duration::days(1)),
+                why: None,
+                weekly_goal: None,
+            };
+            handle_transition(&mut state, add).unwrap();
+            state.save(&state_path).unwrap();
+        }
+        assert_eq!(ids(&state_path).len(), 1);
+        let backup = find(&state_path, "1").unwrap();
+        assert_eq!(std::fs::read(&backup).unwrap(), before);
+
+        // Restoring saves the backup in the statefile's place, itself backed up first. Backups
+        // are named to the millisecond.
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let mut state = State::load(&state_path).unwrap();
+        let restored = State::from_bytes(std::fs::read(&backup).unwrap(), None).unwrap();
+        state.replace(restored);
+        state.save(&state_path).unwrap();
+        assert_eq!(std::fs::read(&state_path).unwrap(), before);
+        assert_eq!(ids(&state_path).len(), 2);
+    }
+
+    #[test]
+    fn test_encrypting_seals_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_path = dir.path().join("prac.json");
+        State::new().save(&state_path).unwrap();
+        backup(&state_path, at(1, 12, 0)).unwrap();
+
+        let mut state = State::load(&state_path).unwrap();
+        let key = Key::weak("hunter2");
+        state.set_encryption(Some(key.clone()));
+        state.save(&state_path).unwrap();
+
+        let backups = list(&state_path).unwrap().backups;
+        assert_eq!(backups.len(), 1);
+        for backup in backups {
+            let bytes = std::fs::read(&backup.path).unwrap();
+            assert!(crypt::is_encrypted(&bytes));
+            // They can still be restored.
+            State::from_bytes(bytes, Some(&key)).unwrap();
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! `prac block`: a countdown through a block of practice, optionally in pomodoro cycles of
+//! practice and break, after which the time practiced is logged.
+
+use std::io::Write;
+use std::str::FromStr;
+
+use anyhow::{ensure, Result};
+use chrono::Duration;
+use crossterm::cursor::MoveToColumn;
+use crossterm::terminal::{Clear, ClearType};
+
+use crate::clock::SharedClock;
+use crate::error::Error;
+use crate::i18n::t;
+use crate::time::{self, Span};
+use crate::utils::{self, BarGlyphs};
+
+/// Width of the countdown bar, in characters.
+const BAR_WIDTH: usize = 30;
+
+/// Cycles of practice and break, e.g. `25/5` for 25 minutes of practice then 5 of break.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pomodoro {
+    work: Duration,
+    rest: Duration,
+}
+
+impl FromStr for Pomodoro {
+    type Err = anyhow::Error;
+
+    /// Parse `<practice>/<break>`, each in minutes or as a systemd.time-like time span.
+    fn from_str(string: &str) -> Result<Self> {
+        let parse = |part: &str| {
+            let part = part.trim();
+            part.parse::<i64>().map_or_else(
+                |_| time::parse_time_span(part),
+                |minutes| Ok(Duration::minutes(minutes)),
+            )
+        };
+        let (work, rest) = string
+            .split_once('/')
+            .ok_or_else(|| Error::Parse(t!("pomodoro-invalid", input = string)))?;
+        let pomodoro = Self {
+            work: parse(work)?,
+            rest: parse(rest)?,
+        };
+        ensure!(
+            pomodoro.work > Duration::zero() && pomodoro.rest >= Duration::zero(),
+            Error::Parse(t!("pomodoro-invalid", input = string))
+        );
+        Ok(pomodoro)
+    }
+}
+
+impl Pomodoro {
+    /// Time practiced `elapsed` into the block: all of it, but for the breaks.
+    fn practiced(self, elapsed: Duration) -> Duration {
+        let cycle = (self.work + self.rest).num_seconds();
+        let elapsed = elapsed.num_seconds();
+        let into_cycle = Duration::seconds(elapsed % cycle).min(self.work);
+        self.work * i32::try_from(elapsed / cycle).unwrap_or(i32::MAX) + into_cycle
+    }
+
+    /// Whether `elapsed` into the block falls in a break, and which cycle it is in, from 1.
+    fn phase(self, elapsed: Duration) -> (bool, i64) {
+        let cycle = (self.work + self.rest).num_seconds();
+        let elapsed = elapsed.num_seconds();
+        (
+            elapsed % cycle >= self.work.num_seconds(),
+            elapsed / cycle + 1,
+        )
+    }
+}
+
+/// Count down `length`, drawing a bar of the time left, until it is up or ctrl-c is pressed. With
+/// a `pomodoro`, the block alternates between practice and breaks. With `bell`, the terminal bell
+/// rings at the end, and between practice and breaks. Returns the time practiced.
+#[allow(clippy::cast_precision_loss)]
+pub fn run(
+    clock: &SharedClock,
+    length: Duration,
+    pomodoro: Option<Pomodoro>,
+    bell: bool,
+    ascii: bool,
+) -> Result<Duration> {
+    let interrupt = crate::interrupt::catch();
+    let glyphs = BarGlyphs::new(None, None, ascii);
+    let start = clock.now();
+    let mut last_phase = None;
+    let mut elapsed = Duration::zero();
+    while !interrupt.caught() && elapsed < length {
+        let phase = pomodoro.map(|pomodoro| pomodoro.phase(elapsed));
+        if bell && last_phase.is_some() && phase != last_phase {
+            print!("\x07");
+        }
+        last_phase = phase;
+        let label = match phase {
+            Some((true, _)) => t!("block-break"),
+            Some((false, cycle)) => t!("block-cycle", cycle = cycle),
+            None => t!("block-practice"),
+        };
+        let fraction = elapsed.num_milliseconds() as f64 / length.num_milliseconds() as f64;
+        let mut stdout = std::io::stdout();
+        crossterm::queue!(stdout, MoveToColumn(0), Clear(ClearType::CurrentLine))?;
+        print!(
+            "{} {}",
+            utils::bar(BAR_WIDTH, fraction, glyphs),
+            t!(
+                "block-left",
+                label = label,
+                left = Span::from(length - elapsed).format_seconds()
+            )
+        );
+        stdout.flush()?;
+        std::thread::sleep(std::time::Duration::from_millis(250));
+        elapsed = clock.now() - start;
+    }
+
+    let elapsed = elapsed.min(length);
+    if bell {
+        print!("\x07");
+    }
+    crossterm::execute!(
+        std::io::stdout(),
+        MoveToColumn(0),
+        Clear(ClearType::CurrentLine)
+    )?;
+    println!("{}", t!("block-done"));
+    Ok(pomodoro.map_or(elapsed, |pomodoro| pomodoro.practiced(elapsed)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pomodoro;
+    use chrono::Duration;
+
+    #[test]
+    fn test_pomodoro() {
+        let pomodoro = "25/5".parse::<Pomodoro>().unwrap();
+        assert_eq!(pomodoro, "25m/5m".parse().unwrap());
+        assert!("25".parse::<Pomodoro>().is_err());
+        assert!("0/5".parse::<Pomodoro>().is_err());
+
+        // An hour is two cycles of 25 minutes practice and 5 of break.
+        assert_eq!(
+            pomodoro.practiced(Duration::hours(1)),
+            Duration::minutes(50)
+        );
+        assert_eq!(
+            pomodoro.practiced(Duration::minutes(27)),
+            Duration::minutes(25)
+        );
+        assert_eq!(
+            pomodoro.practiced(Duration::minutes(40)),
+            Duration::minutes(35)
+        );
+        assert_eq!(pomodoro.phase(Duration::minutes(27)), (true, 1));
+        assert_eq!(pomodoro.phase(Duration::minutes(31)), (false, 2));
+    }
+}
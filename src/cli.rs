@@ -1,9 +1,21 @@
 use std::path::PathBuf;
 
-use super::time::parse_time_span;
-use chrono::Duration;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use super::application::{
+    Lifecycle, ListGroup, ListSort, LogMode, ReportFormat, ADHERENCE_PERIODS,
+};
+use super::block::Pomodoro;
+use super::color::ColorChoice;
+use super::error::Error;
+use super::import::ImportFormat;
+use super::output::OutputFormat;
+use super::time::{parse_time_bound, parse_time_point, parse_time_span, TimeWindow};
+use chrono::{DateTime, Duration, Utc};
 use clap::{Parser, Subcommand};
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Parser, Debug)]
 #[command(author = "Henry Merrilees")]
 #[command(version = env!("CARGO_PKG_VERSION"))]
@@ -16,6 +28,36 @@ pub struct Cli {
     /// alias friends='prac --path=/path/to/friends_state'
     #[arg(long, env = "PRAC_PATH")]
     pub(super) path: Option<PathBuf>,
+    /// Use made-up practices instead of the statefile, e.g. to try commands or take screenshots.
+    /// Changes are thrown away, and hooks and webhooks don't run.
+    #[arg(long, global = true)]
+    pub(super) demo: bool,
+    /// Go through a command without saving anything: changes are checked and described, but the
+    /// statefile is left alone, and hooks and webhooks don't run.
+    #[arg(long, global = true)]
+    pub(super) dry_run: bool,
+    /// Don't print errors, only exit with their code: 2 if something wasn't found, 3 if input
+    /// couldn't be parsed, 4 if aborted, 5 if the statefile is corrupt, 6 if something already
+    /// exists, 7 if the statefile can't be changed, and 1 otherwise.
+    #[arg(short, long, global = true)]
+    pub(super) quiet: bool,
+    /// When to use color, overriding the `color` config. `auto` (the default) colors only when
+    /// printing to a terminal and `NO_COLOR` is unset.
+    #[arg(long, value_enum, global = true)]
+    pub(super) color: Option<ColorChoice>,
+    /// Don't ask for confirmation: of times that look like `M` (months) and `m` (minutes) were
+    /// mixed up, e.g. a period of 30m or a session of 2M, of the time `prac block` logs, or of
+    /// removing practices.
+    #[arg(short, long, global = true)]
+    pub(super) yes: bool,
+    /// Never change the statefile: commands that would fail before doing anything. This is the
+    /// default when the statefile, or the directory it is in, isn't writable.
+    #[arg(long, global = true, conflicts_with = "demo")]
+    pub(super) read_only: bool,
+    /// Take a practice name that matches no practice to mean the closest one, rather than failing
+    /// with suggestions, e.g. for scripts.
+    #[arg(long, global = true)]
+    pub(super) assume_closest: bool,
     #[command(subcommand)]
     pub(super) command: SubCommand,
 }
@@ -24,7 +66,16 @@ pub struct Cli {
 pub enum SubCommand {
     /// List practices w/ progress bars showing time elapsed through period. `help list` for options
     #[command(
-        after_long_help = "-p -c together will display both, but each truncated to the largest unit",
+        after_long_help = "\
+            -p -c together will display both, but each truncated to the largest unit.\n\n\
+            Placeholders for --template: {name} {period} {period_seconds} {elapsed} \
+            {elapsed_seconds} {elapsed_pct} {last_logged} {created} {cumulative} \
+            {cumulative_seconds} {context} {state} {group} {why} {aliases}, and {meta.<field>} for \
+            a field set with `prac meta`. Those a practice has no value for are left empty, e.g. \
+            {period} of a log-only practice. \\t and \\n are a tab and a newline; write {{ or }} \
+            for literal braces.\n\n\
+            Example: prac list --template \"{name},{elapsed_pct},{meta.teacher}\"\
+            ",
         alias = "ls"
     )]
     List {
@@ -37,15 +88,145 @@ pub enum SubCommand {
         /// Show "danger bar" that dissplays sum progression through periods.
         #[arg(short, long, default_value = "false")]
         danger: bool,
+        /// Maximum bar width (defaults to config, else the full terminal).
+        #[arg(long)]
+        bar_width: Option<usize>,
+        /// Character for the elapsed part of bars.
+        #[arg(long)]
+        fill: Option<char>,
+        /// Character for the remaining part of bars.
+        #[arg(long)]
+        empty: Option<char>,
+        /// Draw bars with ASCII characters only.
+        #[arg(long, default_value = "false")]
+        ascii: bool,
+        /// Also show archived practices.
+        #[arg(short, long, default_value = "false")]
+        archived: bool,
+        /// Order of practices (defaults to the `list_sort` config).
+        #[arg(short, long, value_enum)]
+        sort: Option<ListSort>,
+        /// Show how each of the last 10 periods went after the bars: on time (tall and green), in
+        /// the grace period (yellow), or missed (flat and red).
+        #[arg(long, default_value = "false")]
+        sparkline: bool,
+        /// Show time logged this period against the practice's budget (see `prac budget`).
+        #[arg(short, long, default_value = "false")]
+        budget: bool,
+        /// Show when each practice was last logged and when it is due, e.g. "last logged 3d ago,
+        /// due in 4d (grace until +1d)".
+        #[arg(short, long, default_value = "false")]
+        verbose: bool,
+        /// Only show practices that can be done in this context, and those without one (see
+        /// `prac context`).
+        #[arg(long, env = "PRAC_CONTEXT")]
+        context: Option<String>,
+        /// Show practices in sections by context or state, each headed by how many practices it
+        /// has and their total time, and sorted as usual within.
+        #[arg(short, long, value_enum)]
+        group_by: Option<ListGroup>,
+        /// Only show practices at least this far through their (grace adjusted) period, as a
+        /// fraction: 1 (if not given) for those due, 0.5 for those half way there.
+        #[arg(long, value_name = "FRACTION", num_args = 0..=1, default_missing_value = "1")]
+        due_only: Option<f64>,
+        /// Only show practices logged within this time span, e.g. `7d`.
+        #[arg(long, value_name = "SPAN", value_parser = parse_time_span)]
+        recent: Option<Duration>,
+        /// Also show hidden practices (see `prac hide`).
+        #[arg(long, default_value = "false")]
+        hidden: bool,
+        /// Show long names in full, rather than cut short to the `list_name_width` config or to
+        /// fit the terminal.
+        #[arg(long, default_value = "false")]
+        full_names: bool,
+        /// Show the aliases of practices after their names (see `prac alias`).
+        #[arg(long, default_value = "false")]
+        aliases: bool,
+        /// Keep the list on screen, redrawing it every interval (a time span, 30s if not given) and
+        /// whenever the statefile changes. Stop with Ctrl-C.
+        #[arg(
+            short,
+            long,
+            value_name = "INTERVAL",
+            value_parser = parse_time_span,
+            num_args = 0..=1,
+            default_missing_value = "30s",
+            conflicts_with = "output"
+        )]
+        watch: Option<Duration>,
+        /// Print a line per practice rendered from a template instead of bars, e.g.
+        /// `"{name}\t{elapsed_pct}\t{last_logged}"`. See `help list` for the placeholders.
+        #[arg(long, conflicts_with_all = ["output", "watch"])]
+        template: Option<String>,
+        /// Show the list as it would be at another time, e.g. `"next monday 9am"` or `"in 2d"`,
+        /// to see what will be due by then. Logs are taken as they are now.
+        #[arg(long, value_name = "TIME", value_parser = parse_time_point, conflicts_with = "watch")]
+        as_of: Option<DateTime<Utc>>,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// Print a compact one-line summary, fast enough to run on every shell prompt.
+    ///
+    /// Shows counts of fresh (under half way through their period), aging, and overdue practices.
+    /// For example, in bash: PS1='$(prac prompt) \$ '
+    Prompt {
+        /// Show only the most overdue practice and how far through its period it is.
+        #[arg(short, long, default_value = "false")]
+        next: bool,
+    },
+    /// Summarize practices for status bars, e.g. starship custom modules or tmux status-right.
+    #[command(after_long_help = "\
+        Placeholders for --format: {fresh_count} {aging_count} {overdue_count} {total_count} \
+        {next_name} {next_pct} {next_why}\n\n\
+        \"next\" is the practice furthest through its period, and {next_why} is only filled in \
+        once it is slumping (see `prac why`). Write {{ or }} for literal braces.\n\n\
+        Example for tmux: set -g status-right '#(prac status --format \"{next_name} {next_pct}%\")'\
+        ")]
+    Status {
+        /// Template for the status line, e.g. `"{overdue_count} overdue, next: {next_name}"`.
+        #[arg(short, long)]
+        format: Option<String>,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with = "format")]
+        output: OutputFormat,
+    },
+    /// Search practice names, reasons (see `prac why`), and notes.
+    Find {
+        /// Text to search for (case insensitive).
+        query: String,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
     },
     /// Add a new practice.
     Add {
         /// A (unique) name for the practice.
-        #[arg(required_unless_present = "interactive")]
+        #[arg(required_unless_present_any = ["interactive", "from_file"])]
         name: Option<String>,
-        /// Anticipated time period between practice sessions (as systemd.time-like time span).
-        #[arg(value_parser = parse_time_span, required_unless_present = "interactive")]
+        /// Anticipated time period between practice sessions, as systemd.time-like time span, e.g.
+        /// `2d` or `1 week`. Defaults to the `default_period` config, if set.
+        #[arg(value_parser = parse_time_span, conflicts_with = "log_only")]
         period: Option<Duration>,
+        /// Add a practice without a period, which only accumulates time. Log-only practices have
+        /// no bar and are never due; `prac list --cumulative` shows their totals.
+        #[arg(long, default_value = "false")]
+        log_only: bool,
+        /// Why this practice matters to you. Shown by `prac list` when the practice slumps.
+        #[arg(short, long)]
+        why: Option<String>,
+        /// Time to spend on the practice per week, e.g. `3h`, tracked alongside the period. See
+        /// `prac goal`.
+        #[arg(long, value_name = "SPAN", value_parser = parse_time_span)]
+        weekly_goal: Option<Duration>,
+        /// Add every practice in a TOML file instead, as written by `prac template save`.
+        /// Practices that already exist are skipped.
+        #[arg(long, conflicts_with_all = ["name", "interactive"])]
+        from_file: Option<PathBuf>,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
         /// Interactive
         #[arg(short, long, default_value = "false")]
         interactive: bool,
@@ -55,43 +236,534 @@ pub enum SubCommand {
         /// Specify practice to log, or leave blank to fuzzy search.
         #[arg(required_unless_present = "interactive")]
         name: Option<String>,
-        /// Time practiced, as systemd.time-like time span.
+        /// Time practiced, as systemd.time-like time span, e.g. `1h30m`.
         #[arg(value_parser = parse_time_span, requires = "name", required_unless_present = "interactive")]
         max_time: Option<Duration>,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Block out time for a practice, e.g. `prac block 1h steno`: count it down, then log the time
+    /// practiced.
+    ///
+    /// The time is confirmed (or adjusted) before it is logged, unless `--yes` is given or the
+    /// terminal isn't interactive. Ctrl-c ends the block early.
+    Block {
+        /// Length of the block, as systemd.time-like time span, e.g. `25m`.
+        #[arg(value_parser = parse_time_span, required_unless_present = "interactive")]
+        time: Option<Duration>,
+        /// Specify practice to log, or leave blank to fuzzy search.
+        #[arg(required_unless_present = "interactive")]
+        name: Option<String>,
+        /// Split the block into cycles of practice and break, e.g. `25/5` for 25 minutes of
+        /// practice then 5 of break. Only the practice is logged.
+        #[arg(long, value_name = "PRACTICE/BREAK")]
+        pomodoro: Option<Pomodoro>,
+        /// Ring the terminal bell at the end of the block, and between practice and breaks.
+        #[arg(long, default_value = "false")]
+        bell: bool,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Start a stopwatch session of a practice, to log with `prac stop` when done.
+    ///
+    /// Sessions longer than the `max_session` config (4h by default), e.g. one forgotten
+    /// overnight, aren't logged as they are: `prac stop` asks what to log, or trims them.
+    /// Only one session runs at a time, so time isn't counted twice, unless `--parallel` is given.
+    Start {
+        /// Specify practice, or leave blank to fuzzy search.
+        #[arg(required_unless_present = "interactive")]
+        name: Option<String>,
+        /// Start the session even while others are running.
+        #[arg(long, default_value = "false")]
+        parallel: bool,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
         /// Interactive
         #[arg(short, long, default_value = "false")]
         interactive: bool,
     },
+    /// Stop a session started with `prac start`, logging the time since.
+    Stop {
+        /// Practice to stop, if more than one is running.
+        name: Option<String>,
+        /// Log this time instead of the time since the session started, as systemd.time-like time
+        /// span, e.g. `45m`.
+        #[arg(short, long, value_name = "SPAN", value_parser = parse_time_span)]
+        time: Option<Duration>,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// List the sessions started with `prac start` and still running, with their elapsed time.
+    Sessions {
+        /// Choose a session to stop or cancel.
+        #[arg(short, long, default_value = "false", conflicts_with = "output")]
+        interactive: bool,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// Discard a session started with `prac start`, logging nothing.
+    Cancel {
+        /// Practice to cancel, if more than one is running.
+        name: Option<String>,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
     /// Log time participated in a practice outside a session
     Log {
         /// Specify practice to log, or leave blank to fuzzy search.
         #[arg(required_unless_present = "interactive")]
         name: Option<String>,
-        /// Time practiced, as systemd.time-like time span.
+        /// Time practiced, as systemd.time-like time span, e.g. `1h30m`.
         #[arg(value_parser = parse_time_span, requires = "name", required_unless_present = "interactive")]
         time: Option<Duration>,
+        /// When the session was, if not just now, e.g. "2h ago", "yesterday 18:00", "last tue".
+        #[arg(long, value_parser = parse_time_point)]
+        at: Option<DateTime<Utc>>,
+        /// A line about how the session went, shown by `prac history`. Asked for with
+        /// `--interactive`, which also shows the practice's bar afterwards.
+        #[arg(long)]
+        note: Option<String>,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
         /// Interactive
         #[arg(short, long, default_value = "false")]
         interactive: bool,
     },
+    /// Restart a practice's bar without logging time, for days you only touched it.
+    ///
+    /// Cumulative time stays as it is, unless the `touch_time` config sets a nominal amount to
+    /// log, e.g. `prac config set touch_time 5m`.
+    Touch {
+        /// Specify practice, or leave blank to fuzzy search.
+        #[arg(required_unless_present = "interactive")]
+        name: Option<String>,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Skip the current period of a practice on purpose, e.g. when ill or away.
+    ///
+    /// The bar restarts as if the practice were logged, but without time. The skip shows in
+    /// `prac history` and the `--sparkline`, and doesn't break the streak, which a silent lapse
+    /// would.
+    Skip {
+        /// Specify practice, or leave blank to fuzzy search.
+        #[arg(required_unless_present = "interactive")]
+        name: Option<String>,
+        /// Why the period is skipped, shown by `prac history`.
+        #[arg(short, long)]
+        reason: Option<String>,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Show the logged sessions of a practice, or correct them with `history edit`.
+    #[command(args_conflicts_with_subcommands = true)]
+    History {
+        #[command(subcommand)]
+        action: Option<HistoryAction>,
+        /// Specify practice, or leave blank to fuzzy search.
+        #[arg(required_unless_present = "interactive")]
+        name: Option<String>,
+        /// Only show sessions from this time on, e.g. "2w" (ago), "last mon", or "2024-03-01".
+        #[arg(long, value_parser = parse_time_bound)]
+        since: Option<DateTime<Utc>>,
+        /// Only show sessions before this time, e.g. "today" for all but today's.
+        #[arg(long, value_parser = parse_time_bound)]
+        until: Option<DateTime<Utc>>,
+        /// Group the sessions by day, with each day's total.
+        #[arg(long, default_value = "false")]
+        timeline: bool,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Chart the time logged per week or month, to see whether practice is trending up or down.
+    Chart {
+        /// Practice to chart, or leave blank for all of them together.
+        name: Option<String>,
+        /// Sum the time logged per week (the default).
+        #[arg(long, default_value = "false", conflicts_with = "monthly")]
+        weekly: bool,
+        /// Sum the time logged per month.
+        #[arg(long, default_value = "false")]
+        monthly: bool,
+        /// Number of weeks or months to show, counting the current one.
+        #[arg(short = 'n', long, default_value_t = 12, value_parser = clap::value_parser!(u32).range(1..))]
+        count: u32,
+        /// Chart the time logged in all by the end of each week or month, rather than in it.
+        #[arg(long, default_value = "false")]
+        cumulative: bool,
+        /// Save the chart to this file instead, as an SVG or PNG image by its extension, e.g. for
+        /// weekly review notes.
+        #[arg(long, value_name = "FILE", conflicts_with = "output")]
+        save: Option<PathBuf>,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Choose the practice with fuzzy search.
+        #[arg(short, long, default_value = "false", conflicts_with = "name")]
+        interactive: bool,
+    },
+    /// Write an overview of every practice as a Markdown or HTML document: totals, a table per
+    /// context with streaks and adherence, and notable gaps between sessions, plus a chart of the
+    /// last weeks in HTML. Handy for committing into a weekly review repository.
+    Report {
+        /// Document format, by default that of the `--save` file's extension, else Markdown.
+        #[arg(long, value_enum)]
+        format: Option<ReportFormat>,
+        /// Save the report to this file instead of printing it.
+        #[arg(long, value_name = "FILE")]
+        save: Option<PathBuf>,
+    },
+    /// Show a calendar of the time logged each day over the last weeks, GitHub style.
+    Heatmap {
+        /// Practice to show, or leave blank for all of them together.
+        name: Option<String>,
+        /// Number of weeks to show, counting the current one.
+        #[arg(short, long, default_value_t = 13)]
+        weeks: usize,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Choose the practice with fuzzy search.
+        #[arg(short, long, default_value = "false", conflicts_with = "name")]
+        interactive: bool,
+    },
     /// Edit practice notes in your $EDITOR.
     /// If you don't know vi or have your editor set otherwise, it's probably wise to leave this alone.
+    ///
+    /// Notes can also be given as an argument or piped in with `--stdin`, e.g.
+    /// `journal | prac notes steno --stdin --append`.
     Notes {
         /// Specify practice to edit, or leave blank to fuzzy search.
         #[arg(required_unless_present = "interactive")]
         name: Option<String>,
-        #[arg(required_unless_present = "interactive")]
+        #[arg(required_unless_present_any = ["interactive", "stdin", "history", "show"])]
         new_notes: Option<String>,
+        /// Read notes from stdin.
+        #[arg(long, default_value = "false", conflicts_with_all = ["new_notes", "interactive"])]
+        stdin: bool,
+        /// Add to the existing notes under a timestamped separator, rather than replacing them.
+        #[arg(short, long, default_value = "false")]
+        append: bool,
+        /// Show every earlier version of the notes, with when it was written, instead of editing.
+        #[arg(long, default_value = "false", conflicts_with_all = ["new_notes", "stdin", "append"])]
+        history: bool,
+        /// Read the notes, rendered as markdown (headings, lists, emphasis, code), instead of editing.
+        #[arg(short, long, default_value = "false", conflicts_with_all = ["new_notes", "stdin", "append", "history"])]
+        show: bool,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Set (or clear) the reason you keep up a practice.
+    ///
+    /// When a practice goes untouched for more than twice its period, `prac list` shows this line
+    /// beneath its bar, as a nudge in your own words.
+    Why {
+        /// Specify practice, or leave blank to fuzzy search.
+        #[arg(required_unless_present_any = ["interactive", "clear"])]
+        name: Option<String>,
+        /// Short motivation statement.
+        #[arg(required_unless_present_any = ["interactive", "clear"])]
+        why: Option<String>,
+        /// Remove the statement instead of setting it.
+        #[arg(short, long, default_value = "false", conflicts_with = "why")]
+        clear: bool,
         /// Interactive
         #[arg(short, long, default_value = "false")]
         interactive: bool,
     },
+    /// Set (or clear) a weekly time goal for a practice, e.g. `prac goal guitar 3h`.
+    ///
+    /// Some practices are better framed as time per week than as a period between sessions. A
+    /// weekly goal is tracked alongside the period: `prac list` shows a second bar beneath the
+    /// practice that fills up as you log time, and empties again when the week starts over (see
+    /// the `week_start` config).
+    Goal {
+        /// Specify practice, or leave blank to fuzzy search.
+        #[arg(required_unless_present_any = ["interactive", "clear"])]
+        name: Option<String>,
+        /// Time to spend on the practice per week, as systemd.time-like time span, e.g. `3h`.
+        #[arg(value_parser = parse_time_span, required_unless_present_any = ["interactive", "clear"])]
+        goal: Option<Duration>,
+        /// Remove the goal instead of setting it.
+        #[arg(short, long, default_value = "false", conflicts_with = "goal")]
+        clear: bool,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Set a time budget per period for a practice, e.g. `prac budget piano 5h` for a weekly
+    /// practice.
+    ///
+    /// Unlike the bar, which restarts whenever you log, the budget adds up every session in the
+    /// current period; periods are counted from when the practice was added. `prac list --budget`
+    /// shows it as a second bar that fills up as you log time.
+    Budget {
+        /// Specify practice, or leave blank to fuzzy search.
+        #[arg(required_unless_present_any = ["interactive", "clear"])]
+        name: Option<String>,
+        /// Time to spend on the practice per period, as systemd.time-like time span, e.g. `1h`.
+        #[arg(value_parser = parse_time_span, required_unless_present_any = ["interactive", "clear"])]
+        target: Option<Duration>,
+        /// Remove the budget instead of setting it.
+        #[arg(short, long, default_value = "false", conflicts_with = "target")]
+        clear: bool,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Set (or clear) how long a session of a practice typically takes, e.g. `prac edit-session
+    /// steno 45m`.
+    ///
+    /// `prac suggest --have 20m` then only suggests practices that fit in 20 minutes.
+    EditSession {
+        /// Specify practice, or leave blank to fuzzy search.
+        #[arg(required_unless_present_any = ["interactive", "clear"])]
+        name: Option<String>,
+        /// Typical session length, as systemd.time-like time span, e.g. `20m`.
+        #[arg(value_parser = parse_time_span, required_unless_present_any = ["interactive", "clear"])]
+        length: Option<Duration>,
+        /// Remove the session length instead of setting it.
+        #[arg(short, long, default_value = "false", conflicts_with = "length")]
+        clear: bool,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Set (or clear) the time of day a practice goes best, e.g. `prac best-time steno evenings`.
+    ///
+    /// `prac suggest` prefers practices whose best time it is. Times are `mornings` (5-12),
+    /// `afternoons` (12-17), `evenings` (17-22), `nights` (22-5), or a window like `20:00-23:00`.
+    BestTime {
+        /// Specify practice, or leave blank to fuzzy search.
+        #[arg(required_unless_present_any = ["interactive", "clear"])]
+        name: Option<String>,
+        /// Time of day, e.g. `mornings` or `20:00-23:00`.
+        #[arg(required_unless_present_any = ["interactive", "clear"])]
+        window: Option<TimeWindow>,
+        /// Remove the time of day instead of setting it.
+        #[arg(short, long, default_value = "false", conflicts_with = "window")]
+        clear: bool,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Set (or clear) where a practice can be done, e.g. `prac context steno home`.
+    ///
+    /// `prac list --context office` and `prac suggest --context office` then leave steno out, as
+    /// do both with `PRAC_CONTEXT=office` set. Practices without a context can be done anywhere.
+    /// Without a context, the practice's current one is shown.
+    Context {
+        /// Specify practice, or leave blank to fuzzy search.
+        #[arg(required_unless_present = "interactive")]
+        name: Option<String>,
+        /// A context, e.g. `home`, `office`, or `gym`.
+        context: Option<String>,
+        /// Remove the context, so the practice can be done anywhere.
+        #[arg(short, long, default_value = "false", conflicts_with = "context")]
+        clear: bool,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Archive practices, hiding them from `prac list` without losing their history.
+    ///
+    /// Several practices can be given, as names or globs like `"piano*"`, or all those in a
+    /// context with `--context`. Changing more than one asks first, listing them.
+    Archive {
+        /// Practices to archive, or leave blank to fuzzy search.
+        #[arg(required_unless_present_any = ["interactive", "context"])]
+        names: Vec<String>,
+        /// Archive the practices in this context too (see `prac context`).
+        #[arg(long)]
+        context: Option<String>,
+        /// Bring an archived practice back instead.
+        #[arg(short, long, default_value = "false")]
+        unarchive: bool,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Hide practices from `prac list`, while still tracking them as usual. `prac list --hidden`
+    /// shows them.
+    ///
+    /// Several practices can be given, as names or globs like `"piano*"`, or all those in a
+    /// context with `--context`. Changing more than one asks first, listing them.
+    Hide {
+        /// Practices to hide, or leave blank to fuzzy search.
+        #[arg(required_unless_present_any = ["interactive", "context"])]
+        names: Vec<String>,
+        /// Hide the practices in this context too (see `prac context`).
+        #[arg(long)]
+        context: Option<String>,
+        /// Show a hidden practice again instead.
+        #[arg(short, long, default_value = "false")]
+        unhide: bool,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Give a practice a short alias, accepted wherever its name is, e.g.
+    /// `prac alias "distributed systems programming" dsp`, then `prac log dsp 1h`.
+    ///
+    /// Without an alias, the practice's aliases are listed. `prac list --aliases` shows them too.
+    Alias {
+        /// The practice, or one of its aliases.
+        name: String,
+        /// The alias to add.
+        alias: Option<String>,
+        /// Remove the alias instead.
+        #[arg(short, long, default_value = "false", requires = "alias")]
+        remove: bool,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// Keep free-form fields with a practice, e.g. `prac meta set piano teacher "Ms. Ivanova"`.
+    ///
+    /// Fields are included in `prac list --output json` and `prac export json`.
+    Meta {
+        #[command(subcommand)]
+        action: MetaAction,
+    },
+    /// Set how much a practice is kept up: `active`, `maintenance`, or `retired`.
+    ///
+    /// Practices in maintenance are skills you only want to keep warm. They are due after four
+    /// times their period, and `prac list` shows them, in italics, below the active ones. Retired
+    /// practices are archived.
+    SetState {
+        /// Specify practice, or leave blank to fuzzy search.
+        #[arg(required_unless_present = "interactive")]
+        name: Option<String>,
+        #[arg(value_enum, required_unless_present = "interactive")]
+        state: Option<Lifecycle>,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Declare that a practice is best done after another on the same day, e.g.
+    /// `prac depend steno --on warmups`.
+    ///
+    /// On days `warmups` hasn't been logged yet, `prac suggest` and `prac list --sort smart` put it
+    /// before `steno`. Without `--on`, the practices `steno` depends on are listed.
+    Depend {
+        /// The practice to do later.
+        name: String,
+        /// The practice to do first.
+        #[arg(long)]
+        on: Option<String>,
+        /// Remove the dependency instead.
+        #[arg(short, long, default_value = "false", requires = "on")]
+        remove: bool,
+    },
+    /// Suggest the practice to do next: the one furthest through its period, after those it
+    /// depends on (see `prac depend`).
+    Suggest {
+        /// Only suggest practices that can be done in this context, and those without one (see
+        /// `prac context`).
+        #[arg(long, env = "PRAC_CONTEXT")]
+        context: Option<String>,
+        /// Only suggest practices whose typical session fits in this much time, e.g. `30m`, and those
+        /// without a typical session length (see `prac edit-session`).
+        #[arg(long, value_name = "SPAN", value_parser = parse_time_span)]
+        have: Option<Duration>,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// Review practices that need attention: those added long ago but never logged, and those
+    /// logged at a different pace than their period, which is worth adjusting to keep the feedback
+    /// useful.
+    ///
+    /// How long "long ago" is can be set with `prac config --prune-after-periods`.
+    #[command(alias = "neglected")]
+    Review,
+    /// Show totals over every practice, and how often each was practiced on time: the share of its
+    /// last periods logged within the period, within the grace period, or missed. Skipped periods
+    /// aren't counted.
+    Stats {
+        /// Number of past periods of each practice to count.
+        #[arg(long, default_value_t = ADHERENCE_PERIODS)]
+        periods: usize,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
     /// Reset all progress bars if you fall behind.
     /// Equivalent to tracking all practices w/ zero time.
+    ///
+    /// Requires typing "reset" to confirm, and backs up the statefile first.
     Reset,
     /// Show state file location. `help state-location` for more info.
     ///
-    /// State is stored in $PRAC_PATH, [dirs::data_dir]/prac/prac.json
+    /// State is stored in $PRAC_PATH, [dirs::data_dir]/prac/prac.json (e.g.
+    /// ~/.local/share/prac/prac.json, or %APPDATA%\prac\prac.json on Windows)
     /// or [dirs::home_dir]/.prac.json, searched in that order.
     ///
     /// It's a good idea to vcs your state file.
@@ -102,22 +774,237 @@ pub enum SubCommand {
         /// Specify name of practice whose period to edit
         #[arg(required_unless_present = "interactive")]
         name: Option<String>,
-        /// Anticipated time period between practice sessions.
-        #[arg(value_parser = parse_time_span, required_unless_present = "interactive")]
+        /// Anticipated time period between practice sessions, as systemd.time-like time span, e.g.
+        /// `2d` or `1 week`.
+        #[arg(value_parser = parse_time_span, required_unless_present_any = ["interactive", "log_only"])]
         period: Option<Duration>,
+        /// Remove the period, making the practice log-only (see `prac add --log-only`).
+        #[arg(long, default_value = "false", conflicts_with = "period")]
+        log_only: bool,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
         /// Interactive
         #[arg(short, long, default_value = "false")]
         interactive: bool,
     },
-    Remove {
-        /// Specify name of practice to remove, or leave blank to fuzzy search.
+    /// Check off an item of a practice's checklist, or edit the checklist.
+    ///
+    /// Checklists hold the things to get done each period, e.g. `prac check blog "write summary"
+    /// --add`. Checked items show as done in `prac list` until the bar restarts. Without an item,
+    /// the checklist is shown.
+    Check {
+        /// Specify practice, or leave blank to fuzzy search.
+        #[arg(required_unless_present = "interactive")]
+        name: Option<String>,
+        /// Checklist item.
+        item: Option<String>,
+        /// Add the item to the checklist.
+        #[arg(short, long, default_value = "false", requires = "item")]
+        add: bool,
+        /// Remove the item from the checklist.
+        #[arg(
+            short,
+            long,
+            default_value = "false",
+            requires = "item",
+            conflicts_with = "add"
+        )]
+        remove: bool,
+        /// Mark the item as not done.
+        #[arg(short, long, default_value = "false", requires = "item", conflicts_with_all = ["add", "remove"])]
+        uncheck: bool,
+        /// Choose the practice and item to check off with fuzzy search.
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Change how logging a practice restarts its bar.
+    ///
+    /// `reset` (the default) restarts it on every log. `accumulate` adds logs up toward the
+    /// practice's budget (see `prac budget`) and restarts once it's reached, e.g. for "5h a week".
+    /// `checkpoint` restarts on the first log after the period ends, so logging twice in a period
+    /// doesn't push the next deadline out.
+    #[command(alias = "em")]
+    EditMode {
+        /// Specify name of practice whose mode to edit
         #[arg(required_unless_present = "interactive")]
         name: Option<String>,
+        #[arg(value_enum, required_unless_present = "interactive")]
+        mode: Option<LogMode>,
         /// Interactive
         #[arg(short, long, default_value = "false")]
         interactive: bool,
     },
+    /// Remove practices. They stay in the trash for a while (see the `trash_retention` config),
+    /// and can be brought back with `prac trash restore`.
+    ///
+    /// Several practices can be given, as names or globs like `"piano*"`, or all those in a
+    /// context with `--context`.
+    Remove {
+        /// Practices to remove, or leave blank to fuzzy search.
+        #[arg(required_unless_present_any = ["interactive", "context"])]
+        names: Vec<String>,
+        /// Remove the practices in this context too (see `prac context`).
+        #[arg(long)]
+        context: Option<String>,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Merge one practice into another, e.g. after adding the same practice twice.
+    ///
+    /// Cumulative time and log histories are combined, notes are concatenated, and the earlier
+    /// creation date is kept. The `into` practice keeps its name and period; `from` is removed.
+    Merge {
+        /// Practice to merge away.
+        #[arg(required_unless_present = "interactive")]
+        from: Option<String>,
+        /// Practice to merge into.
+        #[arg(required_unless_present = "interactive")]
+        into: Option<String>,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+    /// Merge another statefile into this one, e.g. one kept on another machine.
+    ///
+    /// Practices are matched by name, and those only in the other statefile are added. Sessions
+    /// are combined (a session logged at the same moment in both is kept once), and the larger
+    /// cumulative time is kept. You are asked which notes and period to keep where they differ.
+    MergeState {
+        /// Statefile to merge in. It is left unchanged.
+        other: PathBuf,
+    },
+    /// Share your practices between machines through a git remote.
+    ///
+    /// The state is committed to a git repository kept beside the statefile, then pushed to and
+    /// pulled from the `sync_remote` config, e.g. `prac config set sync_remote
+    /// git@example.com:me/practices.git`. If both machines changed the state since they last
+    /// synced, the changes are merged: sessions logged on either are kept, and for notes edited on
+    /// both, the latest edit wins.
+    Sync,
+    /// Encrypt the statefile with a passphrase, from now on.
+    ///
+    /// The passphrase is asked for whenever prac reads the statefile, unless it is in the
+    /// `PRAC_PASSPHRASE` environment variable. `prac prompt` and `prac status` show nothing for an
+    /// encrypted statefile without it. Backups made before encrypting are encrypted too, and the
+    /// audit log is removed, but commits to the `prac sync` repository stay as they were.
+    Encrypt,
+    /// Store the statefile as plain JSON again.
+    Decrypt,
+    /// Check the statefile for problems, e.g. after editing it by hand.
+    ///
+    /// Looks for unknown fields, negative durations, times in the future, sessions out of order,
+    /// duplicate-looking names, and version mismatches. `--fix` applies the repairs that are safe to
+    /// make automatically; the rest are left to you.
+    Doctor {
+        /// Apply safe repairs.
+        #[arg(long, default_value = "false")]
+        fix: bool,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// Import habits and their history from another habit tracker, or time from a time tracker.
+    ///
+    /// Reads Loop Habit Tracker's CSV export (unzipped), Habitica's user data JSON, or Streaks' CSV
+    /// export, adding a practice per habit with a session logged for each day it was done. Habits
+    /// that already exist as practices are skipped. See `prac import toggl` for time trackers.
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Import {
+        #[command(subcommand)]
+        source: Option<ImportSource>,
+        /// The export: a directory for Loop, a .json file for Habitica, a .csv file for Streaks.
+        #[arg(required = true)]
+        path: Option<PathBuf>,
+        /// Format of the export, if it can't be told from the path.
+        #[arg(short, long, value_enum)]
+        format: Option<ImportFormat>,
+        /// Time to log per completion, e.g. `15m`, as habit trackers record that, not how long.
+        #[arg(short, long, value_name = "SPAN", value_parser = parse_time_span, default_value = "0s")]
+        time: Duration,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// Serve your practices as JSON over localhost HTTP, for widgets and launcher extensions.
+    ///
+    /// Read-only: GET /practices (as `prac list`, add `?archived=true` for archived ones too),
+    /// /practices/<name>/history (as `prac history`), and /stats for totals. Only listens on
+    /// 127.0.0.1, and only answers requests addressed to 127.0.0.1 or localhost.
+    Serve {
+        /// Port to listen on.
+        #[arg(short, long, default_value_t = 7700)]
+        port: u16,
+        /// Serve an encrypted statefile, decrypted, to any program on this machine that asks.
+        #[arg(long)]
+        decrypted: bool,
+    },
+    /// Print the manual page of prac or of one of its subcommands, or install all of them.
+    ///
+    /// e.g. `prac man backup restore | man -l -`, or, for packagers,
+    /// `prac man --install /usr/share/man/man1`.
+    Man {
+        /// The subcommand to show the page of, e.g. `backup restore`. Prac's own if not given.
+        subcommand: Vec<String>,
+        /// Write the pages of prac and every subcommand into this directory instead.
+        #[arg(long, value_name = "DIR", conflicts_with = "subcommand")]
+        install: Option<PathBuf>,
+    },
+    /// Show how to write time spans and points in time, with examples.
+    #[command(long_about = super::time::syntax_reference())]
+    Syntax,
+    /// Run commands read from stdin as one transaction: all of them are saved together, or, if
+    /// any fails, none are.
+    ///
+    /// Commands are given without `prac`, one per line (blank lines and lines starting with `#`
+    /// are skipped), or as a JSON array of command lines or of argument arrays, e.g.
+    /// `["log run 30m", ["add", "deep work", "1d"]]`. Hooks and webhooks don't run for batched
+    /// commands.
+    Batch,
+    /// Export every practice and its history for other tools.
+    ///
+    /// Unlike the statefile, whose layout changes as prac does, the export has a documented
+    /// format, versioned by its `version` field.
+    Export {
+        #[command(subcommand)]
+        format: ExportFormat,
+    },
+    /// Save your practices as a named template, or add the practices of one.
+    ///
+    /// Templates hold how practices are set up (periods, reasons, goals, checklists) but no
+    /// logged time, and are kept as TOML files in a `templates` directory beside the statefile.
+    /// Copy one to a new machine and add its practices with `prac add --from-file`.
+    Template {
+        #[command(subcommand)]
+        action: TemplateAction,
+    },
+    /// List or restore the backups of the statefile taken before it changes.
+    ///
+    /// The latest `backup_count` backups are kept (10 by default), and the last of each of the
+    /// last 7 days and 4 weeks. They're in the `backups` directory beside the statefile.
+    Backup {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+    /// List or restore removed practices.
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
     /// Rename a practice.
+    ///
+    /// Should a practice of the new name already exist, you are asked whether to merge the two
+    /// instead, as `prac merge` does. `--merge` merges without asking.
     Rename {
         /// Current (old) name of practice.
         #[arg(required_unless_present = "interactive")]
@@ -125,21 +1012,390 @@ pub enum SubCommand {
         /// New name of practice.
         #[arg(required_unless_present = "interactive")]
         new_name: Option<String>,
+        /// If a practice of the new name exists, merge this practice into it.
+        #[arg(short, long, default_value = "false")]
+        merge: bool,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
         /// Interactive
         #[arg(short, long, default_value = "false")]
         interactive: bool,
     },
-    /// Edit configuration. `help config` for info on fields.
+    /// Edit configuration, or without arguments, print it. `help config` for info on fields.
     #[command(after_long_help = "\
         Grace period pads the end of the bars of `prac list` with some extra time to give you a little \
         flexibility and prevent tasks from creeping earlier on each iteration.\n\n\
+        Bar width, fill, and empty characters change how bars are drawn. If your terminal or font \
+        renders the default bar poorly, try `--ascii true`.\n\n\
+        The flags below are shortcuts for a few fields. Every field can be read and written by name \
+        with `prac config get [key]`, `prac config set <key> <value>`, and `prac config unset <key>`, \
+        which restores its default. `prac config get` lists all keys.\n\n\
         ")]
+    #[command(args_conflicts_with_subcommands = true)]
     Config {
-        /// Grace period
-        #[arg(short, long, value_parser = parse_time_span, required_unless_present = "interactive")]
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+        /// Grace period added to every period, as systemd.time-like time span, e.g. `12h`.
+        #[arg(short, long, value_name = "SPAN", value_parser = parse_time_span)]
         grace_period: Option<Duration>,
+        /// Maximum width of bars in `prac list`, 0 to fill the terminal.
+        #[arg(long)]
+        bar_width: Option<usize>,
+        /// Character for the elapsed part of bars.
+        #[arg(long)]
+        bar_fill: Option<char>,
+        /// Character for the remaining part of bars.
+        #[arg(long)]
+        bar_empty: Option<char>,
+        /// Draw bars with ASCII characters only.
+        #[arg(long)]
+        ascii: Option<bool>,
+        /// Periods a practice may go without ever being logged before `prac review` suggests archiving it.
+        #[arg(long)]
+        prune_after_periods: Option<u32>,
+        /// Interactive
+        #[arg(short, long, default_value = "false")]
+        interactive: bool,
+    },
+}
+
+impl SubCommand {
+    /// Whether the command changes the state, and so can't be run on a read-only statefile. It
+    /// fails before prompting for anything or opening an editor. Commands whose arguments decide,
+    /// like `prac notes`, count only in their forms that change it.
+    pub const fn changes_state(&self) -> bool {
+        matches!(
+            self,
+            Self::Add { .. }
+                | Self::Session { .. }
+                | Self::Block { .. }
+                | Self::Start { .. }
+                | Self::Stop { .. }
+                | Self::Cancel { .. }
+                | Self::Log { .. }
+                | Self::Touch { .. }
+                | Self::Skip { .. }
+                | Self::Notes {
+                    history: false,
+                    show: false,
+                    ..
+                }
+                | Self::Why { .. }
+                | Self::Goal { .. }
+                | Self::Budget { .. }
+                | Self::EditSession { .. }
+                | Self::BestTime { .. }
+                | Self::Context {
+                    context: Some(_),
+                    ..
+                }
+                | Self::Context { clear: true, .. }
+                | Self::Context {
+                    interactive: true,
+                    ..
+                }
+                | Self::Archive { .. }
+                | Self::Hide { .. }
+                | Self::Alias { alias: Some(_), .. }
+                | Self::SetState { .. }
+                | Self::Depend { on: Some(_), .. }
+                | Self::Reset
+                | Self::EditPeriod { .. }
+                | Self::Check { item: Some(_), .. }
+                | Self::Check {
+                    interactive: true,
+                    ..
+                }
+                | Self::EditMode { .. }
+                | Self::Remove { .. }
+                | Self::Merge { .. }
+                | Self::MergeState { .. }
+                | Self::Sync
+                | Self::Encrypt
+                | Self::Decrypt
+                | Self::Import { .. }
+                | Self::Batch
+                | Self::Rename { .. }
+                | Self::Doctor { fix: true, .. }
+                | Self::Meta {
+                    action: MetaAction::Set { .. } | MetaAction::Unset { .. },
+                }
+                | Self::History {
+                    action: Some(HistoryAction::Edit { .. } | HistoryAction::Delete { .. }),
+                    ..
+                }
+                | Self::Template {
+                    action: TemplateAction::Apply { .. },
+                }
+                | Self::Backup {
+                    action: BackupAction::Restore { .. },
+                }
+                | Self::Trash {
+                    action: TrashAction::Restore { .. },
+                }
+                | Self::Config {
+                    action: Some(ConfigAction::Set { .. } | ConfigAction::Unset { .. }),
+                    ..
+                }
+                | Self::Config {
+                    interactive: true,
+                    ..
+                }
+        )
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Print the value of a config key, or all of them.
+    Get {
+        /// Config key, e.g. `grace_period`.
+        key: Option<String>,
+    },
+    /// Set a config key, e.g. `prac config set default_period 1w`.
+    Set {
+        /// Config key, e.g. `grace_period`.
+        key: String,
+        /// New value, parsed according to the key.
+        value: String,
+    },
+    /// Restore a config key to its default, e.g. `prac config unset bar_fill`.
+    Unset {
+        /// Config key, e.g. `grace_period`.
+        key: String,
+    },
+    /// Show current configuration.
+    Show {
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ImportSource {
+    /// Log time from a Toggl Track or Clockify detailed CSV export.
+    ///
+    /// Each time entry is logged as a session of the practice its project maps to, or else of the
+    /// practice named like the project. Entries already imported are skipped, so the export can be
+    /// imported again as it grows.
+    #[command(visible_alias = "clockify")]
+    Toggl {
+        csv: PathBuf,
+        /// Log a project's entries to a practice, as "Project=practice". Can be repeated.
+        #[arg(short, long = "map", value_name = "PROJECT=PRACTICE", value_parser = parse_mapping)]
+        map: Vec<(String, String)>,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+}
+
+/// Parse a "Project=practice" mapping.
+fn parse_mapping(mapping: &str) -> Result<(String, String), String> {
+    let (project, practice) = mapping
+        .split_once('=')
+        .ok_or_else(|| format!("expected PROJECT=PRACTICE, got \"{mapping}\""))?;
+    let (project, practice) = (project.trim(), practice.trim());
+    if practice.is_empty() {
+        return Err(format!("no practice given for \"{project}\""));
+    }
+    Ok((project.to_owned(), practice.to_owned()))
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ExportFormat {
+    /// Export as JSON.
+    Json {
+        /// Print the JSON Schema of the export instead.
+        #[arg(long, default_value = "false")]
+        schema: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TemplateAction {
+    /// Save the unarchived practices as a template.
+    Save {
+        name: String,
+        /// Replace an existing template of the same name.
+        #[arg(short, long, default_value = "false")]
+        force: bool,
+    },
+    /// Add the practices of a template, skipping those that already exist.
+    Apply { name: String },
+    /// List saved templates.
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BackupAction {
+    /// Show the backups, latest first.
+    List {
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// Replace the state with a backup. The state replaced is backed up in turn.
+    Restore {
+        /// Number of the backup, as shown by `prac backup list`, or its id.
+        id: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MetaAction {
+    /// Show the fields of a practice, or the value of one.
+    Get {
+        name: String,
+        key: Option<String>,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// Set a field of a practice, replacing its value if it has one.
+    Set {
+        name: String,
+        key: String,
+        value: String,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// Remove a field of a practice.
+    Unset {
+        name: String,
+        key: String,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TrashAction {
+    /// Show removed practices and when they will be purged.
+    List {
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// Bring back a removed practice, with its history and notes.
+    Restore {
+        /// Name of the removed practice, or leave blank to fuzzy search.
+        #[arg(required_unless_present = "interactive")]
+        name: Option<String>,
+        /// Restore under a new name, e.g. if the old one has been reused.
+        #[arg(long = "as")]
+        new_name: Option<String>,
+        /// Output format of the result: the practice before and after, e.g. its period,
+        /// cumulative time, and streak. Nothing is printed for `text`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
         /// Interactive
         #[arg(short, long, default_value = "false")]
         interactive: bool,
     },
 }
+
+#[derive(Subcommand, Debug)]
+pub enum HistoryAction {
+    /// Correct a logged session, e.g. its time, or move it to the practice it really was.
+    ///
+    /// `prac history edit steno 3 --time 30m` corrects the third session of steno to 30 minutes,
+    /// and the session can also be picked by when it was, e.g. `"2024-03-01 18:00"`. `prac history
+    /// edit steno 3 --to exercise` reassigns the whole session to exercise, while adding `--split
+    /// 30m` moves only 30 minutes of it. Cumulative time follows.
+    #[command(group(clap::ArgGroup::new("change").required(true).multiple(true)))]
+    Edit {
+        /// Practice the session is currently logged to.
+        name: String,
+        /// Number of the session, as shown by `prac history`, or when it was, to the minute.
+        entry: String,
+        /// Time practiced, as systemd.time-like time span, e.g. `1h30m`.
+        #[arg(long, value_name = "SPAN", value_parser = parse_time_span, group = "change", conflicts_with = "to")]
+        time: Option<Duration>,
+        /// When the session was, e.g. "yesterday 18:00".
+        #[arg(long, value_parser = parse_time_point, group = "change", conflicts_with = "to")]
+        at: Option<DateTime<Utc>>,
+        /// Note on the session, empty to remove it.
+        #[arg(long, group = "change", conflicts_with = "to")]
+        note: Option<String>,
+        /// Practice to move the session to.
+        #[arg(long, group = "change")]
+        to: Option<String>,
+        /// Only move this much of the session, e.g. `30m`, splitting it between both practices.
+        #[arg(long, value_name = "SPAN", value_parser = parse_time_span, requires = "to")]
+        split: Option<Duration>,
+    },
+    /// Delete a logged session, e.g. one logged by mistake. Its time comes off the cumulative time.
+    Delete {
+        /// Practice the session is logged to.
+        name: String,
+        /// Number of the session, as shown by `prac history`, or when it was, to the minute.
+        entry: String,
+    },
+}
+
+/// A single command of `prac batch`.
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+struct BatchCommand {
+    #[command(subcommand)]
+    command: SubCommand,
+}
+
+/// A command of a JSON batch, as a command line or its arguments.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BatchEntry {
+    Line(String),
+    Args(Vec<String>),
+}
+
+/// Parse the commands of `prac batch`, as lines or a JSON array. Commands that can't be part of a
+/// transaction are refused.
+pub fn parse_batch(input: &str) -> Result<Vec<SubCommand>> {
+    let commands = if input.trim_start().starts_with('[') {
+        serde_json::from_str::<Vec<BatchEntry>>(input)
+            .context(Error::Parse("expected a JSON array of commands".to_owned()))?
+            .into_iter()
+            .map(|entry| match entry {
+                BatchEntry::Line(line) => Ok(shell_words::split(&line)?),
+                BatchEntry::Args(args) => Ok(args),
+            })
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(shell_words::split)
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    commands
+        .into_iter()
+        .enumerate()
+        .map(|(index, args)| {
+            let command = BatchCommand::try_parse_from(&args)
+                .with_context(|| {
+                    Error::Parse(format!("command {} of the batch is invalid", index + 1))
+                })?
+                .command;
+            match command {
+                SubCommand::Batch
+                | SubCommand::Prompt { .. }
+                | SubCommand::Status { .. }
+                | SubCommand::Serve { .. }
+                | SubCommand::Sync => {
+                    bail!("command {} of the batch can't be batched", index + 1)
+                }
+                command => Ok(command),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,87 @@
+//! Where prac gets the current time from. The application layer asks the state's [`Clock`] rather
+//! than the system, so that the time can be fixed, e.g. for tests, or to see how practices will
+//! stand at another time.
+//!
+//! Setting `PRAC_FAKE_NOW` to a point in time, like "2024-03-01" or "yesterday 18:00", runs
+//! prac as if it were then. Relative points are taken from the actual time. The clock keeps
+//! running from there, so sessions and blocks still last as long as they actually do.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local, Utc};
+
+use crate::error::Error;
+use crate::i18n::t;
+
+/// Environment variable fixing the time prac runs at.
+const FAKE_NOW_VAR: &str = "PRAC_FAKE_NOW";
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system's clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock stopped at a point in time.
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// The system's clock, set forward or back by an offset.
+pub struct ShiftedClock(pub Duration);
+
+impl Clock for ShiftedClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now() + self.0
+    }
+}
+
+/// A clock shared by the state and whatever it hands it to; the system's by default.
+#[derive(Clone)]
+pub struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        Self(Arc::new(clock))
+    }
+
+    /// The system's clock, or, if `PRAC_FAKE_NOW` is set, one shifted to start at it.
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(FAKE_NOW_VAR) {
+            Ok(value) if !value.trim().is_empty() => {
+                let actual = Local::now();
+                let now = DateTime::parse_from_rfc3339(value.trim())
+                    .map(Into::into)
+                    .or_else(|_| crate::time::parse_time_point_from(&value, &actual))
+                    .with_context(|| {
+                        Error::Parse(t!("fake-now-invalid", var = FAKE_NOW_VAR, value = value))
+                    })?;
+                Ok(Self::new(ShiftedClock(now - actual.with_timezone(&Utc))))
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.0.now()
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
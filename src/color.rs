@@ -0,0 +1,54 @@
+use std::io::IsTerminal;
+
+use owo_colors::{OwoColorize, Style};
+use serde::{Deserialize, Serialize};
+
+/// When to colorize output. See [`ColorChoice::enabled`] for how `auto` is resolved.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorChoice {
+    /// Color only if stdout is a terminal and `NO_COLOR` is unset.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Resolve the choice against the environment. Honors <https://no-color.org>, and never colors
+    /// piped output (e.g. prompt integrations) or legacy Windows consoles unless explicitly asked
+    /// to.
+    pub fn enabled(self) -> bool {
+        match self {
+            Self::Always => {
+                // Windows consoles take colors only once asked to.
+                crate::utils::ansi_terminal();
+                true
+            }
+            Self::Never => false,
+            Self::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && std::io::stdout().is_terminal()
+                    && crate::utils::ansi_terminal()
+            }
+        }
+    }
+}
+
+/// Apply `style` to `text` only if color is enabled.
+pub fn paint(text: &str, style: Style, color: bool) -> String {
+    if color {
+        text.style(style).to_string()
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Color for a bar at the given fraction of its period, fading from green through yellow to red.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn gradient(fraction: f64) -> Style {
+    let fraction = fraction.clamp(0.0, 1.0);
+    let red = (510.0 * fraction).min(255.0) as u8;
+    let green = (510.0 * (1.0 - fraction)).min(255.0) as u8;
+    Style::new().truecolor(red, green, 0)
+}
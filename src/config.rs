@@ -0,0 +1,361 @@
+//! Typed registry of [`UserConfig`] fields, read and written by name through
+//! `prac config get/set/unset`. Adding a field to the config only takes an entry in [`KEYS`].
+
+use anyhow::{anyhow, bail, ensure, Result};
+use chrono::{Duration, Weekday};
+
+use crate::application::{AdaptivePeriods, ListSort, UserConfig};
+use crate::color::ColorChoice;
+use crate::error::Error;
+use crate::storage::StateFormat;
+use crate::time::SpanStyle;
+
+/// A type that can be stored in a config field.
+pub trait ConfigValue: Sized {
+    /// Human readable form of the value.
+    fn show(&self) -> String;
+    /// Parse a value as typed on the command line.
+    fn parse(value: &str) -> Result<Self>;
+}
+
+impl ConfigValue for Duration {
+    fn show(&self) -> String {
+        crate::time::Span::from(*self).format()
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        crate::time::parse_time_span(value)
+    }
+}
+
+impl ConfigValue for bool {
+    fn show(&self) -> String {
+        self.to_string()
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        value.parse().map_err(|_| anyhow!("expected true or false"))
+    }
+}
+
+impl ConfigValue for u32 {
+    fn show(&self) -> String {
+        self.to_string()
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        Ok(value.parse()?)
+    }
+}
+
+impl ConfigValue for usize {
+    fn show(&self) -> String {
+        self.to_string()
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        Ok(value.parse()?)
+    }
+}
+
+impl ConfigValue for f64 {
+    fn show(&self) -> String {
+        self.to_string()
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        let value = value.parse::<Self>()?;
+        ensure!(
+            value.is_finite() && value > 0.0,
+            "expected a positive number"
+        );
+        Ok(value)
+    }
+}
+
+impl ConfigValue for char {
+    fn show(&self) -> String {
+        // Quoted, so whitespace is visible.
+        format!("{self:?}")
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => bail!("expected a single character"),
+        }
+    }
+}
+
+impl ConfigValue for String {
+    fn show(&self) -> String {
+        self.clone()
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        ensure!(!value.is_empty(), "value can't be empty");
+        Ok(value.to_owned())
+    }
+}
+
+impl ConfigValue for Weekday {
+    fn show(&self) -> String {
+        self.to_string()
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        value
+            .parse()
+            .map_err(|_| anyhow!("expected a weekday, e.g. mon"))
+    }
+}
+
+impl ConfigValue for ListSort {
+    fn show(&self) -> String {
+        value_name(self)
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        parse_value(value)
+    }
+}
+
+impl ConfigValue for AdaptivePeriods {
+    fn show(&self) -> String {
+        value_name(self)
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        parse_value(value)
+    }
+}
+
+impl ConfigValue for SpanStyle {
+    fn show(&self) -> String {
+        value_name(self)
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        parse_value(value)
+    }
+}
+
+impl ConfigValue for StateFormat {
+    fn show(&self) -> String {
+        value_name(self)
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        parse_value(value)
+    }
+}
+
+impl ConfigValue for ColorChoice {
+    fn show(&self) -> String {
+        value_name(self)
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        parse_value(value)
+    }
+}
+
+/// Lists are set all at once, separated by whitespace or commas.
+impl ConfigValue for Vec<String> {
+    fn show(&self) -> String {
+        if self.is_empty() {
+            "none".to_owned()
+        } else {
+            self.join(" ")
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        Ok(value
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|item| !item.is_empty())
+            .map(ToOwned::to_owned)
+            .collect())
+    }
+}
+
+/// Optional fields are set by giving a value, and cleared with `prac config unset`.
+impl<T: ConfigValue> ConfigValue for Option<T> {
+    fn show(&self) -> String {
+        self.as_ref()
+            .map_or_else(|| "unset".to_owned(), ConfigValue::show)
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        T::parse(value).map(Some)
+    }
+}
+
+/// Name of a [`clap::ValueEnum`] variant, as typed on the command line.
+fn value_name(value: &impl clap::ValueEnum) -> String {
+    value
+        .to_possible_value()
+        .map_or_else(String::new, |value| value.get_name().to_owned())
+}
+
+fn parse_value<T: clap::ValueEnum>(value: &str) -> Result<T> {
+    T::from_str(value, true).map_err(|_| {
+        let expected = T::value_variants()
+            .iter()
+            .map(|variant| value_name(variant))
+            .collect::<Vec<_>>();
+        anyhow!("expected one of: {}", expected.join(", "))
+    })
+}
+
+/// A field of [`UserConfig`], by name.
+pub struct ConfigKey {
+    pub name: &'static str,
+    /// Short explanation, used as the prompt of `prac config -i`.
+    pub description: &'static str,
+    get: fn(&UserConfig) -> String,
+    set: fn(&mut UserConfig, &str) -> Result<()>,
+    unset: fn(&mut UserConfig),
+}
+
+/// Registry entry for the [`UserConfig`] field of the same name, whose type implements
+/// [`ConfigValue`].
+macro_rules! key {
+    ($field:ident, $description:literal) => {
+        ConfigKey {
+            name: stringify!($field),
+            description: $description,
+            get: |config| config.$field.show(),
+            set: |config, value| {
+                config.$field = ConfigValue::parse(value.trim())?;
+                Ok(())
+            },
+            unset: |config| config.$field = UserConfig::default().$field,
+        }
+    };
+}
+
+/// Every config key, in the order `prac config get` lists them.
+pub const KEYS: &[ConfigKey] = &[
+    key!(grace_period, "Grace period added to every period"),
+    key!(default_period, "Period for `prac add` when none is given"),
+    key!(editor, "Editor for notes, if $VISUAL and $EDITOR are unset"),
+    key!(
+        exact_names,
+        "Only accept practice names with the same case and Unicode normalization as stored"
+    ),
+    key!(
+        list_sort,
+        "Order of `prac list` (name, progress, period, cumulative)"
+    ),
+    key!(
+        list_cumulative,
+        "Always show cumulative time in `prac list`"
+    ),
+    key!(list_period, "Always show periods in `prac list`"),
+    key!(list_danger, "Always show the danger bar in `prac list`"),
+    key!(
+        list_sparkline,
+        "Always show how the last periods went in `prac list`"
+    ),
+    key!(list_budget, "Always show budget bars in `prac list`"),
+    key!(
+        list_verbose,
+        "Always show when practices were last logged and are due in `prac list`"
+    ),
+    key!(
+        list_name_width,
+        "Longest name `prac list` shows in full (0 for the terminal's width)"
+    ),
+    key!(color, "When to use color (auto, always, never)"),
+    key!(week_start, "Day weekly goals start over on"),
+    key!(touch_time, "Time `prac touch` logs"),
+    key!(
+        max_session,
+        "Longest plausible session `prac stop` logs without asking (0 for no limit)"
+    ),
+    key!(
+        span_style,
+        "How durations are written out (short, compact, long)"
+    ),
+    key!(
+        locale,
+        "Language for messages, e.g. de or pt-BR, unset to follow $LANG"
+    ),
+    key!(
+        bar_width,
+        "Maximum bar width, 0 or unset to fill the terminal"
+    ),
+    key!(bar_fill, "Character for the elapsed part of bars"),
+    key!(bar_empty, "Character for the remaining part of bars"),
+    key!(
+        bar_grace,
+        "Character for the elapsed part of bars within the grace period"
+    ),
+    key!(ascii, "Draw bars with ASCII characters only"),
+    key!(sync_remote, "Git remote for `prac sync`"),
+    key!(
+        trash_retention,
+        "How long removed practices are kept in the trash"
+    ),
+    key!(
+        audit_log,
+        "Append every change to an audit log beside the statefile"
+    ),
+    key!(
+        state_format,
+        "How the statefile is written unless its extension says (json, toml)"
+    ),
+    key!(state_compact, "Write the statefile without pretty printing"),
+    key!(state_compress, "Compress the statefile with zstd"),
+    key!(
+        backup_count,
+        "Backups kept from before changes, besides daily and weekly ones (0 for none)"
+    ),
+    key!(
+        prune_after_periods,
+        "Periods before `prac review` suggests archiving never-logged practices"
+    ),
+    key!(
+        adaptive_periods,
+        "Adapt the periods of practices in maintenance to how they go (off, suggest, auto)"
+    ),
+    key!(
+        adaptive_factor,
+        "Factor adaptive periods lengthen or shorten periods by"
+    ),
+    key!(
+        adaptive_streak,
+        "Periods on time in a row before adaptive periods lengthen a period"
+    ),
+    key!(webhooks, "URLs to post to when a practice is logged"),
+];
+
+impl ConfigKey {
+    /// Look up a key by name.
+    pub fn find(name: &str) -> Result<&'static Self> {
+        KEYS.iter().find(|key| key.name == name).ok_or_else(|| {
+            let names = KEYS.iter().map(|key| key.name).collect::<Vec<_>>();
+            anyhow!(Error::NotFound(format!(
+                "unknown config key \"{name}\", expected one of: {}",
+                names.join(", ")
+            )))
+        })
+    }
+
+    /// Human readable value of this field.
+    pub fn get(&self, config: &UserConfig) -> String {
+        (self.get)(config)
+    }
+
+    /// Parse `value` and set this field to it.
+    pub fn set(&self, config: &mut UserConfig, value: &str) -> Result<()> {
+        (self.set)(config, value)
+    }
+
+    /// Reset this field to its default.
+    pub fn unset(&self, config: &mut UserConfig) {
+        (self.unset)(config);
+    }
+}
@@ -0,0 +1,207 @@
+//! Optional at-rest encryption of the statefile, turned on with `prac encrypt`.
+//!
+//! An encrypted statefile is [`MAGIC`], the scrypt cost, a salt, and a nonce, followed by the
+//! AES-256-GCM encrypted JSON. The key is derived from a passphrase, taken from `PRAC_PASSPHRASE`
+//! or asked for.
+
+use std::io::IsTerminal;
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail, ensure, Result};
+
+use crate::i18n::t;
+
+/// Start of every encrypted statefile, which can't be mistaken for JSON.
+const MAGIC: &[u8] = b"prac-encrypted-v1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+/// Base 2 logarithm of the scrypt cost for new keys, chosen to take well under a second.
+const LOG_N: u8 = 15;
+/// Highest scrypt cost a statefile may ask for, a little over [`LOG_N`], so that a crafted one
+/// can't demand hours of work and gigabytes of memory to open.
+const MAX_LOG_N: u8 = LOG_N + 3;
+const HEADER_LEN: usize = MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+
+/// Environment variable a passphrase is read from before asking for one.
+const PASSPHRASE_VAR: &str = "PRAC_PASSPHRASE";
+
+/// A key derived from a passphrase. The passphrase is kept to open files with other salts, e.g.
+/// the same statefile encrypted on another machine.
+#[derive(Clone)]
+pub struct Key {
+    passphrase: String,
+    log_n: u8,
+    salt: [u8; SALT_LEN],
+    derived: [u8; 32],
+}
+
+impl Key {
+    /// Derive a key from `passphrase` with a fresh salt.
+    pub fn new(passphrase: String) -> Result<Self> {
+        let mut salt = [0; SALT_LEN];
+        getrandom::getrandom(&mut salt)
+            .map_err(|e| anyhow!(t!("salt-failed", error = e.to_string())))?;
+        Self::derive(passphrase, LOG_N, salt)
+    }
+
+    /// A key that is quick to derive, and so to guess, for tests elsewhere.
+    #[cfg(test)]
+    pub fn weak(passphrase: &str) -> Self {
+        Self::derive(passphrase.to_owned(), 4, [0; SALT_LEN]).unwrap()
+    }
+
+    fn derive(passphrase: String, log_n: u8, salt: [u8; SALT_LEN]) -> Result<Self> {
+        let params = scrypt::Params::new(log_n, 8, 1, 32)
+            .map_err(|_| anyhow!(t!("key-parameters-invalid")))?;
+        let mut derived = [0; 32];
+        scrypt::scrypt(passphrase.as_bytes(), &salt, &params, &mut derived)
+            .map_err(|_| anyhow!(t!("key-derivation-failed")))?;
+        Ok(Self {
+            passphrase,
+            log_n,
+            salt,
+            derived,
+        })
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(&self.derived.into())
+    }
+
+    /// Encrypt `plaintext` into the format of an encrypted statefile.
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0; NONCE_LEN];
+        getrandom::getrandom(&mut nonce)
+            .map_err(|e| anyhow!(t!("nonce-failed", error = e.to_string())))?;
+        let ciphertext = self
+            .cipher()
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| anyhow!(t!("encrypt-state-failed")))?;
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(self.log_n);
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(&nonce);
+        bytes.extend_from_slice(&ciphertext);
+        Ok(bytes)
+    }
+}
+
+/// Whether `bytes` are an encrypted statefile.
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// The passphrase from `PRAC_PASSPHRASE`, if set.
+pub fn env_passphrase() -> Option<String> {
+    std::env::var(PASSPHRASE_VAR).ok()
+}
+
+/// The passphrase from `PRAC_PASSPHRASE`, or else asked for, twice if `confirm`.
+pub fn passphrase(confirm: bool) -> Result<String> {
+    if let Some(passphrase) = env_passphrase() {
+        return Ok(passphrase);
+    }
+    ensure!(
+        std::io::stdin().is_terminal(),
+        t!("passphrase-not-interactive", var = PASSPHRASE_VAR)
+    );
+    let mut prompt = dialoguer::Password::new();
+    prompt.with_prompt(t!("passphrase-prompt"));
+    if confirm {
+        prompt.with_confirmation(t!("passphrase-repeat"), t!("passphrases-differ"));
+    }
+    Ok(prompt.interact()?)
+}
+
+/// Decrypt an encrypted statefile, returning the plaintext and the key that opened it. `key` is
+/// reused if it was derived for this file, and otherwise its passphrase is; without one, the
+/// passphrase is asked for.
+fn decrypt(bytes: &[u8], key: Option<&Key>) -> Result<(Vec<u8>, Key)> {
+    ensure!(
+        is_encrypted(bytes) && bytes.len() >= HEADER_LEN,
+        t!("not-encrypted-statefile")
+    );
+    let header = &bytes[MAGIC.len()..HEADER_LEN];
+    let log_n = header[0];
+    ensure!(log_n <= MAX_LOG_N, t!("key-cost-too-high", log_n = log_n));
+    let salt: [u8; SALT_LEN] = header[1..=SALT_LEN].try_into()?;
+    let nonce = &header[1 + SALT_LEN..];
+
+    let key = match key {
+        Some(key) if key.log_n == log_n && key.salt == salt => key.clone(),
+        Some(key) => Key::derive(key.passphrase.clone(), log_n, salt)?,
+        None => Key::derive(passphrase(false)?, log_n, salt)?,
+    };
+    let Ok(plaintext) = key
+        .cipher()
+        .decrypt(Nonce::from_slice(nonce), &bytes[HEADER_LEN..])
+    else {
+        bail!(t!("decrypt-failed"))
+    };
+    Ok((plaintext, key))
+}
+
+/// The JSON of a statefile, decrypted if it is encrypted, along with the key that opened it.
+pub fn open(bytes: Vec<u8>, key: Option<&Key>) -> Result<(Vec<u8>, Option<Key>)> {
+    if !is_encrypted(&bytes) {
+        return Ok((bytes, None));
+    }
+    let (plaintext, key) = decrypt(&bytes, key)?;
+    Ok((plaintext, Some(key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open, Key, MAGIC, MAX_LOG_N, SALT_LEN};
+
+    /// A cheap cost, to keep the tests fast.
+    const TEST_LOG_N: u8 = 4;
+
+    fn key(passphrase: &str, salt: u8) -> Key {
+        Key::derive(passphrase.to_owned(), TEST_LOG_N, [salt; SALT_LEN]).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let plaintext = br#"{"practices": {}}"#;
+        let encrypted = key("correct horse", 1).encrypt(plaintext).unwrap();
+        assert!(encrypted.starts_with(MAGIC));
+        assert!(!encrypted
+            .windows(plaintext.len())
+            .any(|window| window == plaintext));
+
+        let (decrypted, opened_with) =
+            open(encrypted.clone(), Some(&key("correct horse", 1))).unwrap();
+        assert_eq!(decrypted, plaintext);
+        assert!(opened_with.is_some());
+        // The passphrase of a key with another salt opens it too.
+        let (decrypted, _) = open(encrypted, Some(&key("correct horse", 2))).unwrap();
+        assert_eq!(decrypted, plaintext);
+
+        let (plain, opened_with) = open(plaintext.to_vec(), None).unwrap();
+        assert_eq!(plain, plaintext);
+        assert!(opened_with.is_none());
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let encrypted = key("correct horse", 1).encrypt(b"{}").unwrap();
+        let error = open(encrypted, Some(&key("battery staple", 1)))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("wrong passphrase"), "{error}");
+    }
+
+    #[test]
+    fn test_cost_capped() {
+        let mut encrypted = key("correct horse", 1).encrypt(b"{}").unwrap();
+        encrypted[MAGIC.len()] = MAX_LOG_N + 1;
+        let error = open(encrypted, Some(&key("correct horse", 1)))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("key derivation cost"), "{error}");
+    }
+}
@@ -0,0 +1,74 @@
+//! Kinds of failure that scripts can tell apart by prac's exit code, rather than by its messages.
+//!
+//! Errors are still passed around as [`anyhow::Error`]; one of these, raised or attached as
+//! context anywhere in the chain, decides the [exit code](exit_code).
+
+use std::fmt::{Display, Formatter};
+
+/// Exit code for anything not covered by an [`Error`].
+pub const GENERIC: u8 = 1;
+/// Exit code for [`Error::NotFound`].
+pub const NOT_FOUND: u8 = 2;
+/// Exit code for [`Error::Parse`], and for invalid command line arguments.
+pub const PARSE: u8 = 3;
+/// Exit code for [`Error::Aborted`].
+pub const ABORTED: u8 = 4;
+/// Exit code for [`Error::StateCorrupt`].
+pub const STATE_CORRUPT: u8 = 5;
+/// Exit code for [`Error::Conflict`].
+pub const CONFLICT: u8 = 6;
+/// Exit code for [`Error::ReadOnly`].
+pub const READ_ONLY: u8 = 7;
+
+/// A failure with its own exit code, carrying the message to show.
+#[derive(Debug)]
+pub enum Error {
+    /// A practice, template, or other named thing doesn't exist.
+    NotFound(String),
+    /// Input couldn't be understood: time spans, times, config values, or batches.
+    Parse(String),
+    /// The user backed out of a confirmation or selection.
+    Aborted(String),
+    /// The statefile couldn't be read as a state.
+    StateCorrupt(String),
+    /// Something of that name already exists.
+    Conflict(String),
+    /// The statefile would be changed, but is read-only.
+    ReadOnly(String),
+}
+
+impl Error {
+    /// The process exit code for the error.
+    pub const fn exit_code(&self) -> u8 {
+        match self {
+            Self::NotFound(_) => NOT_FOUND,
+            Self::Parse(_) => PARSE,
+            Self::Aborted(_) => ABORTED,
+            Self::StateCorrupt(_) => STATE_CORRUPT,
+            Self::Conflict(_) => CONFLICT,
+            Self::ReadOnly(_) => READ_ONLY,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(message)
+            | Self::Parse(message)
+            | Self::Aborted(message)
+            | Self::StateCorrupt(message)
+            | Self::Conflict(message)
+            | Self::ReadOnly(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The exit code for `error`: that of the outermost [`Error`] in it, or [`GENERIC`].
+pub fn exit_code(error: &anyhow::Error) -> u8 {
+    error
+        .downcast_ref::<Error>()
+        .map_or(GENERIC, Error::exit_code)
+}
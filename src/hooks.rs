@@ -0,0 +1,67 @@
+//! Hook scripts run after transitions, e.g. `~/.config/prac/hooks/post-log`.
+//!
+//! A hook is an executable named `post-` and the transition's [name](StateTransition::name), in
+//! the `hooks` directory of prac's config directory. It gets the transition as JSON on stdin, and
+//! `PRAC_PATH` set to the statefile, which is saved before it runs, so it can call prac itself.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+
+use crate::application::StateTransition;
+use crate::i18n::t;
+
+/// Directory hooks are looked up in.
+fn dir() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("prac").join("hooks"))
+}
+
+/// The hook for `transition`, if there is one.
+pub fn find(transition: &StateTransition) -> Option<PathBuf> {
+    let path = dir()?.join(format!("post-{}", transition.name()));
+    path.is_file().then_some(path)
+}
+
+/// Run the hook at `hook` with `payload` on stdin. A failing hook is reported, but doesn't fail
+/// the command, as the transition has already been applied.
+pub fn run(hook: &Path, payload: &str, state_path: &Path) -> Result<()> {
+    let mut child = match Command::new(hook)
+        .env("PRAC_PATH", state_path)
+        .stdin(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(error) => {
+            let error = error.to_string();
+            eprintln!(
+                "{}",
+                t!(
+                    "hook-run-failed",
+                    hook = hook.display().to_string(),
+                    error = error
+                )
+            );
+            return Ok(());
+        }
+    };
+    // A hook may exit without reading its input, which isn't an error.
+    let _ = child
+        .stdin
+        .take()
+        .context(t!("hook-no-stdin"))?
+        .write_all(payload.as_bytes());
+    let status = child.wait().context(t!("hook-wait-failed"))?;
+    if !status.success() {
+        eprintln!(
+            "{}",
+            t!(
+                "hook-failed",
+                hook = hook.display().to_string(),
+                status = status.to_string()
+            )
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,184 @@
+//! Translatable messages. Everything prac says is looked up by id in a
+//! [Fluent](https://projectfluent.org) catalog with [`t!`], the English one being built in from
+//! `i18n/en-US.ftl`.
+//!
+//! The language is the `locale` config if set, else the first of `LC_ALL`, `LC_MESSAGES`, and
+//! `LANG`. A translation is a catalog named after the language in the `locales` directory of
+//! prac's config directory, e.g. `~/.config/prac/locales/de.ftl` (or `de-AT.ftl` for a region).
+//! Messages it leaves out are shown in English.
+
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+/// The built in catalog, which every message must be in.
+const ENGLISH: &str = include_str!("i18n/en-US.ftl");
+
+/// Catalogs to look messages up in, the preferred first and English last.
+struct Catalog {
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+static CATALOG: RwLock<Option<Catalog>> = RwLock::new(None);
+
+/// Look up `id` in a message `t!("id")`, or with arguments `t!("id", name = value, ...)`, where a
+/// value is a string or a number.
+macro_rules! t {
+    ($id:literal) => {
+        $crate::i18n::message($id, None)
+    };
+    ($id:literal, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::message($id, Some(&args))
+    }};
+}
+pub(crate) use t;
+
+/// Translate messages into `locale`, or the one the environment asks for if `None`. Until this
+/// is called, they are translated as the environment asks.
+pub fn set_locale(locale: Option<&str>) {
+    let catalog = Catalog::new(locale);
+    *CATALOG
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(catalog);
+}
+
+/// The message `id`, formatted with `args`. Used through [`t!`].
+pub fn message(id: &str, args: Option<&FluentArgs>) -> String {
+    if let Some(catalog) = CATALOG
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .as_ref()
+    {
+        return catalog.format(id, args);
+    }
+    set_locale(None);
+    message(id, args)
+}
+
+/// The language identifier in a locale like `de_AT.UTF-8@euro`, unless it is the C locale.
+fn language(locale: &str) -> Option<LanguageIdentifier> {
+    let name = locale.split(['.', '@']).next()?.replace('_', "-");
+    if name.is_empty() || name == "C" || name == "POSIX" {
+        return None;
+    }
+    name.parse().ok()
+}
+
+/// The language asked for by the environment.
+fn environment_language() -> Option<LanguageIdentifier> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| language(&value))
+}
+
+/// Directory translations are looked up in.
+fn dir() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("prac").join("locales"))
+}
+
+/// Read the translation for `language`, if there is one. A catalog with errors is still used, as
+/// far as it parses.
+fn translation(language: &LanguageIdentifier) -> Option<FluentResource> {
+    let path = dir()?.join(format!("{language}.ftl"));
+    let source = std::fs::read_to_string(&path).ok()?;
+    Some(
+        FluentResource::try_new(source).unwrap_or_else(|(resource, errors)| {
+            eprintln!(
+                "Skipped {} unreadable message(s) in {}.",
+                errors.len(),
+                path.display()
+            );
+            resource
+        }),
+    )
+}
+
+fn bundle(language: LanguageIdentifier, resource: FluentResource) -> FluentBundle<FluentResource> {
+    let mut bundle = FluentBundle::new_concurrent(vec![language]);
+    // Unicode isolation marks around arguments only garble terminals.
+    bundle.set_use_isolating(false);
+    // Catalogs hold each message once, so adding can't clash.
+    let _ = bundle.add_resource(resource);
+    bundle
+}
+
+impl Catalog {
+    fn new(locale: Option<&str>) -> Self {
+        let english: LanguageIdentifier = "en-US".parse().expect("valid language identifier");
+        let mut bundles = Vec::new();
+        if let Some(language) = locale.and_then(language).or_else(environment_language) {
+            // The regional catalog, then the language's own, e.g. `de-AT` and then `de`.
+            let general = LanguageIdentifier::from_parts(language.language, None, None, &[]);
+            for candidate in [language, general] {
+                if bundles
+                    .iter()
+                    .any(|bundle: &FluentBundle<_>| bundle.locales.first() == Some(&candidate))
+                {
+                    continue;
+                }
+                if let Some(resource) = translation(&candidate) {
+                    bundles.push(bundle(candidate, resource));
+                }
+            }
+        }
+        let resource = FluentResource::try_new(ENGLISH.to_owned())
+            .unwrap_or_else(|_| panic!("the built in catalog must parse"));
+        bundles.push(bundle(english, resource));
+        Self { bundles }
+    }
+
+    /// Format message `id` from the first catalog that has it.
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        for bundle in &self.bundles {
+            if let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) {
+                let mut errors = Vec::new();
+                return bundle
+                    .format_pattern(pattern, args, &mut errors)
+                    .into_owned();
+            }
+        }
+        // A missing message is a bug, but the id still says something.
+        id.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{language, Catalog};
+
+    #[test]
+    fn test_language() {
+        assert_eq!(language("de_AT.UTF-8").unwrap().to_string(), "de-AT");
+        assert_eq!(language("fr@euro").unwrap().to_string(), "fr");
+        assert!(language("C.UTF-8").is_none());
+        assert!(language("").is_none());
+    }
+
+    #[test]
+    fn test_catalog() {
+        use fluent_bundle::FluentArgs;
+
+        // Every message is in the built in catalog, which formats arguments without isolation
+        // marks and picks plurals.
+        let catalog = Catalog::new(Some("en-US"));
+        let mut args = FluentArgs::new();
+        args.set("count", 1);
+        assert_eq!(
+            catalog.format("added-practices", Some(&args)),
+            "Added 1 practice."
+        );
+        args.set("count", 3);
+        assert_eq!(
+            catalog.format("added-practices", Some(&args)),
+            "Added 3 practices."
+        );
+        assert_eq!(catalog.format("no-such-message", None), "no-such-message");
+    }
+}
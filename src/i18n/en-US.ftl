@@ -0,0 +1,614 @@
+# Messages prac shows, in English. A translation is a copy of this file with the messages it
+# translates; see src/i18n.rs for where prac looks for one.
+#
+# Arguments like { $name } are filled in by prac. Durations arrive already formatted.
+
+## Shared
+
+none = none
+aborted = aborted
+interrupt-handler-failed = could not set up handling Ctrl-C
+nothing-selected = No item selected
+practice-exists = Practice with name "{ $name }" already exists.
+practice-exists-like = Practice "{ $other }" already exists, differing from "{ $name }" only in case or Unicode normalization. Set the `exact_names` config to keep both.
+practice-not-found = "{ $name }" not found.
+practice-name-not-found = Practice with name "{ $name }" not found.
+practice-not-found-suggest = Practice with name "{ $name }" not found. Did you mean { $suggestions }?
+assuming-closest = No practice "{ $name }", taking it to mean "{ $closest }".
+no-practice-matches = No practice matches "{ $pattern }".
+no-practice-in-context = No practice is in context "{ $context }".
+bad-pattern = Invalid pattern "{ $pattern }": { $error }.
+bulk-prompt = Make these { $count } changes?
+read-failed = could not read { $path }
+write-failed = could not write { $path }
+create-failed = could not create { $path }
+remove-failed = could not remove { $path }
+state-path-no-parent = state path has no parent
+state-path-no-file-name = state path has no file name
+path-not-absolute = Path { $path } is not absolute
+no-practice-name = no practice name provided
+no-time = no time provided
+no-period = no period provided
+prompt-period = How often (not how long) would you like to practice "{ $name }?"
+period-in-minutes = A period of { $span }. Did you mean months (M) rather than minutes (m)?
+time-in-months = { $span } of practice. Did you mean minutes (m) rather than months (M)?
+keep-span = Keep { $span }?
+edit-field-prompt = { $msg } [currently { $current }, enter to keep]
+added-practices = Added { $count } { $count ->
+        [one] practice
+       *[other] practices
+    }.
+skipped-existing = Skipped existing: { $names }.
+serialize-transition-failed = failed to serialize transition
+serialize-json-failed = failed to serialize to json
+serialize-yaml-failed = failed to serialize to yaml
+serialize-toml-failed = failed to serialize to toml
+
+## prac list --watch
+
+watch-interval-not-positive = watch interval must be positive
+watch-failed = failed to watch statefile
+watch-dir-failed = failed to watch { $dir }
+watch-stopped = stopped watching the statefile
+
+## prac add
+
+add-prompt-name = What would you like to practice?
+add-prompt-why = Why practice "{ $name }?" (optional)
+no-period-or-default = no period provided, and no default_period configured
+
+## prac log and prac session
+
+log-prompt-time = How long did you practice "{ $name }?"
+log-prompt-note = Note on the session (optional)
+log-feedback-streak = { $count ->
+    [one] 1 period in a row
+   *[other] { $count } periods in a row
+}
+log-feedback-total = { $name }: { $total } in total
+session-prompt-time = How long (not how often) would you like to practice "{ $name }?"
+session-interrupted = ctrl-c pressed
+session-elapsed-of = { $elapsed } elapsed of { $total }
+session-elapsed = { $elapsed } elapsed
+sessions-numbered-from-one = sessions are numbered from 1
+
+## prac start, prac stop, and prac cancel
+
+not-running = "{ $name }" has no running session.
+nothing-running = No session is running.
+several-running = Several sessions are running, name one of { $names }.
+already-running = A session of "{ $name }" is already running.
+other-running = A session of "{ $other }" is running. Stop it first, or start "{ $name }" with --parallel.
+session-entry = { $name }: { $elapsed } since { $started }
+sessions-prompt = Which session?
+sessions-prompt-action = What should happen to the session of "{ $name }"?
+sessions-stop = Stop and log it
+sessions-cancel = Cancel it, logging nothing
+session-implausible = "{ $name }" ran for { $measured }, longer than you likely practiced. What should be logged?
+session-choice-trim = Trim it to { $cap }
+session-choice-enter = Enter the time practiced
+session-choice-keep = Log all { $measured }
+session-trimmed = "{ $name }" ran for { $measured }, so only the max_session of { $cap } is logged.
+
+## prac block
+
+block-prompt-time = How long a block of "{ $name }" would you like?
+block-practice = Practice
+block-cycle = Practice, cycle { $cycle }
+block-break = Break
+block-left = { $label }: { $left } left
+block-done = Block over.
+block-prompt-log = Time to log for "{ $name }", 0 to log nothing
+block-not-logged = Nothing logged.
+pomodoro-invalid = invalid pomodoro "{ $input }", expected practice/break like 25/5
+skip-log-only = Log-only practices have no period to skip.
+reassign-skipped = A skipped period can't be moved to another practice.
+
+## prac notes and prac why
+
+no-notes = "{ $name }" has no notes yet.
+no-notes-given = no notes provided
+read-notes-stdin-failed = failed to read notes from stdin
+why-prompt = Why practice "{ $name }?" (empty to clear)
+no-reason = no reason provided
+
+## prac goal and prac budget
+
+goal-prompt = Weekly time goal for "{ $name }", 0 for none
+no-goal = no goal provided
+best-time-prompt = Time of day "{ $name }" goes best, e.g. mornings or 20:00-23:00, "none" for any
+no-best-time = no time of day provided
+budget-prompt = Time to spend on "{ $name }" per period, 0 for none
+no-target-time = no target time provided
+session-length-prompt = Typical session length of "{ $name }", 0 for none
+no-session-length = no session length provided
+
+## prac context
+
+context-prompt = Where can "{ $name }" be done? Empty for anywhere
+context-of = "{ $name }" can be done at { $context }.
+context-anywhere = "{ $name }" can be done anywhere.
+
+## prac depend and prac suggest
+
+depends-on = "{ $name }" is best done after { $on }.
+depends-on-nothing = "{ $name }" doesn't depend on any practice.
+no-aliases = "{ $name }" has no aliases.
+alias-is-practice = There is already a practice named "{ $alias }".
+alias-taken = "{ $alias }" already stands for "{ $name }".
+alias-not-found = "{ $alias }" isn't an alias of "{ $name }".
+no-meta = "{ $name }" has no fields, see `prac meta set`.
+meta-key-empty = Field names can't be empty.
+meta-key-not-found = "{ $name }" has no field "{ $key }".
+depend-on-itself = A practice can't depend on itself.
+dependency-cycle = "{ $on }" already depends on "{ $name }", directly or through others.
+dependency-not-found = "{ $name }" doesn't depend on "{ $on }".
+suggestion = Next up: { $name }
+nothing-to-suggest = No practices to suggest.
+
+## prac review
+
+review-drifting = You log "{ $name }" every ~{ $average }, but its period is { $period }.
+review-nothing = Nothing to review, nice work.
+review-never-logged = "{ $name }" was { $explanation }.
+review-prompt-archive = Archive "{ $name }?"
+review-consider-period = Consider `prac edit-period { $name } { $period }`.
+review-prompt-period = Change its period to { $period }?
+review-adapt-longer = "{ $name }" has been on time for a while, so its period could grow from { $period } to { $adapted }.
+review-adapt-shorter = "{ $name }" missed a period, so its period could shrink from { $period } to { $adapted }.
+chart-week = week
+chart-month = month
+chart-all = All practices
+chart-hours = Hours
+chart-saved = Saved the chart to { $path }.
+chart-format-unknown = Can't tell what to save "{ $path }" as: name it .svg or .png.
+chart-write-failed = Could not save the chart to { $path }.
+chart-render-failed = Could not render the chart.
+heatmap-no-weeks = Show at least one week.
+chart-trend = { $trend ->
+    [up] Trending up, from { $earlier } to { $recent } a { $bucket } on average.
+    [down] Trending down, from { $earlier } to { $recent } a { $bucket } on average.
+   *[steady] Holding steady at about { $recent } a { $bucket }.
+}
+report-title = Practice report, { $date }
+report-chart = Time logged over the last { $weeks } weeks
+report-practice = Practice
+report-period = Period
+report-last-logged = Last logged
+report-total = In all
+report-this-week = This week
+report-streak = Streak
+report-on-time = On time
+report-grace = In grace
+report-missed = Missed
+report-log-only = log only
+report-never = never
+report-no-context = Anywhere
+report-gaps = Notable gaps
+report-gap = { $name }: { $length } without practice from { $from }, for a period of { $period }
+report-saved = Saved the report to { $path }.
+report-write-failed = Could not save the report to { $path }.
+stats-time = Practicing since { $since }: { $total } in all, { $week } this week in { $sessions ->
+    [one] one session
+   *[other] { $sessions } sessions
+}.
+stats-practices = { $practices ->
+    [one] One practice
+   *[other] { $practices } practices
+}, { $archived } archived, { $overdue } overdue.
+stats-adjustments = Period adjustments:
+stats-adjustment = { $at }  { $name }: { $from } to { $to }{ $automatic ->
+    [true] , automatically
+   *[other] {""}
+}
+stats-adherence-overall = Overall, { $adherence }.
+adherence = completed { $on_time }% of the last { $periods ->
+    [one] period
+   *[other] { $periods } periods
+} on time, { $grace }% in grace, { $missed }% missed
+
+## prac reset
+
+reset-prompt = Type "reset" to continue
+backed-up = Backed up state to { $path }
+no-backups = No backups yet.
+backup-not-found = No backup { $id }, see `prac backup list`.
+backup-failed = could not back up state to { $path }
+man-not-found = No command "prac { $command }" to show the manual page of.
+man-render-failed = Could not render the manual page.
+man-install-failed = Could not write manual pages to { $path }.
+man-installed = Installed { $path }
+restored-backup = Restored the state from { $path }.
+would-restore-backup = Would restore the state from { $path }.
+
+## prac edit-period, prac edit-mode, and prac check
+
+edit-period-prompt-log-only = Make "{ $name }" log-only, removing its period?
+edit-period-prompt = Change period of "{ $name }" to { $period }?
+edit-mode-prompt = How should logging "{ $name }" restart its bar?
+no-mode = no mode provided
+set-state-prompt = How much do you want to keep up "{ $name }"?
+no-state = no state provided
+checklist-empty-hint = "{ $name }" has no checklist; add items with `prac check { $name } <item> --add`.
+checklist-prompt = Check off
+checklist-empty = "{ $name }" has no checklist.
+
+## prac remove, prac merge, and prac rename
+
+remove-prompt = Remove practice "{ $name }?" (it can be restored from the trash for { $retention })
+remove-many-prompt = Remove these { $count } practices? (they can be restored from the trash for { $retention })
+merge-prompt-from = Practice to merge away
+merge-prompt-into = Merge "{ $from }" into
+merge-prompt = Merge "{ $from }" into "{ $into }", removing "{ $from }"?
+no-merge-from = no practice to merge from provided
+no-merge-into = no practice to merge into provided
+rename-prompt = Rename "{ $name }" to
+rename-merge-prompt = "{ $into }" already exists. Merge "{ $from }" into it?
+rename-target-exists = Practice with name "{ $name }" already exists. `prac rename --merge` merges the two instead.
+no-current-name = no current practice name provided
+no-new-name = no new practice name provided
+
+## prac merge-state
+
+no-statefile-at = No statefile at "{ $path }".
+merge-state-conflict = "{ $name }" has different { $field } in each statefile.
+merge-state-prompt = Keep which { $field }?
+merge-state-ours = this one: { $value }
+merge-state-theirs = the other: { $value }
+merged-nothing-new = Merged, no new practices.
+merged-adding = Merged, adding { $names }.
+
+## prac encrypt and prac decrypt
+
+already-encrypted = The statefile is already encrypted.
+encrypted = The statefile is now encrypted. Keep the passphrase safe: without it, your practices can't be recovered.
+not-encrypted = The statefile isn't encrypted.
+decrypted = The statefile is now stored unencrypted.
+passphrase-prompt = Passphrase
+passphrase-repeat = Repeat passphrase
+passphrases-differ = Passphrases don't match.
+passphrase-not-interactive = The statefile is encrypted; set { $var } to use prac non-interactively.
+not-encrypted-statefile = not an encrypted statefile
+key-cost-too-high = The statefile asks for a key derivation cost of 2^{ $log_n }, more than prac ever writes; it is damaged or wasn't written by prac.
+decrypt-failed = Could not decrypt the statefile: wrong passphrase, or the file is damaged.
+salt-failed = could not generate salt: { $error }
+nonce-failed = could not generate nonce: { $error }
+key-parameters-invalid = invalid key derivation parameters
+key-derivation-failed = could not derive key
+encrypt-state-failed = could not encrypt state
+
+## prac import and prac export
+
+no-export = no export given
+
+## prac template
+
+practice-set-invalid-span = invalid { $field } for "{ $name }"
+practice-set-parse-failed = failed to parse practice set
+practice-set-serialize-failed = failed to serialize practice set
+
+template-name-invalid = Template names can't be empty, contain slashes, or start with a dot.
+template-exists = Template "{ $name }" already exists, pass --force to replace it.
+template-nothing-to-save = No practices to save.
+template-path-no-parent = template path has no parent
+template-saved = Saved template "{ $name }" to { $path }
+template-not-found = No template named "{ $name }".
+no-templates = No templates yet. Save one with `prac template save <name>`.
+
+## prac sync
+
+git-not-run = failed to run git, is it installed?
+git-failed = git { $command } failed: { $error }
+sync-parse-failed = failed to parse state committed at { $rev }
+sync-repository-created = Created sync repository at { $path }
+sync-committed-locally = Committed locally. Set a remote to sync with `prac config set sync_remote <url>`.
+sync-remote-empty = remote has no statefile
+sync-pulled = Pulled changes from { $remote }.
+sync-merged = Merged changes from { $remote }.
+sync-pushed = Pushed to { $remote }.
+
+## prac doctor
+
+doctor-no-problems = No problems found.
+doctor-fixed = fixed
+doctor-fixable = fixable
+doctor-manual = manual
+doctor-run-fix = Run `prac doctor --fix` to apply the fixable repairs.
+doctor-unknown-field = unknown field "{ $path }" is ignored by this version of prac and dropped whenever it saves
+doctor-other-version = statefile was last written by prac { $written }, this is prac { $version }
+doctor-state-format = state_format is { $configured } but the statefile's name ends in .{ $format }, which wins; rename it to end in .{ $configured } to switch
+doctor-large-pretty = statefile is { $size } and takes { $millis } ms to write; `prac config set state_compact true` makes it { $compact_size } in { $compact_millis } ms
+doctor-large-compress = statefile is { $size } written as configured; `prac config set state_compress true` makes it { $compressed_size }, taking { $compressed_millis } ms to write instead of { $millis } ms
+doctor-grace-negative = grace period is negative
+doctor-misnamed = stored under "{ $key }" but named "{ $name }"
+doctor-duplicate = looks like a duplicate of "{ $other }", consider `prac merge`
+doctor-period-not-positive = period of { $seconds }s is not positive
+doctor-negative-sessions = { $count } { $count ->
+        [one] session
+       *[other] sessions
+    } with negative time
+doctor-cumulative-negative = cumulative time is negative
+doctor-goal-not-positive = weekly goal is not positive
+doctor-budget-not-positive = target time per period is not positive
+doctor-created-in-future = created in the future
+doctor-logged-in-future = last logged in the future
+doctor-sessions-in-future = { $count } { $count ->
+        [one] session
+       *[other] sessions
+    } logged in the future
+doctor-out-of-order = sessions are out of order
+doctor-cumulative-short = cumulative time is less than the sessions logged
+
+## prac trash
+
+trash-empty = The trash is empty.
+restore-prompt = Restore practice
+
+## prac config
+
+config-invalid-value = invalid value for { $key }
+state-format-overridden = Warning: { $path } ends in .{ $format }, so it stays { $format } whatever state_format says. To switch to { $configured }, rename it to end in .{ $configured } and point --path or PRAC_PATH at it.
+
+## Errors about practices and the statefile
+
+practice-unknown = Practice not found.
+practice-name-empty = Practice name can't be empty.
+never-logged = practice was never logged
+log-in-future = Can't log a session in the future.
+budget-log-only = "{ $name }" is log-only, so has no period to budget time for.
+checklist-item-empty = Checklist item can't be empty.
+checklist-item-exists = "{ $name }" already has "{ $item }" on its checklist.
+checklist-item-not-found = "{ $name }" has no "{ $item }" on its checklist.
+accumulate-needs-budget = Accumulating needs a time to accumulate: set one with `prac budget { $name } <time>`.
+reassign-same-practice = Can't move a session to the practice it's already in.
+reassign-time-out-of-range = Can only move between nothing and all of the session.
+no-entry = "{ $name }" has no entry { $number }.
+no-entry-at = "{ $name }" has no session at { $at }.
+several-entries-at = "{ $name }" has several sessions at { $at }, pick one by number.
+edit-skipped-time = A skipped period has no time to correct.
+edit-log-no-time = A session needs some time; delete it instead.
+not-in-trash = No practice named "{ $name }" in the trash.
+restore-name-taken = Practice with name "{ $name }" already exists. Restore it under another name with --as.
+merge-into-itself = Can't merge a practice into itself.
+read-state-failed = could not read statefile
+parse-state-failed = failed to parse state at "{ $path }".
+    Until automated state upgrading is implemented, you will either have to satisfy the parser's demands, or start with a new statefile. Be sure to save though.
+create-parents-failed = failed to create parent directories
+read-only-asked = not changing the statefile, as --read-only was given
+read-only-not-writable = can't change the statefile, as it or the directory it is in isn't writable. Commands that only show it still work.
+fake-now-invalid = could not read ${ $var }="{ $value }" as a point in time
+serialize-state-failed = failed to serialize state
+compress-state-failed = failed to compress state
+decompress-state-failed = failed to decompress state
+write-state-failed = failed to write state to file
+audit-write-failed = failed to append to the audit log { $path }
+audit-remove-failed = failed to remove the audit log { $path }
+no-home-dir = could not find home directory
+select-practice = Select practice
+selection-failed = Selection error.
+
+## prac list
+
+list-no-practices = You don't have any practices yet. Add some with `prac add`.
+list-all-archived = All your practices are archived. See them with `prac list --archived`.
+list-cumulative-and-period = { $cumulative } c / { $period } p
+list-week = week
+list-budget = budget
+list-danger = danger
+list-why = why?
+list-log-only = log only
+list-no-context = no context
+list-none-shown = No practices to show, try without --due-only or --recent.
+list-hidden = { $count } hidden { $count ->
+        [one] practice
+       *[other] practices
+    } not shown, see `prac list --hidden`.
+list-group-subtotal = { $count } { $count ->
+        [one] practice
+       *[other] practices
+    }, { $cumulative } logged
+list-log-only-hidden = { $count } log-only { $count ->
+        [one] practice
+       *[other] practices
+    } not shown, see `prac list --cumulative`.
+termsize-failed = failed to obtain termsize
+term-too-narrow = term width { $width } too small, must be at least { $needed }
+timing = { $last }, { $due }
+timing-with-age = practicing for { $age }, { $last }
+timing-last-logged = last logged { $ago } ago
+timing-never-logged = never logged, added { $ago } ago
+timing-due-in = due in { $due }
+timing-due-in-grace = due in { $due } (grace until +{ $grace })
+timing-in-grace = in grace: { $grace } remaining (due { $ago } ago)
+timing-overdue = overdue by { $overdue }
+prune-nudge = { $count } { $count ->
+        [one] practice
+       *[other] practices
+    } never logged. Consider archiving with `prac review`.
+never-logged-explanation = added { $age } ago to practice every { $period }, but never logged
+
+## prac reset
+
+reset-description = Reset restarts the bars of { $count } practices, forgetting when each was last logged:
+reset-practice = { $name } (last logged { $ago } ago)
+reset-kept = Cumulative time ({ $total } in total), notes, and everything else are kept.
+
+## prac find
+
+find-nothing = Nothing matched "{ $query }".
+field-names = names
+field-why = why
+field-notes = notes
+field-note-history = earlier notes
+
+## prac history, prac notes --history, and prac trash list
+
+history-empty = Nothing logged to "{ $name }" yet.
+history-empty-range = Nothing logged to "{ $name }" in that time.
+history-skipped = skipped
+history-untracked = plus { $time } logged before history was kept
+no-note-history = No earlier versions of the notes of "{ $name }" were kept.
+note-history-untracked = before revisions were kept
+trash-entry = { $name }  removed { $removed }, purged after { $purged }
+
+## prac import
+
+imported-practice = Imported "{ $name }": { $count } { $count ->
+        [one] completion
+       *[other] completions
+    }, every { $period }
+imported-practice-guessed = Imported "{ $name }": { $count } { $count ->
+        [one] completion
+       *[other] completions
+    }, every { $period } (guessed from the history)
+imported-time = Logged { $time } to "{ $name }" from { $count } { $count ->
+        [one] entry
+       *[other] entries
+    }
+imported-time-duplicates = Skipped { $count } entries imported before
+imported-time-unmatched = No practice for: { $projects }. Map projects with --map "Project=practice".
+no-project = (no project)
+nothing-to-import = Nothing to import.
+
+## Hooks and webhooks
+
+hook-run-failed = Could not run hook { $hook }: { $error }
+hook-no-stdin = hook has no stdin
+hook-wait-failed = failed to wait for hook
+hook-failed = Hook { $hook } failed ({ $status }).
+
+log-event = Practiced { $name } for { $time }
+log-event-streak = Practiced { $name } for { $time }, { $streak } periods in a row
+
+## Times and time windows
+
+syntax-hint = See `prac help syntax` for how to write times.
+parse-invalid = Invalid { $kind }: { $problem }
+time-span = time span
+span-no-unit = the number has no unit, e.g. "h" or "min".
+span-expected = expected a time span, e.g. "1h 30min".
+span-unknown-unit = unknown unit "{ $token }".
+span-unknown-unit-suggest = unknown unit "{ $token }", did you mean "{ $unit }"?
+span-needs-number = "{ $token }" needs a number before it.
+span-unexpected = unexpected "{ $token }".
+span-too-long = too long, time spans go up to 10000 years.
+span-spaced-number = numbers can't have spaces in them.
+iso-duration = ISO 8601 duration
+iso-expected = expected a number and a designator, e.g. P1D or PT30M.
+iso-no-values = it has no values, e.g. P1D or PT30M.
+iso-too-long = too long, durations go up to 10000 years.
+iso-fraction-not-last = only the last value may have a fraction.
+time-point-invalid = Invalid time: "{ $string }". Try e.g. "3h ago", "yesterday 18:00", "last tue", or "2024-03-01".
+no-such-date = There is no { $date }.
+time-of-day-invalid = Invalid time of day: "{ $text }". Write e.g. "14:00" or "2pm".
+time-of-day-hour-invalid = Invalid time of day: "{ $text }", 12 hour times go from 1 to 12.
+time-of-day-out-of-range = Invalid time of day: "{ $text }".
+time-window-invalid = invalid time window "{ $string }", expected e.g. "mornings" or "20:00-23:00"
+
+## Durations, written out in full
+
+span-zero = 0 seconds
+span-year = { $count } { $count ->
+        [one] year
+       *[other] years
+    }
+span-month = { $count } { $count ->
+        [one] month
+       *[other] months
+    }
+span-week = { $count } { $count ->
+        [one] week
+       *[other] weeks
+    }
+span-day = { $count } { $count ->
+        [one] day
+       *[other] days
+    }
+span-hour = { $count } { $count ->
+        [one] hour
+       *[other] hours
+    }
+span-minute = { $count } { $count ->
+        [one] minute
+       *[other] minutes
+    }
+span-second = { $count } { $count ->
+        [one] second
+       *[other] seconds
+    }
+span-millisecond = { $count } { $count ->
+        [one] millisecond
+       *[other] milliseconds
+    }
+span-microsecond = { $count } { $count ->
+        [one] microsecond
+       *[other] microseconds
+    }
+span-nanosecond = { $count } { $count ->
+        [one] nanosecond
+       *[other] nanoseconds
+    }
+
+## --dry-run
+
+dry-run-not-saved = Dry run, nothing was saved.
+dry-run-sync = Sync can't be dry-run, as it pushes and pulls with the remote.
+would-save-template = Would save template "{ $name }" to { $path }
+would-add = Would add "{ $name }", to practice every { $period }.
+would-add-log-only = Would add "{ $name }", log-only.
+would-log = Would log { $time } to "{ $name }".
+would-touch = Would restart the bar of "{ $name }".
+would-start = Would start a session of "{ $name }".
+would-stop = Would stop the session of "{ $name }", logging { $time }.
+would-cancel = Would discard the session of "{ $name }".
+would-skip = Would skip the current period of "{ $name }".
+would-append-notes = Would add to the notes of "{ $name }".
+would-set-notes = Would replace the notes of "{ $name }".
+would-set-why = Would set why you practice "{ $name }".
+would-clear-why = Would clear why you practice "{ $name }".
+would-set-goal = Would set the weekly goal of "{ $name }" to { $goal }.
+would-clear-goal = Would clear the weekly goal of "{ $name }".
+would-set-context = Would set the context of "{ $name }" to { $context }.
+would-clear-context = Would let "{ $name }" be done anywhere.
+would-set-best-time = Would set the best time of "{ $name }" to { $window }.
+would-clear-best-time = Would clear the best time of "{ $name }".
+would-set-session-length = Would set the typical session length of "{ $name }" to { $length }.
+would-clear-session-length = Would clear the typical session length of "{ $name }".
+would-set-budget = Would set the time to spend on "{ $name }" per period to { $target }.
+would-clear-budget = Would clear the time to spend on "{ $name }" per period.
+would-set-state = Would set the state of "{ $name }" to { $state }.
+would-depend = Would make "{ $name }" depend on "{ $on }".
+would-undepend = Would make "{ $name }" no longer depend on "{ $on }".
+would-edit-mode = Would set the log mode of "{ $name }" to { $mode }.
+would-add-item = Would add "{ $item }" to the checklist of "{ $name }".
+would-remove-item = Would remove "{ $item }" from the checklist of "{ $name }".
+would-check-item = Would check off "{ $item }" for "{ $name }".
+would-uncheck-item = Would uncheck "{ $item }" for "{ $name }".
+would-archive = Would archive "{ $name }".
+would-unarchive = Would unarchive "{ $name }".
+would-hide = Would hide "{ $name }" from `prac list`.
+would-unhide = Would show "{ $name }" in `prac list` again.
+would-alias = Would let "{ $alias }" stand for "{ $name }".
+would-unalias = Would stop "{ $alias }" standing for "{ $name }".
+would-set-meta = Would set "{ $key }" of "{ $name }" to "{ $value }".
+would-unset-meta = Would remove "{ $key }" from "{ $name }".
+would-edit-log = Would correct session { $number } of "{ $name }".
+would-delete-log = Would delete session { $number } of "{ $name }".
+would-reassign = Would move session { $number } of "{ $name }" to "{ $to }".
+would-reassign-part = Would move { $time } of session { $number } of "{ $name }" to "{ $to }".
+would-remove = Would move "{ $name }" to the trash.
+would-restore = Would restore "{ $name }" from the trash.
+would-restore-as = Would restore "{ $name }" from the trash as "{ $new_name }".
+would-rename = Would rename "{ $name }" to "{ $new_name }".
+would-merge = Would merge "{ $from }" into "{ $into }", removing "{ $from }".
+would-reset = Would restart the bars of every practice.
+would-edit-period = Would change the period of "{ $name }" to { $period }.
+period-too-short = A period of { $period } is too short, periods are at least a second.
+would-adapt-period = Would adapt the period of "{ $name }" to { $period }.
+would-edit-period-log-only = Would make "{ $name }" log-only, removing its period.
+would-set-config = Would update the config.
+
+## prac batch
+
+read-batch-failed = failed to read commands from stdin
+batch-failed = command { $number } of the batch failed, so none of it was saved
@@ -0,0 +1,517 @@
+//! Importers for the exports of other habit trackers and time trackers, for `prac import`.
+//!
+//! Each habit tracker importer reads habits and the days they were completed into
+//! [`ImportedHabit`]s, which [`State::import`](crate::application::State::import) turns into
+//! practices with a log entry per completion. Time trackers' entries are read into [`TimeEntry`]s,
+//! which [`State::import_time`](crate::application::State::import_time) logs to existing practices.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde_json::Value;
+
+/// Habit trackers `prac import` reads.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// Loop Habit Tracker: the unzipped "Export as CSV" directory.
+    Loop,
+    /// Habitica: the user data JSON from Settings > Backups.
+    Habitica,
+    /// Streaks: the CSV export.
+    Streaks,
+}
+
+impl ImportFormat {
+    /// Guess the format from the path: a directory for Loop, JSON for Habitica, CSV for Streaks.
+    pub fn detect(path: &Path) -> Result<Self> {
+        if path.is_dir() {
+            return Ok(Self::Loop);
+        }
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Ok(Self::Habitica),
+            Some("csv") => Ok(Self::Streaks),
+            _ => bail!("can't tell the format of {}, pass --format", path.display()),
+        }
+    }
+}
+
+/// A habit read from another tracker.
+pub struct ImportedHabit {
+    pub name: String,
+    pub description: Option<String>,
+    /// How often the habit is due, if the tracker says.
+    pub period: Option<Duration>,
+    pub archived: bool,
+    /// When the habit was completed, oldest first.
+    pub completions: Vec<DateTime<Utc>>,
+}
+
+/// Read the export at `path`.
+pub fn read(path: &Path, format: ImportFormat) -> Result<Vec<ImportedHabit>> {
+    let mut habits = match format {
+        ImportFormat::Loop => read_loop(path),
+        ImportFormat::Habitica => read_habitica(path),
+        ImportFormat::Streaks => read_streaks(path),
+    }
+    .with_context(|| format!("failed to import {}", path.display()))?;
+    for habit in &mut habits {
+        habit.completions.sort();
+        habit.completions.dedup();
+    }
+    Ok(habits)
+}
+
+/// Trackers record days rather than times; completions are placed at local noon, so they stay
+/// on the same day in nearby time zones.
+fn at_noon(date: NaiveDate) -> DateTime<Utc> {
+    let noon = date.and_hms_opt(12, 0, 0).expect("noon exists");
+    Local
+        .from_local_datetime(&noon)
+        .earliest()
+        .map_or_else(|| Utc.from_utc_datetime(&noon), |at| at.with_timezone(&Utc))
+}
+
+/// A period of `count` times `days` days, failing rather than overflowing on numbers too large
+/// to be one, as exports may have anything in them.
+fn period_days(count: i64, days: i64) -> Result<Duration> {
+    count
+        .checked_mul(days)
+        .filter(|days| (1..=crate::time::MAX_SPAN_DAYS).contains(days))
+        .and_then(Duration::try_days)
+        .with_context(|| format!("a period of {count} times {days} days is out of range"))
+}
+
+fn csv_reader(path: &Path) -> Result<csv::Reader<std::fs::File>> {
+    csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .with_context(|| format!("could not read {}", path.display()))
+}
+
+/// Index of the column named `name` (case insensitive).
+fn column(headers: &csv::StringRecord, name: &str) -> Option<usize> {
+    headers
+        .iter()
+        .position(|header| header.trim().eq_ignore_ascii_case(name))
+}
+
+/// Loop's export has a `Habits.csv` of habits and a `Checkmarks.csv` with a row per day and a
+/// column per habit, where 2 marks a completion (1 is Loop's own "implicitly done").
+fn read_loop(dir: &Path) -> Result<Vec<ImportedHabit>> {
+    let mut habits = csv_reader(&dir.join("Habits.csv"))?;
+    let headers = habits.headers()?.clone();
+    let name = column(&headers, "Name").context("Habits.csv has no Name column")?;
+    let description = column(&headers, "Question").or_else(|| column(&headers, "Description"));
+    let repetitions = column(&headers, "NumRepetitions");
+    let interval = column(&headers, "Interval");
+    let archived = column(&headers, "Archived?");
+
+    let mut imported = Vec::new();
+    for record in habits.records() {
+        let record = record?;
+        let field = |index: Option<usize>| index.and_then(|index| record.get(index)).map(str::trim);
+        let number = |index| field(index).and_then(|value| value.parse::<i64>().ok());
+        let period = match (number(repetitions), number(interval)) {
+            (Some(repetitions), Some(interval)) if repetitions > 0 && interval > 0 => {
+                Some(period_days(interval, 1)? / i32::try_from(repetitions)?)
+            }
+            _ => None,
+        };
+        imported.push(ImportedHabit {
+            name: field(Some(name)).unwrap_or_default().to_owned(),
+            description: field(description)
+                .filter(|text| !text.is_empty())
+                .map(ToOwned::to_owned),
+            period,
+            archived: field(archived).is_some_and(|value| value.eq_ignore_ascii_case("true")),
+            completions: Vec::new(),
+        });
+    }
+
+    let mut checkmarks = csv_reader(&dir.join("Checkmarks.csv"))?;
+    let headers = checkmarks.headers()?.clone();
+    let columns = imported
+        .iter()
+        .map(|habit| column(&headers, &habit.name))
+        .collect::<Vec<_>>();
+    for record in checkmarks.records() {
+        let record = record?;
+        let Some(date) = record
+            .get(0)
+            .and_then(|date| NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok())
+        else {
+            continue;
+        };
+        for (habit, column) in imported.iter_mut().zip(&columns) {
+            if column.and_then(|column| record.get(column)).map(str::trim) == Some("2") {
+                habit.completions.push(at_noon(date));
+            }
+        }
+    }
+    Ok(imported)
+}
+
+/// A Habitica history entry's date, which is milliseconds since the epoch in older exports and
+/// an ISO 8601 string in newer ones.
+fn habitica_date(entry: &Value) -> Option<DateTime<Utc>> {
+    match &entry["date"] {
+        Value::Number(millis) => DateTime::from_timestamp_millis(millis.as_i64()?),
+        Value::String(date) => date
+            .parse::<DateTime<Utc>>()
+            .ok()
+            .or_else(|| DateTime::from_timestamp_millis(date.parse().ok()?)),
+        _ => None,
+    }
+}
+
+/// Habitica's export holds habits (scored up or down any number of times) and dailies (due on a
+/// schedule). Completions are the history entries scored up, or for dailies, marked completed or
+/// raising the task's value.
+fn read_habitica(path: &Path) -> Result<Vec<ImportedHabit>> {
+    let json = std::fs::read_to_string(path).context("could not read export")?;
+    let export: Value = serde_json::from_str(&json).context("export is not JSON")?;
+    let tasks = &export["tasks"];
+    if !tasks.is_object() {
+        bail!("no tasks found, is this a Habitica user data export?");
+    }
+
+    let mut imported = Vec::new();
+    for (kind, task) in ["habits", "dailys"].into_iter().flat_map(|kind| {
+        tasks[kind]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(move |task| (kind, task))
+    }) {
+        let history = task["history"].as_array().map_or(&[][..], Vec::as_slice);
+        let mut completions = Vec::new();
+        let mut previous = None;
+        for entry in history {
+            let value = entry["value"].as_f64();
+            let completed = if kind == "habits" {
+                entry["scoredUp"].as_u64().is_some_and(|up| up > 0)
+            } else {
+                entry["completed"]
+                    .as_bool()
+                    .unwrap_or(matches!((previous, value), (Some(a), Some(b)) if b > a))
+            };
+            previous = value;
+            if let (true, Some(at)) = (completed, habitica_date(entry)) {
+                completions.push(at);
+            }
+        }
+
+        let period = if kind == "dailys" {
+            let every = task["everyX"].as_i64().unwrap_or(1).max(1);
+            match task["frequency"].as_str() {
+                Some("weekly") => {
+                    let days = task["repeat"]
+                        .as_object()
+                        .map_or(1, |repeat| {
+                            repeat
+                                .values()
+                                .filter(|on| on.as_bool() == Some(true))
+                                .count()
+                        })
+                        .max(1);
+                    Some(period_days(every, 7)? / i32::try_from(days)?)
+                }
+                Some("monthly") => Some(period_days(every, 30)?),
+                Some("yearly") => Some(period_days(every, 365)?),
+                _ => Some(period_days(every, 1)?),
+            }
+        } else {
+            None
+        };
+        imported.push(ImportedHabit {
+            name: task["text"].as_str().unwrap_or_default().trim().to_owned(),
+            description: task["notes"]
+                .as_str()
+                .map(str::trim)
+                .filter(|notes| !notes.is_empty())
+                .map(ToOwned::to_owned),
+            period,
+            archived: false,
+            completions,
+        });
+    }
+    Ok(imported)
+}
+
+/// Streaks exports a row per entry with the task's `title`, an `entry_date` (as `YYYYMMDD` or
+/// `YYYY-MM-DD`) or `entry_timestamp`, and an `entry_type`; completions are the entries whose
+/// type mentions "completed".
+fn read_streaks(path: &Path) -> Result<Vec<ImportedHabit>> {
+    let mut reader = csv_reader(path)?;
+    let headers = reader.headers()?.clone();
+    let title = column(&headers, "title").context("no title column, is this a Streaks export?")?;
+    let date = column(&headers, "entry_date");
+    let timestamp = column(&headers, "entry_timestamp");
+    let kind = column(&headers, "entry_type");
+
+    let mut habits = BTreeMap::<String, Vec<DateTime<Utc>>>::new();
+    for record in reader.records() {
+        let record = record?;
+        let field = |index: Option<usize>| index.and_then(|index| record.get(index)).map(str::trim);
+        let Some(title) = field(Some(title)).filter(|title| !title.is_empty()) else {
+            continue;
+        };
+        let completions = habits.entry(title.to_owned()).or_default();
+        if field(kind).is_some_and(|kind| !kind.to_lowercase().contains("completed")) {
+            continue;
+        }
+        let at = field(timestamp)
+            .and_then(|at| at.parse::<DateTime<Utc>>().ok())
+            .or_else(|| {
+                let date = field(date)?;
+                NaiveDate::parse_from_str(date, "%Y%m%d")
+                    .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+                    .ok()
+                    .map(at_noon)
+            });
+        completions.extend(at);
+    }
+    Ok(habits
+        .into_iter()
+        .map(|(name, completions)| ImportedHabit {
+            name,
+            description: None,
+            period: None,
+            archived: false,
+            completions,
+        })
+        .collect())
+}
+
+/// A time entry read from a time tracker.
+pub struct TimeEntry {
+    /// The tracker's ID for the entry, or where the export has none, one made of its contents,
+    /// so importing the same export again finds the entries it already logged.
+    pub id: String,
+    pub project: String,
+    pub start: DateTime<Utc>,
+    pub time: Duration,
+}
+
+/// Date formats of time tracker exports, which follow the user's settings.
+const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%m/%d/%Y", "%d.%m.%Y", "%d-%m-%Y"];
+const TIME_FORMATS: [&str; 4] = ["%H:%M:%S", "%H:%M", "%I:%M:%S %p", "%I:%M %p"];
+
+/// A duration written as `H:MM:SS` (or `H:MM`), as both trackers export them.
+fn clock_duration(text: &str) -> Option<Duration> {
+    let mut parts = text.split(':').map(|part| part.trim().parse::<i64>().ok());
+    let hours = parts.next()??;
+    let minutes = parts.next()??;
+    let seconds = parts.next().unwrap_or(Some(0))?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Duration::try_hours(hours)?
+        .checked_add(&Duration::try_minutes(minutes)?)?
+        .checked_add(&Duration::try_seconds(seconds)?)
+}
+
+/// A duration written as decimal hours, like `1.25` (or `1,25`), as Clockify's decimal duration
+/// columns have them.
+fn decimal_duration(text: &str) -> Option<Duration> {
+    let hours = text.trim().replace(',', ".").parse::<f64>().ok()?;
+    if !hours.is_finite() || hours < 0.0 || hours > f64::from(u32::MAX) {
+        return None;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    Duration::try_seconds((hours * 3600.0).round() as i64)
+}
+
+/// Read a detailed time entry CSV export from Toggl Track or Clockify. Both have a project, a
+/// start date and time (in local time), and a duration; an ID column is used when present.
+pub fn read_time_entries(path: &Path) -> Result<Vec<TimeEntry>> {
+    read_time_csv(path).with_context(|| format!("failed to import {}", path.display()))
+}
+
+fn read_time_csv(path: &Path) -> Result<Vec<TimeEntry>> {
+    let mut reader = csv_reader(path)?;
+    let headers = reader.headers()?.clone();
+    let project = column(&headers, "Project")
+        .context("no Project column, is this a Toggl or Clockify export?")?;
+    let date = column(&headers, "Start date").context("no Start date column")?;
+    let time = column(&headers, "Start time").context("no Start time column")?;
+    // Clockify names its duration columns by format, but what they hold follows its settings,
+    // so either format is read from any of them.
+    let duration = ["Duration", "Duration (h)", "Duration (decimal)"]
+        .into_iter()
+        .find_map(|name| column(&headers, name))
+        .context("no Duration column")?;
+    let id = ["ID", "Id", "Time entry ID", "Entry ID"]
+        .into_iter()
+        .find_map(|name| column(&headers, name));
+    let description = column(&headers, "Description");
+
+    let mut entries = Vec::new();
+    for (line, record) in reader.records().enumerate() {
+        let record = record?;
+        let field = |index: Option<usize>| index.and_then(|index| record.get(index)).map(str::trim);
+        // Line numbers as shown in an editor, after the header.
+        let line = line + 2;
+        let project = field(Some(project)).unwrap_or_default();
+        let start_date = field(Some(date)).unwrap_or_default();
+        let start_time = field(Some(time)).unwrap_or_default();
+        let start_date = DATE_FORMATS
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(start_date, format).ok())
+            .with_context(|| format!("line {line}: unrecognized date \"{start_date}\""))?;
+        let start_time = TIME_FORMATS
+            .iter()
+            .find_map(|format| NaiveTime::parse_from_str(start_time, format).ok())
+            .with_context(|| format!("line {line}: unrecognized time \"{start_time}\""))?;
+        let start = Local
+            .from_local_datetime(&start_date.and_time(start_time))
+            .earliest()
+            .map_or_else(
+                || Utc.from_utc_datetime(&start_date.and_time(start_time)),
+                |start| start.with_timezone(&Utc),
+            );
+        let text = field(Some(duration)).unwrap_or_default();
+        let time = clock_duration(text)
+            .or_else(|| decimal_duration(text))
+            .with_context(|| format!("line {line}: unrecognized duration \"{text}\""))?;
+        let id = field(id).filter(|id| !id.is_empty()).map_or_else(
+            || {
+                format!(
+                    "{}/{}/{project}/{}",
+                    start.to_rfc3339(),
+                    time.num_seconds(),
+                    field(description).unwrap_or_default()
+                )
+            },
+            ToOwned::to_owned,
+        );
+        entries.push(TimeEntry {
+            id,
+            project: project.to_owned(),
+            start,
+            time,
+        });
+    }
+    entries.sort_by_key(|entry| entry.start);
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read, read_time_entries, ImportFormat, ImportedHabit};
+    use chrono::{DateTime, Duration, NaiveDate};
+    use std::path::{Path, PathBuf};
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/import")
+            .join(name)
+    }
+
+    fn dates(habit: &ImportedHabit) -> Vec<NaiveDate> {
+        habit.completions.iter().map(DateTime::date_naive).collect()
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn test_loop() {
+        let path = fixture("loop");
+        assert_eq!(ImportFormat::detect(&path).unwrap(), ImportFormat::Loop);
+        let habits = read(&path, ImportFormat::Loop).unwrap();
+        assert_eq!(habits.len(), 2);
+
+        let scales = &habits[0];
+        assert_eq!(scales.name, "Scales");
+        assert_eq!(
+            scales.description.as_deref(),
+            Some("Did you play scales today?")
+        );
+        assert_eq!(scales.period, Some(Duration::days(1)));
+        assert!(!scales.archived);
+        assert_eq!(dates(scales), vec![date(1), date(3)]);
+
+        let stretch = &habits[1];
+        assert_eq!(stretch.period, Some(Duration::days(7) / 3));
+        assert!(stretch.archived);
+        // 1 is Loop's "implicitly done", not a completion.
+        assert_eq!(dates(stretch), vec![date(1), date(2)]);
+    }
+
+    #[test]
+    fn test_habitica() {
+        let path = fixture("habitica.json");
+        assert_eq!(ImportFormat::detect(&path).unwrap(), ImportFormat::Habitica);
+        let habits = read(&path, ImportFormat::Habitica).unwrap();
+        let names = habits
+            .iter()
+            .map(|habit| habit.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["Sight reading", "Ear training", "Recital prep"]);
+
+        assert_eq!(habits[0].period, None);
+        assert_eq!(dates(&habits[0]), vec![date(1)]);
+        assert_eq!(habits[1].description.as_deref(), Some("Intervals first"));
+        assert_eq!(habits[1].period, Some(Duration::weeks(1) / 3));
+        assert_eq!(dates(&habits[1]), vec![date(1)]);
+        assert_eq!(habits[2].period, Some(Duration::days(60)));
+    }
+
+    #[test]
+    fn test_habitica_period_out_of_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("habitica.json");
+        let export = r#"{"tasks": {"dailys": [
+            {"text": "Forever", "frequency": "yearly", "everyX": 9223372036854775807}
+        ]}}"#;
+        std::fs::write(&path, export).unwrap();
+        let error = read(&path, ImportFormat::Habitica).err().unwrap();
+        assert!(format!("{error:#}").contains("out of range"), "{error:#}");
+    }
+
+    #[test]
+    fn test_streaks() {
+        let path = fixture("streaks.csv");
+        assert_eq!(ImportFormat::detect(&path).unwrap(), ImportFormat::Streaks);
+        let habits = read(&path, ImportFormat::Streaks).unwrap();
+        assert_eq!(habits.len(), 2);
+        assert_eq!(habits[0].name, "Arpeggios");
+        assert_eq!(dates(&habits[0]), vec![date(1), date(3)]);
+        assert_eq!(habits[1].name, "Journal");
+        assert_eq!(dates(&habits[1]), vec![date(2)]);
+    }
+
+    #[test]
+    fn test_clockify_decimal_hours() {
+        let entries = read_time_entries(&fixture("clockify.csv")).unwrap();
+        let times = entries
+            .iter()
+            .map(|entry| (entry.project.as_str(), entry.time))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            times,
+            vec![
+                ("Piano", Duration::minutes(75)),
+                ("Guitar", Duration::minutes(30)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clockify_duration_out_of_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clockify.csv");
+        let export = "Project,Start date,Start time,Duration (h)\n\
+                      Piano,2024-03-01,09:00:00,9223372036854775807:00:00\n";
+        std::fs::write(&path, export).unwrap();
+        let error = read_time_entries(&path).err().unwrap();
+        assert!(
+            format!("{error:#}").contains("unrecognized duration"),
+            "{error:#}"
+        );
+    }
+}
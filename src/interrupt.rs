@@ -0,0 +1,83 @@
+//! What Ctrl-C does. Mostly it aborts prac on the spot, as backing out of a prompt does: the cursor
+//! prompts hide is shown again, "aborted" is printed, and prac exits with [`error::ABORTED`]
+//! before saving, so a change half made never reaches the statefile. Timed sessions [`catch`] it
+//! instead, to end early and log the time so far.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, Result};
+
+use crate::error;
+use crate::i18n::t;
+
+/// Whether Ctrl-C is caught rather than aborting, see [`catch`].
+static CATCHING: AtomicBool = AtomicBool::new(false);
+/// Whether Ctrl-C was pressed while caught.
+static CAUGHT: AtomicBool = AtomicBool::new(false);
+/// Whether prac is aborting already, as both the signal and a prompt's failed read may tell it to.
+static ABORTING: AtomicBool = AtomicBool::new(false);
+/// Whether to abort without saying so, for `--quiet`.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Handle Ctrl-C as above for the rest of the run, quietly if `quiet`.
+pub fn install(quiet: bool) -> Result<()> {
+    QUIET.store(quiet, Ordering::SeqCst);
+    ctrlc::set_handler(|| {
+        if CATCHING.load(Ordering::SeqCst) {
+            CAUGHT.store(true, Ordering::SeqCst);
+        } else {
+            abort();
+        }
+    })
+    .context(t!("interrupt-handler-failed"))
+}
+
+/// Restore the terminal and exit as aborted, without saving.
+pub fn abort() -> ! {
+    if ABORTING.swap(true, Ordering::SeqCst) {
+        // The other thread aborting exits soon.
+        loop {
+            std::thread::park();
+        }
+    }
+    let _ = crossterm::execute!(std::io::stderr(), crossterm::cursor::Show);
+    if !QUIET.load(Ordering::SeqCst) {
+        eprintln!("\n{}", t!("aborted"));
+    }
+    std::process::exit(i32::from(error::ABORTED));
+}
+
+/// Whether `error` is Ctrl-C pressed at a prompt, which reads it as a key and fails the read as
+/// interrupted.
+pub fn is_interrupt(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|cause| cause.kind() == std::io::ErrorKind::Interrupted)
+    })
+}
+
+/// Catch Ctrl-C rather than aborting, until the returned [`Catch`] is dropped.
+pub fn catch() -> Catch {
+    CAUGHT.store(false, Ordering::SeqCst);
+    CATCHING.store(true, Ordering::SeqCst);
+    Catch { caught: &CAUGHT }
+}
+
+/// Ctrl-C being caught, see [`catch`].
+pub struct Catch {
+    caught: &'static AtomicBool,
+}
+
+impl Catch {
+    /// Whether Ctrl-C was pressed since it was caught.
+    pub fn caught(&self) -> bool {
+        self.caught.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for Catch {
+    fn drop(&mut self) {
+        CATCHING.store(false, Ordering::SeqCst);
+    }
+}
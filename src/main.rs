@@ -180,7 +180,8 @@
 //! 2s
 //! ```
 //! See [src/time/time.pest](https://github.com/henry-merrilees/prac/blob/main/src/time/time.pest) for the complete grammar.
-//! Errors are decent enough to help you if you get stuck.
+//! Errors are decent enough to help you if you get stuck, and `prac help syntax` lists every unit
+//! and how to write points in time, like `prac log --at` takes.
 
 // TODO: --no-clock for sessions, and make max_time optional. Display bar, and optional ENTER
 // handler to terminate.
@@ -196,148 +197,1583 @@
 )]
 
 mod application;
+mod audit;
+mod backup;
+mod block;
 mod cli;
+mod clock;
+mod color;
+mod config;
+mod crypt;
+mod error;
+mod hooks;
+mod i18n;
+mod import;
+mod interrupt;
+mod man;
+mod output;
+mod prompt;
+mod serve;
+mod storage;
+mod sync;
+mod template;
 mod time;
 mod utils;
+mod webhook;
 
-use anyhow::{bail, Context, Result};
-use application::{handle_transition, State, StateTransition};
+use anyhow::{bail, ensure, Context, Result};
+use application::{
+    handle_transition, ChartBucket, ChecklistEdit, Export, Lifecycle, ListOptions, LogMode,
+    PracticeSet, ReadOnly, ReportFormat, State, StateTransition, TransitionReport, UserConfig,
+};
 use clap::Parser;
-use cli::{Cli, SubCommand};
-use std::io::{BufWriter, Write};
-use std::path::Path;
-use std::sync::atomic::AtomicBool;
-use std::sync::Arc;
-
-fn get_time_span_interactive(msg: &str) -> Result<chrono::Duration> {
-    let time_input = dialoguer::Input::<String>::new()
-        .with_prompt(msg)
-        .allow_empty(false)
-        .interact()?;
+use cli::{
+    BackupAction, Cli, ConfigAction, ExportFormat, HistoryAction, ImportSource, MetaAction,
+    SubCommand, TemplateAction, TrashAction,
+};
+use clock::{FixedClock, SharedClock};
+use config::ConfigKey;
+use error::Error;
+use i18n::t;
+use output::OutputFormat;
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use time::SpanUse;
+
+/// Ask for a time span with `msg`, offering `default`, if any, to be taken with Enter.
+fn get_time_span_interactive(
+    msg: &str,
+    default: Option<chrono::Duration>,
+) -> Result<chrono::Duration> {
+    let mut input = dialoguer::Input::<String>::new();
+    input.with_prompt(msg).allow_empty(false);
+    if let Some(default) = default {
+        input.default(time::Span::from(default).format_with(time::SpanStyle::Short));
+    }
+    let time_input = input.interact()?;
     time::parse_time_span(&time_input)
 }
 
+/// Ask to confirm `span`, given for `usage`, if it looks like `M` (months) and `m` (minutes) were
+/// mixed up, unless `yes`. Without a terminal to ask at, it is only warned about.
+fn confirm_span(span: chrono::Duration, usage: SpanUse, yes: bool) -> Result<()> {
+    if yes || !usage.mixed_up(span) {
+        return Ok(());
+    }
+    let words = time::Span::from(span).format_with(time::SpanStyle::Long);
+    let warning = match usage {
+        SpanUse::Period => t!("period-in-minutes", span = &words),
+        SpanUse::Practiced => t!("time-in-months", span = &words),
+    };
+    eprintln!("{warning}");
+    if std::io::stdin().is_terminal()
+        && !dialoguer::Confirm::new()
+            .with_prompt(t!("keep-span", span = &words))
+            .default(false)
+            .interact()?
+    {
+        bail!(Error::Aborted(t!("aborted")));
+    }
+    Ok(())
+}
+
+/// The practices a command acting on several is to change: one picked interactively, or those
+/// `patterns` and `context` select (see [`State::select_practices`]).
+fn select_practices(
+    state: &State,
+    patterns: &[String],
+    context: Option<&str>,
+    interactive: bool,
+) -> Result<Vec<String>> {
+    if interactive {
+        return Ok(vec![state.find_name()?.to_owned()]);
+    }
+    state.select_practices(patterns, context)
+}
+
+/// One transition of `transitions`, asking first, listing the practices, if there are several,
+/// unless `yes`.
+fn bulk(mut transitions: Vec<StateTransition>, yes: bool) -> Result<StateTransition> {
+    if transitions.len() == 1 {
+        return Ok(transitions.remove(0));
+    }
+    if !yes {
+        for transition in &transitions {
+            eprintln!("  {}", transition.describe());
+        }
+        if std::io::stdin().is_terminal()
+            && !dialoguer::Confirm::new()
+                .with_prompt(t!("bulk-prompt", count = transitions.len()))
+                .interact()?
+        {
+            bail!(Error::Aborted(t!("aborted")));
+        }
+    }
+    Ok(StateTransition::Bulk { transitions })
+}
+
+/// Whether to merge practice `from` into `into`, whose name it was to be renamed to. Only asked on a
+/// terminal; elsewhere, `--merge` has to be given.
+fn confirm_rename_merge(from: &str, into: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    Ok(dialoguer::Confirm::new()
+        .with_prompt(t!("rename-merge-prompt", from = from, into = into))
+        .default(false)
+        .interact()?)
+}
+
+/// The time to log for the running session of `name`: the time since it started, unless that is
+/// longer than the `max_session` config.
+fn session_time(state: &State, name: &str) -> Result<chrono::Duration> {
+    let started = state
+        .running_since(name)
+        .with_context(|| Error::NotFound(t!("not-running", name = name)))?;
+    let measured = state.now() - started;
+    let cap = state.get_user_config().max_session;
+    if cap > chrono::Duration::zero() && measured > cap {
+        plausible_session(name, measured, cap)
+    } else {
+        Ok(measured)
+    }
+}
+
+/// The time to log for a session of `name` that ran `measured`, longer than the `max_session`
+/// config `cap`: asked for if the terminal is interactive, else the cap.
+fn plausible_session(
+    name: &str,
+    measured: chrono::Duration,
+    cap: chrono::Duration,
+) -> Result<chrono::Duration> {
+    let measured_shown = time::Span::from(measured).format_abbreviated();
+    let cap_shown = time::Span::from(cap).format();
+    if !std::io::stdin().is_terminal() {
+        eprintln!(
+            "{}",
+            t!(
+                "session-trimmed",
+                name = name,
+                measured = &measured_shown,
+                cap = &cap_shown
+            )
+        );
+        return Ok(cap);
+    }
+    let choices = [
+        t!("session-choice-trim", cap = &cap_shown),
+        t!("session-choice-enter"),
+        t!("session-choice-keep", measured = &measured_shown),
+    ];
+    let choice = dialoguer::Select::new()
+        .with_prompt(t!(
+            "session-implausible",
+            name = name,
+            measured = &measured_shown
+        ))
+        .items(&choices)
+        .default(0)
+        .interact_opt()?
+        .context(Error::Aborted(t!("aborted")))?;
+    match choice {
+        0 => Ok(cap),
+        1 => get_time_span_interactive(&t!("log-prompt-time", name = name), None),
+        _ => Ok(measured),
+    }
+}
+
+/// Prompt for a new value of a config field, showing the current one. Empty input keeps the
+/// current value (returning `None`), anything else must pass `parse`.
+fn edit_field_interactive<T>(
+    msg: &str,
+    current: &str,
+    parse: impl Fn(&str) -> Result<T>,
+) -> Result<Option<T>> {
+    let input = dialoguer::Input::<String>::new()
+        .with_prompt(t!("edit-field-prompt", msg = msg, current = current))
+        .allow_empty(true)
+        .validate_with(|input: &String| -> Result<(), String> {
+            if input.is_empty() {
+                return Ok(());
+            }
+            parse(input).map(|_| ()).map_err(|err| err.to_string())
+        })
+        .interact_text()?;
+    if input.is_empty() {
+        Ok(None)
+    } else {
+        parse(&input).map(Some)
+    }
+}
+
+/// Where the practice template `name` is kept: a TOML file in the `templates` directory beside
+/// the statefile.
+fn template_path(state_path: &Path, name: &str) -> Result<PathBuf> {
+    ensure!(
+        !name.is_empty() && !name.contains(['/', '\\']) && !name.starts_with('.'),
+        t!("template-name-invalid")
+    );
+    Ok(state_path
+        .parent()
+        .context(t!("state-path-no-parent"))?
+        .join("templates")
+        .join(format!("{name}.toml")))
+}
+
+/// How commands apply their transitions.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Apply them, running hooks and posting to webhooks.
+    Normal,
+    /// Only say what they would do, for `--dry-run`.
+    DryRun,
+    /// Apply them quietly, for `prac batch`, which saves once all commands are done.
+    Batch,
+}
+
+/// Apply `transition`, then run its hook and, for logs, post to the webhooks.
+fn apply(
+    state: &mut State,
+    transition: StateTransition,
+    state_path: &Path,
+    mode: Mode,
+) -> Result<()> {
+    if let StateTransition::Config { new_config } = &transition {
+        warn_state_format(state, new_config, state_path);
+    }
+    if mode == Mode::DryRun {
+        let description = transition.describe();
+        handle_transition(state, transition)?;
+        println!("{description}");
+        return Ok(());
+    }
+    state.ensure_writable()?;
+    if mode == Mode::Batch || state.is_demo() {
+        return handle_transition(state, transition);
+    }
+    let hook = hooks::find(&transition)
+        .map(|hook| {
+            let payload =
+                serde_json::to_string(&transition).context(t!("serialize-transition-failed"))?;
+            anyhow::Ok((hook, payload))
+        })
+        .transpose()?;
+    let logged = match &transition {
+        StateTransition::Log { name, at, .. } => Some((name.clone(), *at)),
+        _ => None,
+    };
+    handle_transition(state, transition)?;
+    if let Some((hook, payload)) = hook {
+        // So the hook sees the state it is told about.
+        state.save(state_path)?;
+        hooks::run(&hook, &payload, state_path)?;
+    }
+    if let Some((name, at)) = logged {
+        webhook::queue(&state.log_event(&name, at)?)?;
+    }
+    Ok(())
+}
+
+/// Warn when `new_config` changes `state_format` to one the extension of the statefile at
+/// `state_path` overrides, as then the change does nothing.
+fn warn_state_format(state: &State, new_config: &UserConfig, state_path: &Path) {
+    let configured = new_config.state_format;
+    if configured == state.get_user_config().state_format {
+        return;
+    }
+    if let Some(format) = storage::StateFormat::overriding(state_path, configured) {
+        let warning = t!(
+            "state-format-overridden",
+            path = state_path.display().to_string(),
+            format = format.name(),
+            configured = configured.name()
+        );
+        eprintln!("{warning}");
+    }
+}
+
+/// Redraw the list every `interval`, and whenever the statefile at `state_path` is written, until
+/// interrupted.
+fn watch_list(
+    state: &State,
+    state_path: &Path,
+    options: &ListOptions,
+    interval: chrono::Duration,
+) -> Result<()> {
+    use notify::Watcher;
+
+    let interval = interval
+        .to_std()
+        .ok()
+        .filter(|interval| !interval.is_zero())
+        .context(t!("watch-interval-not-positive"))?;
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).context(t!("watch-failed"))?;
+    // The directory rather than the file, which editors and sync tools may replace.
+    let dir = state_path.parent().context(t!("state-path-no-parent"))?;
+    watcher
+        .watch(dir, notify::RecursiveMode::NonRecursive)
+        .with_context(|| t!("watch-dir-failed", dir = dir.display().to_string()))?;
+
+    let file_name = state_path.file_name();
+    let mut reloaded = None;
+    loop {
+        let current = reloaded.as_ref().unwrap_or(state);
+        crossterm::execute!(
+            std::io::stdout(),
+            crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
+            crossterm::cursor::MoveTo(0, 0)
+        )?;
+        current.list(options)?;
+        std::io::stdout().flush()?;
+
+        let changed = |event: &notify::Result<notify::Event>| {
+            event.as_ref().is_ok_and(|event| {
+                (event.kind.is_modify() || event.kind.is_create())
+                    && event.paths.iter().any(|path| path.file_name() == file_name)
+            })
+        };
+        // Other files in the directory changing doesn't cut the wait short.
+        let deadline = std::time::Instant::now() + interval;
+        loop {
+            let timeout = deadline.saturating_duration_since(std::time::Instant::now());
+            match receiver.recv_timeout(timeout) {
+                Ok(event) if changed(&event) => break,
+                Ok(_) => (),
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => break,
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                    bail!(t!("watch-stopped"))
+                }
+            }
+        }
+        // Let a writer finish, then skip the rest of its events.
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        while receiver.try_recv().is_ok() {}
+        // A state caught mid-write doesn't parse; the next write brings a whole one.
+        if let Ok(bytes) = std::fs::read(state_path) {
+            if let Ok(state) = State::from_bytes(bytes, state.encryption()) {
+                reloaded = Some(state);
+            }
+        }
+    }
+}
+
+/// Add every practice of `set`, skipping those that already exist.
+fn add_practice_set(
+    state: &mut State,
+    set: &PracticeSet,
+    state_path: &Path,
+    mode: Mode,
+) -> Result<()> {
+    let skipped = set
+        .names()
+        .filter(|name| state.contains(name))
+        .collect::<Vec<_>>();
+    let groups = set.transitions(|name| state.contains(name))?;
+    let added = groups.len();
+    for transitions in groups {
+        for transition in transitions {
+            apply(state, transition, state_path, mode)?;
+        }
+    }
+    println!("{}", t!("added-practices", count = added));
+    if !skipped.is_empty() {
+        println!("{}", t!("skipped-existing", names = skipped.join(", ")));
+    }
+    Ok(())
+}
+
+/// Whether to take unknown practice names to mean the closest existing one, see `--assume-closest`.
+static ASSUME_CLOSEST: AtomicBool = AtomicBool::new(false);
+
+/// The practice `name` refers to (see [`State::resolve_name`]). Names of no practice are refused
+/// with the closest existing names as suggestions, or, with `--assume-closest`, taken to mean the
+/// closest. Names nothing is close to are left for the command to complain about.
+fn resolve_name(state: &State, name: &mut String) -> Result<()> {
+    let resolved = state.resolve_name(std::mem::take(name));
+    let closest = if state.contains(&resolved) {
+        Vec::new()
+    } else {
+        state.closest_names(&resolved)
+    };
+    match closest.first() {
+        Some(&closest) if ASSUME_CLOSEST.load(Ordering::Relaxed) => {
+            eprintln!(
+                "{}",
+                t!("assuming-closest", name = &resolved, closest = closest)
+            );
+            closest.clone_into(name);
+        }
+        Some(_) => bail!(Error::NotFound(t!(
+            "practice-not-found-suggest",
+            name = &resolved,
+            suggestions = closest
+                .iter()
+                .map(|name| format!("\"{name}\""))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+        None => *name = resolved,
+    }
+    Ok(())
+}
+
+/// `subcommand` with the practice names it was given resolved, see [`resolve_name`].
+fn resolve_names(state: &State, mut subcommand: SubCommand) -> Result<SubCommand> {
+    match &mut subcommand {
+        SubCommand::Session { name, .. }
+        | SubCommand::Block { name, .. }
+        | SubCommand::Start { name, .. }
+        | SubCommand::Stop { name, .. }
+        | SubCommand::Cancel { name, .. }
+        | SubCommand::Log { name, .. }
+        | SubCommand::Touch { name, .. }
+        | SubCommand::Skip { name, .. }
+        | SubCommand::History { name, .. }
+        | SubCommand::Heatmap { name, .. }
+        | SubCommand::Chart { name, .. }
+        | SubCommand::Notes { name, .. }
+        | SubCommand::Why { name, .. }
+        | SubCommand::Goal { name, .. }
+        | SubCommand::Budget { name, .. }
+        | SubCommand::EditSession { name, .. }
+        | SubCommand::BestTime { name, .. }
+        | SubCommand::Context { name, .. }
+        | SubCommand::SetState { name, .. }
+        | SubCommand::EditPeriod { name, .. }
+        | SubCommand::Check { name, .. }
+        | SubCommand::EditMode { name, .. }
+        | SubCommand::Rename {
+            current_name: name, ..
+        } => {
+            if let Some(name) = name {
+                resolve_name(state, name)?;
+            }
+        }
+        SubCommand::Archive { names, .. }
+        | SubCommand::Hide { names, .. }
+        | SubCommand::Remove { names, .. } => {
+            for name in names.iter_mut().filter(|name| !application::is_glob(name)) {
+                resolve_name(state, name)?;
+            }
+        }
+        SubCommand::Alias { name, .. }
+        | SubCommand::Meta {
+            action:
+                MetaAction::Get { name, .. }
+                | MetaAction::Set { name, .. }
+                | MetaAction::Unset { name, .. },
+        } => resolve_name(state, name)?,
+        SubCommand::Depend { name, on, .. } => {
+            resolve_name(state, name)?;
+            if let Some(on) = on {
+                resolve_name(state, on)?;
+            }
+        }
+        SubCommand::Merge { from, into, .. } => {
+            for name in from.iter_mut().chain(into) {
+                resolve_name(state, name)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(subcommand)
+}
+
 #[allow(clippy::too_many_lines)]
-fn process_subcommand(state: &mut State, subcommand: SubCommand, state_path: &Path) -> Result<()> {
+fn process_subcommand(
+    state: &mut State,
+    subcommand: SubCommand,
+    state_path: &Path,
+    color: bool,
+    yes: bool,
+    mode: Mode,
+) -> Result<()> {
     // TODO transition generation doesn't require &mut, this should be enforced somehow
     // TODO allow manual field specifications alongside interactive
+    let mut result_output = OutputFormat::Text;
+    let subcommand = resolve_names(state, subcommand)?;
+    // Practice to show the bar of once the transition is applied.
+    let mut feedback = None;
     let transition = match subcommand {
         SubCommand::List {
             cumulative,
             period,
             danger,
+            bar_width,
+            fill,
+            empty,
+            ascii,
+            archived,
+            sort,
+            sparkline,
+            budget,
+            verbose,
+            context,
+            group_by,
+            due_only,
+            recent,
+            hidden,
+            full_names,
+            aliases,
+            watch,
+            template,
+            as_of,
+            output,
         } => {
-            state.list(cumulative, period, danger)?;
+            // The actual clock goes back before saving, lest periods be saved as lapsed by then.
+            let actual = as_of.map(|as_of| state.set_clock(SharedClock::new(FixedClock(as_of))));
+            // Only asked for where it is printed, as asking marks it shown in the statefile.
+            let shows_nudge = output == OutputFormat::Text
+                && as_of.is_none()
+                && watch.is_none()
+                && template.is_none();
+            let nudge = if shows_nudge {
+                state.prune_nudge()
+            } else {
+                None
+            };
+            let options = ListOptions {
+                cumulative,
+                period,
+                danger,
+                color,
+                bar_width,
+                bar_fill: fill,
+                bar_empty: empty,
+                ascii,
+                archived,
+                sort,
+                sparkline,
+                budget,
+                verbose,
+                context,
+                group_by,
+                due_only,
+                recent,
+                hidden,
+                full_names,
+                aliases,
+            };
+            if let Some(interval) = watch {
+                return watch_list(state, state_path, &options, interval);
+            }
+            let printed = template.map_or_else(
+                || {
+                    output::print(output, &state.list_report(&options), || {
+                        state.list(&options)?;
+                        if let Some(nudge) = nudge {
+                            println!("{nudge}");
+                        }
+                        Ok(())
+                    })
+                },
+                |template| state.list_report(&options).print_template(&template),
+            );
+            if let Some(actual) = actual {
+                state.set_clock(actual);
+            }
+            return printed;
+        }
+        SubCommand::Prompt { .. } | SubCommand::Status { .. } | SubCommand::Man { .. } => {
+            unreachable!("prompt, status and man are handled before loading state")
+        }
+        // Handled before loading state too, but may come in a batch.
+        SubCommand::Syntax => {
+            println!("{}", time::syntax_reference());
             return Ok(());
         }
+        SubCommand::Find { query, output } => {
+            let report = state.find(&query);
+            return output::print(output, &report, || {
+                state.print_find(&report, &query, color);
+                Ok(())
+            });
+        }
+        SubCommand::Add {
+            from_file: Some(from_file),
+            ..
+        } => {
+            let toml = std::fs::read_to_string(&from_file)
+                .with_context(|| t!("read-failed", path = from_file.display().to_string()))?;
+            return add_practice_set(state, &PracticeSet::parse(&toml)?, state_path, mode);
+        }
         SubCommand::Add {
             name,
             period,
+            log_only,
+            why,
+            weekly_goal,
             interactive,
+            output,
+            from_file: None,
         } => {
+            result_output = output;
             let name = if interactive {
                 dialoguer::Input::<String>::new()
-                    .with_prompt("What would you like to practice?")
+                    .with_prompt(t!("add-prompt-name"))
                     .allow_empty(false)
                     .interact()?
             } else {
-                name.context("no practice name provided")?
+                name.context(t!("no-practice-name"))?
             };
-            let msg = format!("How often (not how long) would you like to practice \"{name}?\"");
-            let period = if interactive {
-                get_time_span_interactive(&msg)?
+            let msg = t!("prompt-period", name = &name);
+            let period = if log_only {
+                None
+            } else if interactive {
+                let suggested = state.suggested_period(&name);
+                Some(get_time_span_interactive(&msg, suggested)?)
             } else {
-                period.context("no period provided")?
+                let period = period
+                    .or_else(|| state.get_user_config().default_period)
+                    .context(t!("no-period-or-default"))?;
+                Some(period)
             };
-            StateTransition::Add { name, period }
+            if let Some(period) = period {
+                confirm_span(period, SpanUse::Period, yes)?;
+            }
+            let why = if interactive {
+                dialoguer::Input::<String>::new()
+                    .with_prompt(t!("add-prompt-why", name = &name))
+                    .allow_empty(true)
+                    .interact()?
+            } else {
+                why.unwrap_or_default()
+            };
+            let why = Some(why.trim().to_owned()).filter(|why| !why.is_empty());
+            StateTransition::Add {
+                name,
+                period,
+                why,
+                weekly_goal,
+            }
         }
         SubCommand::Log {
             name,
             time,
+            at,
+            note,
             interactive,
+            output,
         } => {
+            result_output = output;
             let name = if interactive {
                 state.find_name()?.to_owned()
             } else {
-                name.context("no practice name provided")?
+                name.context(t!("no-practice-name"))?
             };
-            let msg = format!("How long did you practice \"{name}?\"");
+            let msg = t!("log-prompt-time", name = &name);
             let time = if interactive {
-                get_time_span_interactive(&msg)?
+                get_time_span_interactive(&msg, None)?
+            } else {
+                time.context(t!("no-time"))?
+            };
+            confirm_span(time, SpanUse::Practiced, yes)?;
+            let note = if interactive && note.is_none() {
+                let note = dialoguer::Input::<String>::new()
+                    .with_prompt(t!("log-prompt-note"))
+                    .allow_empty(true)
+                    .interact()?;
+                Some(note.trim().to_owned()).filter(|note| !note.is_empty())
+            } else {
+                note
+            };
+            if interactive {
+                feedback = Some(name.clone());
+            }
+            StateTransition::Log {
+                name,
+                time,
+                at,
+                note,
+            }
+        }
+        SubCommand::Start {
+            name,
+            parallel,
+            interactive,
+            output,
+        } => {
+            result_output = output;
+            let name = if interactive {
+                state.find_name()?.to_owned()
+            } else {
+                name.context(t!("no-practice-name"))?
+            };
+            StateTransition::Start { name, parallel }
+        }
+        SubCommand::Stop { name, time, output } => {
+            result_output = output;
+            let name = state.running_name(name)?;
+            if let Some(time) = time {
+                confirm_span(time, SpanUse::Practiced, yes)?;
+            }
+            let time = time.map_or_else(|| session_time(state, &name), Ok)?;
+            StateTransition::Stop { name, time }
+        }
+        SubCommand::Sessions {
+            interactive: false,
+            output,
+        } => {
+            let report = state.sessions_report();
+            return output::print(output, &report, || {
+                report.print();
+                Ok(())
+            });
+        }
+        SubCommand::Sessions {
+            interactive: true, ..
+        } => {
+            let report = state.sessions_report();
+            if report.is_empty() {
+                report.print();
+                return Ok(());
+            }
+            let names = report.names();
+            let index = dialoguer::Select::new()
+                .with_prompt(t!("sessions-prompt"))
+                .items(&names)
+                .default(0)
+                .interact_opt()?
+                .context(Error::Aborted(t!("nothing-selected")))?;
+            let name = names[index].to_owned();
+            let actions = [t!("sessions-stop"), t!("sessions-cancel")];
+            let action = dialoguer::Select::new()
+                .with_prompt(t!("sessions-prompt-action", name = &name))
+                .items(&actions)
+                .default(0)
+                .interact_opt()?
+                .context(Error::Aborted(t!("nothing-selected")))?;
+            if action == 0 {
+                let time = session_time(state, &name)?;
+                StateTransition::Stop { name, time }
+            } else {
+                StateTransition::Cancel { name }
+            }
+        }
+        SubCommand::Cancel { name, output } => {
+            result_output = output;
+            StateTransition::Cancel {
+                name: state.running_name(name)?,
+            }
+        }
+        SubCommand::Touch {
+            name,
+            interactive,
+            output,
+        } => {
+            result_output = output;
+            let name = if interactive {
+                state.find_name()?.to_owned()
             } else {
-                time.context("no time provided")?
+                name.context(t!("no-practice-name"))?
             };
-            StateTransition::Log { name, time }
+            StateTransition::Touch { name }
+        }
+        SubCommand::Skip {
+            name,
+            reason,
+            interactive,
+            output,
+        } => {
+            result_output = output;
+            let name = if interactive {
+                state.find_name()?.to_owned()
+            } else {
+                name.context(t!("no-practice-name"))?
+            };
+            StateTransition::Skip { name, reason }
         }
         SubCommand::Session {
             name,
             max_time,
             interactive,
+            output,
         } => {
+            result_output = output;
             let name = if interactive {
                 state.find_name()?.to_owned()
             } else {
-                name.context("no practice name provided")?
+                name.context(t!("no-practice-name"))?
             };
 
             let max_time = if interactive {
-                let msg =
-                    format!("How long (not how often) would you like to practice \"{name}?\"");
-                get_time_span_interactive(&msg)?
+                let msg = t!("session-prompt-time", name = &name);
+                get_time_span_interactive(&msg, None)?
             } else {
-                max_time.context("no time provided")?
+                max_time.context(t!("no-time"))?
             };
+            confirm_span(max_time, SpanUse::Practiced, yes)?;
 
             // Print out how much time has passed untile ctrl-c is pressed.
 
-            let running = Arc::new(AtomicBool::new(true));
-            let r = running.clone();
-
-            ctrlc::set_handler(move || {
-                r.store(false, std::sync::atomic::Ordering::SeqCst);
-                println!("ctrl-c pressed");
-            })?;
-
+            let interrupt = interrupt::catch();
             let mut time = chrono::Duration::seconds(0);
-            let start = chrono::Utc::now();
-            while running.load(std::sync::atomic::Ordering::SeqCst) && time < max_time {
+            let start = state.now();
+            while !interrupt.caught() && time < max_time {
                 // TODO use bar, you already have it
                 print!(
-                    "\r{} elapsed of {}",
-                    time::FlatTime::from(time).format_seconds(),
-                    time::FlatTime::from(max_time).format_seconds()
+                    "\r{}",
+                    t!(
+                        "session-elapsed-of",
+                        elapsed = time::Span::from(time).format_seconds(),
+                        total = time::Span::from(max_time).format_seconds()
+                    )
                 );
                 std::io::stdout().flush()?;
                 std::thread::sleep(std::time::Duration::from_millis(1000));
-                time = chrono::Utc::now() - start;
+                time = state.now() - start;
             }
 
-            let time = (chrono::Utc::now() - start).min(max_time);
+            let time = (state.now() - start).min(max_time);
+            if interrupt.caught() {
+                println!("{}", t!("session-interrupted"));
+            }
 
-            print!("\r{} elapsed", time::FlatTime::from(time).format_seconds());
+            print!(
+                "\r{}",
+                t!(
+                    "session-elapsed",
+                    elapsed = time::Span::from(time).format_seconds()
+                )
+            );
             std::io::stdout().flush()?;
 
-            StateTransition::Log { name, time }
+            StateTransition::Log {
+                name,
+                time,
+                at: None,
+                note: None,
+            }
+        }
+        SubCommand::Block {
+            time,
+            name,
+            pomodoro,
+            bell,
+            interactive,
+            output,
+        } => {
+            result_output = output;
+            let name = if interactive {
+                state.find_name()?.to_owned()
+            } else {
+                name.context(t!("no-practice-name"))?
+            };
+            ensure!(
+                state.contains(&name),
+                Error::NotFound(t!("practice-name-not-found", name = &name))
+            );
+            let length = if interactive {
+                let msg = t!("block-prompt-time", name = &name);
+                get_time_span_interactive(&msg, None)?
+            } else {
+                time.context(t!("no-time"))?
+            };
+            confirm_span(length, SpanUse::Practiced, yes)?;
+
+            let mut time = block::run(
+                state.clock(),
+                length,
+                pomodoro,
+                bell,
+                state.get_user_config().ascii,
+            )?;
+            if !yes && std::io::stdin().is_terminal() {
+                let msg = t!("block-prompt-log", name = &name);
+                let practiced = time::Span::from(time).format_seconds();
+                if let Some(adjusted) =
+                    edit_field_interactive(&msg, &practiced, time::parse_time_span)?
+                {
+                    time = adjusted;
+                }
+            }
+            ensure!(
+                time > chrono::Duration::zero(),
+                Error::Aborted(t!("block-not-logged"))
+            );
+            StateTransition::Log {
+                name,
+                time,
+                at: None,
+                note: None,
+            }
+        }
+        SubCommand::History {
+            action:
+                Some(HistoryAction::Edit {
+                    name,
+                    entry,
+                    time,
+                    at,
+                    note,
+                    to,
+                    split,
+                }),
+            ..
+        } => {
+            let index = state.find_log(&name, &entry)?;
+            for time in time.iter().chain(&split) {
+                confirm_span(*time, SpanUse::Practiced, yes)?;
+            }
+            match to {
+                Some(to) => StateTransition::ReassignLog {
+                    name,
+                    index,
+                    to,
+                    time: split,
+                },
+                None => StateTransition::EditLog {
+                    name,
+                    index,
+                    time,
+                    at,
+                    note,
+                },
+            }
+        }
+        SubCommand::History {
+            action: Some(HistoryAction::Delete { name, entry }),
+            ..
+        } => StateTransition::DeleteLog {
+            index: state.find_log(&name, &entry)?,
+            name,
+        },
+        SubCommand::History {
+            action: None,
+            name,
+            since,
+            until,
+            timeline,
+            output,
+            interactive,
+        } => {
+            let name = if interactive {
+                state.find_name()?.to_owned()
+            } else {
+                name.context(t!("no-practice-name"))?
+            };
+            let history = state.history(&name, since, until)?;
+            return output::print(output, &history, || {
+                if timeline {
+                    history.print_timeline();
+                } else {
+                    history.print();
+                }
+                Ok(())
+            });
+        }
+        SubCommand::Chart {
+            name,
+            weekly: _,
+            monthly,
+            count,
+            cumulative,
+            save,
+            output,
+            interactive,
+        } => {
+            let name = if interactive {
+                Some(state.find_name()?.to_owned())
+            } else {
+                name
+            };
+            let bucket = if monthly {
+                ChartBucket::Month
+            } else {
+                ChartBucket::Week
+            };
+            let chart = state.chart(name.as_deref(), bucket, count, cumulative)?;
+            if let Some(path) = save {
+                chart.save(&path)?;
+                println!("{}", t!("chart-saved", path = path.display().to_string()));
+                return Ok(());
+            }
+            return output::print(output, &chart, || {
+                let user_config = state.get_user_config();
+                chart.print(utils::BarGlyphs::new(
+                    user_config.bar_fill,
+                    user_config.bar_empty,
+                    user_config.ascii,
+                ));
+                Ok(())
+            });
+        }
+        SubCommand::Report { format, save } => {
+            let format = format.unwrap_or_else(|| {
+                save.as_deref()
+                    .map_or(ReportFormat::Markdown, ReportFormat::for_path)
+            });
+            let report = state.report(format)?;
+            match save {
+                Some(path) => {
+                    std::fs::write(&path, report).with_context(|| {
+                        t!("report-write-failed", path = path.display().to_string())
+                    })?;
+                    println!("{}", t!("report-saved", path = path.display().to_string()));
+                }
+                None => print!("{report}"),
+            }
+            return Ok(());
+        }
+        SubCommand::Heatmap {
+            name,
+            weeks,
+            output,
+            interactive,
+        } => {
+            let name = if interactive {
+                Some(state.find_name()?.to_owned())
+            } else {
+                name
+            };
+            let heatmap = state.heatmap(name.as_deref(), weeks)?;
+            return output::print(output, &heatmap, || {
+                heatmap.print(
+                    color,
+                    state.get_user_config().ascii || !utils::unicode_terminal(),
+                );
+                Ok(())
+            });
         }
         SubCommand::Notes {
             name,
             new_notes,
+            stdin,
+            append,
+            history,
+            show,
             interactive,
         } => {
             let name = if interactive {
                 state.find_name()?.to_owned()
             } else {
-                name.context("no practice name provided")?
+                name.context(t!("no-practice-name"))?
             };
+            if history {
+                return state.print_note_history(&name, color);
+            }
+            if show {
+                let notes = state.get_notes(&name)?;
+                if notes.trim().is_empty() {
+                    println!("{}", t!("no-notes", name = &name));
+                } else {
+                    utils::print_markdown(notes, color);
+                }
+                return Ok(());
+            }
             let notes = if interactive {
-                let old_notes = state.get_notes(&name)?;
-                utils::long_edit(Some(old_notes))?
+                // When appending, start from a blank page rather than the existing notes.
+                let old_notes = if append { "" } else { state.get_notes(&name)? };
+                let editor = state.get_user_config().editor.as_deref();
+                utils::long_edit(Some(old_notes), editor)?
+            } else if stdin {
+                let mut notes = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut notes)
+                    .context(t!("read-notes-stdin-failed"))?;
+                notes
+            } else {
+                new_notes.context(t!("no-notes-given"))?
+            };
+            StateTransition::Notes {
+                name,
+                notes,
+                append,
+            }
+        }
+        SubCommand::Why {
+            name,
+            why,
+            clear,
+            interactive,
+        } => {
+            let name = if interactive {
+                state.find_name()?.to_owned()
+            } else {
+                name.context(t!("no-practice-name"))?
+            };
+            let why = if clear {
+                None
+            } else if interactive {
+                let old_why = state.get_why(&name)?.unwrap_or_default().to_owned();
+                let new_why = dialoguer::Input::<String>::new()
+                    .with_prompt(t!("why-prompt", name = &name))
+                    .with_initial_text(old_why)
+                    .allow_empty(true)
+                    .interact_text()?;
+                Some(new_why)
+            } else {
+                Some(why.context(t!("no-reason"))?)
+            };
+            let why = why
+                .map(|why| why.trim().to_owned())
+                .filter(|why| !why.is_empty());
+            StateTransition::Why { name, why }
+        }
+        SubCommand::Context {
+            name,
+            context,
+            clear,
+            interactive,
+            output,
+        } => {
+            result_output = output;
+            let name = if interactive {
+                state.find_name()?.to_owned()
+            } else {
+                name.context(t!("no-practice-name"))?
+            };
+            let context = if clear {
+                None
+            } else if interactive {
+                let current = state.get_context(&name)?.unwrap_or_default().to_owned();
+                let input = dialoguer::Input::<String>::new()
+                    .with_prompt(t!("context-prompt", name = &name))
+                    .with_initial_text(current)
+                    .allow_empty(true)
+                    .interact_text()?;
+                Some(input)
+            } else if context.is_some() {
+                context
+            } else {
+                let message = state.get_context(&name)?.map_or_else(
+                    || t!("context-anywhere", name = &name),
+                    |context| t!("context-of", name = &name, context = context),
+                );
+                println!("{message}");
+                return Ok(());
+            };
+            StateTransition::Context { name, context }
+        }
+        SubCommand::EditSession {
+            name,
+            length,
+            clear,
+            interactive,
+            output,
+        } => {
+            result_output = output;
+            let name = if interactive {
+                state.find_name()?.to_owned()
+            } else {
+                name.context(t!("no-practice-name"))?
+            };
+            let session_length = if clear {
+                None
+            } else if interactive {
+                let current = state
+                    .get_session_length(&name)?
+                    .map_or_else(|| t!("none"), |length| time::Span::from(length).format());
+                let msg = t!("session-length-prompt", name = &name);
+                match edit_field_interactive(&msg, &current, time::parse_time_span)? {
+                    Some(length) => Some(length),
+                    None => state.get_session_length(&name)?,
+                }
             } else {
-                new_notes.context("no notes provided")?
+                Some(length.context(t!("no-session-length"))?)
             };
-            StateTransition::Notes { name, notes }
+            StateTransition::SessionLength {
+                name,
+                session_length: session_length.filter(|length| *length > chrono::Duration::zero()),
+            }
+        }
+        SubCommand::BestTime {
+            name,
+            window,
+            clear,
+            interactive,
+            output,
+        } => {
+            result_output = output;
+            let name = if interactive {
+                state.find_name()?.to_owned()
+            } else {
+                name.context(t!("no-practice-name"))?
+            };
+            let best_time = if clear {
+                None
+            } else if interactive {
+                let current = state
+                    .get_best_time(&name)?
+                    .map_or_else(|| t!("none"), |window| window.to_string());
+                let msg = t!("best-time-prompt", name = &name);
+                let parse = |input: &str| {
+                    if input.trim().eq_ignore_ascii_case("none") {
+                        Ok(None)
+                    } else {
+                        input.parse().map(Some)
+                    }
+                };
+                match edit_field_interactive(&msg, &current, parse)? {
+                    Some(window) => window,
+                    None => state.get_best_time(&name)?,
+                }
+            } else {
+                Some(window.context(t!("no-best-time"))?)
+            };
+            StateTransition::BestTime { name, best_time }
+        }
+        SubCommand::Goal {
+            name,
+            goal,
+            clear,
+            interactive,
+            output,
+        } => {
+            result_output = output;
+            let name = if interactive {
+                state.find_name()?.to_owned()
+            } else {
+                name.context(t!("no-practice-name"))?
+            };
+            let weekly_goal = if clear {
+                None
+            } else if interactive {
+                let current = state
+                    .get_weekly_goal(&name)?
+                    .map_or_else(|| t!("none"), |goal| time::Span::from(goal).format());
+                let msg = t!("goal-prompt", name = &name);
+                match edit_field_interactive(&msg, &current, time::parse_time_span)? {
+                    Some(goal) => Some(goal),
+                    None => state.get_weekly_goal(&name)?,
+                }
+            } else {
+                Some(goal.context(t!("no-goal"))?)
+            };
+            let weekly_goal = weekly_goal.filter(|goal| *goal > chrono::Duration::zero());
+            StateTransition::Goal { name, weekly_goal }
+        }
+        SubCommand::Budget {
+            name,
+            target,
+            clear,
+            interactive,
+            output,
+        } => {
+            result_output = output;
+            let name = if interactive {
+                state.find_name()?.to_owned()
+            } else {
+                name.context(t!("no-practice-name"))?
+            };
+            let target = if clear {
+                None
+            } else if interactive {
+                let current = state
+                    .get_target_time_per_period(&name)?
+                    .map_or_else(|| t!("none"), |target| time::Span::from(target).format());
+                let msg = t!("budget-prompt", name = &name);
+                match edit_field_interactive(&msg, &current, time::parse_time_span)? {
+                    Some(target) => Some(target),
+                    None => state.get_target_time_per_period(&name)?,
+                }
+            } else {
+                Some(target.context(t!("no-target-time"))?)
+            };
+            StateTransition::Budget {
+                name,
+                target_time_per_period: target.filter(|target| *target > chrono::Duration::zero()),
+            }
+        }
+        SubCommand::Archive {
+            names,
+            context,
+            unarchive,
+            interactive,
+            output,
+        } => {
+            result_output = output;
+            let names = select_practices(state, &names, context.as_deref(), interactive)?;
+            let transitions = names
+                .into_iter()
+                .map(|name| StateTransition::Archive {
+                    name,
+                    archived: !unarchive,
+                })
+                .collect();
+            bulk(transitions, yes || mode == Mode::DryRun)?
+        }
+        SubCommand::Hide {
+            names,
+            context,
+            unhide,
+            interactive,
+            output,
+        } => {
+            result_output = output;
+            let names = select_practices(state, &names, context.as_deref(), interactive)?;
+            let transitions = names
+                .into_iter()
+                .map(|name| StateTransition::Hide {
+                    name,
+                    hidden: !unhide,
+                })
+                .collect();
+            bulk(transitions, yes || mode == Mode::DryRun)?
+        }
+        SubCommand::Alias {
+            name, alias: None, ..
+        } => {
+            let aliases = state.aliases_of(&name)?;
+            if aliases.is_empty() {
+                println!("{}", t!("no-aliases", name = &name));
+            } else {
+                for alias in aliases {
+                    println!("{alias}");
+                }
+            }
+            return Ok(());
+        }
+        SubCommand::Alias {
+            name,
+            alias: Some(alias),
+            remove,
+            output,
+        } => {
+            result_output = output;
+            StateTransition::Alias {
+                name,
+                alias,
+                remove,
+            }
+        }
+        SubCommand::Meta {
+            action: MetaAction::Get { name, key, output },
+        } => {
+            let metadata = state.metadata_of(&name)?;
+            if let Some(key) = key {
+                let value = metadata.get(&key).with_context(|| {
+                    Error::NotFound(t!("meta-key-not-found", name = &name, key = &key))
+                })?;
+                return output::print(output, value, || {
+                    println!("{value}");
+                    Ok(())
+                });
+            }
+            return output::print(output, metadata, || {
+                if metadata.is_empty() {
+                    println!("{}", t!("no-meta", name = &name));
+                }
+                for (key, value) in metadata {
+                    println!("{key}: {value}");
+                }
+                Ok(())
+            });
+        }
+        SubCommand::Meta {
+            action:
+                MetaAction::Set {
+                    name,
+                    key,
+                    value,
+                    output,
+                },
+        } => {
+            result_output = output;
+            StateTransition::Meta {
+                name,
+                key,
+                value: Some(value),
+            }
+        }
+        SubCommand::Meta {
+            action: MetaAction::Unset { name, key, output },
+        } => {
+            result_output = output;
+            StateTransition::Meta {
+                name,
+                key,
+                value: None,
+            }
+        }
+        SubCommand::Depend { name, on: None, .. } => {
+            let dependencies = state.dependencies_of(&name)?;
+            if dependencies.is_empty() {
+                println!("{}", t!("depends-on-nothing", name = &name));
+            } else {
+                let on = dependencies
+                    .iter()
+                    .map(|name| format!("\"{name}\""))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                println!("{}", t!("depends-on", name = &name, on = on));
+            }
+            return Ok(());
+        }
+        SubCommand::Depend {
+            name,
+            on: Some(on),
+            remove,
+        } => StateTransition::Depend { name, on, remove },
+        SubCommand::Suggest {
+            context,
+            have,
+            output,
+        } => {
+            let suggestion = state
+                .suggest(context, have)
+                .with_context(|| Error::NotFound(t!("nothing-to-suggest")))?;
+            return output::print(output, &suggestion, || {
+                suggestion.print();
+                Ok(())
+            });
+        }
+        SubCommand::Stats { periods, output } => {
+            let report = state.stats(periods);
+            return output::print(output, &report, || {
+                report.print();
+                Ok(())
+            });
+        }
+        SubCommand::Review => {
+            let never_logged = state
+                .never_logged()
+                .into_iter()
+                .map(|(name, explanation)| (name.to_owned(), explanation))
+                .collect::<Vec<_>>();
+            let drifting = state
+                .drifting()
+                .into_iter()
+                .map(|drift| {
+                    let message = t!(
+                        "review-drifting",
+                        name = drift.name,
+                        average = time::Span::from(drift.average).format_abbreviated(),
+                        period = time::Span::from(drift.period).format(),
+                    );
+                    (drift.name.to_owned(), message, drift.suggested)
+                })
+                .collect::<Vec<_>>();
+            let adaptations = state
+                .adaptations()
+                .into_iter()
+                .map(|adaptation| {
+                    let period = time::Span::from(adaptation.period).format();
+                    let adapted = time::Span::from(adaptation.adapted).format();
+                    let message = if adaptation.adapted > adaptation.period {
+                        t!(
+                            "review-adapt-longer",
+                            name = adaptation.name,
+                            period = period,
+                            adapted = adapted
+                        )
+                    } else {
+                        t!(
+                            "review-adapt-shorter",
+                            name = adaptation.name,
+                            period = period,
+                            adapted = adapted
+                        )
+                    };
+                    (adaptation.name.to_owned(), message, adaptation.adapted)
+                })
+                .collect::<Vec<_>>();
+            if never_logged.is_empty() && drifting.is_empty() && adaptations.is_empty() {
+                println!("{}", t!("review-nothing"));
+                return Ok(());
+            }
+
+            let interactive = std::io::stdin().is_terminal();
+            for (name, explanation) in never_logged {
+                println!(
+                    "{}",
+                    t!(
+                        "review-never-logged",
+                        name = &name,
+                        explanation = explanation
+                    )
+                );
+                // Confirm reads a single keypress, so pruning a long list stays quick.
+                if interactive
+                    && dialoguer::Confirm::new()
+                        .with_prompt(t!("review-prompt-archive", name = &name))
+                        .interact()?
+                {
+                    apply(
+                        state,
+                        StateTransition::Archive {
+                            name,
+                            archived: true,
+                        },
+                        state_path,
+                        mode,
+                    )?;
+                }
+            }
+            for (name, message, suggested) in drifting {
+                println!("{message}");
+                let shown = time::Span::from(suggested).format();
+                if !interactive {
+                    println!(
+                        "{}",
+                        t!("review-consider-period", name = &name, period = &shown)
+                    );
+                } else if dialoguer::Confirm::new()
+                    .with_prompt(t!("review-prompt-period", period = &shown))
+                    .interact()?
+                {
+                    apply(
+                        state,
+                        StateTransition::EditPeriod {
+                            name,
+                            new_period: Some(suggested),
+                        },
+                        state_path,
+                        mode,
+                    )?;
+                }
+            }
+            for (name, message, adapted) in adaptations {
+                println!("{message}");
+                let shown = time::Span::from(adapted).format();
+                if !interactive {
+                    println!(
+                        "{}",
+                        t!("review-consider-period", name = &name, period = &shown)
+                    );
+                } else if dialoguer::Confirm::new()
+                    .with_prompt(t!("review-prompt-period", period = &shown))
+                    .interact()?
+                {
+                    apply(
+                        state,
+                        StateTransition::AdaptPeriod {
+                            name,
+                            period: adapted,
+                        },
+                        state_path,
+                        mode,
+                    )?;
+                }
+            }
+            return Ok(());
+        }
+        SubCommand::Reset => {
+            println!("{}", state.describe_reset());
+            // Like deleting a repository on GitHub, make sure this isn't muscle memory.
+            let confirmation = dialoguer::Input::<String>::new()
+                .with_prompt(t!("reset-prompt"))
+                .allow_empty(true)
+                .interact_text()?;
+            if confirmation.trim() != "reset" {
+                bail!(Error::Aborted(t!("aborted")))
+            }
+            let backup_path = if mode == Mode::DryRun {
+                None
+            } else {
+                backup::backup(state_path, state.now())?
+            };
+            if let Some(backup_path) = backup_path {
+                println!(
+                    "{}",
+                    t!("backed-up", path = backup_path.display().to_string())
+                );
+            }
+            StateTransition::Reset
         }
-        SubCommand::Reset => StateTransition::Reset,
         SubCommand::StateLocation => {
             println!("{}", state_path.display());
             return Ok(());
@@ -345,77 +1781,604 @@ fn process_subcommand(state: &mut State, subcommand: SubCommand, state_path: &Pa
         SubCommand::EditPeriod {
             name,
             period,
+            log_only,
             interactive,
+            output,
         } => {
+            result_output = output;
             let name = if interactive {
                 state.find_name()?.to_owned()
             } else {
-                name.context("no practice name provided")?
+                name.context(t!("no-practice-name"))?
             };
-            let msg = format!("How often (not how long) would you like to practice \"{name}?\"");
-            let new_period = if interactive {
-                get_time_span_interactive(&msg)?
+            let msg = t!("prompt-period", name = &name);
+            let new_period = if log_only {
+                None
+            } else if interactive {
+                Some(get_time_span_interactive(&msg, None)?)
             } else {
-                period.context("no period provided")?
+                Some(period.context(t!("no-period"))?)
             };
-            let display_period = time::FlatTime::from(new_period).format();
-            if !dialoguer::Confirm::new()
-                .with_prompt(format!("Change period of \"{name}\" to {display_period}?",))
-                .interact()?
-            {
-                bail!("aborted")
+            if let Some(period) = new_period {
+                confirm_span(period, SpanUse::Period, yes)?;
+            }
+            let prompt = new_period.map_or_else(
+                || t!("edit-period-prompt-log-only", name = &name),
+                |period| {
+                    let period = time::Span::from(period).format();
+                    t!("edit-period-prompt", name = &name, period = period)
+                },
+            );
+            if !dialoguer::Confirm::new().with_prompt(prompt).interact()? {
+                bail!(Error::Aborted(t!("aborted")))
             }
             StateTransition::EditPeriod { name, new_period }
         }
-        SubCommand::Remove { name, interactive } => {
+        SubCommand::Check {
+            name,
+            item,
+            add,
+            remove,
+            uncheck,
+            interactive,
+        } => {
+            let name = if interactive {
+                state.find_name()?.to_owned()
+            } else {
+                name.context(t!("no-practice-name"))?
+            };
+            let item = if interactive && item.is_none() {
+                let checklist = state.get_checklist(&name)?;
+                ensure!(
+                    !checklist.is_empty(),
+                    t!("checklist-empty-hint", name = &name)
+                );
+                let items = checklist
+                    .iter()
+                    .map(|(item, done)| format!("{} {item}", if *done { "[x]" } else { "[ ]" }))
+                    .collect::<Vec<_>>();
+                let index = dialoguer::FuzzySelect::new()
+                    .with_prompt(t!("checklist-prompt"))
+                    .items(&items)
+                    .interact()?;
+                checklist[index].0.to_owned()
+            } else if let Some(item) = item {
+                item
+            } else {
+                let checklist = state.get_checklist(&name)?;
+                if checklist.is_empty() {
+                    println!("{}", t!("checklist-empty", name = &name));
+                }
+                for (item, done) in checklist {
+                    println!("  {} {item}", if done { "[x]" } else { "[ ]" });
+                }
+                return Ok(());
+            };
+            let edit = if add {
+                ChecklistEdit::Add
+            } else if remove {
+                ChecklistEdit::Remove
+            } else if uncheck {
+                ChecklistEdit::Uncheck
+            } else {
+                ChecklistEdit::Check
+            };
+            StateTransition::Checklist { name, item, edit }
+        }
+        SubCommand::EditMode {
+            name,
+            mode,
+            interactive,
+        } => {
+            let name = if interactive {
+                state.find_name()?.to_owned()
+            } else {
+                name.context(t!("no-practice-name"))?
+            };
+            let mode = if interactive {
+                let modes = <LogMode as clap::ValueEnum>::value_variants();
+                let names = modes
+                    .iter()
+                    .filter_map(clap::ValueEnum::to_possible_value)
+                    .map(|value| value.get_name().to_owned())
+                    .collect::<Vec<_>>();
+                let index = dialoguer::Select::new()
+                    .with_prompt(t!("edit-mode-prompt", name = &name))
+                    .items(&names)
+                    .default(0)
+                    .interact()?;
+                modes[index]
+            } else {
+                mode.context(t!("no-mode"))?
+            };
+            StateTransition::EditMode { name, mode }
+        }
+        SubCommand::SetState {
+            name,
+            state: lifecycle,
+            interactive,
+            output,
+        } => {
+            result_output = output;
             let name = if interactive {
                 state.find_name()?.to_owned()
             } else {
-                name.context("no practice name provided")?
+                name.context(t!("no-practice-name"))?
+            };
+            let lifecycle = if interactive {
+                let lifecycles = <Lifecycle as clap::ValueEnum>::value_variants();
+                let names = lifecycles
+                    .iter()
+                    .filter_map(clap::ValueEnum::to_possible_value)
+                    .map(|value| value.get_name().to_owned())
+                    .collect::<Vec<_>>();
+                let index = dialoguer::Select::new()
+                    .with_prompt(t!("set-state-prompt", name = &name))
+                    .items(&names)
+                    .default(0)
+                    .interact()?;
+                lifecycles[index]
+            } else {
+                lifecycle.context(t!("no-state"))?
+            };
+            StateTransition::SetState { name, lifecycle }
+        }
+        SubCommand::Remove {
+            names,
+            context,
+            interactive,
+            output,
+        } => {
+            result_output = output;
+            let names = select_practices(state, &names, context.as_deref(), interactive)?;
+            // Dry runs change nothing, and batches are scripted, so neither asks.
+            if !yes && mode == Mode::Normal {
+                let retention = time::Span::from(state.get_user_config().trash_retention).format();
+                let prompt = if let [name] = &names[..] {
+                    t!("remove-prompt", name = name, retention = &retention)
+                } else {
+                    for name in &names {
+                        eprintln!("  {name}");
+                    }
+                    t!(
+                        "remove-many-prompt",
+                        count = names.len(),
+                        retention = &retention
+                    )
+                };
+                if !dialoguer::Confirm::new().with_prompt(prompt).interact()? {
+                    bail!(Error::Aborted(t!("aborted")))
+                }
+            }
+            let transitions = names
+                .into_iter()
+                .map(|name| StateTransition::Remove { name })
+                .collect();
+            // Already confirmed.
+            bulk(transitions, true)?
+        }
+        SubCommand::Merge {
+            from,
+            into,
+            interactive,
+            output,
+        } => {
+            result_output = output;
+            let (from, into) = if interactive {
+                let from = state.select_name(&t!("merge-prompt-from"))?.to_owned();
+                let into = state
+                    .select_name(&t!("merge-prompt-into", from = &from))?
+                    .to_owned();
+                (from, into)
+            } else {
+                (
+                    from.context(t!("no-merge-from"))?,
+                    into.context(t!("no-merge-into"))?,
+                )
             };
             if !dialoguer::Confirm::new()
-                .with_prompt(format!("Remove practice \"{name}?\"",))
+                .with_prompt(t!("merge-prompt", from = &from, into = &into))
                 .interact()?
             {
-                bail!("aborted")
+                bail!(Error::Aborted(t!("aborted")))
+            }
+            StateTransition::Merge { from, into }
+        }
+        SubCommand::MergeState { other } => {
+            ensure!(
+                other.exists(),
+                Error::NotFound(t!("no-statefile-at", path = other.display().to_string()))
+            );
+            let other = State::load(&other)?;
+            let added = state.merge_file(other, |conflict| {
+                println!(
+                    "{}",
+                    t!(
+                        "merge-state-conflict",
+                        name = conflict.practice,
+                        field = conflict.field.to_string()
+                    )
+                );
+                let choice = dialoguer::Select::new()
+                    .with_prompt(t!("merge-state-prompt", field = conflict.field.to_string()))
+                    .items(&[
+                        t!("merge-state-ours", value = &conflict.ours),
+                        t!("merge-state-theirs", value = &conflict.theirs),
+                    ])
+                    .default(0)
+                    .interact()?;
+                Ok(choice == 1)
+            })?;
+            if added.is_empty() {
+                println!("{}", t!("merged-nothing-new"));
+            } else {
+                println!("{}", t!("merged-adding", names = added.join(", ")));
+            }
+            return Ok(());
+        }
+        SubCommand::Sync => {
+            let remote = state.get_user_config().sync_remote.clone();
+            ensure!(mode != Mode::DryRun, t!("dry-run-sync"));
+            sync::sync(state, state_path, remote.as_deref())?;
+            return Ok(());
+        }
+        SubCommand::Encrypt => {
+            ensure!(state.encryption().is_none(), t!("already-encrypted"));
+            let key = crypt::Key::new(crypt::passphrase(true)?)?;
+            state.set_encryption(Some(key));
+            println!("{}", t!("encrypted"));
+            return Ok(());
+        }
+        SubCommand::Decrypt => {
+            ensure!(state.encryption().is_some(), t!("not-encrypted"));
+            state.set_encryption(None);
+            println!("{}", t!("decrypted"));
+            return Ok(());
+        }
+        SubCommand::Doctor { fix, output } => {
+            // The statefile as it is on disk, whatever its format, compression and encryption.
+            let raw = std::fs::read(state_path)
+                .ok()
+                .and_then(|bytes| crypt::open(bytes, state.encryption()).ok())
+                .and_then(|(bytes, _)| storage::read::<serde_json::Value>(&bytes).ok());
+            let format =
+                storage::StateFormat::for_path(state_path, state.get_user_config().state_format);
+            let report = state.doctor(raw.as_ref(), format, fix);
+            return output::print(output, &report, || {
+                report.print();
+                Ok(())
+            });
+        }
+        SubCommand::Import {
+            source: Some(ImportSource::Toggl { csv, map, output }),
+            ..
+        } => {
+            let entries = import::read_time_entries(&csv)?;
+            let report = state.import_time(entries, &map.into_iter().collect())?;
+            return output::print(output, &report, || {
+                report.print();
+                Ok(())
+            });
+        }
+        SubCommand::Import {
+            source: None,
+            path,
+            format,
+            time,
+            output,
+        } => {
+            let path = path.context(t!("no-export"))?;
+            let format = format.map_or_else(|| import::ImportFormat::detect(&path), Ok)?;
+            let habits = import::read(&path, format)?;
+            let report = state.import(habits, time)?;
+            return output::print(output, &report, || {
+                report.print();
+                Ok(())
+            });
+        }
+        SubCommand::Batch => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .context(t!("read-batch-failed"))?;
+            // A failing command returns before the state is saved, undoing the whole batch.
+            let mode = if mode == Mode::DryRun {
+                mode
+            } else {
+                Mode::Batch
+            };
+            for (index, command) in cli::parse_batch(&input)?.into_iter().enumerate() {
+                process_subcommand(state, command, state_path, color, yes, mode)
+                    .with_context(|| t!("batch-failed", number = index + 1))?;
+            }
+            return Ok(());
+        }
+        SubCommand::Serve { port, decrypted } => {
+            return serve::serve(state_path, port, state.encryption(), decrypted)
+        }
+        SubCommand::Export {
+            format: ExportFormat::Json { schema },
+        } => {
+            let json = if schema {
+                serde_json::to_string_pretty(&Export::schema())
+            } else {
+                serde_json::to_string_pretty(&state.export())
+            }
+            .context(t!("serialize-json-failed"))?;
+            println!("{json}");
+            return Ok(());
+        }
+        SubCommand::Template {
+            action: TemplateAction::Save { name, force },
+        } => {
+            let path = template_path(state_path, &name)?;
+            ensure!(
+                force || !path.exists(),
+                Error::Conflict(t!("template-exists", name = &name))
+            );
+            let set = state.practice_set();
+            ensure!(!set.is_empty(), t!("template-nothing-to-save"));
+            if mode == Mode::DryRun {
+                let path = path.display().to_string();
+                println!("{}", t!("would-save-template", name = &name, path = path));
+                return Ok(());
             }
-            StateTransition::Remove { name }
+            let dir = path.parent().context(t!("template-path-no-parent"))?;
+            std::fs::create_dir_all(dir)
+                .with_context(|| t!("create-failed", path = dir.display().to_string()))?;
+            std::fs::write(&path, set.to_toml()?)
+                .with_context(|| t!("write-failed", path = path.display().to_string()))?;
+            println!(
+                "{}",
+                t!(
+                    "template-saved",
+                    name = &name,
+                    path = path.display().to_string()
+                )
+            );
+            return Ok(());
+        }
+        SubCommand::Template {
+            action: TemplateAction::Apply { name },
+        } => {
+            let path = template_path(state_path, &name)?;
+            ensure!(
+                path.exists(),
+                Error::NotFound(t!("template-not-found", name = &name))
+            );
+            let toml = std::fs::read_to_string(&path)
+                .with_context(|| t!("read-failed", path = path.display().to_string()))?;
+            return add_practice_set(state, &PracticeSet::parse(&toml)?, state_path, mode);
+        }
+        SubCommand::Template {
+            action: TemplateAction::List,
+        } => {
+            let dir = template_path(state_path, "_")?
+                .parent()
+                .context(t!("template-path-no-parent"))?
+                .to_owned();
+            let mut names = std::fs::read_dir(&dir)
+                .map(|entries| {
+                    entries
+                        .filter_map(|entry| Some(entry.ok()?.path()))
+                        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            names.sort();
+            if names.is_empty() {
+                println!("{}", t!("no-templates"));
+            }
+            for name in names {
+                println!("  {name}");
+            }
+            return Ok(());
+        }
+        SubCommand::Backup {
+            action: BackupAction::List { output },
+        } => {
+            let report = backup::list(state_path)?;
+            return output::print(output, &report, || {
+                report.print();
+                Ok(())
+            });
+        }
+        SubCommand::Backup {
+            action: BackupAction::Restore { id },
+        } => {
+            let path = backup::find(state_path, &id)?;
+            let bytes = std::fs::read(&path).context(t!("read-state-failed"))?;
+            let restored = State::from_bytes(bytes, state.encryption()).with_context(|| {
+                Error::StateCorrupt(t!("parse-state-failed", path = path.display().to_string()))
+            })?;
+            state.replace(restored);
+            let path = path.display().to_string();
+            if mode == Mode::DryRun {
+                println!("{}", t!("would-restore-backup", path = path));
+            } else {
+                println!("{}", t!("restored-backup", path = path));
+            }
+            return Ok(());
+        }
+        SubCommand::Trash {
+            action: TrashAction::List { output },
+        } => {
+            let report = state.trash_report();
+            return output::print(output, &report, || {
+                report.print();
+                Ok(())
+            });
+        }
+        SubCommand::Trash {
+            action:
+                TrashAction::Restore {
+                    name,
+                    new_name,
+                    interactive,
+                    output,
+                },
+        } => {
+            result_output = output;
+            let name = if interactive {
+                let names = state.trashed_names();
+                ensure!(!names.is_empty(), t!("trash-empty"));
+                let index = dialoguer::FuzzySelect::new()
+                    .with_prompt(t!("restore-prompt"))
+                    .items(&names)
+                    .interact_opt()?
+                    .context(Error::Aborted(t!("nothing-selected")))?;
+                names[index].to_owned()
+            } else {
+                name.context(t!("no-practice-name"))?
+            };
+            StateTransition::Restore { name, new_name }
         }
         SubCommand::Rename {
             current_name,
             new_name,
+            merge,
             interactive,
+            output,
         } => {
+            result_output = output;
             let current_name = if interactive {
                 state.find_name()?.to_owned()
             } else {
-                current_name.context("no current practice name provided")?
+                current_name.context(t!("no-current-name"))?
             };
             let new_name = if interactive {
                 dialoguer::Input::<String>::new()
-                    .with_prompt("New practice name")
+                    .with_prompt(t!("rename-prompt", name = &current_name))
                     .allow_empty(false)
-                    .interact()? // returning already exists
+                    .validate_with(|input: &String| -> Result<(), String> {
+                        if state.contains(input.trim()) && !merge {
+                            Err(t!("practice-exists", name = input.trim()))
+                        } else {
+                            Ok(())
+                        }
+                    })
+                    .interact_text()?
             } else {
-                new_name.context("no new practice name provided")?
+                new_name.context(t!("no-new-name"))?
             };
-            StateTransition::Rename {
-                current_name,
-                new_name,
+            // A practice the new name already refers to, other than the one renamed.
+            let existing = Some(state.resolve_name(new_name.trim().to_owned()))
+                .filter(|existing| state.contains(existing) && *existing != current_name);
+            match existing {
+                Some(into) if merge || confirm_rename_merge(&current_name, &into)? => {
+                    StateTransition::Merge {
+                        from: current_name,
+                        into,
+                    }
+                }
+                Some(into) => bail!(Error::Conflict(t!("rename-target-exists", name = &into))),
+                None => StateTransition::Rename {
+                    current_name,
+                    new_name,
+                },
+            }
+        }
+        SubCommand::Config {
+            action: Some(ConfigAction::Show { output }),
+            ..
+        } => {
+            let config = state.get_user_config();
+            return output::print(output, config, || {
+                config.print();
+                Ok(())
+            });
+        }
+        SubCommand::Config {
+            action: Some(ConfigAction::Get { key }),
+            ..
+        } => {
+            let config = state.get_user_config();
+            match key {
+                Some(key) => println!("{}", ConfigKey::find(&key)?.get(config)),
+                None => config.print(),
             }
+            return Ok(());
+        }
+        SubCommand::Config {
+            action: Some(ConfigAction::Set { key, value }),
+            ..
+        } => {
+            let mut new_config = state.get_user_config().clone();
+            ConfigKey::find(&key)?
+                .set(&mut new_config, &value)
+                .with_context(|| Error::Parse(t!("config-invalid-value", key = &key)))?;
+            StateTransition::Config { new_config }
         }
         SubCommand::Config {
+            action: Some(ConfigAction::Unset { key }),
+            ..
+        } => {
+            let mut new_config = state.get_user_config().clone();
+            ConfigKey::find(&key)?.unset(&mut new_config);
+            StateTransition::Config { new_config }
+        }
+        SubCommand::Config {
+            action: None,
             grace_period,
+            bar_width,
+            bar_fill,
+            bar_empty,
+            ascii,
+            prune_after_periods,
             interactive,
         } => {
-            let mut new_config = *state.get_user_config(); // TODO, this can't be right
+            let mut new_config = state.get_user_config().clone();
+            // Fields provided on the command line are always taken as given.
+            let mut given = Vec::new();
+            if let Some(grace_period) = grace_period {
+                new_config.grace_period = grace_period;
+                given.push("grace_period");
+            }
+            if let Some(bar_width) = bar_width {
+                new_config.bar_width = Some(bar_width).filter(|&width| width > 0);
+                given.push("bar_width");
+            }
+            if let Some(bar_fill) = bar_fill {
+                new_config.bar_fill = Some(bar_fill);
+                given.push("bar_fill");
+            }
+            if let Some(bar_empty) = bar_empty {
+                new_config.bar_empty = Some(bar_empty);
+                given.push("bar_empty");
+            }
+            if let Some(ascii) = ascii {
+                new_config.ascii = ascii;
+                given.push("ascii");
+            }
+            if let Some(prune_after_periods) = prune_after_periods {
+                new_config.prune_after_periods = prune_after_periods;
+                given.push("prune_after_periods");
+            }
+            // Without anything to change, show what there is.
+            if given.is_empty() && !interactive {
+                new_config.print();
+                return Ok(());
+            }
+
+            // If interactive, walk through the rest, where "" leaves the field the same and
+            // "unset" restores its default.
             if interactive {
-                // If interactive, we can either confirm on each non-provided field or "" for leave same
-                unimplemented!();
-            } else {
-                // else, only update provided fields
-                if let Some(grace_period) = grace_period {
-                    new_config.grace_period = grace_period;
+                for key in config::KEYS.iter().filter(|key| !given.contains(&key.name)) {
+                    let current = key.get(&new_config);
+                    let parse = |input: &str| {
+                        let mut config = new_config.clone();
+                        if input.trim() == "unset" {
+                            key.unset(&mut config);
+                        } else {
+                            key.set(&mut config, input)?;
+                        }
+                        Ok(config)
+                    };
+                    if let Some(config) = edit_field_interactive(key.description, &current, parse)?
+                    {
+                        new_config = config;
+                    }
                 }
             }
 
@@ -423,15 +2386,54 @@ fn process_subcommand(state: &mut State, subcommand: SubCommand, state_path: &Pa
         }
     };
 
-    handle_transition(state, transition)
+    if result_output == OutputFormat::Text {
+        apply(state, transition, state_path, mode)?;
+        if let Some(name) = feedback {
+            state.print_bar(&name, color)?;
+        }
+        return Ok(());
+    }
+    let report = TransitionReport::new(state, &transition);
+    if mode == Mode::DryRun {
+        // The report already says what would change, and must be all that is printed.
+        handle_transition(state, transition)?;
+    } else {
+        apply(state, transition, state_path, mode)?;
+    }
+    output::print(result_output, &report.finish(state), || Ok(()))
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+fn run(cli: Cli) -> Result<()> {
+    interrupt::install(cli.quiet)?;
+    ASSUME_CLOSEST.store(cli.assume_closest, Ordering::Relaxed);
+    // Man pages need no state.
+    if let SubCommand::Man {
+        subcommand,
+        install,
+    } = &cli.command
+    {
+        if let Some(dir) = install {
+            for page in man::install(dir)? {
+                println!("{}", t!("man-installed", path = page.display().to_string()));
+            }
+            return Ok(());
+        }
+        return man::print(subcommand);
+    }
+    if matches!(cli.command, SubCommand::Syntax) {
+        println!("{}", time::syntax_reference());
+        return Ok(());
+    }
 
-    let path = if let Some(path) = cli.path {
+    // The demo's statefile is made up afresh in a directory that is removed on exit.
+    let demo_dir = cli.demo.then(tempfile::tempdir).transpose()?;
+    let path = if let Some(dir) = &demo_dir {
+        let path = dir.path().join("prac.json");
+        State::demo().save(&path)?;
+        path
+    } else if let Some(path) = cli.path {
         if !path.is_absolute() {
-            bail!("Path {} is not absolute", path.display())
+            bail!(t!("path-not-absolute", path = path.display().to_string()))
         } else {
             path
         }
@@ -439,30 +2441,69 @@ fn main() -> Result<()> {
         State::get_path()?
     };
 
-    let mut state = if path.exists() {
-        serde_json::from_str(
-            &std::fs::read_to_string(&path).context("could not read statefile")?,
-        )
-        .with_context(|| format!("failed to parse state at \"{}\".\n\
-        Until automated state upgrading is implemented, you will either have to satisfy the parser's demands, or start with a new statefile. \
-        Be sure to save though.", path.display()))?
-    } else {
-        State::new()
-    };
+    match cli.command {
+        SubCommand::Prompt { next } => return prompt::print(&path, next, cli.color),
+        SubCommand::Status { format, output } => {
+            return prompt::print_status(&path, format.as_deref(), output)
+        }
+        _ => (),
+    }
 
-    process_subcommand(&mut state, cli.command, &path)?;
+    let mut state = State::load(&path)?;
+    if cli.read_only {
+        state.set_read_only(ReadOnly::Asked);
+    } else if demo_dir.is_none() && !storage::writable(&path) {
+        state.set_read_only(ReadOnly::NotWritable);
+    }
+    // Commands that surely change the state fail before doing anything, e.g. timing a session.
+    if !cli.dry_run && cli.command.changes_state() {
+        state.ensure_writable()?;
+    }
+    state.purge_trash();
+    time::set_span_style(state.get_user_config().span_style);
+    if let Some(locale) = &state.get_user_config().locale {
+        i18n::set_locale(Some(locale));
+    }
 
-    if !path.parent().is_some_and(Path::exists) {
-        // create all subdirs
-        let parent = path.parent().context("state path has no parent")?;
-        if !parent.exists() {
-            std::fs::create_dir_all(parent).context("failed to create parent directories")?;
+    let color = cli
+        .color
+        .unwrap_or_else(|| state.get_user_config().color)
+        .enabled();
+    if cli.dry_run {
+        let before = serde_json::to_vec(&state)?;
+        process_subcommand(&mut state, cli.command, &path, color, cli.yes, Mode::DryRun)?;
+        if serde_json::to_vec(&state)? != before {
+            eprintln!("{}", t!("dry-run-not-saved"));
         }
+        return Ok(());
     }
+    process_subcommand(&mut state, cli.command, &path, color, cli.yes, Mode::Normal)?;
 
-    let state_file = std::fs::File::create(path).context("failed to create state file")?;
-    state.update_version();
-    serde_json::to_writer_pretty(BufWriter::new(state_file), &state)
-        .context("failed to write state to file")?;
+    state.save(&path)?;
+    webhook::post_queued(&state.get_user_config().webhooks);
     Ok(())
 }
+
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(error) => {
+            // Help and version come this way too, but aren't failures.
+            let _ = error.print();
+            return ExitCode::from(if error.use_stderr() { error::PARSE } else { 0 });
+        }
+    };
+    let quiet = cli.quiet;
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            if interrupt::is_interrupt(&error) {
+                interrupt::abort();
+            }
+            if !quiet {
+                eprintln!("Error: {error:?}");
+            }
+            ExitCode::from(error::exit_code(&error))
+        }
+    }
+}
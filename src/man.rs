@@ -0,0 +1,75 @@
+//! `prac man`: manual pages for prac and each of its subcommands, rendered from the same clap
+//! definitions as `--help`, so they can't fall out of date.
+//!
+//! Pages are named as git's are: `prac.1` for the top command, `prac-backup.1` for
+//! `prac backup`, `prac-backup-restore.1` for `prac backup restore`, and so on.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+
+use crate::cli::Cli;
+use crate::error::Error;
+use crate::i18n::t;
+
+/// The name of each page, without the section, and the command it documents, prac's first.
+fn pages() -> Vec<(String, clap::Command)> {
+    fn collect(bin_name: &str, command: clap::Command, pages: &mut Vec<(String, clap::Command)>) {
+        let name = bin_name.replace(' ', "-");
+        let subcommands = command
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set())
+            .cloned()
+            .collect::<Vec<_>>();
+        pages.push((name.clone(), command.bin_name(bin_name).display_name(name)));
+        for subcommand in subcommands {
+            let bin_name = format!("{bin_name} {}", subcommand.get_name());
+            collect(&bin_name, subcommand, pages);
+        }
+    }
+    let mut pages = Vec::new();
+    collect("prac", Cli::command(), &mut pages);
+    pages
+}
+
+/// Render the page of `command` in roff.
+fn render(command: clap::Command) -> Result<Vec<u8>> {
+    let mut page = Vec::new();
+    clap_mangen::Man::new(command)
+        .section("1")
+        .source(concat!("prac ", env!("CARGO_PKG_VERSION")))
+        .render(&mut page)
+        .context(t!("man-render-failed"))?;
+    Ok(page)
+}
+
+/// Print the page of `subcommand`, e.g. `backup restore`, or of prac itself.
+pub fn print(subcommand: &[String]) -> Result<()> {
+    let name = std::iter::once("prac")
+        .chain(subcommand.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join("-");
+    let (_, command) = pages()
+        .into_iter()
+        .find(|(page, _)| *page == name)
+        .with_context(|| Error::NotFound(t!("man-not-found", command = subcommand.join(" "))))?;
+    std::io::stdout().write_all(&render(command)?)?;
+    Ok(())
+}
+
+/// Write every page into `dir`, creating it if need be, and return their paths.
+pub fn install(dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| t!("man-install-failed", path = dir.display().to_string()))?;
+    pages()
+        .into_iter()
+        .map(|(name, command)| {
+            let path = dir.join(format!("{name}.1"));
+            std::fs::write(&path, render(command)?)
+                .with_context(|| t!("man-install-failed", path = path.display().to_string()))?;
+            Ok(path)
+        })
+        .collect()
+}
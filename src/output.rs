@@ -0,0 +1,38 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::i18n::t;
+
+/// Output format of read commands, and of the results of commands that change practices.
+/// Everything but `text` is meant for scripts.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable, the default.
+    #[default]
+    Text,
+    Json,
+    Yaml,
+    Toml,
+}
+
+/// Print `report` in the requested format, or call `text` to render it for humans.
+///
+/// Every read command goes through here so that they are all scriptable the same way.
+pub fn print<T: Serialize>(
+    format: OutputFormat,
+    report: &T,
+    text: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+    let serialized = match format {
+        OutputFormat::Text => return text(),
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(report).context(t!("serialize-json-failed"))?
+        }
+        OutputFormat::Yaml => serde_yaml::to_string(report).context(t!("serialize-yaml-failed"))?,
+        OutputFormat::Toml => {
+            toml::to_string_pretty(report).context(t!("serialize-toml-failed"))?
+        }
+    };
+    println!("{}", serialized.trim_end());
+    Ok(())
+}
@@ -0,0 +1,306 @@
+//! Fast paths for `prac prompt` and `prac status`, which may run on every shell prompt or status
+//! bar refresh. Only the handful of fields needed for a summary are deserialized, notes and
+//! everything else are skipped, and the statefile is never written.
+//!
+//! Even skipping them, years of history take a while to parse, so saving the state also writes
+//! those fields to a small summary beside the statefile, e.g. `prac.summary.json` next to
+//! `prac.json`, which prompts read instead. It is only trusted while the statefile has the size
+//! and modification time it had when the summary was written, so hand edits, syncs and older
+//! versions of prac fall back to reading the statefile itself.
+//!
+//! `prac list` isn't served from the summary. Its weekly goals, budgets and sparklines are worked
+//! out from session history, so a summary that covered it would hold most of the state.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use owo_colors::Style;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use crate::clock::SharedClock;
+use crate::color::{paint, ColorChoice};
+use crate::output::OutputFormat;
+
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+struct PromptPractice {
+    logged: DateTime<Utc>,
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    period: Option<Duration>,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    maintenance: bool,
+    #[serde(default)]
+    why: Option<String>,
+}
+
+impl PromptPractice {
+    /// The period the practice is due after, as [`Practice`](crate::application::Practice) has
+    /// it.
+    fn due_period(&self) -> Option<Duration> {
+        let factor = if self.maintenance {
+            crate::application::MAINTENANCE_FACTOR
+        } else {
+            1
+        };
+        self.period.map(|period| period * factor)
+    }
+}
+
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+struct PromptUserConfig {
+    #[serde_as(as = "serde_with::DurationSeconds<i64>")]
+    #[serde(rename = "grace_period_in_seconds", default)]
+    grace_period: Duration,
+    #[serde(default)]
+    color: ColorChoice,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PromptConfig {
+    user_config: PromptUserConfig,
+}
+
+/// The subset of [`State`](crate::application::State) needed for a prompt summary.
+#[derive(Serialize, Deserialize)]
+struct PromptState {
+    config: PromptConfig,
+    practices: BTreeMap<String, PromptPractice>,
+    #[serde(skip)]
+    clock: SharedClock,
+}
+
+/// The [`PromptState`] of a statefile, as of its size and modification time.
+#[derive(Serialize, Deserialize)]
+struct Summary {
+    state_size: u64,
+    state_modified: SystemTime,
+    state: PromptState,
+}
+
+/// The summary of the statefile at `state_path`: beside it, with `.summary.json` for its
+/// extension.
+pub fn summary_path(state_path: &Path) -> PathBuf {
+    state_path.with_extension("summary.json")
+}
+
+/// Write the summary of the statefile just saved to `state_path` with (uncompressed, unencrypted)
+/// contents `bytes`.
+pub fn write_summary(state_path: &Path, bytes: &[u8]) -> Result<()> {
+    let metadata = std::fs::metadata(state_path)?;
+    let summary = Summary {
+        state_size: metadata.len(),
+        state_modified: metadata.modified()?,
+        state: crate::storage::read(bytes)?,
+    };
+    let path = summary_path(state_path);
+    std::fs::write(&path, serde_json::to_vec(&summary)?)
+        .with_context(|| format!("could not write {}", path.display()))
+}
+
+impl PromptState {
+    /// Read the statefile at `path`, or `None` if there isn't one yet. An encrypted statefile is
+    /// only read with `PRAC_PASSPHRASE` set, as a prompt can't ask for a passphrase.
+    fn read(path: &Path) -> Result<Option<Self>> {
+        let mut state = if let Some(state) = Self::read_summary(path) {
+            state
+        } else {
+            let Ok(bytes) = std::fs::read(path) else {
+                return Ok(None);
+            };
+            if crate::crypt::is_encrypted(&bytes) && crate::crypt::env_passphrase().is_none() {
+                return Ok(None);
+            }
+            let (bytes, _) = crate::crypt::open(bytes, None)?;
+            crate::storage::read::<Self>(&bytes).context("failed to parse state for prompt")?
+        };
+        state.clock = SharedClock::from_env()?;
+        Ok(Some(state))
+    }
+
+    /// The state from the summary of the statefile at `path`, if there is one still up to date.
+    fn read_summary(path: &Path) -> Option<Self> {
+        // Summaries are written beside where a symlinked statefile points.
+        let path = std::fs::canonicalize(path).ok()?;
+        let metadata = std::fs::metadata(&path).ok()?;
+        let summary: Summary =
+            serde_json::from_slice(&std::fs::read(summary_path(&path)).ok()?).ok()?;
+        (summary.state_size == metadata.len()
+            && Some(summary.state_modified) == metadata.modified().ok())
+        .then_some(summary.state)
+    }
+
+    /// Progress of each unarchived, periodic practice through its grace adjusted period, as in
+    /// `prac list`.
+    fn progress(&self) -> impl Iterator<Item = (&str, &PromptPractice, f64)> {
+        let now = self.clock.now();
+        let grace_period = self.config.user_config.grace_period;
+        self.practices
+            .iter()
+            .filter(|(_, practice)| !practice.archived)
+            .filter_map(move |(name, practice)| {
+                #[allow(clippy::cast_precision_loss)]
+                let fraction = (now - practice.logged).num_seconds() as f64
+                    / (practice.due_period()? + grace_period).num_seconds() as f64;
+                Some((name.as_str(), practice, fraction))
+            })
+    }
+
+    fn status(&self) -> StatusReport {
+        let (mut fresh_count, mut aging_count, mut overdue_count) = (0, 0, 0);
+        for (_, _, fraction) in self.progress() {
+            match fraction {
+                f if f >= 1.0 => overdue_count += 1,
+                f if f >= 0.5 => aging_count += 1,
+                _ => fresh_count += 1,
+            }
+        }
+
+        let next = self
+            .progress()
+            .max_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+            .map(|(name, practice, fraction)| {
+                let slumping = practice
+                    .due_period()
+                    .is_some_and(|period| self.clock.now() - practice.logged > period * 2);
+                #[allow(clippy::cast_possible_truncation)]
+                NextPractice {
+                    name: name.to_owned(),
+                    pct: (fraction * 100.0).round() as i64,
+                    why: practice.why.clone().filter(|_| slumping),
+                }
+            });
+
+        StatusReport {
+            fresh_count,
+            aging_count,
+            overdue_count,
+            next,
+        }
+    }
+}
+
+/// The most overdue practice.
+#[derive(Serialize)]
+struct NextPractice {
+    name: String,
+    /// Percent of the grace adjusted period elapsed.
+    pct: i64,
+    /// The practice's reason, only present if it is slumping (see `prac why`).
+    why: Option<String>,
+}
+
+/// Summary of all unarchived practices, as shown by `prac status`.
+#[derive(Serialize)]
+struct StatusReport {
+    /// Practices less than half way through their period.
+    fresh_count: usize,
+    /// Practices at least half way through their period.
+    aging_count: usize,
+    /// Practices past their (grace adjusted) period.
+    overdue_count: usize,
+    next: Option<NextPractice>,
+}
+
+/// Placeholders understood by `prac status --format`.
+pub const STATUS_PLACEHOLDERS: [&str; 7] = [
+    "fresh_count",
+    "aging_count",
+    "overdue_count",
+    "total_count",
+    "next_name",
+    "next_pct",
+    "next_why",
+];
+
+impl StatusReport {
+    fn placeholder(&self, placeholder: &str) -> Option<String> {
+        let next = self.next.as_ref();
+        let value = match placeholder {
+            "fresh_count" => self.fresh_count.to_string(),
+            "aging_count" => self.aging_count.to_string(),
+            "overdue_count" => self.overdue_count.to_string(),
+            "total_count" => (self.fresh_count + self.aging_count + self.overdue_count).to_string(),
+            "next_name" => next.map(|next| next.name.clone()).unwrap_or_default(),
+            "next_pct" => next.map(|next| next.pct.to_string()).unwrap_or_default(),
+            "next_why" => next.and_then(|next| next.why.clone()).unwrap_or_default(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// Print a one-line summary of the statefile at `path`: counts of fresh (under half way), aging,
+/// and overdue practices, or with `next`, the single most overdue practice. `color` overrides the
+/// configured color choice.
+pub fn print(path: &Path, next: bool, color: Option<ColorChoice>) -> Result<()> {
+    // No statefile yet means nothing to say, and a prompt is no place for errors about it.
+    let Some(state) = PromptState::read(path)? else {
+        return Ok(());
+    };
+    let status = state.status();
+    let color = color.unwrap_or(state.config.user_config.color).enabled();
+
+    if next {
+        if let Some(next) = status.next {
+            println!("{} {}%", next.name, next.pct);
+        }
+        return Ok(());
+    }
+
+    let summary = [
+        (status.fresh_count, "fresh", Style::new().green()),
+        (status.aging_count, "aging", Style::new().yellow()),
+        (status.overdue_count, "overdue", Style::new().red()),
+    ]
+    .into_iter()
+    .filter(|(count, _, _)| *count > 0)
+    .map(|(count, label, style)| paint(&format!("{count} {label}"), style, color))
+    .collect::<Vec<_>>();
+    println!("{}", summary.join(" "));
+    Ok(())
+}
+
+/// Print the status of the statefile at `path`, rendered with `template` if given.
+pub fn print_status(path: &Path, template: Option<&str>, output: OutputFormat) -> Result<()> {
+    let status = PromptState::read(path)?.map_or_else(
+        || StatusReport {
+            fresh_count: 0,
+            aging_count: 0,
+            overdue_count: 0,
+            next: None,
+        },
+        |state| state.status(),
+    );
+
+    crate::output::print(output, &status, || {
+        if let Some(template) = template {
+            let rendered =
+                crate::template::render(template, &STATUS_PLACEHOLDERS, |placeholder| {
+                    status.placeholder(placeholder)
+                })?;
+            println!("{rendered}");
+            return Ok(());
+        }
+
+        match &status.next {
+            Some(next) => {
+                println!(
+                    "{} overdue, next up: {} ({}%)",
+                    status.overdue_count, next.name, next.pct
+                );
+                if let Some(why) = &next.why {
+                    println!("why? {why}");
+                }
+            }
+            None => println!("No practices to keep up yet."),
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,160 @@
+//! `prac serve`: a read-only JSON API over localhost, for widgets and launcher extensions.
+//!
+//! The statefile is read again for every request, so changes made with prac meanwhile show up.
+//! Responses are the same as the `--output json` of the matching commands.
+//!
+//! Only requests addressed to `127.0.0.1` or `localhost` are answered, so that a web page can't
+//! read the state by pointing a domain of its own at 127.0.0.1 (DNS rebinding).
+
+use std::path::Path;
+
+use anyhow::{anyhow, ensure, Result};
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::application::{ListOptions, State, ADHERENCE_PERIODS};
+use crate::crypt::Key;
+
+/// A response to send: a status code and a JSON body.
+struct Reply {
+    status: u16,
+    body: String,
+}
+
+impl Reply {
+    fn json(report: &impl Serialize) -> Self {
+        match serde_json::to_string_pretty(report) {
+            Ok(body) => Self { status: 200, body },
+            Err(error) => Self::error(500, &error.to_string()),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+/// Decode `%XX` escapes in a path segment, e.g. `deep%20work`.
+fn percent_decode(segment: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.bytes();
+    while let Some(byte) = rest.next() {
+        if byte == b'%' {
+            let hex = [rest.next()?, rest.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Whether `host`, a request's `Host` header, names this server on `port`.
+fn local_host(host: Option<&str>, port: u16) -> bool {
+    let Some(host) = host else {
+        return false;
+    };
+    let (name, given_port) = host.rsplit_once(':').unwrap_or((host, "80"));
+    let name_is_local = ["127.0.0.1", "localhost"]
+        .iter()
+        .any(|local| name.eq_ignore_ascii_case(local));
+    name_is_local && given_port.parse() == Ok(port)
+}
+
+/// Answer a GET of `url` from `state`.
+fn route(state: &State, url: &str) -> Reply {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(percent_decode)
+        .collect::<Option<Vec<_>>>();
+    let Some(segments) = segments else {
+        return Reply::error(400, "malformed path");
+    };
+    match segments.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["practices"] => {
+            let options = ListOptions {
+                archived: query.split('&').any(|pair| pair == "archived=true"),
+                ..ListOptions::default()
+            };
+            Reply::json(&state.list_report(&options))
+        }
+        ["practices", name, "history"] => match state.history(name, None, None) {
+            Ok(history) => Reply::json(&history),
+            Err(error) => Reply::error(404, &error.to_string()),
+        },
+        ["stats"] => Reply::json(&state.stats(ADHERENCE_PERIODS)),
+        _ => Reply::error(
+            404,
+            "not found, try /practices, /practices/<name>/history, or /stats",
+        ),
+    }
+}
+
+/// Answer one request, reading the statefile at `state_path` afresh.
+fn respond(request: Request, state_path: &Path, port: u16, key: Option<&Key>) -> Result<()> {
+    let host = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Host"))
+        .map(|header| header.value.as_str());
+    let reply = if !local_host(host, port) {
+        Reply::error(403, "only requests to 127.0.0.1 or localhost are answered")
+    } else if *request.method() == Method::Get {
+        match std::fs::read(state_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| State::from_bytes(bytes, key))
+        {
+            Ok(state) => route(&state, request.url()),
+            Err(error) => Reply::error(500, &format!("could not read state: {error:#}")),
+        }
+    } else {
+        Reply::error(405, "the API is read-only, only GET is supported")
+    };
+    let content_type = Header::from_bytes("Content-Type", "application/json")
+        .map_err(|()| anyhow!("invalid header"))?;
+    let response = Response::from_string(reply.body)
+        .with_status_code(reply.status)
+        .with_header(content_type);
+    request.respond(response)?;
+    Ok(())
+}
+
+/// Serve the statefile at `state_path` on localhost until interrupted. `key` opens it if it is
+/// encrypted, in which case every local program can read what the encryption hides, so that
+/// takes `decrypted`.
+pub fn serve(state_path: &Path, port: u16, key: Option<&Key>, decrypted: bool) -> Result<()> {
+    ensure!(
+        key.is_none() || decrypted,
+        "the statefile is encrypted, and prac serve would hand it decrypted to any program on \
+         this machine; pass --decrypted to serve it anyway"
+    );
+    let server = Server::http(("127.0.0.1", port))
+        .map_err(|error| anyhow!("could not listen on port {port}: {error}"))?;
+    println!("Serving practices on http://127.0.0.1:{port}, press Ctrl-C to stop.");
+    for request in server.incoming_requests() {
+        if let Err(error) = respond(request, state_path, port, key) {
+            eprintln!("Failed to respond: {error}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::local_host;
+
+    #[test]
+    fn test_local_host() {
+        assert!(local_host(Some("127.0.0.1:8080"), 8080));
+        assert!(local_host(Some("LOCALHOST:8080"), 8080));
+        assert!(local_host(Some("localhost"), 80));
+        assert!(!local_host(Some("localhost:8081"), 8080));
+        assert!(!local_host(Some("attacker.example:8080"), 8080));
+        assert!(!local_host(None, 8080));
+    }
+}
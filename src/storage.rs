@@ -0,0 +1,221 @@
+//! How the statefile is written to disk: pretty JSON, or TOML for easier hand editing and diffs.
+//!
+//! A statefile is written as TOML if its name ends in `.toml`, as JSON if it ends in `.json`, and
+//! otherwise in the `state_format` config. Either is read whatever its name, so renaming
+//! `prac.json` to `prac.toml` (and pointing `--path` or `PRAC_PATH` at it) converts it the next
+//! time prac saves it. As the default statefile is `prac.json`, setting `state_format` alone
+//! changes nothing there, which `prac config` and `prac doctor` warn about.
+//!
+//! The `state_compact` config drops the pretty printing, and `state_compress` compresses the
+//! result with zstd (before encrypting it, if the state is encrypted). Zstd both compresses JSON
+//! better than gzip and is several times faster to decompress, which matters for a file read on
+//! every command. Compressed statefiles are recognized by their
+//! first bytes and read whatever the config says, so either can be turned on or off at any time.
+
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::t;
+
+/// First bytes of a zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+/// Zstd's default level, which compresses about as well as gzip's best, and far faster.
+const ZSTD_LEVEL: i32 = 3;
+
+/// On-disk representation of the state.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StateFormat {
+    #[default]
+    Json,
+    Toml,
+}
+
+impl StateFormat {
+    /// The format to write the statefile at `path` in, `configured` unless its extension says.
+    pub fn for_path(path: &Path, configured: Self) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Self::Json,
+            Some(extension) if extension.eq_ignore_ascii_case("toml") => Self::Toml,
+            _ => configured,
+        }
+    }
+
+    /// The format the extension of `path` forces instead of `configured`, if they disagree.
+    pub fn overriding(path: &Path, configured: Self) -> Option<Self> {
+        Some(Self::for_path(path, configured)).filter(|&format| format != configured)
+    }
+
+    /// Name of the format, as in the `state_format` config and file extensions.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Toml => "toml",
+        }
+    }
+
+    /// The format `bytes` are in: JSON if they start with an object, else TOML.
+    fn detect(bytes: &[u8]) -> Self {
+        match bytes.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(b'{') | None => Self::Json,
+            Some(_) => Self::Toml,
+        }
+    }
+
+    /// Serialize `value`, pretty printed unless `compact`.
+    pub fn write(self, value: &impl Serialize, compact: bool) -> Result<Vec<u8>> {
+        let bytes: Result<Vec<u8>> = match (self, compact) {
+            (Self::Json, false) => serde_json::to_vec_pretty(value).map_err(Into::into),
+            (Self::Json, true) => serde_json::to_vec(value).map_err(Into::into),
+            (Self::Toml, false) => toml::to_string_pretty(value)
+                .map(String::into_bytes)
+                .map_err(Into::into),
+            (Self::Toml, true) => toml::to_string(value)
+                .map(String::into_bytes)
+                .map_err(Into::into),
+        };
+        bytes.context(t!("serialize-state-failed"))
+    }
+}
+
+/// Whether the statefile at `path` can be saved: it mustn't be marked read-only, and the directory
+/// it is in (or would be created in) must take new files, as it is written beside itself and then
+/// renamed into place.
+pub fn writable(path: &Path) -> bool {
+    let path = std::fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_owned());
+    if std::fs::metadata(&path).is_ok_and(|metadata| metadata.permissions().readonly()) {
+        return false;
+    }
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.is_dir())
+        .is_some_and(|dir| tempfile::NamedTempFile::new_in(dir).is_ok())
+}
+
+/// Write `bytes` to `path` beside it and rename them over it, so that it is never left half
+/// written. An existing file keeps its permissions.
+pub fn replace(path: &Path, bytes: &[u8]) -> Result<()> {
+    let parent = path.parent().context(t!("state-path-no-parent"))?;
+    let mut file = tempfile::NamedTempFile::new_in(parent)?;
+    file.write_all(bytes)?;
+    if let Ok(metadata) = std::fs::metadata(path) {
+        file.as_file().set_permissions(metadata.permissions())?;
+    }
+    file.persist(path)?;
+    Ok(())
+}
+
+/// Compress a serialized state.
+pub fn compress(bytes: &[u8]) -> Result<Vec<u8>> {
+    zstd::encode_all(bytes, ZSTD_LEVEL).context(t!("compress-state-failed"))
+}
+
+/// Undo [`compress`], if `bytes` were compressed.
+pub fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>> {
+    if !bytes.starts_with(&ZSTD_MAGIC) {
+        return Ok(bytes);
+    }
+    zstd::decode_all(&bytes[..]).context(t!("decompress-state-failed"))
+}
+
+/// Parse a statefile's (decrypted) contents, in whichever format they are and compressed or not.
+pub fn read<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    let decompressed;
+    let bytes = if bytes.starts_with(&ZSTD_MAGIC) {
+        decompressed = decompress(bytes.to_vec())?;
+        &decompressed[..]
+    } else {
+        bytes
+    };
+    match StateFormat::detect(bytes) {
+        StateFormat::Json => Ok(serde_json::from_slice(bytes)?),
+        StateFormat::Toml => Ok(toml::from_str(std::str::from_utf8(bytes)?)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress, decompress, read, StateFormat};
+    use crate::application::{handle_transition, State, StateTransition};
+    use chrono::Duration;
+    use std::path::Path;
+
+    /// A state with a practice, a logged session and a note, to write and read back.
+    fn state() -> State {
+        let mut state = State::new();
+        let add = StateTransition::Add {
+            name: "Scales".to_owned(),
+            period: Some(Duration::days(1)),
+            why: Some("Fluency".to_owned()),
+            weekly_goal: None,
+        };
+        let log = StateTransition::Log {
+            name: "Scales".to_owned(),
+            time: Duration::minutes(20),
+            at: None,
+            note: Some("C major".to_owned()),
+        };
+        handle_transition(&mut state, add).unwrap();
+        handle_transition(&mut state, log).unwrap();
+        state
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let state = state();
+        let expected = serde_json::to_value(&state).unwrap();
+        for compact in [false, true] {
+            let bytes = StateFormat::Toml.write(&state, compact).unwrap();
+            assert_eq!(StateFormat::detect(&bytes), StateFormat::Toml);
+            let read_back: State = read(&bytes).unwrap();
+            assert_eq!(serde_json::to_value(&read_back).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_compressed_round_trip() {
+        let state = state();
+        let expected = serde_json::to_value(&state).unwrap();
+        for format in [StateFormat::Json, StateFormat::Toml] {
+            let bytes = format.write(&state, false).unwrap();
+            let compressed = compress(&bytes).unwrap();
+            assert!(compressed.len() < bytes.len());
+            let read_back: State = read(&compressed).unwrap();
+            assert_eq!(serde_json::to_value(&read_back).unwrap(), expected);
+            assert_eq!(decompress(compressed).unwrap(), bytes);
+        }
+        // Uncompressed bytes pass through.
+        assert_eq!(decompress(b"{}".to_vec()).unwrap(), b"{}");
+    }
+
+    #[test]
+    fn test_for_path() {
+        let json = Path::new("prac.json");
+        let toml = Path::new("prac.TOML");
+        let other = Path::new("prac.state");
+        assert_eq!(
+            StateFormat::for_path(json, StateFormat::Toml),
+            StateFormat::Json
+        );
+        assert_eq!(
+            StateFormat::for_path(toml, StateFormat::Json),
+            StateFormat::Toml
+        );
+        assert_eq!(
+            StateFormat::for_path(other, StateFormat::Toml),
+            StateFormat::Toml
+        );
+        assert_eq!(
+            StateFormat::overriding(json, StateFormat::Toml),
+            Some(StateFormat::Json)
+        );
+        assert_eq!(StateFormat::overriding(json, StateFormat::Json), None);
+        assert_eq!(StateFormat::overriding(other, StateFormat::Toml), None);
+    }
+}
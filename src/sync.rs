@@ -0,0 +1,194 @@
+//! `prac sync`: share the state between machines through a git remote.
+//!
+//! The state is copied into a git repository kept next to the statefile, committed, and pushed to
+//! (and pulled from) the configured `sync_remote`. When both sides changed, the states are merged
+//! structurally (see [`State::merge`]) rather than line by line.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crate::application::State;
+use crate::crypt::Key;
+use crate::i18n::t;
+
+/// Name of the statefile within the sync repository.
+const FILE: &str = "prac.json";
+
+/// Run git in `repo`, returning its trimmed stdout.
+fn git(repo: &Path, args: &[&str]) -> Result<String> {
+    let stdout = git_bytes(repo, args)?;
+    Ok(String::from_utf8_lossy(&stdout).trim().to_owned())
+}
+
+/// Run git in `repo`, returning its stdout as is.
+fn git_bytes(repo: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .current_dir(repo)
+        .args(args)
+        .output()
+        .context(t!("git-not-run"))?;
+    if !output.status.success() {
+        bail!(t!(
+            "git-failed",
+            command = args.join(" "),
+            error = String::from_utf8_lossy(&output.stderr).trim().to_owned()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// Run git in `repo` for its exit status alone.
+fn git_succeeds(repo: &Path, args: &[&str]) -> Result<bool> {
+    let status = Command::new("git")
+        .current_dir(repo)
+        .args(args)
+        .output()
+        .context(t!("git-not-run"))?
+        .status;
+    Ok(status.success())
+}
+
+/// The sync repository for the statefile at `state_path`, a hidden directory beside it.
+fn repo_path(state_path: &Path) -> Result<PathBuf> {
+    let parent = state_path.parent().context(t!("state-path-no-parent"))?;
+    let stem = state_path
+        .file_stem()
+        .context(t!("state-path-no-file-name"))?
+        .to_string_lossy();
+    let stem = stem.trim_start_matches('.');
+    Ok(parent.join(format!(".{stem}-sync")))
+}
+
+/// The state as committed at `rev`, if the statefile exists there. An encrypted one is opened
+/// with `key`'s passphrase.
+fn state_at(repo: &Path, rev: &str, key: Option<&Key>) -> Result<Option<State>> {
+    let spec = format!("{rev}:{FILE}");
+    if !git_succeeds(repo, &["cat-file", "-e", &spec])? {
+        return Ok(None);
+    }
+    let content = git_bytes(repo, &["show", &spec])?;
+    let state =
+        State::from_bytes(content, key).with_context(|| t!("sync-parse-failed", rev = rev))?;
+    Ok(Some(state))
+}
+
+/// Write `state` to the statefile in the sync repository. It is a copy, not saved like the
+/// statefile itself, so that the repository gets no backups, audit log or summary.
+fn write(repo: &Path, state: &mut State) -> Result<()> {
+    let path = repo.join(FILE);
+    let bytes = state.file_contents(&path)?;
+    std::fs::write(&path, bytes)
+        .with_context(|| t!("write-failed", path = path.display().to_string()))
+}
+
+/// Write `state` to the sync repository and commit it, if anything changed.
+fn commit(repo: &Path, state: &mut State, message: &str) -> Result<()> {
+    write(repo, state)?;
+    git(repo, &["add", FILE])?;
+    if !git_succeeds(repo, &["diff", "--cached", "--quiet"])? {
+        git(repo, &["commit", "--quiet", "-m", message])?;
+    }
+    Ok(())
+}
+
+/// Commit the state and, with a remote, exchange it. On return `state` holds the merged state.
+pub fn sync(state: &mut State, state_path: &Path, remote: Option<&str>) -> Result<()> {
+    let repo = repo_path(state_path)?;
+    if !repo.join(".git").exists() {
+        std::fs::create_dir_all(&repo)
+            .with_context(|| t!("create-failed", path = repo.display().to_string()))?;
+        git(&repo, &["init", "--quiet"])?;
+        // Commits need an author; the repository is prac's own, so a placeholder will do.
+        if !git_succeeds(&repo, &["config", "user.email"])? {
+            git(&repo, &["config", "user.name", "prac"])?;
+            git(&repo, &["config", "user.email", "prac@localhost"])?;
+        }
+        println!(
+            "{}",
+            t!("sync-repository-created", path = repo.display().to_string())
+        );
+    }
+
+    // Whether the statefile is encrypted is up to each machine, whatever the remote's copy is.
+    let key = state.encryption().cloned();
+    let stamp = state
+        .now()
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M");
+    commit(&repo, state, &format!("Update practices ({stamp})"))?;
+
+    let Some(remote) = remote else {
+        println!("{}", t!("sync-committed-locally"));
+        return Ok(());
+    };
+    match git(&repo, &["remote", "get-url", "origin"]) {
+        Ok(url) if url == remote => (),
+        Ok(_) => {
+            git(&repo, &["remote", "set-url", "origin", remote])?;
+        }
+        Err(_) => {
+            git(&repo, &["remote", "add", "origin", remote])?;
+        }
+    }
+
+    let branch = git(&repo, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+    let upstream = format!("origin/{branch}");
+    git(&repo, &["fetch", "--quiet", "origin"])?;
+
+    if !git_succeeds(&repo, &["rev-parse", "--verify", "--quiet", &upstream])? {
+        // Nothing on the remote yet.
+    } else if git_succeeds(&repo, &["merge-base", "--is-ancestor", &upstream, "HEAD"])? {
+        // The remote has nothing we don't.
+    } else if git_succeeds(&repo, &["merge-base", "--is-ancestor", "HEAD", &upstream])? {
+        git(&repo, &["merge", "--quiet", "--ff-only", &upstream])?;
+        let pulled = state_at(&repo, "HEAD", key.as_ref())?.context(t!("sync-remote-empty"))?;
+        // Replaced rather than assigned, so the statefile is still backed up before it is
+        // overwritten.
+        state.replace(pulled);
+        state.set_encryption(key);
+        println!("{}", t!("sync-pulled", remote = remote));
+        return Ok(());
+    } else {
+        let base = match git(&repo, &["merge-base", "HEAD", &upstream]) {
+            Ok(base) => state_at(&repo, &base, key.as_ref())?,
+            Err(_) => None,
+        };
+        let ours = std::mem::take(state);
+        *state = match state_at(&repo, &upstream, key.as_ref())? {
+            Some(theirs) => State::merge(base.as_ref(), ours, theirs),
+            None => ours,
+        };
+        // Record both histories, with the structurally merged state as the result.
+        git(
+            &repo,
+            &[
+                "merge",
+                "--quiet",
+                "--no-commit",
+                "--no-ff",
+                "--allow-unrelated-histories",
+                "-s",
+                "ours",
+                &upstream,
+            ],
+        )?;
+        write(&repo, state)?;
+        git(&repo, &["add", FILE])?;
+        git(
+            &repo,
+            &[
+                "commit",
+                "--quiet",
+                "-m",
+                "Merge practices from other machine",
+            ],
+        )?;
+        println!("{}", t!("sync-merged", remote = remote));
+    }
+
+    git(&repo, &["push", "--quiet", "-u", "origin", &branch])?;
+    println!("{}", t!("sync-pushed", remote = remote));
+    Ok(())
+}
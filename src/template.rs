@@ -0,0 +1,108 @@
+use anyhow::{bail, Result};
+
+/// Render a template, replacing each `{placeholder}` with `lookup(placeholder)`. Literal braces
+/// are written doubled, `{{` and `}}`. `known` is only used to list valid placeholders in errors.
+pub fn render(
+    template: &str,
+    known: &[&str],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.as_str().starts_with('{') => {
+                chars.next();
+                rendered.push('{');
+            }
+            '}' if chars.as_str().starts_with('}') => {
+                chars.next();
+                rendered.push('}');
+            }
+            '{' => {
+                let rest = chars.as_str();
+                let Some(end) = rest.find('}') else {
+                    bail!("unclosed \"{{\" in template \"{template}\"");
+                };
+                let placeholder = &rest[..end];
+                let Some(value) = lookup(placeholder) else {
+                    bail!(
+                        "unknown placeholder \"{{{placeholder}}}\", expected one of: {}",
+                        known
+                            .iter()
+                            .map(|known| format!("{{{known}}}"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                };
+                rendered.push_str(&value);
+                chars = rest[end + 1..].chars();
+            }
+            '}' => bail!(
+                "unmatched \"}}\" in template \"{template}\", write \"}}}}\" for a literal brace"
+            ),
+            c => rendered.push(c),
+        }
+    }
+    Ok(rendered)
+}
+
+/// Turn the escapes `\t`, `\n`, and `\\` in a template given on the command line into a tab, a
+/// newline, and a backslash, as shells leave them be in quotes. Other backslashes are kept.
+pub fn unescape(template: &str) -> String {
+    let mut unescaped = String::with_capacity(template.len());
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.as_str().chars().next()) {
+            ('\\', Some(escaped @ ('t' | 'n' | '\\'))) => {
+                chars.next();
+                unescaped.push(match escaped {
+                    't' => '\t',
+                    'n' => '\n',
+                    _ => '\\',
+                });
+            }
+            (c, _) => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, unescape};
+
+    fn lookup(placeholder: &str) -> Option<String> {
+        match placeholder {
+            "name" => Some("steno".to_owned()),
+            "pct" => Some("42".to_owned()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_render_placeholders() {
+        let rendered = render("{name}: {pct}%", &["name", "pct"], lookup).unwrap();
+        assert_eq!(rendered, "steno: 42%");
+    }
+
+    #[test]
+    fn test_render_escaped_braces() {
+        let rendered = render("{{{name}}}", &["name"], lookup).unwrap();
+        assert_eq!(rendered, "{steno}");
+    }
+
+    #[test]
+    fn test_render_errors() {
+        assert!(render("{nope}", &["name"], lookup).is_err());
+        assert!(render("{name", &["name"], lookup).is_err());
+        assert!(render("name}", &["name"], lookup).is_err());
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape(r"{name}\t{pct}\n"), "{name}\t{pct}\n");
+        assert_eq!(unescape(r"a\\tb"), r"a\tb");
+        assert_eq!(unescape(r"\x\"), r"\x\");
+    }
+}
@@ -1,77 +1,589 @@
-use anyhow::{bail, Result};
-use chrono::Duration;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use fluent_bundle::FluentArgs;
+use pest::iterators::Pair;
 use pest::Parser;
 
+use crate::clock::SharedClock;
+use crate::error::Error;
+use crate::i18n::{self, t};
+use crate::utils;
+
 /// Parser for an approximate superset of [systemd.time](https://www.freedesktop.org/software/systemd/man/systemd.time.html#:~:text=Internally%2C%20systemd%20generally%20operates%20with,usually%20seconds%20(see%20above)).
 /// Exceptions, also contains ns. Year is 365 days not 365.25, and month is 30 days not 30.44.
 #[derive(pest_derive::Parser)]
 #[grammar = "time/time.pest"]
 pub struct SystemDStyleTimeParser;
 
+/// Longest time span accepted, in days: ten thousand years. Anything longer is surely a typo, and
+/// would overflow when added to a date.
+pub const MAX_SPAN_DAYS: i64 = 365 * 10_000;
+
+/// The grammar, whose spellings of each unit [`syntax_reference`] lists.
+const GRAMMAR: &str = include_str!("time/time.pest");
+
+/// Units of time spans by their rule in the grammar, smallest first, with what the longer ones
+/// are taken to be.
+const UNIT_RULES: [(&str, &str); 13] = [
+    ("nanosecond", ""),
+    ("microsecond", ""),
+    ("millisecond", ""),
+    ("second", ""),
+    ("minute", ""),
+    ("hour", ""),
+    ("day", ""),
+    ("week", "7 days"),
+    ("fortnight", "2 weeks"),
+    ("month", "30 days"),
+    ("quarter", "3 months"),
+    ("semester", "6 months"),
+    ("year", "365 days"),
+];
+
+/// The ways the grammar spells the unit `rule`, shortest first.
+fn unit_spellings(rule: &str) -> Vec<&'static str> {
+    let Some(alternatives) = GRAMMAR.lines().find_map(|line| {
+        let (name, definition) = line.split_once('=')?;
+        (name.trim() == rule).then_some(definition)
+    }) else {
+        return Vec::new();
+    };
+    let mut spellings = alternatives
+        .split('"')
+        .skip(1)
+        .step_by(2)
+        .collect::<Vec<_>>();
+    spellings.reverse();
+    spellings
+}
+
+/// How to write time spans and points in time, for `prac help syntax`. The units and their
+/// spellings come from the grammar, so they can't go out of date.
+pub fn syntax_reference() -> String {
+    let mut units = String::new();
+    for (rule, worth) in UNIT_RULES {
+        let spellings = unit_spellings(rule).join(", ");
+        let line = format!("  {rule:<13}{spellings:<44}{worth}");
+        units.push_str(line.trim_end());
+        units.push('\n');
+    }
+    format!(
+        "\
+Time spans, e.g. periods and times practiced, are a number and a unit, any number of times over:
+
+  1day
+  2days          plural is fine
+  3days15hours   combined quantities
+  1w4d           abbreviations
+  \"1w 4d\"        whitespace is fine, but needs quotes to stay one argument
+  0              zero needs no unit
+
+Units, smallest first. Case doesn't matter, except in M (month), m (minute) and ms.
+
+{units}
+ISO 8601 durations work too, e.g. P1W, PT90M or P1DT12H. After the T, M is minutes.
+
+Points in time, e.g. for `prac list --as-of` and `prac log --at`, are relative to now, in local
+time:
+
+  now, 3h ago, in 2d
+  today, tomorrow, yesterday
+  tue            the last Tuesday up to today
+  last tue       the last Tuesday before today
+  next tue       a week after tue
+  2024-03-01
+  2024-03-01 9:30pm, yesterday 18:00, next mon 9am
+  14:00:30       today at that time
+
+Times of day are 14:00, 14:00:30, 2:30pm or 2pm."
+    )
+}
+
+/// A parse error about the part of `input` at `range`, underlined, e.g.
+///
+/// ```text
+/// Invalid time span: unknown unit "hx".
+///   2hx 30min
+///    ^^
+/// See `prac help syntax` for how to write times.
+/// ```
+fn highlight(kind: &str, input: &str, range: std::ops::Range<usize>, problem: &str) -> Error {
+    let column = input[..range.start].chars().count();
+    let width = input[range].chars().count().max(1);
+    Error::Parse(format!(
+        "{}\n  {input}\n  {}{}\n{}",
+        t!("parse-invalid", kind = kind, problem = problem),
+        " ".repeat(column),
+        "^".repeat(width),
+        t!("syntax-hint")
+    ))
+}
+
+/// Explain why the time span `input` doesn't parse from byte `position` on.
+fn span_error(input: &str, position: usize) -> Error {
+    let rest = &input[position..];
+    let before = input[..position].trim_end();
+    if rest.trim().is_empty() {
+        let problem = if before.ends_with(|c: char| c.is_ascii_digit()) {
+            t!("span-no-unit")
+        } else {
+            t!("span-expected")
+        };
+        return highlight(&t!("time-span"), input, input.len()..input.len(), &problem);
+    }
+    let skipped = rest.len() - rest.trim_start().len();
+    // Words are reported whole, so "2hx" underlines "hx", not just "x".
+    let start = if skipped == 0 && rest.starts_with(char::is_alphabetic) {
+        input[..position]
+            .rfind(|c: char| !c.is_alphabetic())
+            .map_or(0, |index| index + 1)
+    } else {
+        position + skipped
+    };
+    let before = input[..start].trim_end();
+    let rest = &input[start..];
+    let digits = rest.starts_with(|c: char| c.is_ascii_digit());
+    let length = rest
+        .find(|c: char| c.is_whitespace() || c.is_ascii_digit() != digits)
+        .unwrap_or(rest.len());
+    let token = &input[start..start + length];
+    let problem = match token.chars().next() {
+        _ if digits => t!("span-no-unit"),
+        Some(c) if c.is_alphabetic() && before.ends_with(|c: char| c.is_ascii_digit()) => {
+            nearest_unit(token).map_or_else(
+                || t!("span-unknown-unit", token = token),
+                |unit| t!("span-unknown-unit-suggest", token = token, unit = unit),
+            )
+        }
+        Some(c) if c.is_alphabetic() => t!("span-needs-number", token = token),
+        _ => t!("span-unexpected", token = token),
+    };
+    highlight(&t!("time-span"), input, start..start + length, &problem)
+}
+
+/// Unit spellings suggested for unknown units, one or two per unit.
+const UNIT_WORDS: [&str; 22] = [
+    "nanoseconds",
+    "microseconds",
+    "milliseconds",
+    "ms",
+    "seconds",
+    "s",
+    "minutes",
+    "min",
+    "m",
+    "hours",
+    "h",
+    "days",
+    "d",
+    "weeks",
+    "w",
+    "fortnights",
+    "months",
+    "M",
+    "quarters",
+    "semesters",
+    "years",
+    "y",
+];
+
+/// The unit `word` was most likely meant to be, if any is close.
+fn nearest_unit(word: &str) -> Option<&'static str> {
+    // A typo per three letters, so that short nonsense isn't "corrected".
+    let length = word.chars().count();
+    let allowed = (length / 3).max(1);
+    UNIT_WORDS
+        .iter()
+        .map(|unit| (utils::edit_distance(word, unit), *unit))
+        .filter(|&(distance, _)| distance <= allowed && distance < length)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, unit)| unit)
+}
+
+/// Byte offset a pest error occurred at.
+const fn error_position(error: &pest::error::Error<Rule>) -> usize {
+    match error.location {
+        pest::error::InputLocation::Pos(position) => position,
+        pest::error::InputLocation::Span((start, _)) => start,
+    }
+}
+
 /// Convert a string to a duration. This wraps the pest parser and does some extra validation.
+/// ISO 8601 durations, like `PT1H30M`, are accepted too. Errors point at the offending part of
+/// the input, and spans of more than [`MAX_SPAN_DAYS`] are refused rather than overflowing.
 pub fn parse_time_span(string: &str) -> Result<Duration> {
-    let time_span = SystemDStyleTimeParser::parse(Rule::time_span, string)?
+    // As in systemd, zero needs no unit. Prompts offer it to mean "none".
+    if string.trim() == "0" {
+        return Ok(Duration::zero());
+    }
+    if string.trim_start().starts_with(['P', 'p']) {
+        return parse_iso_duration(string.trim());
+    }
+    let time_span = SystemDStyleTimeParser::parse(Rule::time_span, string)
+        .map_err(|error| span_error(string, error_position(&error)))?
         .next()
         .unwrap();
 
-    let end = time_span.as_span().end_pos().pos();
-    if end != string.len() {
-        let matched = &string[..end];
-        let unmatched = &string[end..];
-        eprintln!(
-            "Invalid time span: \"{string}\"\n\n\
-                I was able to parse \"{matched}\" \
-                but I'm not sure what to do with the rest.\n\
-                Let me try \"{unmatched}\" on it's own and see if I can give you a more helpful error message.\n"
-        );
-        parse_time_span(unmatched)?;
-        bail!("Parse failed.");
+    let end = time_span.as_span().end();
+    if end < string.trim_end().len() {
+        bail!(span_error(string, end));
     }
 
-    let span_elements = time_span.into_inner();
-
+    let too_long = |range: std::ops::Range<usize>| {
+        highlight(&t!("time-span"), string, range, &t!("span-too-long"))
+    };
     let mut duration = Duration::zero();
-
-    for element in span_elements {
+    for element in time_span.into_inner() {
+        let range = element.as_span().start()..element.as_span().end();
         let mut element_pairs = element.into_inner();
         let quantity_pair = element_pairs.next().unwrap();
         assert_eq!(quantity_pair.as_rule(), Rule::quantity);
 
-        let quantity = quantity_pair.as_str().trim().parse::<i64>()?;
+        let quantity = quantity_pair
+            .as_str()
+            .trim()
+            .parse::<i64>()
+            .map_err(|error| {
+                if *error.kind() == std::num::IntErrorKind::PosOverflow {
+                    too_long(range.clone())
+                } else {
+                    highlight(
+                        &t!("time-span"),
+                        string,
+                        range.clone(),
+                        &t!("span-spaced-number"),
+                    )
+                }
+            })?;
         let unit = element_pairs.next().unwrap().into_inner().next().unwrap();
 
         let element_duration = match unit.as_rule() {
-            Rule::nanosecond => Duration::nanoseconds(quantity),
-            Rule::microsecond => Duration::microseconds(quantity),
-            Rule::millisecond => Duration::milliseconds(quantity),
-            Rule::second => Duration::seconds(quantity),
-            Rule::minute => Duration::minutes(quantity),
-            Rule::hour => Duration::hours(quantity),
-            Rule::day => Duration::days(quantity),
-            Rule::week => Duration::weeks(quantity),
+            Rule::nanosecond => Some(Duration::nanoseconds(quantity)),
+            Rule::microsecond => Some(Duration::microseconds(quantity)),
+            Rule::millisecond => Duration::try_milliseconds(quantity),
+            Rule::second => Duration::try_seconds(quantity),
+            Rule::minute => Duration::try_minutes(quantity),
+            Rule::hour => Duration::try_hours(quantity),
+            Rule::day => Duration::try_days(quantity),
+            Rule::week => Duration::try_weeks(quantity),
+            Rule::fortnight => quantity.checked_mul(2).and_then(Duration::try_weeks),
             // TODO: the following both round down, which I am okay with because input is integer
             // anyways, so users would just use smalller units, but eventually something worth
             // fixing to comply with systemd.time expectations.
-            Rule::month => Duration::days(quantity * 30),
-            Rule::year => Duration::days(quantity * 365),
-            Rule::time_span => todo!(),
-            Rule::span_element => todo!(),
-            Rule::quantity => todo!(),
-            Rule::unit => todo!(),
-            Rule::WHITESPACE => todo!(),
+            Rule::month => quantity.checked_mul(30).and_then(Duration::try_days),
+            Rule::quarter => quantity.checked_mul(90).and_then(Duration::try_days),
+            Rule::semester => quantity.checked_mul(180).and_then(Duration::try_days),
+            Rule::year => quantity.checked_mul(365).and_then(Duration::try_days),
+            _ => unreachable!("time spans only contain units"),
         };
-        duration = duration + element_duration;
+        duration = element_duration
+            .and_then(|element| duration.checked_add(&element))
+            .filter(|duration| duration.num_days() <= MAX_SPAN_DAYS)
+            .ok_or_else(|| too_long(range))?;
     }
     Ok(duration)
 }
 
-/// For unrolling duration into a human readable display
+/// Convert an ISO 8601 duration, e.g. `P1DT12H`, to a duration. As in time spans, a month is 30
+/// days and a year 365.
+fn parse_iso_duration(string: &str) -> Result<Duration> {
+    let kind = t!("iso-duration");
+    let iso_span = SystemDStyleTimeParser::parse(Rule::iso_duration, string)
+        .map_err(|error| {
+            let position = error_position(&error);
+            highlight(
+                &kind,
+                string,
+                position
+                    ..string[position..]
+                        .chars()
+                        .next()
+                        .map_or(position, |c| position + c.len_utf8()),
+                &t!("iso-expected"),
+            )
+        })?
+        .next()
+        .unwrap()
+        .into_inner()
+        .next()
+        .unwrap();
+    let elements = iso_span.into_inner().collect::<Vec<_>>();
+    if elements.is_empty() || string.ends_with(['T', 't']) {
+        bail!(highlight(
+            &kind,
+            string,
+            0..string.len(),
+            &t!("iso-no-values")
+        ));
+    }
+
+    let mut duration = Duration::zero();
+    for (index, element) in elements.iter().enumerate() {
+        let range = element.as_span().start()..element.as_span().end();
+        let too_long = || highlight(&kind, string, range.clone(), &t!("iso-too-long"));
+        let unit = match element.as_rule() {
+            Rule::iso_years => Duration::days(365),
+            Rule::iso_months => Duration::days(30),
+            Rule::iso_weeks => Duration::weeks(1),
+            Rule::iso_days => Duration::days(1),
+            Rule::iso_hours => Duration::hours(1),
+            Rule::iso_minutes => Duration::minutes(1),
+            _ => Duration::seconds(1),
+        };
+        let quantity = element.clone().into_inner().next().unwrap().as_str();
+        let (whole, fraction) = quantity.split_once(['.', ',']).unwrap_or((quantity, ""));
+        if !fraction.is_empty() && index + 1 != elements.len() {
+            bail!(highlight(
+                &kind,
+                string,
+                range,
+                &t!("iso-fraction-not-last")
+            ));
+        }
+        let whole = whole.parse::<i32>().map_err(|_| too_long())?;
+        duration = unit
+            .checked_mul(whole)
+            .and_then(|value| duration.checked_add(&value))
+            .ok_or_else(too_long)?;
+        if !fraction.is_empty() {
+            // Down to milliseconds, ignoring any finer digits.
+            let millis = format!("{fraction:0<3}")[..3].parse::<i32>()?;
+            duration = unit
+                .checked_mul(millis)
+                .and_then(|value| duration.checked_add(&(value / 1000)))
+                .ok_or_else(too_long)?;
+        }
+        if duration.num_days() > MAX_SPAN_DAYS {
+            bail!(too_long());
+        }
+    }
+    Ok(duration)
+}
+
+/// Convert a point in time, like "3 hours ago", "in 2h", "yesterday", "last tuesday 14:00", "next
+/// monday 9am", or "2024-03-01", to a time. Days without a time of day mean their start, at local
+/// midnight.
+/// Relative points are taken from the state's clock, see [`crate::clock`].
+pub fn parse_time_point(string: &str) -> Result<DateTime<Utc>> {
+    let now = SharedClock::from_env()?.now().with_timezone(&Local);
+    parse_time_point_from(string, &now)
+}
+
+/// [`parse_time_point`] relative to `now`.
+pub fn parse_time_point_from(string: &str, now: &DateTime<Local>) -> Result<DateTime<Utc>> {
+    time_point_from(string, now).map(|point| point.with_timezone(&Utc))
+}
+
+/// Like [`parse_time_point`], but a bare time span, like "2w", means that long ago.
+pub fn parse_time_bound(string: &str) -> Result<DateTime<Utc>> {
+    parse_time_point(string).or_else(|error| {
+        let now = SharedClock::from_env()?.now();
+        parse_time_span(string)
+            .map(|span| now - span)
+            .map_err(|_| error)
+    })
+}
+
+/// What a time span is for, to tell whether it looks like `M` (months) and `m` (minutes) were
+/// mixed up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanUse {
+    /// How often a practice is due.
+    Period,
+    /// How long a practice was practiced.
+    Practiced,
+}
+
+impl SpanUse {
+    /// Whether `span` looks like a mix-up of `M` and `m`: a period of whole minutes under an hour
+    /// was likely meant in months, and a practiced time of whole months likely in minutes.
+    pub fn mixed_up(self, span: Duration) -> bool {
+        match self {
+            Self::Period => {
+                span > Duration::zero()
+                    && span < Duration::hours(1)
+                    && span.num_seconds() % 60 == 0
+                    && span.subsec_nanos() == 0
+            }
+            Self::Practiced => {
+                span >= Duration::days(30)
+                    && span.num_days() % 30 == 0
+                    && span == Duration::days(span.num_days())
+            }
+        }
+    }
+}
+
+/// [`parse_time_point`] relative to `now`, in its time zone.
+fn time_point_from<Tz: TimeZone>(string: &str, now: &DateTime<Tz>) -> Result<DateTime<Tz>> {
+    let time_point = SystemDStyleTimeParser::parse(Rule::time_point, string.trim())
+        .with_context(|| {
+            Error::Parse(format!(
+                "{} {}",
+                t!("time-point-invalid", string = string),
+                t!("syntax-hint")
+            ))
+        })?
+        .next()
+        .unwrap()
+        .into_inner()
+        .next()
+        .unwrap();
+
+    if time_point.as_rule() == Rule::relative_point {
+        let inner = time_point.into_inner().next().unwrap();
+        return match inner.as_rule() {
+            Rule::now => Ok(now.clone()),
+            Rule::ahead => {
+                let span = inner.into_inner().next().unwrap();
+                Ok(now.clone() + parse_time_span(span.as_str())?)
+            }
+            _ => Ok(now.clone() - parse_time_span(inner.as_str())?),
+        };
+    }
+
+    let today = now.date_naive();
+    let mut date = today;
+    let mut time = NaiveTime::MIN;
+    for part in time_point.into_inner() {
+        match part.as_rule() {
+            Rule::day_ref => date = day_ref(part, today)?,
+            Rule::clock => time = clock(part)?,
+            _ => unreachable!("absolute points are made of days and times"),
+        }
+    }
+    let local = date.and_time(time);
+    // Around DST changes the time may be ambiguous or skipped; take the earliest sensible instant.
+    Ok(now
+        .timezone()
+        .from_local_datetime(&local)
+        .earliest()
+        .unwrap_or_else(|| now.timezone().from_utc_datetime(&local)))
+}
+
+/// The date a `day_ref` refers to, from `today`. Weekdays are the last one up to today, "last"
+/// weekdays the one before today, and "next" weekdays the one after today.
+fn day_ref(pair: Pair<Rule>, today: NaiveDate) -> Result<NaiveDate> {
+    let day = pair.into_inner().next().unwrap();
+    let weekday_before = |weekday: Pair<Rule>, strictly: bool| {
+        let weekday = match weekday.into_inner().next().unwrap().as_rule() {
+            Rule::monday => Weekday::Mon,
+            Rule::tuesday => Weekday::Tue,
+            Rule::wednesday => Weekday::Wed,
+            Rule::thursday => Weekday::Thu,
+            Rule::friday => Weekday::Fri,
+            Rule::saturday => Weekday::Sat,
+            _ => Weekday::Sun,
+        };
+        let mut days_back =
+            (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+        if strictly && days_back == 0 {
+            days_back = 7;
+        }
+        today - Duration::days(i64::from(days_back))
+    };
+    let weekday_after = |weekday: Pair<Rule>| weekday_before(weekday, false) + Duration::weeks(1);
+    Ok(match day.as_rule() {
+        Rule::today => today,
+        Rule::tomorrow => today + Duration::days(1),
+        Rule::yesterday => today - Duration::days(1),
+        Rule::weekday => weekday_before(day, false),
+        Rule::last_weekday => weekday_before(day.into_inner().next().unwrap(), true),
+        Rule::next_weekday => weekday_after(day.into_inner().next().unwrap()),
+        Rule::date => {
+            let mut parts = day.into_inner().map(|part| part.as_str().parse::<u32>());
+            let (year, month, day) = (
+                parts.next().unwrap()?,
+                parts.next().unwrap()?,
+                parts.next().unwrap()?,
+            );
+            NaiveDate::from_ymd_opt(i32::try_from(year)?, month, day)
+                .with_context(|| t!("no-such-date", date = format!("{year}-{month:02}-{day:02}")))?
+        }
+        _ => unreachable!("day references are one of the above"),
+    })
+}
+
+/// The time of day a `clock` reads, in 24 or 12 hour time.
+fn clock(pair: Pair<Rule>) -> Result<NaiveTime> {
+    let text = pair.as_str();
+    let (mut hour, mut minute, mut second, mut half) = (0, None, 0, None);
+    for part in pair.into_inner() {
+        match part.as_rule() {
+            Rule::clock_hour => hour = part.as_str().parse::<u32>()?,
+            Rule::clock_minute => minute = Some(part.as_str().parse::<u32>()?),
+            Rule::clock_second => second = part.as_str().parse::<u32>()?,
+            Rule::meridiem => half = part.into_inner().next().map(|pair| pair.as_rule()),
+            _ => unreachable!("clocks are made of the above"),
+        }
+    }
+    if minute.is_none() && half.is_none() {
+        bail!(t!("time-of-day-invalid", text = text));
+    }
+    if let Some(half) = half {
+        if !(1..=12).contains(&hour) {
+            bail!(t!("time-of-day-hour-invalid", text = text));
+        }
+        hour = hour % 12 + if half == Rule::pm { 12 } else { 0 };
+    }
+    NaiveTime::from_hms_opt(hour, minute.unwrap_or(0), second)
+        .with_context(|| t!("time-of-day-out-of-range", text = text))
+}
+
+/// How spans are written out: `1w 2d`, `1w2d`, or `1 week 2 days`. Each parses back to the same
+/// duration, though unit names are translated, so long spans only do in English.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    clap::ValueEnum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanStyle {
+    /// Abbreviated units, separated by spaces: `1w 2d`.
+    #[default]
+    Short,
+    /// Abbreviated units, run together: `1w2d`.
+    Compact,
+    /// Unit names in full: `1 week 2 days`.
+    Long,
+}
+
+/// The style spans are formatted in, set once from the config by [`set_span_style`].
+static SPAN_STYLE: AtomicU8 = AtomicU8::new(SpanStyle::Short as u8);
+
+/// Format every span from here on in `style`.
+pub fn set_span_style(style: SpanStyle) {
+    SPAN_STYLE.store(style as u8, Ordering::Relaxed);
+}
+
+fn span_style() -> SpanStyle {
+    match SPAN_STYLE.load(Ordering::Relaxed) {
+        1 => SpanStyle::Compact,
+        2 => SpanStyle::Long,
+        _ => SpanStyle::Short,
+    }
+}
+
+/// A duration broken down into years, months, weeks, and so on down to nanoseconds, for
+/// displaying it. Like the parser, a year is 365 days and a month 30, so every span formats back
+/// to something that parses to the same duration.
 #[allow(
     non_snake_case,
     clippy::module_name_repetitions,
     clippy::missing_docs_in_private_items
 )]
-pub struct FlatTime {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
     y: i64,
     M: i64,
     w: i64,
@@ -84,76 +596,95 @@ pub struct FlatTime {
     ns: i64,
 }
 
-impl IntoIterator for &FlatTime {
-    type Item = (i64, &'static str);
-    type IntoIter = Box<dyn Iterator<Item = Self::Item>>;
+impl IntoIterator for &Span {
+    type Item = (i64, usize);
+    type IntoIter = std::iter::Zip<std::array::IntoIter<i64, 10>, std::ops::Range<usize>>;
 
-    fn into_iter(
-        self,
-    ) -> std::boxed::Box<(dyn std::iter::Iterator<Item = (i64, &'static str)> + 'static)> {
+    /// Each component with the index of its unit in [`UNITS`] and [`UNITS_LONG`].
+    fn into_iter(self) -> Self::IntoIter {
         let values = [
             self.y, self.M, self.w, self.d, self.h, self.m, self.s, self.ms, self.us, self.ns,
         ];
-        Box::new(values.into_iter().zip(UNITS))
+        values.into_iter().zip(0..UNITS.len())
     }
 }
 
-impl FlatTime {
+impl Span {
+    /// The duration the span adds up to.
+    pub fn to_duration(self) -> Duration {
+        Duration::days(self.y * 365 + self.M * 30)
+            + Duration::weeks(self.w)
+            + Duration::days(self.d)
+            + Duration::hours(self.h)
+            + Duration::minutes(self.m)
+            + Duration::seconds(self.s)
+            + Duration::milliseconds(self.ms)
+            + Duration::microseconds(self.us)
+            + Duration::nanoseconds(self.ns)
+    }
+
     /// Format the time as a string, but only include the first non-zero unit.
     pub fn format_abbreviated(&self) -> String {
-        for (value, unit) in self {
-            if value == 0 {
-                continue;
-            }
-            return format!("{value}{unit}");
-        }
-        "0s".to_string()
+        let first = self.into_iter().find(|&(value, _)| value != 0);
+        Self::join(first.into_iter(), span_style())
     }
 
     /// Format the time as a string, including all units.
     pub fn format(&self) -> String {
-        let mut result = String::new();
-        let mut first = true;
-        for (value, unit) in self {
-            if value == 0 {
-                continue;
-            }
-            if !first {
-                result.push(' ');
-            }
-            first = false;
-            result.push_str(&format!("{value}{unit}"));
-        }
-        if result.is_empty() {
-            result.push_str("0s");
-        }
-        result
+        self.format_with(span_style())
     }
 
     /// Format the time in units with precision to a second.
     pub fn format_seconds(&self) -> String {
-        let mut result = String::new();
-        let mut first = true;
-        for (value, unit) in self.into_iter().take(7) {
-            if value == 0 {
-                continue;
-            }
-            if !first {
-                result.push(' ');
-            }
-            first = false;
-            result.push_str(&format!("{value}{unit}"));
-        }
-        if result.is_empty() {
-            result.push_str("0s");
+        Self::join(self.into_iter().take(7), span_style())
+    }
+
+    /// Format the time as a string in `style`, including all units, regardless of the configured
+    /// style.
+    pub fn format_with(&self, style: SpanStyle) -> String {
+        Self::join(self.into_iter(), style)
+    }
+
+    /// Write out the non-zero `components` in `style`, or zero seconds if there are none.
+    fn join(components: impl Iterator<Item = (i64, usize)>, style: SpanStyle) -> String {
+        let words = components
+            .filter(|&(value, _)| value != 0)
+            .map(|(value, unit)| match style {
+                SpanStyle::Short | SpanStyle::Compact => format!("{value}{}", UNITS[unit]),
+                SpanStyle::Long => {
+                    let mut args = FluentArgs::new();
+                    args.set("count", value);
+                    i18n::message(&format!("span-{}", UNITS_LONG[unit]), Some(&args))
+                }
+            })
+            .collect::<Vec<_>>();
+        if words.is_empty() {
+            return match style {
+                SpanStyle::Short | SpanStyle::Compact => "0s".to_string(),
+                SpanStyle::Long => t!("span-zero"),
+            };
         }
-        result
+        let separator = if style == SpanStyle::Compact { "" } else { " " };
+        words.join(separator)
     }
+}
+
+impl FromStr for Span {
+    type Err = anyhow::Error;
+
+    fn from_str(string: &str) -> Result<Self> {
+        parse_time_span(string).map(Self::from)
+    }
+}
 
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format())
+    }
 }
 
 #[allow(non_snake_case, clippy::many_single_char_names)]
-impl From<Duration> for FlatTime {
+impl From<Duration> for Span {
     fn from(mut value: Duration) -> Self {
         let mut days = value.num_days();
         let y = days / 365;
@@ -191,22 +722,111 @@ impl From<Duration> for FlatTime {
 }
 
 const UNITS: [&str; 10] = ["y", "M", "w", "d", "h", "m", "s", "ms", "us", "ns"];
-const _UNITS_LONG: [&str; 10] = [
-    "nanosecond",
-    "microsecond",
-    "millisecond",
-    "second",
-    "minute",
-    "hour",
-    "day",
-    "week",
-    "month",
+const UNITS_LONG: [&str; 10] = [
     "year",
+    "month",
+    "week",
+    "day",
+    "hour",
+    "minute",
+    "second",
+    "millisecond",
+    "microsecond",
+    "nanosecond",
 ];
 
+/// Start (midnight) of the week containing `now`, where weeks begin on `first_day`.
+pub fn week_start<Tz: TimeZone>(now: &DateTime<Tz>, first_day: Weekday) -> DateTime<Tz> {
+    let days_in = (now.weekday().num_days_from_monday() + 7 - first_day.num_days_from_monday()) % 7;
+    let midnight = (now.date_naive() - Duration::days(i64::from(days_in)))
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight exists");
+    // Around DST changes midnight may be ambiguous or skipped; take the earliest sensible instant.
+    now.timezone()
+        .from_local_datetime(&midnight)
+        .earliest()
+        .unwrap_or_else(|| now.timezone().from_utc_datetime(&midnight))
+}
+
+/// A daily window of clock time, e.g. `20:00-23:00`, which may wrap past midnight. Saved as
+/// written by [`Display`](fmt::Display).
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, serde_with::SerializeDisplay, serde_with::DeserializeFromStr,
+)]
+pub struct TimeWindow {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+/// Named windows accepted by [`TimeWindow`], singular or plural.
+const NAMED_WINDOWS: [(&str, u32, u32); 4] = [
+    ("morning", 5, 12),
+    ("afternoon", 12, 17),
+    ("evening", 17, 22),
+    ("night", 22, 5),
+];
+
+impl TimeWindow {
+    /// Whether `time` falls in the window, counting its start but not its end.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl FromStr for TimeWindow {
+    type Err = anyhow::Error;
+
+    /// Parse `mornings`, `afternoons`, `evenings`, `nights` (or the singular), or `HH:MM-HH:MM`.
+    fn from_str(string: &str) -> Result<Self> {
+        let string = string.trim().to_lowercase();
+        let name = string.strip_suffix('s').unwrap_or(&string);
+        if let Some(&(_, start, end)) = NAMED_WINDOWS.iter().find(|(named, ..)| *named == name) {
+            let hour = |hour| NaiveTime::from_hms_opt(hour, 0, 0).expect("valid hour");
+            return Ok(Self {
+                start: hour(start),
+                end: hour(end),
+            });
+        }
+        let invalid = || Error::Parse(t!("time-window-invalid", string = string.as_str()));
+        let (start, end) = string.split_once('-').with_context(invalid)?;
+        let time =
+            |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").with_context(invalid);
+        let window = Self {
+            start: time(start)?,
+            end: time(end)?,
+        };
+        if window.start == window.end {
+            bail!(invalid());
+        }
+        Ok(window)
+    }
+}
+
+impl fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+/// Local midnight starting the day of `now`.
+pub fn day_start<Tz: TimeZone>(now: &DateTime<Tz>) -> DateTime<Tz> {
+    week_start(now, now.weekday())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{parse_time_span, Rule, SystemDStyleTimeParser};
+    use super::{
+        parse_time_span, unit_spellings, Rule, SystemDStyleTimeParser, GRAMMAR, UNIT_RULES,
+    };
     use chrono::Duration;
     use pest::Parser;
 
@@ -241,6 +861,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_syntax_reference_units() {
+        let unit = GRAMMAR
+            .lines()
+            .find_map(|line| line.strip_prefix("unit = {"))
+            .unwrap();
+        let rules = unit.trim_end_matches('}').split('|').map(str::trim);
+        for rule in rules {
+            assert!(
+                UNIT_RULES.iter().any(|(listed, _)| *listed == rule),
+                "{rule}"
+            );
+        }
+        for (rule, _) in UNIT_RULES {
+            let one = parse_time_span(&format!("1{rule}")).unwrap();
+            let spellings = unit_spellings(rule);
+            assert!(!spellings.is_empty(), "{rule}");
+            for spelling in spellings {
+                assert_eq!(
+                    parse_time_span(&format!("2{spelling}")).unwrap(),
+                    one * 2,
+                    "{spelling}"
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_parse_invalid_strings() {
         let invalid_time_spans = vec!["2hx  30min", "10dd 5h 30m", "watermelon"];
@@ -250,6 +897,108 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_iso_duration() {
+        let cases = [
+            ("P1W", Duration::weeks(1)),
+            ("PT90M", Duration::minutes(90)),
+            ("P1DT12H", Duration::days(1) + Duration::hours(12)),
+            ("P1M", Duration::days(30)),
+            ("PT1M", Duration::minutes(1)),
+            (
+                "P1Y2M3DT4H5M6S",
+                Duration::days(365 + 60 + 3)
+                    + Duration::hours(4)
+                    + Duration::minutes(5)
+                    + Duration::seconds(6),
+            ),
+            ("PT1.5H", Duration::minutes(90)),
+            ("pt0,25s", Duration::milliseconds(250)),
+        ];
+        for (duration, answer) in cases {
+            assert_eq!(parse_time_span(duration).unwrap(), answer, "{duration}");
+        }
+
+        for invalid in ["P", "PT", "P1H", "PT1D", "P1D2W", "PT1.5H30M", "P1DT"] {
+            assert!(parse_time_span(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    proptest::proptest! {
+        /// No input, however mangled, makes the parsers panic.
+        #[test]
+        fn test_parse_anything(string in "\\PC{0,40}") {
+            let _ = parse_time_span(&string);
+            let _ = super::parse_time_point(&string);
+        }
+
+        /// Spans of any size parse to their exact duration, or are refused as too long.
+        #[test]
+        fn test_parse_any_span(
+            elements in proptest::collection::vec(
+                (proptest::prop_oneof![0..1000i64, 0..i64::MAX], 0..super::UNITS.len()),
+                1..5,
+            )
+        ) {
+            const DAY: i128 = 86_400 * 1_000_000_000;
+            let nanoseconds = [
+                365 * DAY,
+                30 * DAY,
+                7 * DAY,
+                DAY,
+                3_600 * 1_000_000_000,
+                60 * 1_000_000_000,
+                1_000_000_000,
+                1_000_000,
+                1_000,
+                1,
+            ];
+            let string = elements
+                .iter()
+                .map(|&(quantity, unit)| format!("{quantity}{}", super::UNITS[unit]))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let expected = elements
+                .iter()
+                .map(|&(quantity, unit)| i128::from(quantity) * nanoseconds[unit])
+                .sum::<i128>();
+            match parse_time_span(&string) {
+                Ok(duration) => proptest::prop_assert_eq!(
+                    i128::from(duration.num_seconds()) * 1_000_000_000
+                        + i128::from(duration.subsec_nanos()),
+                    expected
+                ),
+                Err(_) => proptest::prop_assert!(
+                    expected / DAY > i128::from(super::MAX_SPAN_DAYS),
+                    "{string}"
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_unit_words() {
+        let cases = [
+            ("5mins", Duration::minutes(5)),
+            ("2mo", Duration::days(60)),
+            ("3 mths", Duration::days(90)),
+            ("1fortnight", Duration::weeks(2)),
+            ("1 quarter", Duration::days(90)),
+            (
+                "1semester 2secs",
+                Duration::days(180) + Duration::seconds(2),
+            ),
+            ("2wks 1yr", Duration::weeks(2) + Duration::days(365)),
+            ("5ms", Duration::milliseconds(5)),
+        ];
+        for (span, answer) in cases {
+            assert_eq!(parse_time_span(span).unwrap(), answer, "{span}");
+        }
+
+        let error = parse_time_span("3mnths").unwrap_err().to_string();
+        assert!(error.contains("did you mean \"months\""), "{error}");
+    }
+
     #[test]
     fn test_parse_empty() {
         assert!(parse_time_span("").is_err());
@@ -271,12 +1020,29 @@ mod tests {
         assert!(hard.is_ok_and(|d| d == hard_answer));
     }
 
+    #[test]
+    fn test_sub_minute_spans() {
+        assert_eq!(parse_time_span("90s").unwrap(), Duration::seconds(90));
+        assert_eq!(parse_time_span("1m30s").unwrap(), Duration::seconds(90));
+        assert_eq!(
+            parse_time_span("1min 30sec").unwrap(),
+            Duration::seconds(90)
+        );
+        assert_eq!(parse_time_span("1s").unwrap(), Duration::seconds(1));
+        assert_eq!(parse_time_span(" 0 ").unwrap(), Duration::zero());
+
+        let span = crate::time::Span::from(Duration::seconds(90));
+        assert_eq!(span.format_with(crate::time::SpanStyle::Short), "1m 30s");
+        let span = crate::time::Span::from(Duration::milliseconds(1500));
+        assert_eq!(span.format_seconds(), "1s");
+    }
+
     #[test]
     fn test_display() {
-        use crate::time::FlatTime;
+        use crate::time::Span;
         use chrono::Duration;
 
-        let time = FlatTime::from(
+        let time = Span::from(
             Duration::days(365)
                 + Duration::days(30 * 11)
                 + Duration::weeks(3)
@@ -289,13 +1055,186 @@ mod tests {
         assert_eq!(time.format(), "1y 11M 3w 6d 23h 59m 59s");
         assert_eq!(time.format_abbreviated(), "1y");
     }
+    #[test]
+    fn test_span_round_trip() {
+        use crate::time::{parse_time_span, Span, SpanStyle};
+        use chrono::Duration;
+
+        let duration = Duration::days(365 + 30 * 11)
+            + Duration::weeks(1)
+            + Duration::days(6)
+            + Duration::hours(1)
+            + Duration::minutes(59)
+            + Duration::seconds(2)
+            + Duration::milliseconds(807)
+            + Duration::microseconds(1)
+            + Duration::nanoseconds(30);
+        let span = Span::from(duration);
+
+        assert_eq!(
+            span.format_with(SpanStyle::Short),
+            "1y 11M 1w 6d 1h 59m 2s 807ms 1us 30ns"
+        );
+        assert_eq!(
+            span.format_with(SpanStyle::Compact),
+            "1y11M1w6d1h59m2s807ms1us30ns"
+        );
+        assert_eq!(
+            span.format_with(SpanStyle::Long),
+            "1 year 11 months 1 week 6 days 1 hour 59 minutes 2 seconds 807 milliseconds \
+                1 microsecond 30 nanoseconds"
+        );
+        assert_eq!(span.to_duration(), duration);
+        for style in [SpanStyle::Short, SpanStyle::Compact, SpanStyle::Long] {
+            let formatted = span.format_with(style);
+            assert!(
+                parse_time_span(&formatted).is_ok_and(|d| d == duration),
+                "{formatted}"
+            );
+            assert_eq!(formatted.parse::<Span>().unwrap(), span);
+        }
+        assert_eq!(
+            Span::from(Duration::zero()).format_with(SpanStyle::Long),
+            "0 seconds"
+        );
+    }
+
     #[test]
     fn max_time() {
-        let time = crate::time::FlatTime::from(chrono::Duration::milliseconds(i64::MAX));
+        let time = crate::time::Span::from(chrono::Duration::milliseconds(i64::MAX));
         assert_eq!(
             time.format(),
             "292471208y 8M 1w 7h 12m 55s 807ms",
             "Unchecked past num of years... simply a regression test"
         );
     }
+
+    #[test]
+    fn test_parse_time_point() {
+        use super::time_point_from;
+        use chrono::{TimeZone, Utc};
+
+        // A Thursday afternoon.
+        let now = Utc.with_ymd_and_hms(2023, 8, 10, 15, 30, 0).unwrap();
+        let cases = [
+            ("now", now),
+            (
+                "3 hours ago",
+                Utc.with_ymd_and_hms(2023, 8, 10, 12, 30, 0).unwrap(),
+            ),
+            (
+                "1d 2h ago",
+                Utc.with_ymd_and_hms(2023, 8, 9, 13, 30, 0).unwrap(),
+            ),
+            ("today", Utc.with_ymd_and_hms(2023, 8, 10, 0, 0, 0).unwrap()),
+            (
+                "yesterday 18:00",
+                Utc.with_ymd_and_hms(2023, 8, 9, 18, 0, 0).unwrap(),
+            ),
+            (
+                "Tuesday",
+                Utc.with_ymd_and_hms(2023, 8, 8, 0, 0, 0).unwrap(),
+            ),
+            (
+                "last tuesday 14:00",
+                Utc.with_ymd_and_hms(2023, 8, 8, 14, 0, 0).unwrap(),
+            ),
+            ("thu", Utc.with_ymd_and_hms(2023, 8, 10, 0, 0, 0).unwrap()),
+            (
+                "in 1d 2h",
+                Utc.with_ymd_and_hms(2023, 8, 11, 17, 30, 0).unwrap(),
+            ),
+            (
+                "tomorrow 9am",
+                Utc.with_ymd_and_hms(2023, 8, 11, 9, 0, 0).unwrap(),
+            ),
+            (
+                "next monday 9:00",
+                Utc.with_ymd_and_hms(2023, 8, 14, 9, 0, 0).unwrap(),
+            ),
+            (
+                "next thu",
+                Utc.with_ymd_and_hms(2023, 8, 17, 0, 0, 0).unwrap(),
+            ),
+            (
+                "last thu",
+                Utc.with_ymd_and_hms(2023, 8, 3, 0, 0, 0).unwrap(),
+            ),
+            (
+                "2024-03-01",
+                Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+            ),
+            (
+                "2024-03-01T09:05:30",
+                Utc.with_ymd_and_hms(2024, 3, 1, 9, 5, 30).unwrap(),
+            ),
+            ("2pm", Utc.with_ymd_and_hms(2023, 8, 10, 14, 0, 0).unwrap()),
+            (
+                "yesterday 12:15 am",
+                Utc.with_ymd_and_hms(2023, 8, 9, 0, 15, 0).unwrap(),
+            ),
+        ];
+        for (point, answer) in cases {
+            assert_eq!(time_point_from(point, &now).unwrap(), answer, "{point}");
+        }
+
+        for invalid in [
+            "",
+            "3h",
+            "14",
+            "13pm",
+            "25:00",
+            "2023-02-30",
+            "last",
+            "next",
+            "in",
+            "watermelon",
+        ] {
+            assert!(time_point_from(invalid, &now).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_time_window() {
+        use super::TimeWindow;
+        use chrono::NaiveTime;
+
+        let at = |hour| NaiveTime::from_hms_opt(hour, 0, 0).unwrap();
+        let mornings: TimeWindow = "Mornings".parse().unwrap();
+        assert_eq!(mornings.to_string(), "05:00-12:00");
+        assert!(mornings.contains(at(5)) && !mornings.contains(at(12)));
+
+        let late: TimeWindow = "22:30-01:00".parse().unwrap();
+        assert_eq!(late.to_string().parse::<TimeWindow>().unwrap(), late);
+        assert!(late.contains(at(0)) && late.contains(at(23)) && !late.contains(at(22)));
+
+        for invalid in ["", "brunch", "20:00", "20:00-20:00", "25:00-26:00"] {
+            assert!(invalid.parse::<TimeWindow>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_week_start() {
+        use super::week_start;
+        use chrono::{TimeZone, Utc, Weekday};
+
+        // A Thursday afternoon.
+        let now = Utc.with_ymd_and_hms(2023, 8, 10, 15, 30, 0).unwrap();
+        assert_eq!(
+            week_start(&now, Weekday::Mon),
+            Utc.with_ymd_and_hms(2023, 8, 7, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            week_start(&now, Weekday::Sun),
+            Utc.with_ymd_and_hms(2023, 8, 6, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            week_start(&now, Weekday::Thu),
+            Utc.with_ymd_and_hms(2023, 8, 10, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            week_start(&now, Weekday::Fri),
+            Utc.with_ymd_and_hms(2023, 8, 4, 0, 0, 0).unwrap()
+        );
+    }
 }
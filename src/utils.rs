@@ -1,11 +1,194 @@
-use anyhow::{Context, Result};
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
 
-/// Write content to file
-pub fn long_edit(intitial_content: Option<&str>) -> Result<String> {
-    dialoguer::Editor::new()
-        .require_save(false)
-        .edit(intitial_content.unwrap_or_default())?
-        .context("Content not saved")
+use anyhow::{bail, ensure, Context, Result};
+
+/// Editor when none is set: Notepad on Windows, vi elsewhere.
+const DEFAULT_EDITOR: &str = if cfg!(windows) { "notepad" } else { "vi" };
+/// Editors that return as soon as they have opened a file unless told to wait until it is closed,
+/// by their program name, and the flag telling them.
+const WAIT_FLAGS: [(&str, &str); 4] = [
+    ("code", "--wait"),
+    ("code-insiders", "--wait"),
+    ("codium", "--wait"),
+    ("subl", "--wait"),
+];
+/// The terminal, to read from and write to whatever our own stdio is.
+#[cfg(windows)]
+const TTY: (&str, &str) = ("CONIN$", "CONOUT$");
+#[cfg(not(windows))]
+const TTY: (&str, &str) = ("/dev/tty", "/dev/tty");
+
+/// Editor command line: `$VISUAL`, then `$EDITOR`, then `configured`, then [`DEFAULT_EDITOR`]. The
+/// command may carry arguments, e.g. `code --wait`, which is added for editors known to need it.
+fn editor_command(configured: Option<&str>) -> Result<Vec<String>> {
+    let editor = ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .chain(configured.map(ToOwned::to_owned))
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_owned());
+    // Backslashes are escapes to shell words, but path separators on Windows.
+    let mut words = if cfg!(windows) {
+        shell_words::split(&editor.replace('\\', "/"))
+    } else {
+        shell_words::split(&editor)
+    }
+    .with_context(|| format!("could not parse editor command \"{editor}\""))?;
+    ensure!(!words.is_empty(), "editor command is empty");
+    let program = std::path::Path::new(&words[0])
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase());
+    if let Some((_, flag)) = WAIT_FLAGS
+        .iter()
+        .find(|(name, _)| program.as_deref() == Some(*name))
+    {
+        if !words.iter().any(|word| word == flag || word == "-w") {
+            words.push((*flag).to_owned());
+        }
+    }
+    Ok(words)
+}
+
+/// Where to run `program` from. On Windows, a bare name is looked up on `PATH` with each of the
+/// extensions in `PATHEXT`, as the shell does, so that e.g. `code` finds `code.cmd`; elsewhere, and
+/// for anything else, it is run as given.
+fn program_path(program: &str) -> PathBuf {
+    let path = PathBuf::from(program);
+    if !cfg!(windows) || path.extension().is_some() || path.components().count() > 1 {
+        return path;
+    }
+    let extensions = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_owned());
+    std::env::var_os("PATH")
+        .iter()
+        .flat_map(std::env::split_paths)
+        .flat_map(|dir| {
+            extensions
+                .split(';')
+                .filter(|extension| !extension.is_empty())
+                .map(move |extension| dir.join(format!("{program}{extension}")))
+        })
+        .find(|candidate| candidate.is_file())
+        .unwrap_or(path)
+}
+
+/// Terminal to attach the editor to, so it works even when our own stdio is piped.
+fn tty() -> (Stdio, Stdio) {
+    let input = std::fs::File::open(TTY.0);
+    let output = std::fs::OpenOptions::new().write(true).open(TTY.1);
+    match (input, output) {
+        (Ok(input), Ok(output)) => (input.into(), output.into()),
+        _ => (Stdio::inherit(), Stdio::inherit()),
+    }
+}
+
+/// Write content to file in the user's editor (see [`editor_command`]) and return the result.
+/// Fails, rather than returning something to overwrite with, if the editor fails or the content
+/// is left unchanged.
+pub fn long_edit(intitial_content: Option<&str>, editor: Option<&str>) -> Result<String> {
+    let initial = intitial_content.unwrap_or_default();
+    let mut file = tempfile::Builder::new()
+        .prefix("prac-")
+        .suffix(".md")
+        .tempfile()
+        .context("failed to create temporary file")?;
+    std::io::Write::write_all(&mut file, initial.as_bytes())
+        .context("failed to write temporary file")?;
+
+    let words = editor_command(editor)?;
+    let (stdin, stdout) = tty();
+    let status = Command::new(program_path(&words[0]))
+        .args(&words[1..])
+        .arg(file.path())
+        .stdin(stdin)
+        .stdout(stdout)
+        .status()
+        .with_context(|| format!("failed to run editor \"{}\"", words.join(" ")))?;
+    ensure!(
+        status.success(),
+        "editor exited with {status}, nothing changed"
+    );
+
+    let content =
+        std::fs::read_to_string(file.path()).context("failed to read back temporary file")?;
+    if content == initial {
+        bail!("content unchanged, nothing to save");
+    }
+    Ok(content)
+}
+
+/// Print markdown rendered for the terminal, styled only if `color`.
+pub fn print_markdown(text: &str, color: bool) {
+    let skin = if color {
+        termimad::MadSkin::default()
+    } else {
+        termimad::MadSkin::no_style()
+    };
+    skin.print_text(text);
+}
+
+/// Whether the terminal takes ANSI escape sequences, e.g. for colors, turning them on first if
+/// need be: always outside Windows, and on Windows unless it is a legacy console.
+#[allow(clippy::missing_const_for_fn)]
+pub fn ansi_terminal() -> bool {
+    #[cfg(windows)]
+    return crossterm::ansi_support::supports_ansi();
+    #[cfg(not(windows))]
+    true
+}
+
+/// Whether the terminal can be trusted to show the characters bars are drawn with by default.
+/// Legacy Windows consoles mostly have fonts without them, so on Windows only terminals that say
+/// who they are are trusted: Windows Terminal, `ConEmu`, and those setting `TERM` or
+/// `TERM_PROGRAM`, like mintty and VS Code's.
+pub fn unicode_terminal() -> bool {
+    !cfg!(windows)
+        || ["WT_SESSION", "ConEmuANSI", "TERM", "TERM_PROGRAM"]
+            .into_iter()
+            .any(|var| std::env::var_os(var).is_some())
+}
+
+/// Characters a bar is drawn with.
+#[derive(Clone, Copy)]
+pub struct BarGlyphs {
+    fill: char,
+    empty: char,
+    /// For the elapsed part of bars past the period, within the grace period.
+    grace: char,
+}
+
+impl BarGlyphs {
+    /// Resolve glyphs, falling back to the defaults for unset characters. In ASCII mode, which
+    /// terminals that can't show the defaults are always in (see [`unicode_terminal`]), any
+    /// non-ASCII character is replaced by its ASCII default.
+    pub fn new(fill: Option<char>, empty: Option<char>, ascii: bool) -> Self {
+        let ascii = ascii || !unicode_terminal();
+        let (default_fill, default_empty) = if ascii {
+            ('#', ' ')
+        } else {
+            ('\u{025AC}', ' ')
+        };
+        let pick = |glyph: Option<char>, default: char| match glyph {
+            Some(glyph) if !ascii || glyph.is_ascii() => glyph,
+            _ => default,
+        };
+        Self {
+            fill: pick(fill, default_fill),
+            empty: pick(empty, default_empty),
+            grace: if ascii { '=' } else { '\u{2550}' },
+        }
+    }
+
+    /// Use `grace` for the part of bars within the grace period, unless it is unset, or isn't
+    /// ASCII in ASCII mode.
+    pub fn with_grace(mut self, grace: Option<char>, ascii: bool) -> Self {
+        let ascii = ascii || !unicode_terminal();
+        if let Some(grace) = grace.filter(|grace| !ascii || grace.is_ascii()) {
+            self.grace = grace;
+        }
+        self
+    }
 }
 
 /// generate a bar for a practice
@@ -14,11 +197,88 @@ pub fn long_edit(intitial_content: Option<&str>) -> Result<String> {
     clippy::cast_possible_truncation,
     clippy::cast_sign_loss
 )]
-pub fn bar(bar_width: usize, mut fraction: f64) -> String {
+pub fn bar(bar_width: usize, mut fraction: f64, glyphs: BarGlyphs) -> String {
     fraction = fraction.clamp(0.0, 1.0);
     //.with_context(|| "fraction must be between 0 and 1")?;
     let filled = (fraction * bar_width as f64) as usize;
     let empty = bar_width - filled;
     assert!(filled + empty == bar_width);
-    format!("{}{}", "\u{025AC}".repeat(filled), " ".repeat(empty))
+    format!(
+        "{}{}",
+        glyphs.fill.to_string().repeat(filled),
+        glyphs.empty.to_string().repeat(empty)
+    )
+}
+
+/// Like [`bar`], for a bar whose grace period starts at `grace_from` (a fraction of the bar): the
+/// elapsed part up to the grace period, the elapsed part within it, and the remaining part, apart
+/// so they can be styled differently.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub fn grace_bar(
+    bar_width: usize,
+    fraction: f64,
+    grace_from: f64,
+    glyphs: BarGlyphs,
+) -> (String, String, String) {
+    let elapsed = (fraction.clamp(0.0, 1.0) * bar_width as f64) as usize;
+    let before_grace = elapsed.min((grace_from.clamp(0.0, 1.0) * bar_width as f64) as usize);
+    (
+        glyphs.fill.to_string().repeat(before_grace),
+        glyphs.grace.to_string().repeat(elapsed - before_grace),
+        glyphs.empty.to_string().repeat(bar_width - elapsed),
+    )
+}
+
+/// `text` cut to `width` characters, ending in an ellipsis if anything was cut.
+pub fn ellipsize(text: &str, width: usize, ascii: bool) -> Cow<'_, str> {
+    if text.chars().count() <= width {
+        return Cow::Borrowed(text);
+    }
+    let ellipsis = if ascii { "..." } else { "…" };
+    let kept = width.saturating_sub(ellipsis.chars().count());
+    Cow::Owned(text.chars().take(kept).collect::<String>() + ellipsis)
+}
+
+/// Number of single character insertions, deletions, substitutions, or swaps of adjacent
+/// characters to turn `a` into `b` (the optimal string alignment distance), so that `stneo` is a
+/// typo away from `steno`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    // Distances from the prefixes of `a` two rows and one row back to each prefix of `b`.
+    let mut before_previous = Vec::new();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for i in 0..a.len() {
+        let mut current = vec![i + 1];
+        for j in 0..b.len() {
+            let substitution = previous[j] + usize::from(a[i] != b[j]);
+            let mut distance = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+            if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+                distance = distance.min(before_previous[j - 1] + 1);
+            }
+            current.push(distance);
+        }
+        before_previous = std::mem::replace(&mut previous, current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::edit_distance;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("steno", "steno"), 0);
+        assert_eq!(edit_distance("", "steno"), 5);
+        assert_eq!(edit_distance("sten", "steno"), 1);
+        assert_eq!(edit_distance("stemo", "steno"), 1);
+        assert_eq!(edit_distance("stneo", "steno"), 1);
+        assert_eq!(edit_distance("tsneo", "steno"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
 }
@@ -0,0 +1,50 @@
+//! Posting events to the webhooks set in the `webhooks` config, e.g. a dashboard or a Discord
+//! channel. Events are queued as they happen, and only posted once the state is saved, so that a
+//! failed save tells no one about changes that were lost.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+/// How long to wait on a webhook, so a slow one doesn't hold up the command.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Payloads of the events queued, waiting for the state to be saved.
+static QUEUED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Queue `event` to be posted by [`post_queued`].
+pub fn queue(event: &impl Serialize) -> Result<()> {
+    let body = serde_json::to_string(event).context("failed to serialize webhook payload")?;
+    QUEUED
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .push(body);
+    Ok(())
+}
+
+/// Post the queued events as JSON to every URL in `urls`. A webhook failing doesn't stop the
+/// others, or the command; it is only reported.
+pub fn post_queued(urls: &[String]) {
+    let bodies = std::mem::take(
+        &mut *QUEUED
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner),
+    );
+    if urls.is_empty() || bodies.is_empty() {
+        return;
+    }
+    let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+    for body in &bodies {
+        for url in urls {
+            if let Err(error) = agent
+                .post(url)
+                .set("Content-Type", "application/json")
+                .send_string(body)
+            {
+                eprintln!("Webhook failed: {error}");
+            }
+        }
+    }
+}
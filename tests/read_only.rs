@@ -0,0 +1,69 @@
+//! Commands that change the state fail on a read-only statefile before prompting for anything.
+
+use std::process::{Command, Stdio};
+
+/// Exit code of prac run with `args` on a read-only statefile, with no terminal to prompt on and
+/// an editor that fails.
+fn exit_code(args: &[&str]) -> Option<i32> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("prac.json");
+    Command::new(env!("CARGO_BIN_EXE_prac"))
+        .arg("--path")
+        .arg(&path)
+        .arg("--read-only")
+        .args(args)
+        .env("EDITOR", "false")
+        .env("VISUAL", "false")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .unwrap()
+        .code()
+}
+
+#[test]
+fn test_fails_before_prompting() {
+    let commands: &[&[&str]] = &[
+        &["notes", "-i"],
+        &["notes", "steno", "-i"],
+        &["notes", "steno", "--stdin"],
+        &["notes", "steno", "Legato"],
+        &["why", "-i"],
+        &["why", "steno", "Speed"],
+        &["goal", "-i"],
+        &["goal", "steno", "2h"],
+        &["budget", "-i"],
+        &["budget", "steno", "--clear"],
+        &["best-time", "-i"],
+        &["context", "-i"],
+        &["context", "steno", "desk"],
+        &["context", "steno", "--clear"],
+        &["alias", "steno", "st"],
+        &["depend", "steno", "--on", "chess"],
+        &["check", "-i"],
+        &["check", "steno", "Warm up"],
+        &["edit-session", "-i"],
+        &["config", "-i"],
+    ];
+    for args in commands {
+        // The exit code of a read-only statefile, rather than of the prompt failing.
+        assert_eq!(exit_code(args), Some(7), "prac {}", args.join(" "));
+    }
+}
+
+#[test]
+fn test_reading_is_allowed() {
+    let commands: &[&[&str]] = &[
+        &["notes", "steno", "--show"],
+        &["notes", "steno", "--history"],
+        &["context", "steno"],
+        &["alias", "steno"],
+        &["depend", "steno"],
+        &["check", "steno"],
+    ];
+    for args in commands {
+        // There is no such practice, which is found out only past the read-only check.
+        assert_eq!(exit_code(args), Some(2), "prac {}", args.join(" "));
+    }
+}